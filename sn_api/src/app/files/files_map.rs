@@ -11,10 +11,14 @@ use super::{
     metadata::FileMeta,
     ProcessedFiles, RealPath,
 };
-use crate::{app::consts::*, Error, Result, Safe, XorUrl};
+use crate::{app::consts::*, app::nrs::VersionHash, Error, Result, Safe, XorUrl};
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
 
 // To use for mapping files names (with path in a flattened hierarchy) to FileInfos
 pub type FilesMap = BTreeMap<String, FileInfo>;
@@ -186,6 +190,84 @@ async fn gen_new_file_item(
     Ok(file_item)
 }
 
+/// Merges the `FilesMap`s of concurrent versions of a FilesContainer, path by path, against
+/// the `FilesMap` of their common ancestor. A path changed on a single branch takes that change.
+/// When a path was changed differently on several branches, a change wins over a removal, and
+/// amongst changes the item modified last wins, ties broken by the greatest version hash.
+/// If `keep_conflicts` is set, the losing items are kept as copies renamed with a
+/// '.conflict-<version>' suffix, unless they are directories.
+pub(crate) fn merge_files_maps(
+    base: &FilesMap,
+    branches: &BTreeMap<VersionHash, FilesMap>,
+    keep_conflicts: bool,
+) -> FilesMap {
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(branches.values().flat_map(|files_map| files_map.keys()))
+        .collect();
+
+    let mut merged = FilesMap::default();
+    for path in paths {
+        let base_item = base.get(path);
+
+        // Distinct changes made to the item on each branch, along with the greatest
+        // version hash of the branches which made each of them.
+        let mut changes = BTreeMap::<Option<&FileInfo>, VersionHash>::new();
+        for (version, files_map) in branches {
+            let item = files_map.get(path);
+            if item != base_item {
+                let _ = changes.insert(item, *version);
+            }
+        }
+
+        if changes.is_empty() {
+            if let Some(item) = base_item {
+                let _ = merged.insert(path.clone(), item.clone());
+            }
+            continue;
+        }
+
+        let mut updates: Vec<(&FileInfo, VersionHash)> = changes
+            .into_iter()
+            .filter_map(|(item, version)| item.map(|item| (item, version)))
+            .collect();
+        updates.sort_by(|(item_a, version_a), (item_b, version_b)| {
+            let modified_a = item_a.get(PREDICATE_MODIFIED);
+            let modified_b = item_b.get(PREDICATE_MODIFIED);
+            (modified_a, version_a).cmp(&(modified_b, version_b))
+        });
+
+        // the item is left out if the only change made to it was its removal
+        if let Some((winner, _)) = updates.pop() {
+            let _ = merged.insert(path.clone(), winner.clone());
+            if keep_conflicts {
+                for (item, version) in updates {
+                    if item.get(PREDICATE_TYPE).map(String::as_str) != Some(MIMETYPE_FILESYSTEM_DIR)
+                    {
+                        let _ = merged.insert(conflict_copy_path(path, &version), item.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    merged
+}
+
+// Path of the copy of an item which lost a merge conflict,
+// e.g. '/dir/file.conflict-1a2b3c4d.txt' for '/dir/file.txt'
+fn conflict_copy_path(path: &str, version: &VersionHash) -> String {
+    let suffix = format!(".conflict-{}", hex::encode(&version.entry_hash().0[..4]));
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(i) if i > 0 => {
+            let (stem, extension) = path.split_at(name_start + i);
+            format!("{}{}{}", stem, suffix, extension)
+        }
+        _ => format!("{}{}", path, suffix),
+    }
+}
+
 /// Returns a new `files_map` at the given path if the given path is a dir.
 pub(crate) fn file_map_for_path(files_map: FilesMap, path: &str) -> Result<FilesMap> {
     let realpath = files_map.realpath(path)?;
@@ -282,3 +364,117 @@ fn filesmap_chroot(urlpath: &str, files_map: &FilesMap) -> Result<FilesMap> {
         Ok(filtered_filesmap)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::register::EntryHash;
+
+    fn file_item(link: &str, modified: &str) -> FileInfo {
+        let mut item = FileInfo::new();
+        let _ = item.insert(PREDICATE_LINK.to_string(), link.to_string());
+        let _ = item.insert(PREDICATE_MODIFIED.to_string(), modified.to_string());
+        let _ = item.insert(PREDICATE_TYPE.to_string(), "text/plain".to_string());
+        item
+    }
+
+    fn version(byte: u8) -> VersionHash {
+        VersionHash::from(&EntryHash([byte; 32]))
+    }
+
+    #[test]
+    fn test_merge_files_maps_non_conflicting_changes() {
+        let base = FilesMap::from([
+            ("/a.txt".to_string(), file_item("a", "2022-01-01T00:00:00Z")),
+            ("/b.txt".to_string(), file_item("b", "2022-01-01T00:00:00Z")),
+            ("/c.txt".to_string(), file_item("c", "2022-01-01T00:00:00Z")),
+        ]);
+
+        // one branch updates a file and removes another, the other one adds a new file
+        let mut branch_1 = base.clone();
+        let updated_a = file_item("a2", "2022-01-02T00:00:00Z");
+        let _ = branch_1.insert("/a.txt".to_string(), updated_a.clone());
+        let _ = branch_1.remove("/b.txt");
+        let mut branch_2 = base.clone();
+        let new_d = file_item("d", "2022-01-02T00:00:00Z");
+        let _ = branch_2.insert("/d.txt".to_string(), new_d.clone());
+
+        let branches = BTreeMap::from([(version(1), branch_1), (version(2), branch_2)]);
+        let merged = merge_files_maps(&base, &branches, true);
+
+        let expected = FilesMap::from([
+            ("/a.txt".to_string(), updated_a),
+            ("/c.txt".to_string(), file_item("c", "2022-01-01T00:00:00Z")),
+            ("/d.txt".to_string(), new_d),
+        ]);
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_merge_files_maps_conflicting_changes() {
+        let base = FilesMap::from([
+            ("/a.txt".to_string(), file_item("a", "2022-01-01T00:00:00Z")),
+            ("/b".to_string(), file_item("b", "2022-01-01T00:00:00Z")),
+        ]);
+
+        // both branches update the same files, the first one also removes one of them
+        let mut branch_1 = base.clone();
+        let latest_a = file_item("a1", "2022-01-03T00:00:00Z");
+        let _ = branch_1.insert("/a.txt".to_string(), latest_a.clone());
+        let _ = branch_1.remove("/b");
+        let mut branch_2 = base.clone();
+        let earliest_a = file_item("a2", "2022-01-02T00:00:00Z");
+        let _ = branch_2.insert("/a.txt".to_string(), earliest_a.clone());
+        let updated_b = file_item("b2", "2022-01-02T00:00:00Z");
+        let _ = branch_2.insert("/b".to_string(), updated_b.clone());
+
+        let branches = BTreeMap::from([(version(1), branch_1), (version(2), branch_2)]);
+
+        // the latest update wins and an update wins over a removal
+        let merged = merge_files_maps(&base, &branches, false);
+        let expected = FilesMap::from([
+            ("/a.txt".to_string(), latest_a.clone()),
+            ("/b".to_string(), updated_b.clone()),
+        ]);
+        assert_eq!(merged, expected);
+
+        // the result is the same regardless of which branch is merged onto which
+        let swapped = BTreeMap::from([
+            (version(1), branches[&version(2)].clone()),
+            (version(2), branches[&version(1)].clone()),
+        ]);
+        assert_eq!(merge_files_maps(&base, &swapped, false), expected);
+
+        // the losing update can be kept as a renamed copy
+        let merged = merge_files_maps(&base, &branches, true);
+        let expected = FilesMap::from([
+            ("/a.txt".to_string(), latest_a),
+            ("/a.conflict-02020202.txt".to_string(), earliest_a),
+            ("/b".to_string(), updated_b),
+        ]);
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_merge_files_maps_tie_is_broken_by_version() {
+        let base = FilesMap::default();
+        let branch_1 = FilesMap::from([(
+            "/dir/a".to_string(),
+            file_item("a1", "2022-01-01T00:00:00Z"),
+        )]);
+        let branch_2 = FilesMap::from([(
+            "/dir/a".to_string(),
+            file_item("a2", "2022-01-01T00:00:00Z"),
+        )]);
+
+        let branches = BTreeMap::from([(version(1), branch_1.clone()), (version(2), branch_2)]);
+        let merged = merge_files_maps(&base, &branches, true);
+
+        let mut expected = branches[&version(2)].clone();
+        let _ = expected.insert(
+            "/dir/a.conflict-01010101".to_string(),
+            branch_1["/dir/a"].clone(),
+        );
+        assert_eq!(merged, expected);
+    }
+}
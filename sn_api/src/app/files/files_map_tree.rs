@@ -0,0 +1,385 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{FileInfo, FilesMap};
+use crate::{ContentType, Error, Result, Safe, SafeUrl, XorUrl, XorUrlBase};
+use bytes::{Buf, Bytes};
+use futures::future::try_join_all;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sn_client::Client;
+use std::collections::BTreeMap;
+use xor_name::XorName;
+
+// Version of the format of the tree a FilesMap is stored as, which is kept in its root node
+const FILES_MAP_TREE_VERSION: u64 = 1;
+
+// Path of a directory as the list of its components, the root directory being empty
+type DirPath = Vec<String>;
+
+/// A node of the tree a `FilesMap` is stored as, one per directory. It holds the items found
+/// directly in the directory, keyed by their name, and the link to the node of each subdirectory.
+/// Since nodes are stored as content-addressed blobs, storing a new version of a `FilesMap`
+/// only stores the nodes of the directories which changed, i.e. the changed paths' ancestors.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FilesMapNode {
+    entries: BTreeMap<String, FileInfo>,
+    subdirs: BTreeMap<String, XorUrl>,
+}
+
+/// The root node of the tree a `FilesMap` is stored as, i.e. the node of the root directory
+/// along with the version of the format of the tree, so it can evolve.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FilesMapRoot {
+    version: u64,
+    node: FilesMapNode,
+}
+
+// Just the version of a root node, to check it before deserialising the rest of it
+#[derive(Deserialize)]
+struct FilesMapTreeVersion {
+    version: u64,
+}
+
+// The nodes of a tree, serialised and keyed by their address, grouped by depth
+type TreeLevels = BTreeMap<usize, BTreeMap<XorName, Bytes>>;
+
+impl Safe {
+    /// Store a `FilesMap` as a tree of nodes, returning the XOR-URL of the root node, or `None`
+    /// if any of its paths cannot be represented in a tree, e.g. it's not absolute.
+    /// Nodes already known to be stored on the network are not uploaded again.
+    pub(crate) async fn store_files_map_tree(
+        &self,
+        files_map: &FilesMap,
+    ) -> Result<Option<XorUrl>> {
+        let (levels, root_xorurl) = match files_map_tree_nodes(files_map, self.xorurl_base)? {
+            Some(tree) => tree,
+            None => return Ok(None),
+        };
+
+        // The nodes of the same depth are stored concurrently, the deepest ones first, so a node
+        // is only stored once those it links to are, in case the upload is interrupted.
+        for (_, nodes) in levels.into_iter().rev() {
+            let _ = try_join_all(
+                nodes
+                    .into_iter()
+                    .map(|(address, bytes)| self.store_files_map_node(address, bytes)),
+            )
+            .await?;
+        }
+
+        Ok(Some(root_xorurl))
+    }
+
+    /// Fetch all the nodes of the tree a `FilesMap` was stored as, given its root node.
+    pub(crate) async fn fetch_files_map_tree(&self, root: FilesMapNode) -> Result<FilesMap> {
+        let mut files_map = FilesMap::default();
+        let mut level = vec![(DirPath::new(), root)];
+        while !level.is_empty() {
+            let mut next_level = Vec::new();
+            for (path, node) in level {
+                for (name, file_info) in node.entries {
+                    let _ = files_map.insert(item_path(&path, &name), file_info);
+                }
+                for (name, xorurl) in node.subdirs {
+                    let mut subdir_path = path.clone();
+                    subdir_path.push(name);
+                    next_level.push(async move {
+                        let node = self.fetch_files_map_node(&xorurl).await?;
+                        Ok::<_, Error>((subdir_path, node))
+                    });
+                }
+            }
+            // all nodes of the same depth are fetched concurrently
+            level = try_join_all(next_level).await?;
+        }
+
+        Ok(files_map)
+    }
+
+    async fn store_files_map_node(&self, address: XorName, bytes: Bytes) -> Result<()> {
+        if self.is_files_map_node_stored(&address) {
+            debug!("FilesMap node at {:?} is already stored", address);
+            return Ok(());
+        }
+
+        let _ = self.store_bytes(bytes, None).await?;
+        if !self.dry_run_mode {
+            self.set_files_map_node_stored(address);
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_files_map_node(&self, xorurl: &str) -> Result<FilesMapNode> {
+        let url = SafeUrl::from_xorurl(xorurl)?;
+        let serialised_node = self.fetch_data(&url, None).await?;
+        let node = serde_json::from_slice(serialised_node.chunk()).map_err(|err| {
+            Error::ContentError(format!(
+                "Couldn't deserialise the FilesMap node found at \"{}\": {:?}",
+                xorurl, err
+            ))
+        })?;
+        self.set_files_map_node_stored(url.xorname());
+
+        Ok(node)
+    }
+
+    fn is_files_map_node_stored(&self, address: &XorName) -> bool {
+        self.stored_files_map_nodes
+            .lock()
+            .map(|nodes| nodes.contains(address))
+            .unwrap_or(false)
+    }
+
+    fn set_files_map_node_stored(&self, address: XorName) {
+        if let Ok(mut nodes) = self.stored_files_map_nodes.lock() {
+            let _ = nodes.insert(address);
+        }
+    }
+}
+
+/// Deserialise the root node of a `FilesMap` tree, returning `None` if the content is not
+/// a root node, e.g. it's a `FilesMap` in the legacy flat format, and an error if it's a root
+/// node of a version of the tree format which isn't supported.
+pub(crate) fn deserialise_files_map_root(content: &[u8]) -> Result<Option<FilesMapNode>> {
+    let version = match serde_json::from_slice::<FilesMapTreeVersion>(content) {
+        Ok(FilesMapTreeVersion { version }) => version,
+        Err(_) => return Ok(None),
+    };
+    if version != FILES_MAP_TREE_VERSION {
+        return Err(Error::ContentError(format!(
+            "The FilesMap is stored in version {} of the tree format, while only version {} \
+            is supported",
+            version, FILES_MAP_TREE_VERSION
+        )));
+    }
+
+    let root = serde_json::from_slice::<FilesMapRoot>(content).map_err(|err| {
+        Error::ContentError(format!(
+            "Couldn't deserialise the root node of the FilesMap: {:?}",
+            err
+        ))
+    })?;
+    Ok(Some(root.node))
+}
+
+// Build the nodes of the tree a FilesMap is stored as, returning them serialised and grouped by
+// depth, along with the XOR-URL of the root node, or `None` if any of the FilesMap's paths cannot
+// be represented in a tree. The address of a node only depends on its content, so the links to
+// the nodes of subdirectories are known without storing them.
+fn files_map_tree_nodes(
+    files_map: &FilesMap,
+    xorurl_base: XorUrlBase,
+) -> Result<Option<(TreeLevels, XorUrl)>> {
+    let mut dirs = match group_by_dir(files_map) {
+        Some(dirs) => dirs,
+        None => return Ok(None),
+    };
+
+    // Subdirectories sort after their parent, so in reverse order the
+    // nodes of all subdirectories are built before their parent's node.
+    let mut levels = TreeLevels::new();
+    let mut subdirs_links = BTreeMap::<DirPath, BTreeMap<String, XorUrl>>::new();
+    while let Some((path, entries)) = dirs.pop_last() {
+        let subdirs = subdirs_links.remove(&path).unwrap_or_default();
+        let node = FilesMapNode { entries, subdirs };
+        let serialised_node = if path.is_empty() {
+            serde_json::to_vec(&FilesMapRoot {
+                version: FILES_MAP_TREE_VERSION,
+                node,
+            })
+        } else {
+            serde_json::to_vec(&node)
+        }
+        .map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't serialise the FilesMap node generated: {:?}",
+                err
+            ))
+        })?;
+        let bytes = Bytes::from(serialised_node);
+        let address = Client::calculate_address(bytes.clone())?;
+        let xorurl = SafeUrl::from_bytes(address, ContentType::Raw)?.encode(xorurl_base);
+        let _ = levels.entry(path.len()).or_default().insert(address, bytes);
+
+        match path.split_last() {
+            Some((name, parent)) => {
+                let _ = subdirs_links
+                    .entry(parent.to_vec())
+                    .or_default()
+                    .insert(name.clone(), xorurl);
+            }
+            None => return Ok(Some((levels, xorurl))),
+        }
+    }
+
+    // the root directory is always there, and the last one to be built
+    Ok(None)
+}
+
+// Group the items of a FilesMap by the directory they are in, including all directories
+// which are ancestors of an item, even if they have no items of their own.
+fn group_by_dir(files_map: &FilesMap) -> Option<BTreeMap<DirPath, BTreeMap<String, FileInfo>>> {
+    let mut dirs = BTreeMap::<DirPath, BTreeMap<String, FileInfo>>::new();
+    let _ = dirs.insert(DirPath::new(), BTreeMap::new());
+
+    for (path, file_info) in files_map {
+        let mut components = path
+            .strip_prefix('/')?
+            .split('/')
+            .map(ToString::to_string)
+            .collect::<DirPath>();
+        if components.iter().any(String::is_empty) {
+            return None;
+        }
+
+        let name = components.pop()?;
+        for depth in 1..=components.len() {
+            let _ = dirs.entry(components[..depth].to_vec()).or_default();
+        }
+        let _ = dirs
+            .entry(components)
+            .or_default()
+            .insert(name, file_info.clone());
+    }
+
+    Some(dirs)
+}
+
+// Path in the FilesMap of an item with the given name within the given directory
+fn item_path(dir: &[String], name: &str) -> String {
+    let mut path = String::new();
+    for component in dir.iter().map(String::as_str).chain([name]) {
+        path.push('/');
+        path.push_str(component);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::consts::*;
+    use std::collections::BTreeSet;
+
+    fn file_item(link: &str) -> FileInfo {
+        BTreeMap::from([
+            (PREDICATE_LINK.to_string(), link.to_string()),
+            (PREDICATE_TYPE.to_string(), "text/plain".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_group_by_dir() -> anyhow::Result<()> {
+        let files_map = FilesMap::from([
+            ("/a.txt".to_string(), file_item("a")),
+            ("/sub/b.txt".to_string(), file_item("b")),
+            ("/sub/deep/er/c.txt".to_string(), file_item("c")),
+        ]);
+
+        let dirs = group_by_dir(&files_map).ok_or_else(|| anyhow::anyhow!("paths are valid"))?;
+        let dir = |path: &[&str]| path.iter().map(ToString::to_string).collect::<DirPath>();
+        assert_eq!(
+            dirs.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                dir(&[]),
+                dir(&["sub"]),
+                dir(&["sub", "deep"]),
+                dir(&["sub", "deep", "er"])
+            ]
+        );
+        assert_eq!(
+            dirs[&dir(&[])],
+            BTreeMap::from([("a.txt".to_string(), file_item("a"))])
+        );
+        assert!(dirs[&dir(&["sub", "deep"])].is_empty());
+        assert_eq!(
+            dirs[&dir(&["sub", "deep", "er"])],
+            BTreeMap::from([("c.txt".to_string(), file_item("c"))])
+        );
+
+        // the items' paths can be rebuilt from the directories
+        let rebuilt: FilesMap = dirs
+            .iter()
+            .flat_map(|(path, entries)| {
+                entries
+                    .iter()
+                    .map(|(name, file_info)| (item_path(path, name), file_info.clone()))
+            })
+            .collect();
+        assert_eq!(rebuilt, files_map);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_dir_with_non_absolute_paths() {
+        for path in ["relative.txt", "/", "/sub//a.txt", "/sub/"] {
+            let files_map = FilesMap::from([(path.to_string(), file_item("a"))]);
+            assert_eq!(group_by_dir(&files_map), None, "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn test_legacy_files_map_is_not_a_root_node() -> anyhow::Result<()> {
+        let legacy = serde_json::to_vec(&FilesMap::from([("/a.txt".to_string(), file_item("a"))]))?;
+        assert_eq!(deserialise_files_map_root(&legacy)?, None);
+        assert_eq!(deserialise_files_map_root(b"{}")?, None);
+
+        let root = FilesMapRoot {
+            version: FILES_MAP_TREE_VERSION,
+            node: FilesMapNode::default(),
+        };
+        let serialised = serde_json::to_vec(&root)?;
+        assert_eq!(deserialise_files_map_root(&serialised)?, Some(root.node));
+
+        // other versions of the tree format are rejected rather than taken for a legacy FilesMap
+        let future = br#"{"version":2,"node":{"entries":{},"subdirs":{}},"more":[]}"#;
+        assert!(deserialise_files_map_root(future).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changing_a_file_only_changes_the_nodes_of_its_ancestors() -> anyhow::Result<()> {
+        let mut files_map = FilesMap::from([
+            ("/a.txt".to_string(), file_item("a")),
+            ("/sub/b.txt".to_string(), file_item("b")),
+            ("/sub/deep/c.txt".to_string(), file_item("c")),
+            ("/other/d.txt".to_string(), file_item("d")),
+        ]);
+        let nodes = |files_map: &FilesMap| -> anyhow::Result<BTreeMap<XorName, usize>> {
+            let (levels, _) = files_map_tree_nodes(files_map, DEFAULT_XORURL_BASE)?
+                .ok_or_else(|| anyhow::anyhow!("paths are valid"))?;
+            Ok(levels
+                .into_iter()
+                .flat_map(|(depth, nodes)| nodes.into_keys().map(move |address| (address, depth)))
+                .collect())
+        };
+
+        let stored = nodes(&files_map)?;
+        assert_eq!(stored.len(), 4);
+
+        let _ = files_map.insert("/sub/deep/c.txt".to_string(), file_item("c2"));
+        let changed = nodes(&files_map)?;
+        let to_store = changed
+            .iter()
+            .filter(|(address, _)| !stored.contains_key(address))
+            .map(|(_, depth)| *depth)
+            .collect::<BTreeSet<_>>();
+
+        // only the nodes of /, /sub and /sub/deep are new, the others are already stored
+        assert_eq!(to_store, BTreeSet::from([0, 1, 2]));
+        assert_eq!(changed.len(), stored.len());
+
+        Ok(())
+    }
+}
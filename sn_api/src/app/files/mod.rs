@@ -8,23 +8,28 @@
 
 mod file_system;
 mod files_map;
+mod files_map_tree;
 mod metadata;
 mod realpath;
 
 use crate::{
-    app::consts::*, app::nrs::VersionHash, resolver::Range, ContentType, DataType, Error, Result,
-    Safe, SafeUrl, XorUrl,
+    app::consts::*,
+    app::nrs::VersionHash,
+    app::register::{Action, Entry, EntryHash, User},
+    resolver::Range,
+    ContentType, DataType, Error, Result, Safe, SafeUrl, XorUrl,
 };
 use bytes::{Buf, Bytes};
 use file_system::{
     file_system_dir_walk, file_system_single_file, normalise_path_separator, upload_file_to_net,
 };
 use files_map::add_or_update_file_item;
+use files_map_tree::deserialise_files_map_root;
 use log::{debug, info, warn};
 use relative_path::RelativePath;
-use sn_client::Client;
+use sn_client::{Client, UploadJournal};
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     iter::FromIterator,
     path::{Path, PathBuf},
     str,
@@ -61,7 +66,7 @@ impl Safe {
     pub async fn files_container_create(&self) -> Result<XorUrl> {
         // Build a Register creation operation
         let xorurl = self
-            .register_create(None, FILES_CONTAINER_TYPE_TAG, ContentType::FilesContainer)
+            .register_create_public(None, FILES_CONTAINER_TYPE_TAG, ContentType::FilesContainer)
             .await?;
 
         Ok(xorurl)
@@ -182,32 +187,22 @@ impl Safe {
                 err => Error::NetDataError(format!("Failed to get current version: {}", err)),
             })?;
 
-        // take the 1st entry (TODO Multiple entries)
         debug!(
             "Retrieved {} entries for register at {}",
             entries.len(),
             safe_url.to_string()
         );
-        if entries.len() > 1 {
-            return Err(Error::NotImplementedError("Multiple file container entries not managed, this happends when 2 clients write concurrently to a file container".to_string()));
-        }
-        let first_entry = entries.iter().next();
-        let (version, files_map_xorurl) = if let Some((v, m)) = first_entry {
-            (v.into(), str::from_utf8(m)?)
+        let (version, files_map) = if entries.len() > 1 {
+            // two clients wrote concurrently to the FilesContainer so let's merge the branches
+            self.merge_files_container_branches(safe_url, entries)
+                .await?
+        } else if let Some((hash, entry)) = entries.iter().next() {
+            (hash.into(), self.fetch_files_map(entry).await?)
         } else {
             warn!("FilesContainer found at \"{:?}\" was empty", safe_url);
             return Ok(None);
         };
 
-        // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
-        let files_map_url = SafeUrl::from_xorurl(files_map_xorurl)?;
-        let serialised_files_map = self.fetch_data(&files_map_url, None).await?;
-        let files_map = serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
-            Error::ContentError(format!(
-                "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
-                err
-            ))
-        })?;
         debug!("Files map retrieved.... {:?}", &version);
 
         Ok(Some((version, files_map)))
@@ -240,6 +235,63 @@ impl Safe {
         follow_links: bool,
         delete: bool,
         update_nrs: bool,
+    ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
+        self.sync_files_container(
+            location,
+            url,
+            recursive,
+            follow_links,
+            delete,
+            update_nrs,
+            &[],
+        )
+        .await
+    }
+
+    /// # Sync up local folder with the content on a `FilesContainer`, removing some paths from it.
+    ///
+    /// The local folder is synced up recursively, and then the paths are removed, each paired with
+    /// whether it's to be removed recursively, as with `files_container_remove_path`. All the
+    /// changes are written as a single new version of the `FilesContainer`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   let safe = Safe::connected(None, None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, false).await.unwrap();
+    ///     let removals = [("/test.md".to_string(), false)];
+    ///     let (optional_version_map, new_processed_files) = safe.files_container_sync_with_removals("./testdata/subfolder/", &xorurl, &removals, false).await.unwrap();
+    ///     if let Some((version, new_files_map)) = optional_version_map {
+    ///         println!("FilesContainer is now at version: {}", version);
+    ///         println!("The files that were synced up and removed are: {:?}", new_processed_files);
+    ///         println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_sync_with_removals<P: AsRef<Path>>(
+        &self,
+        location: P,
+        url: &str,
+        removals: &[(String, bool)],
+        update_nrs: bool,
+    ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
+        self.sync_files_container(location, url, true, false, false, update_nrs, removals)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn sync_files_container<P: AsRef<Path>>(
+        &self,
+        location: P,
+        url: &str,
+        recursive: bool,
+        follow_links: bool,
+        delete: bool,
+        update_nrs: bool,
+        removals: &[(String, bool)],
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
         if delete && !recursive {
             return Err(Error::InvalidInput(
@@ -276,7 +328,7 @@ impl Safe {
 
         let dst_path = Path::new(safe_url.path());
 
-        let (processed_files, new_files_map, success_count) = files_map_sync(
+        let (mut processed_files, mut new_files_map, mut success_count) = files_map_sync(
             self,
             current_files_map,
             location.as_ref(),
@@ -289,6 +341,14 @@ impl Safe {
         )
         .await?;
 
+        for (path, recursive) in removals {
+            let (removed_files, files_map, removed_count) =
+                files_map_remove_path(Path::new(path), new_files_map, *recursive)?;
+            processed_files.extend(removed_files);
+            new_files_map = files_map;
+            success_count += removed_count;
+        }
+
         self.update_files_container(
             success_count,
             current_version,
@@ -620,6 +680,23 @@ impl Safe {
                 bytes.len()
             );
             Client::calculate_address(bytes)?
+        } else if let Some(journals_dir) = &self.upload_journals_dir {
+            // journals are named after the content, so any later attempt
+            // to upload the same content picks up where this one left off
+            let journal_path = journals_dir.join(hex::encode(XorName::from_content(&bytes)));
+            let mut journal = UploadJournal::open(&journal_path).await?;
+            debug!(
+                "Storing {} bytes of data, with {} chunk/s already stored as per journal at {}",
+                bytes.len(),
+                journal.acked_count(),
+                journal_path.display()
+            );
+            let client = self.get_safe_client()?;
+            let address = client
+                .upload_and_verify_with_journal(bytes, &mut journal)
+                .await?;
+            journal.remove().await?;
+            address
         } else {
             debug!("Storing {} bytes of data", bytes.len());
             let client = self.get_safe_client()?;
@@ -684,10 +761,106 @@ impl Safe {
         Ok(data)
     }
 
+    // Private helper to fetch and deserialise the FilesMap a FilesContainer entry links to,
+    // which is either the root node of the tree the FilesMap was stored as, or the whole
+    // FilesMap serialised as a flat JSON as it was stored by earlier versions.
+    async fn fetch_files_map(&self, entry: &Entry) -> Result<FilesMap> {
+        let files_map_xorurl = str::from_utf8(entry)?;
+        let files_map_url = SafeUrl::from_xorurl(files_map_xorurl)?;
+        let serialised_files_map = self.fetch_data(&files_map_url, None).await?;
+        if let Some(root) = deserialise_files_map_root(serialised_files_map.chunk())? {
+            return self.fetch_files_map_tree(root).await;
+        }
+
+        serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
+            Error::ContentError(format!(
+                "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
+                err
+            ))
+        })
+    }
+
+    // Private helper to merge the concurrent versions of a FilesContainer, i.e. the branches
+    // of its Register, against the version they have in common. The merged FilesMap is written
+    // as a new version which replaces all branches if we are allowed to write on the container,
+    // otherwise it's only returned, along with the greatest of the branches' versions.
+    async fn merge_files_container_branches(
+        &self,
+        safe_url: &SafeUrl,
+        entries: BTreeSet<(EntryHash, Entry)>,
+    ) -> Result<(VersionHash, FilesMap)> {
+        let address = self.get_register_address(safe_url)?;
+        let client = self.get_safe_client()?;
+
+        let mut branches = BTreeMap::new();
+        let mut common_ancestors: Option<Vec<(EntryHash, Entry)>> = None;
+        for (hash, entry) in &entries {
+            let _ = branches.insert(VersionHash::from(hash), self.fetch_files_map(entry).await?);
+
+            let ancestors = client
+                .get_register_entry_ancestors(address, *hash)
+                .await
+                .map_err(|err| {
+                    Error::NetDataError(format!(
+                        "Failed to get the history of the FilesContainer branch '{}': {:?}",
+                        VersionHash::from(hash),
+                        err
+                    ))
+                })?;
+            common_ancestors = Some(match common_ancestors {
+                None => ancestors,
+                Some(common) => common
+                    .into_iter()
+                    .filter(|(ancestor, _)| ancestors.iter().any(|(h, _)| h == ancestor))
+                    .collect(),
+            });
+        }
+
+        // ancestors are in causal order, so the last one in common is the merge base
+        let base = match common_ancestors.and_then(|mut common| common.pop()) {
+            Some((_, entry)) => self.fetch_files_map(&entry).await?,
+            None => FilesMap::default(),
+        };
+        info!(
+            "Merging {} concurrent versions of FilesContainer at {}",
+            branches.len(),
+            safe_url
+        );
+        let merged = files_map::merge_files_maps(&base, &branches, self.keep_merge_conflicts);
+
+        let latest = branches.keys().next_back().copied().ok_or_else(|| {
+            Error::ContentError("No FilesContainer versions to merge".to_string())
+        })?;
+        let policy = client.get_register_policy(address).await?;
+        let user = User::Key(client.public_key());
+        if policy.is_action_allowed(user, Action::Write).is_err() {
+            warn!(
+                "Merged versions of FilesContainer at {} not written as we are not allowed to",
+                safe_url
+            );
+            return Ok((latest, merged));
+        }
+
+        let files_map_xorurl = self.store_files_map(&merged).await?;
+        let entry = files_map_xorurl.as_bytes().to_vec();
+        let parents = entries.iter().map(|(hash, _)| *hash).collect();
+        let (entry_hash, reg_op) = client
+            .write_to_local_register(address, entry, parents)
+            .await?;
+        client.publish_register_ops(reg_op).await?;
+
+        Ok((VersionHash::from(&entry_hash), merged))
+    }
+
     // Private helper to serialise a FilesMap and store it in a file
     async fn store_files_map(&self, files_map: &FilesMap) -> Result<String> {
-        // The FilesMapContainer is a Register where each NRS Map version is
-        // an entry containing the XOR-URL of the file that contains the serialised NrsMap.
+        // The FilesContainer is a Register where each version is an entry containing the XOR-URL
+        // of the root node of the tree the FilesMap is stored as, with a node per directory.
+        if let Some(root_xorurl) = self.store_files_map_tree(files_map).await? {
+            return Ok(root_xorurl);
+        }
+
+        // Some of the paths can't be stored in a tree, so let's store it as a flat JSON
         let serialised_files_map = serde_json::to_string(&files_map).map_err(|err| {
             Error::Serialisation(format!(
                 "Couldn't serialise the FilesMap generated: {:?}",
@@ -1856,6 +2029,57 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_container_sync_with_removals() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = new_files_container_from_testdata(&safe).await?;
+
+        let _ = safe.fetch(&xorurl, None).await;
+        let (version0, _) = safe
+            .files_container_get(&xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        let removals = [
+            ("/test.md".to_string(), false),
+            ("/subfolder".to_string(), true),
+        ];
+        let (version1_content, new_processed_files) = safe
+            .files_container_sync_with_removals("./testdata/subfolder/", &xorurl, &removals, false)
+            .await?;
+        let (version1, new_files_map) =
+            version1_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        // all the changes were made in a single version
+        assert_ne!(version1, version0);
+        let (latest_version, _) = safe
+            .files_container_get(&xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(latest_version, version1);
+
+        let file_path1 = Path::new("/test.md");
+        assert!(new_processed_files[file_path1].is_removed());
+        assert_eq!(
+            new_processed_files[file_path1].link(),
+            Some(&files_map[&file_path1.display().to_string()][PREDICATE_LINK])
+        );
+        let file_path2 = Path::new("/subfolder/subexists.md");
+        assert!(new_processed_files[file_path2].is_removed());
+        assert!(!new_files_map.contains_key("/test.md"));
+        assert!(!new_files_map.contains_key("/subfolder/subexists.md"));
+
+        // while the synced file was added
+        let filename3 = Path::new("./testdata/subfolder/subexists.md");
+        assert!(new_processed_files[filename3].is_added());
+        assert_eq!(
+            new_processed_files[filename3].link(),
+            Some(&new_files_map["/subexists.md"][PREDICATE_LINK])
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_sync_delete_without_recursive() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -2144,6 +2368,97 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_container_get_merges_concurrent_versions() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = new_files_container_from_testdata(&safe).await?;
+        let (version0, _) = safe
+            .files_container_get(&xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        // write two versions concurrently on top of the same version,
+        // one removing a file and the other one adding a new one
+        let mut removed_files_map = files_map.clone();
+        let _ = removed_files_map.remove("/test.md");
+        let mut added_files_map = files_map.clone();
+        let _ = added_files_map.insert("/new.md".to_string(), files_map["/another.md"].clone());
+
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(None);
+        let address = safe.get_register_address(&safe_url)?;
+        let client = safe.get_safe_client()?;
+        for branch_files_map in [&removed_files_map, &added_files_map] {
+            let entry = safe.store_files_map(branch_files_map).await?.into_bytes();
+            let parents = BTreeSet::from([version0.entry_hash()]);
+            let (_, reg_op) = client
+                .write_to_local_register(address, entry, parents)
+                .await?;
+            client.publish_register_ops(reg_op).await?;
+        }
+
+        let (merged_version, merged_files_map) = safe
+            .files_container_get(&safe_url.to_string())
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        let mut expected_files_map = added_files_map;
+        let _ = expected_files_map.remove("/test.md");
+        assert_eq!(merged_files_map, expected_files_map);
+
+        // the merge was written as a new version which replaces both branches
+        let (latest_version, latest_files_map) = safe
+            .files_container_get(&safe_url.to_string())
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(latest_version, merged_version);
+        assert_eq!(latest_files_map, expected_files_map);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_get_legacy_files_map() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = new_files_container_from_testdata(&safe).await?;
+
+        // the FilesMap is stored as a tree of nodes
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(None);
+        let entries = safe.register_fetch_entries(&safe_url).await?;
+        let (version0, entry) = entries
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        let root_url = SafeUrl::from_xorurl(str::from_utf8(entry)?)?;
+        let root = safe.fetch_data(&root_url, None).await?;
+        assert!(files_map_tree::deserialise_files_map_root(root.chunk())?.is_some());
+
+        // a FilesMap stored in the legacy flat format can still be read
+        let serialised_files_map = serde_json::to_string(&files_map)?;
+        let legacy_xorurl = safe
+            .store_bytes(Bytes::from(serialised_files_map), None)
+            .await?;
+        let address = safe.get_register_address(&safe_url)?;
+        let client = safe.get_safe_client()?;
+        let (_, reg_op) = client
+            .write_to_local_register(
+                address,
+                legacy_xorurl.into_bytes(),
+                BTreeSet::from([*version0]),
+            )
+            .await?;
+        client.publish_register_ops(reg_op).await?;
+
+        let (_, fetched_files_map) = safe
+            .files_container_get(&safe_url.to_string())
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(fetched_files_map, files_map);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_version() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -33,12 +33,18 @@ mod helpers;
 pub mod test_helpers;
 
 use super::{common, constants, Error, Result};
+use wallet::DEFAULT_DBC_RING_SIZE;
 
 use sn_client::Client;
 use sn_dbc::Owner;
 use sn_interface::types::Keypair;
 
-use std::time::Duration;
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tracing::debug;
 
 const APP_NOT_CONNECTED: &str = "Application is not connected to the network";
@@ -48,6 +54,20 @@ pub struct Safe {
     client: Option<Client>,
     pub xorurl_base: XorUrlBase,
     pub dry_run_mode: bool,
+    /// Directory where uploads of content are journaled, so any upload which is
+    /// interrupted is resumed, rather than started from scratch, when attempted again.
+    /// Uploads are not journaled if it's not set.
+    pub upload_journals_dir: Option<PathBuf>,
+    /// Number of members of the ring each input DBC is mixed in when it's spent, i.e. the DBC
+    /// itself plus `dbc_ring_size - 1` decoys, sampled from the outputs of transactions spent on
+    /// the network. Reissuing fails if not enough of them can be sampled.
+    pub dbc_ring_size: usize,
+    /// Whether to keep the losing versions of files changed concurrently on different versions
+    /// of a FilesContainer when merging them, as copies renamed with a '.conflict-<hash>' suffix.
+    pub keep_merge_conflicts: bool,
+    /// Addresses of the nodes of FilesMap trees known to be stored on the network, so they are
+    /// not uploaded again when storing a new version of a FilesMap which shares them.
+    stored_files_map_nodes: Arc<Mutex<HashSet<XorName>>>,
 }
 
 impl Safe {
@@ -57,6 +77,10 @@ impl Safe {
             client: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: true,
+            upload_journals_dir: None,
+            dbc_ring_size: DEFAULT_DBC_RING_SIZE,
+            keep_merge_conflicts: false,
+            stored_files_map_nodes: Arc::default(),
         }
     }
 
@@ -71,6 +95,10 @@ impl Safe {
             client: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: false,
+            upload_journals_dir: None,
+            dbc_ring_size: DEFAULT_DBC_RING_SIZE,
+            keep_merge_conflicts: false,
+            stored_files_map_nodes: Arc::default(),
         };
 
         safe.connect(keypair, timeout, dbc_owner).await?;
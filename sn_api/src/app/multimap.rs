@@ -23,16 +23,36 @@ pub type MultimapValue = Vec<u8>;
 pub type MultimapKeyValue = (MultimapKey, MultimapValue);
 pub type Multimap = BTreeSet<(EntryHash, MultimapKeyValue)>;
 
-const MULTIMAP_REMOVED_MARK: &[u8] = b"";
+pub(crate) const MULTIMAP_REMOVED_MARK: &[u8] = b"";
 
 impl Safe {
-    /// Create a Multimap on the network
+    /// Create a private Multimap on the network, whose entries only we can read
     pub async fn multimap_create(&self, name: Option<XorName>, type_tag: u64) -> Result<XorUrl> {
         debug!("Creating a Multimap");
         self.register_create(name, type_tag, ContentType::Multimap)
             .await
     }
 
+    /// Create a public Multimap on the network, which anyone can read
+    pub async fn multimap_create_public(
+        &self,
+        name: Option<XorName>,
+        type_tag: u64,
+    ) -> Result<XorUrl> {
+        debug!("Creating a public Multimap");
+        self.register_create_public(name, type_tag, ContentType::Multimap)
+            .await
+    }
+
+    /// Return all the current key-value pairs of a Multimap on the network, i.e. those which
+    /// haven't been removed or replaced
+    pub async fn multimap_read(&self, url: &str) -> Result<Multimap> {
+        debug!("Reading Multimap at: {}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+
+        self.fetch_multimap(&safeurl).await
+    }
+
     /// Return the value of a Multimap on the network corresponding to the key provided
     pub async fn multimap_get_by_key(&self, url: &str, key: &[u8]) -> Result<Multimap> {
         debug!("Getting value by key from Multimap at: {}", url);
@@ -53,6 +73,40 @@ impl Safe {
         self.fetch_multimap_value_by_hash(&safeurl, hash).await
     }
 
+    /// Return all the entries ever inserted into a Multimap on the network, in the order they were
+    /// inserted, including those which have been removed or replaced since.
+    /// Tombstones (deletion markers) written when removing entries are returned with no key-value.
+    pub async fn multimap_history(
+        &self,
+        url: &str,
+    ) -> Result<Vec<(EntryHash, Option<MultimapKeyValue>)>> {
+        debug!("Getting history of Multimap at: {}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+
+        let entries = match self.register_fetch_history(&safeurl).await {
+            Err(Error::ContentNotFound(_)) => Err(Error::ContentNotFound(format!(
+                "No Multimap found at \"{}\"",
+                safeurl
+            ))),
+            Err(Error::AccessDenied(_)) => Err(Error::AccessDenied(format!(
+                "Couldn't read Multimap found at \"{}\"",
+                safeurl
+            ))),
+            other => other,
+        }?;
+
+        entries
+            .into_iter()
+            .map(|(hash, entry)| {
+                if entry == MULTIMAP_REMOVED_MARK {
+                    Ok((hash, None))
+                } else {
+                    Self::decode_multimap_entry(&entry).map(|key_val| (hash, Some(key_val)))
+                }
+            })
+            .collect()
+    }
+
     /// Fetch a multimap without resolving the URL, then filter it for all values matching a key.
     ///
     /// The filtered result is a Multimap itself.
@@ -223,7 +277,7 @@ impl Safe {
         }
     }
 
-    fn decode_multimap_entry(entry: &[u8]) -> Result<MultimapKeyValue> {
+    pub(crate) fn decode_multimap_entry(entry: &[u8]) -> Result<MultimapKeyValue> {
         rmp_serde::from_slice(entry)
             .map_err(|err| Error::ContentError(format!("Couldn't parse Multimap entry: {:?}", err)))
     }
@@ -290,6 +344,40 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_multimap_history() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let key_val = (b"key".to_vec(), b"value".to_vec());
+        let key_val2 = (b"key".to_vec(), b"value2".to_vec());
+
+        let xorurl = safe.multimap_create(None, 25_000).await?;
+
+        let hash = safe
+            .multimap_insert(&xorurl, key_val.clone(), BTreeSet::new())
+            .await?;
+        let hash2 = safe
+            .multimap_insert(&xorurl, key_val2.clone(), vec![hash].into_iter().collect())
+            .await?;
+        let tombstone_hash = safe
+            .multimap_remove(&xorurl, vec![hash2].into_iter().collect())
+            .await?;
+
+        let received_data = safe.multimap_get_by_key(&xorurl, &key_val.0).await?;
+        assert!(received_data.is_empty());
+
+        let history = safe.multimap_history(&xorurl).await?;
+        assert_eq!(
+            history,
+            vec![
+                (hash, Some(key_val)),
+                (hash2, Some(key_val2)),
+                (tombstone_hash, None)
+            ]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_multimap_get_by_hash() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -10,7 +10,7 @@ mod nrs_map;
 
 pub use crate::app::multimap::Multimap;
 pub use crate::safeurl::{ContentType, DataType, VersionHash};
-pub use nrs_map::NrsMap;
+pub use nrs_map::{NrsConflict, NrsMap};
 
 use crate::{app::Safe, register::EntryHash, Error, Result, SafeUrl};
 
@@ -44,7 +44,7 @@ impl Safe {
         }
 
         let _ = self
-            .multimap_create(Some(nrs_xorname), NRS_MAP_TYPE_TAG)
+            .multimap_create_public(Some(nrs_xorname), NRS_MAP_TYPE_TAG)
             .await?;
 
         Ok(nrs_url)
@@ -175,9 +175,12 @@ impl Safe {
         // get nrs_map, ignoring conflicting entries if they are not the ones we're getting
         let nrs_map = match self.nrs_get_subnames_map(public_name, version).await {
             Ok(result) => Ok(result),
-            Err(Error::ConflictingNrsEntries(str, conflicting_entries, map)) => {
-                if conflicting_entries.iter().any(|(p, _)| p == public_name) {
-                    Err(Error::ConflictingNrsEntries(str, conflicting_entries, map))
+            Err(Error::ConflictingNrsEntries(str, conflicts, map)) => {
+                if conflicts
+                    .iter()
+                    .any(|conflict| conflict.public_name == public_name)
+                {
+                    Err(Error::ConflictingNrsEntries(str, conflicts, map))
                 } else {
                     Ok(map)
                 }
@@ -190,6 +193,10 @@ impl Safe {
     }
 
     /// Get the mapping of all subNames and their associated `SafeUrl` for the Nrs Map Container at the given public name
+    ///
+    /// The entries of all branches of the Nrs Map Container are merged by subname. If any of
+    /// the subnames was associated to different links concurrently, a `ConflictingNrsEntries`
+    /// error is returned with those conflicts along with the `NrsMap` of all other subnames.
     pub async fn nrs_get_subnames_map(
         &self,
         public_name: &str,
@@ -223,82 +230,140 @@ impl Safe {
 
         // The set may have duplicate entries; the map doesn't.
         let subnames_set = convert_multimap_to_nrs_set(&multimap, public_name, version)?;
-        let nrs_map = get_nrs_map_from_set(&subnames_set)?;
+        let conflicts = get_nrs_conflicts(&subnames_set);
+        let mut nrs_map = get_nrs_map_from_set(&subnames_set)?;
 
-        if nrs_map.map.len() != subnames_set.len() {
-            let diff_set: BTreeSet<(String, SafeUrl)> = nrs_map.map.clone().into_iter().collect();
-            let conflicting_entries: Vec<(String, SafeUrl)> =
-                subnames_set.difference(&diff_set).cloned().collect();
+        if !conflicts.is_empty() {
+            for conflict in &conflicts {
+                let _ = nrs_map.map.remove(&conflict.public_name);
+            }
             return Err(Error::ConflictingNrsEntries(
                 "Found multiple entries for the same name. This happens when 2 clients write \
-                concurrently to the same NRS mapping. It can be fixed by associating a new link to \
+                concurrently to the same NRS mapping. It can be fixed by choosing one of the \
+                conflicting links with `nrs_resolve_conflict`, or by associating a new link to \
                 the conflicting names."
                     .to_string(),
-                conflicting_entries,
+                conflicts,
                 nrs_map,
             ));
         }
         Ok(nrs_map)
     }
+
+    /// # Gets the conflicts of an NRS Map Container
+    /// Returns the public names of the NRS Map Container registered for the given public name's
+    /// top name which were associated to different links concurrently, along with those links.
+    pub async fn nrs_get_conflicts(&self, public_name: &str) -> Result<Vec<NrsConflict>> {
+        match self.nrs_get_subnames_map(public_name, None).await {
+            Ok(_) => Ok(vec![]),
+            Err(Error::ConflictingNrsEntries(_, conflicts, _)) => Ok(conflicts),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// # Resolves a conflict on a public name
+    /// Associates the given `public_name` to the link it was associated to by the entry with
+    /// the given `version`, which has to be one of the conflicting entries of the public name.
+    /// The new entry replaces all the conflicting ones.
+    /// Returns the versioned NRS `SafeUrl` (containing a `VersionHash`) now pointing to the link:
+    /// `safe://{public_name}?v={version_hash}`
+    pub async fn nrs_resolve_conflict(
+        &self,
+        public_name: &str,
+        version: VersionHash,
+    ) -> Result<SafeUrl> {
+        info!(
+            "Resolving conflict on public name \"{}\" with version {}",
+            public_name, version
+        );
+
+        let mut url = validate_nrs_public_name(public_name)?;
+        let conflict = self
+            .nrs_get_conflicts(public_name)
+            .await?
+            .into_iter()
+            .find(|conflict| conflict.public_name == public_name)
+            .ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "There are no conflicting links for public name \"{}\"",
+                    public_name
+                ))
+            })?;
+        let link = conflict.links.get(&version).ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "Version '{}' is not one of the conflicting entries of public name \"{}\"",
+                version, public_name
+            ))
+        })?;
+
+        let entry = (
+            public_name.as_bytes().to_vec(),
+            link.to_string().as_bytes().to_vec(),
+        );
+        let replace = conflict
+            .links
+            .keys()
+            .map(|version| version.entry_hash())
+            .collect();
+        let entry_hash = self
+            .multimap_insert(&url.to_string(), entry, replace)
+            .await?;
+        set_nrs_url_props(&mut url, entry_hash)?;
+
+        Ok(url)
+    }
 }
 
-/// Converts the Multimap to a set, which may contain duplicate entries.
+/// Converts the Multimap to a set of versioned entries, which may contain duplicate entries.
 ///
 /// If the user has requested a specific version of a subname, only that version of it will be in
-/// the set. The 'versioned set' is queried for all entries matching the given subname, then any
-/// that *don't* match the specified version are removed.
+/// the set. Any entries for the given subname which *don't* match the specified version are removed.
 fn convert_multimap_to_nrs_set(
     multimap: &Multimap,
     public_name: &str,
     subname_version: Option<VersionHash>,
-) -> Result<BTreeSet<(String, SafeUrl)>> {
+) -> Result<BTreeSet<(VersionHash, String, SafeUrl)>> {
+    let mut versioned_set = multimap
+        .iter()
+        .map(|(hash, (key, value))| {
+            let public_name = str::from_utf8(key)?;
+            let url = SafeUrl::from_url(str::from_utf8(value)?)?;
+            Ok((VersionHash::from(hash), public_name.to_owned(), url))
+        })
+        .collect::<Result<BTreeSet<(VersionHash, String, SafeUrl)>>>()?;
+
     if let Some(version) = subname_version {
-        let mut versioned_set: BTreeSet<(VersionHash, String, SafeUrl)> = multimap
-            .clone()
-            .into_iter()
-            .map(|x| {
-                let version = VersionHash::from(&x.0);
-                let kv = x.1;
-                let public_name = str::from_utf8(&kv.0)?;
-                let url = SafeUrl::from_url(str::from_utf8(&kv.1)?)?;
-                Ok((version, public_name.to_owned(), url))
-            })
-            .collect::<Result<BTreeSet<(VersionHash, String, SafeUrl)>>>()?;
-        let duplicate_entries = versioned_set
-            .clone()
-            .into_iter()
-            .filter(|x| x.1 == public_name)
-            .filter(|x| x.0 != version)
-            .collect::<BTreeSet<(VersionHash, String, SafeUrl)>>();
-        for entry in &duplicate_entries {
-            versioned_set.remove(entry);
-        }
-        let set: BTreeSet<(String, SafeUrl)> = versioned_set
-            .iter()
-            .map(|x| (x.1.clone(), x.2.clone()))
-            .collect::<BTreeSet<(String, SafeUrl)>>();
-        return Ok(set);
+        versioned_set.retain(|(v, name, _)| name != public_name || *v == version);
+    }
+
+    Ok(versioned_set)
+}
+
+/// Finds the public names associated to more than one distinct link in the set.
+fn get_nrs_conflicts(set: &BTreeSet<(VersionHash, String, SafeUrl)>) -> Vec<NrsConflict> {
+    let mut links_by_name = BTreeMap::<&String, BTreeMap<VersionHash, SafeUrl>>::new();
+    for (version, public_name, url) in set {
+        let _ = links_by_name
+            .entry(public_name)
+            .or_default()
+            .insert(*version, url.clone());
     }
 
-    let set: BTreeSet<(String, SafeUrl)> = multimap
-        .clone()
+    links_by_name
         .into_iter()
-        .map(|x| {
-            let kv = x.1;
-            let public_name = str::from_utf8(&kv.0)?;
-            let url = SafeUrl::from_url(str::from_utf8(&kv.1)?)?;
-            Ok((public_name.to_owned(), url))
+        .filter(|(_, links)| links.values().collect::<BTreeSet<_>>().len() > 1)
+        .map(|(public_name, links)| NrsConflict {
+            public_name: public_name.clone(),
+            links,
         })
-        .collect::<Result<BTreeSet<(String, SafeUrl)>>>()?;
-    Ok(set)
+        .collect()
 }
 
-fn get_nrs_map_from_set(set: &BTreeSet<(String, SafeUrl)>) -> Result<NrsMap> {
+fn get_nrs_map_from_set(set: &BTreeSet<(VersionHash, String, SafeUrl)>) -> Result<NrsMap> {
     // Duplicate entries are automatically removed from the set -> map conversion.
     let public_names_map: BTreeMap<String, SafeUrl> = set
-        .clone()
-        .into_iter()
-        .map(|x| (x.0, x.1))
+        .iter()
+        .map(|x| (x.1.clone(), x.2.clone()))
         .collect::<BTreeMap<String, SafeUrl>>();
     let nrs_map = NrsMap {
         map: public_names_map,
@@ -350,7 +415,7 @@ fn validate_nrs_url(link: &SafeUrl) -> Result<()> {
                 "{} content is versionable. NRS requires the supplied link to specify a version hash.",
                 content_type
             )));
-        } else if data_type == DataType::Register {
+        } else if matches!(data_type, DataType::Register | DataType::PrivateRegister) {
             return Err(Error::UnversionedContentError(format!(
                 "{} content is versionable. NRS requires the supplied link to specify a version hash.",
                 data_type
@@ -564,7 +629,7 @@ mod tests {
         let safe = new_safe_instance().await?;
 
         let register_link = safe
-            .register_create(None, NRS_MAP_TYPE_TAG, ContentType::Raw)
+            .register_create_public(None, NRS_MAP_TYPE_TAG, ContentType::Raw)
             .await?;
         let mut register_url = SafeUrl::from_xorurl(&register_link)?;
         register_url.set_content_version(None);
@@ -784,16 +849,23 @@ mod tests {
 
         debug!("--------------------------------------->44444");
         // check for the error content
-        if let Err(Error::ConflictingNrsEntries(_, dups, _)) = conflict_error {
-            let got_entries: Result<()> = dups.into_iter().try_for_each(|(public_name, url)| {
-                assert_eq!(public_name, site_name, "problematic names match");
-                assert!(
-                    url == valid_link || url == another_valid_url,
-                    "theres a url conflict"
-                );
-                Ok(())
-            });
-            assert!(got_entries.is_ok(), "we have some entries");
+        if let Err(Error::ConflictingNrsEntries(_, conflicts, nrs_map)) = conflict_error {
+            assert_eq!(conflicts.len(), 1, "only one name is in conflict");
+            assert_eq!(
+                conflicts[0].public_name, site_name,
+                "problematic names match"
+            );
+            assert!(
+                conflicts[0]
+                    .links
+                    .values()
+                    .all(|url| url == &valid_link || url == &another_valid_url),
+                "theres a url conflict"
+            );
+            assert!(
+                !nrs_map.map.contains_key(&site_name),
+                "the conflicting name is not in the map"
+            );
         }
 
         // resolve the error
@@ -815,6 +887,96 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_nrs_conflicts() -> Result<()> {
+        let version = |byte| VersionHash::from(&EntryHash([byte; 32]));
+        let link_a = SafeUrl::from_url("safe://a.example")?;
+        let link_b = SafeUrl::from_url("safe://b.example")?;
+        let set = BTreeSet::from([
+            (version(1), "a.example".to_string(), link_a.clone()),
+            (version(2), "a.example".to_string(), link_b.clone()),
+            // the same link on different versions is not a conflict
+            (version(3), "b.example".to_string(), link_b.clone()),
+            (version(4), "b.example".to_string(), link_b.clone()),
+        ]);
+
+        let conflicts = get_nrs_conflicts(&set);
+        assert_eq!(
+            conflicts,
+            vec![NrsConflict {
+                public_name: "a.example".to_string(),
+                links: BTreeMap::from([(version(1), link_a), (version(2), link_b)]),
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nrs_resolve_conflict() -> Result<()> {
+        let site_name = random_nrs_name();
+        let safe = new_safe_instance().await?;
+
+        let files_container =
+            TestDataFilesContainer::get_container(["/testdata/test.md", "/testdata/another.md"])
+                .await?;
+        let public_name = format!("test.{site_name}");
+        safe.nrs_create(&site_name).await?;
+        let _ = safe
+            .nrs_associate(&public_name, &files_container["/testdata/test.md"])
+            .await?;
+
+        // there is nothing to resolve yet
+        assert!(safe.nrs_get_conflicts(&public_name).await?.is_empty());
+
+        // concurrently associate the name to another link
+        let url = validate_nrs_top_name(&site_name)?;
+        let link = files_container["/testdata/another.md"].clone();
+        let entry = (
+            public_name.as_bytes().to_vec(),
+            link.to_string().as_bytes().to_vec(),
+        );
+        let _ = safe
+            .multimap_insert(&url.to_string(), entry, BTreeSet::new())
+            .await?;
+
+        let conflicts = safe.nrs_get_conflicts(&public_name).await?;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].public_name, public_name);
+        assert_eq!(conflicts[0].links.len(), 2);
+        let version = conflicts[0]
+            .links
+            .iter()
+            .find(|(_, url)| **url == link)
+            .map(|(version, _)| *version)
+            .ok_or_else(|| anyhow!("the new link should be in conflict"))?;
+
+        // a version which is not in conflict cannot be chosen
+        let random_version = VersionHash::from(&EntryHash(rand::random()));
+        assert_matches!(
+            safe.nrs_resolve_conflict(&public_name, random_version)
+                .await,
+            Err(Error::InvalidInput(_))
+        );
+
+        let resolved_url = safe.nrs_resolve_conflict(&public_name, version).await?;
+        assert_eq!(resolved_url.public_name(), public_name);
+        assert!(safe.nrs_get_conflicts(&public_name).await?.is_empty());
+
+        let (res_url, _) = safe.nrs_get(&public_name, None).await?;
+        assert_eq!(
+            res_url.ok_or_else(|| anyhow!("url should not be None"))?,
+            link
+        );
+
+        // there is nothing else to resolve
+        assert_matches!(
+            safe.nrs_resolve_conflict(&public_name, version).await,
+            Err(Error::InvalidInput(_))
+        );
+
+        Ok(())
+    }
+
     /// The scenario here is:
     /// * Register a topname
     /// * Associate a 'test' subname 3 times with different links
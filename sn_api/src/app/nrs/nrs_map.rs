@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{Error, Result, SafeUrl};
+use crate::{Error, Result, SafeUrl, VersionHash};
 use log::debug;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -35,6 +35,14 @@ pub struct NrsMap {
     pub map: BTreeMap<PublicName, SafeUrl>,
 }
 
+/// A public name which was associated to different links concurrently, i.e. by different
+/// branches of the NRS Map Container, along with the version of each of the entries.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct NrsConflict {
+    pub public_name: PublicName,
+    pub links: BTreeMap<VersionHash, SafeUrl>,
+}
+
 impl NrsMap {
     /// Get the `SafeUrl` associated with the given public name.
     ///
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-pub use sn_interface::types::register::{Entry, EntryHash};
+pub use sn_interface::types::register::{Action, Entry, EntryHash, Permissions, Policy, User};
 
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
@@ -14,10 +14,7 @@ use crate::{Error, Result, Safe};
 use sn_client::Error as ClientError;
 use sn_interface::{
     messaging::data::Error as ErrorMsg,
-    types::{
-        register::{Permissions, Policy, User},
-        DataAddress, Error as SafeNdError, RegisterAddress,
-    },
+    types::{DataAddress, Error as SafeNdError, RegisterAddress},
 };
 
 use log::debug;
@@ -28,22 +25,50 @@ use xor_name::XorName;
 
 impl Safe {
     // === Register data operations ===
-    /// Create a Register on the network
+    /// Create a private Register on the network, whose entries are encrypted
+    /// so only the client which writes them can read them back
     pub async fn register_create(
         &self,
         name: Option<XorName>,
         tag: u64,
         content_type: ContentType,
+    ) -> Result<XorUrl> {
+        self.register_create_with_privacy(name, tag, content_type, true)
+            .await
+    }
+
+    /// Create a public Register on the network, which anyone can read
+    pub async fn register_create_public(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        content_type: ContentType,
+    ) -> Result<XorUrl> {
+        self.register_create_with_privacy(name, tag, content_type, false)
+            .await
+    }
+
+    async fn register_create_with_privacy(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        content_type: ContentType,
+        private: bool,
     ) -> Result<XorUrl> {
         debug!(
-            "Storing Register data with tag type: {}, xorname: {:?}, dry_run: {}",
-            tag, name, self.dry_run_mode
+            "Storing Register data with tag type: {}, xorname: {:?}, private: {}, dry_run: {}",
+            tag, name, private, self.dry_run_mode
         );
 
         let xorname = name.unwrap_or_else(xor_name::rand::random);
         info!("Xorname for new Register storage: {:?}", &xorname);
 
-        let xorurl = SafeUrl::from_register(xorname, tag, content_type)?.encode(self.xorurl_base);
+        let safeurl = if private {
+            SafeUrl::from_private_register(xorname, tag, content_type)?
+        } else {
+            SafeUrl::from_register(xorname, tag, content_type)?
+        };
+        let xorurl = safeurl.encode(self.xorurl_base);
 
         // return early if dry_run_mode
         if self.dry_run_mode {
@@ -55,15 +80,19 @@ impl Safe {
         let owner = User::Key(client.public_key());
 
         // Store the Register on the network
-        let (_, op_batch) = client
-            .create_register(xorname, tag, policy(owner))
-            .await
-            .map_err(|e| {
-                Error::NetDataError(format!(
-                    "Failed to prepare store Register operation: {:?}",
-                    e
-                ))
-            })?;
+        let result = if private {
+            client
+                .create_private_register(xorname, tag, policy(owner))
+                .await
+        } else {
+            client.create_register(xorname, tag, policy(owner)).await
+        };
+        let (_, op_batch) = result.map_err(|e| {
+            Error::NetDataError(format!(
+                "Failed to prepare store Register operation: {:?}",
+                e
+            ))
+        })?;
 
         client.publish_register_ops(op_batch).await?;
 
@@ -78,6 +107,18 @@ impl Safe {
         self.register_fetch_entries(&safeurl).await
     }
 
+    /// Read value from a Register on the network, along with the hashes of the latest entries
+    /// of a private Register which were left out as we aren't one of their readers
+    pub async fn register_read_with_skipped(
+        &self,
+        url: &str,
+    ) -> Result<(BTreeSet<(EntryHash, Entry)>, BTreeSet<EntryHash>)> {
+        debug!("Getting Register data from: {:?}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+
+        self.register_fetch_entries_with_skipped(&safeurl).await
+    }
+
     /// Read value from a Register on the network by its hash
     pub async fn register_read_entry(&self, url: &str, hash: EntryHash) -> Result<Entry> {
         debug!("Getting Register data from: {:?}", url);
@@ -86,6 +127,34 @@ impl Safe {
         self.register_fetch_entry(&safeurl, hash).await
     }
 
+    /// Read the Policy of a Register on the network, i.e. its owner and users' permissions
+    pub async fn register_policy(&self, url: &str) -> Result<Policy> {
+        debug!("Getting Register policy from: {:?}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&safeurl)?;
+        let client = self.get_safe_client()?;
+        client
+            .get_register_policy(address)
+            .await
+            .map_err(|err| match err {
+                ClientError::ErrorMsg {
+                    source: ErrorMsg::AccessDenied(_),
+                    ..
+                } => Error::AccessDenied(format!(
+                    "Couldn't read policy of Register found at \"{}\"",
+                    safeurl
+                )),
+                ClientError::ErrorMsg {
+                    source: ErrorMsg::DataNotFound(_),
+                    ..
+                } => Error::ContentNotFound(format!("No Register found at \"{}\"", safeurl)),
+                err => Error::NetDataError(format!(
+                    "Failed to read policy of Register data: {:?}",
+                    err
+                )),
+            })
+    }
+
     /// Fetch a Register from a `SafeUrl` without performing any type of URL resolution
     /// Supports version hashes:
     /// e.g. safe://mysafeurl?v=ce56a3504c8f27bfeb13bdf9051c2e91409230ea
@@ -93,6 +162,16 @@ impl Safe {
         &self,
         url: &SafeUrl,
     ) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        let (entries, _) = self.register_fetch_entries_with_skipped(url).await?;
+        Ok(entries)
+    }
+
+    // Fetch the entries of a Register as `register_fetch_entries` does, along with the hashes of
+    // the latest entries of a private Register which were left out as we can't decrypt them
+    async fn register_fetch_entries_with_skipped(
+        &self,
+        url: &SafeUrl,
+    ) -> Result<(BTreeSet<(EntryHash, Entry)>, BTreeSet<EntryHash>)> {
         debug!("Fetching Register entries from {}", url);
         let result = match url.content_version() {
             Some(v) => {
@@ -100,17 +179,23 @@ impl Safe {
                 debug!("Take entry with version hash: {:?}", hash);
                 self.register_fetch_entry(url, hash)
                     .await
-                    .map(|entry| vec![(hash, entry)].into_iter().collect())
+                    .map(|entry| (BTreeSet::from([(hash, entry)]), BTreeSet::new()))
             }
             None => {
                 debug!("No version so take latest entry from Register at: {}", url);
                 let address = self.get_register_address(url)?;
                 let client = self.get_safe_client()?;
-                match client.read_register(address).await {
-                    Ok(entry) => Ok(entry),
+                match client.read_register_with_skipped(address).await {
+                    Ok(entries) => Ok(entries),
                     Err(ClientError::NetworkDataError(SafeNdError::NoSuchEntry(_))) => Err(
                         Error::EmptyContent(format!("Empty Register found at \"{}\"", url)),
                     ),
+                    Err(ClientError::NetworkDataError(SafeNdError::EntryDecryption)) => {
+                        Err(Error::AccessDenied(format!(
+                            "Couldn't decrypt entries of private Register found at \"{}\"",
+                            url
+                        )))
+                    }
                     Err(ClientError::ErrorMsg {
                         source: ErrorMsg::AccessDenied(_),
                         ..
@@ -156,20 +241,54 @@ impl Safe {
         client
             .get_register_entry(address, hash)
             .await
-            .map_err(|err| {
-                if let ClientError::ErrorMsg {
+            .map_err(|err| match err {
+                ClientError::ErrorMsg {
                     source: sn_interface::messaging::data::Error::NoSuchEntry(_),
                     ..
-                } = err
-                {
-                    Error::HashNotFound(hash)
-                } else {
-                    Error::NetDataError(format!(
-                        "Failed to retrieve entry with hash '{}' from Register data: {:?}",
+                } => Error::HashNotFound(hash),
+                ClientError::NetworkDataError(SafeNdError::EntryDecryption) => {
+                    Error::AccessDenied(format!(
+                        "Couldn't decrypt entry with hash '{}' of private Register found at \"{}\"",
                         hex::encode(hash.0),
-                        err
+                        url
                     ))
                 }
+                err => Error::NetDataError(format!(
+                    "Failed to retrieve entry with hash '{}' from Register data: {:?}",
+                    hex::encode(hash.0),
+                    err
+                )),
+            })
+    }
+
+    /// Fetch all the entries of a Register from a `SafeUrl`, in causal order, without performing
+    /// any type of URL resolution. Entries removed from a Multimap are included.
+    pub(crate) async fn register_fetch_history(
+        &self,
+        url: &SafeUrl,
+    ) -> Result<Vec<(EntryHash, Entry)>> {
+        debug!("Fetching Register history from {}", url);
+        let address = self.get_register_address(url)?;
+        let client = self.get_safe_client()?;
+        client
+            .get_register_history(address)
+            .await
+            .map_err(|err| match err {
+                ClientError::ErrorMsg {
+                    source: ErrorMsg::AccessDenied(_),
+                    ..
+                } => Error::AccessDenied(format!(
+                    "Couldn't read history of Register found at \"{}\"",
+                    url
+                )),
+                ClientError::ErrorMsg {
+                    source: ErrorMsg::DataNotFound(_),
+                    ..
+                } => Error::ContentNotFound(format!("No Register found at \"{}\"", url)),
+                err => Error::NetDataError(format!(
+                    "Failed to read history of Register data: {:?}",
+                    err
+                )),
             })
     }
 
@@ -240,6 +359,7 @@ fn policy(owner: User) -> Policy {
 
 #[cfg(test)]
 mod tests {
+    use super::{Action, User};
     use crate::{app::test_helpers::new_safe_instance, ContentType, Error};
     use anyhow::{bail, Result};
 
@@ -265,6 +385,26 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_policy() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        let xorurl = safe.register_create(None, 25_000, ContentType::Raw).await?;
+
+        let policy = safe.register_policy(&xorurl).await?;
+        let owner = User::Key(safe.get_safe_client()?.public_key());
+
+        assert_eq!(policy.owner, owner);
+        assert_eq!(
+            policy
+                .permissions(owner)
+                .and_then(|p| p.is_allowed(Action::Write)),
+            Some(true)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_owner_permissions() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -300,4 +440,46 @@ mod tests {
         let _ = safe.register_read(&xorurl).await?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_register_private_entries() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        let xorname = xor_name::rand::random();
+        let private_xorurl = safe
+            .register_create(Some(xorname), 25_000, ContentType::Raw)
+            .await?;
+        let public_xorurl = safe
+            .register_create_public(Some(xorname), 25_000, ContentType::Raw)
+            .await?;
+        assert_ne!(private_xorurl, public_xorurl);
+
+        let data = b"private data".to_vec();
+        let hash = safe
+            .register_write(&private_xorurl, data.clone(), Default::default())
+            .await?;
+        assert_eq!(safe.register_read_entry(&private_xorurl, hash).await?, data);
+        assert_eq!(
+            safe.register_read(&private_xorurl).await?,
+            [(hash, data)].into_iter().collect()
+        );
+
+        // the public Register with the same name and tag is a different one
+        assert!(safe.register_read(&public_xorurl).await?.is_empty());
+
+        // other clients can't decrypt the entries
+        let other_safe = new_safe_instance().await?;
+        match other_safe.register_read(&private_xorurl).await {
+            Err(Error::AccessDenied(msg)) => assert_eq!(
+                msg,
+                format!(
+                    "Couldn't decrypt entries of private Register found at \"{}\"",
+                    private_xorurl
+                )
+            ),
+            other => bail!("An AccessDenied error was expected: {:?}", other),
+        }
+
+        Ok(())
+    }
 }
@@ -107,7 +107,7 @@ impl Safe {
                 self.retrieve_data(&input_url, retrieve_data, None, &metadata, range)
                     .await
             }
-            DataType::Register => {
+            DataType::Register | DataType::PrivateRegister => {
                 let data = if retrieve_data {
                     match input_url.content_version() {
                         None => self.register_fetch_entries(&input_url).await?,
@@ -10,16 +10,21 @@ pub use sn_interface::test_utils::TestSectionTree;
 
 use crate::{Safe, SafeUrl};
 
-use sn_client::utils::test_utils::read_genesis_dbc_from_first_node;
+use sn_client::utils::test_utils::{get_dbc_from_test_funds, read_genesis_dbc_from_first_node};
 use sn_dbc::{rng, Dbc, Owner, OwnerOnce, Token};
 use sn_interface::types::Keypair;
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, Context, Result};
 use async_once::AsyncOnce;
 use bls::SecretKey;
 use lazy_static::lazy_static;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use std::{collections::HashMap, env::var, ops::Index, sync::Once};
+use std::{
+    collections::{BTreeSet, HashMap},
+    env::var,
+    ops::Index,
+    sync::Once,
+};
 use tokio::sync::Mutex;
 use tracing_subscriber::{fmt, EnvFilter};
 
@@ -84,20 +89,18 @@ pub async fn get_next_bearer_dbc() -> Result<(Dbc, Token)> {
     Ok(next_dbc)
 }
 
-// Build a set of bearer DBCs with random amounts, by reissuing them from testnet genesis DBC.
+// Build a set of bearer DBCs with random amounts, by reissuing them from a DBC taken from the
+// testnet genesis DBC funds, as the genesis DBC itself was spent already by a former test run.
 async fn reissue_bearer_dbcs() -> Result<Vec<(Dbc, Token)>> {
-    let total_balance = match GENESIS_DBC.amount_secrets_bearer() {
-        Ok(amount_secrets) => amount_secrets.amount().as_nano(),
-        Err(err) => bail!("Failed to obtain genesis DBC balance: {:?}", err),
-    };
-
     let mut rng = rand::thread_rng();
     let amounts: Vec<u64> = (0..NUM_OF_DBCS_TO_REISSUE)
         .map(|_| rng.gen_range(REISSUED_DBC_MIN_BALANCE..REISSUED_DBC_MAX_BALANCE))
         .collect();
 
     let total_output_amount: u64 = amounts.iter().sum();
-    let change_amount = total_balance - total_output_amount;
+    let input_dbc = get_dbc_from_test_funds(Token::from_nano(total_output_amount))
+        .await
+        .map_err(|err| anyhow!("Failed to obtain a DBC from the test funds: {:?}", err))?;
 
     let output_amounts: Vec<(Token, OwnerOnce)> = amounts
         .into_iter()
@@ -108,14 +111,17 @@ async fn reissue_bearer_dbcs() -> Result<Vec<(Dbc, Token)>> {
         })
         .collect();
 
-    let safe = new_safe_instance().await?;
-    let (output_dbcs, _) = safe
-        .reissue_dbcs(
-            vec![GENESIS_DBC.clone()],
-            output_amounts,
-            Token::from_nano(change_amount),
-        )
-        .await?;
+    // the DBCs are only reissued to be used as inputs by the tests, so they're not mixed with decoys
+    let mut safe = new_safe_instance().await?;
+    safe.dbc_ring_size = 1;
+    let record = safe.prepare_reissue(
+        vec![input_dbc],
+        &[],
+        output_amounts,
+        Token::zero(),
+        BTreeSet::default(),
+    )?;
+    let (output_dbcs, _) = safe.spend_reissue_inputs(record).await?;
 
     Ok(output_dbcs
         .into_iter()
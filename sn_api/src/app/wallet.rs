@@ -8,17 +8,25 @@
 
 pub use sn_dbc::{self as dbc, Dbc, Token};
 
-use super::{helpers::parse_tokens_amount, register::EntryHash};
+use super::{
+    helpers::{gen_timestamp_secs, parse_tokens_amount},
+    multimap::MULTIMAP_REMOVED_MARK,
+    register::EntryHash,
+};
 use crate::{
     safeurl::{ContentType, SafeUrl, XorUrl},
     Error, Result, Safe,
 };
 use bytes::Bytes;
+use futures::future::join_all;
 use log::{debug, warn};
-use sn_client::Client;
+use serde::{Deserialize, Serialize};
+use sn_client::{Client, Error as ClientError};
 use sn_dbc::{
-    rng, AmountSecrets, Error as DbcError, Hash, KeyImage, Owner, OwnerOnce, PublicKey,
-    RingCtTransaction, SpentProof, SpentProofShare, TransactionBuilder,
+    bls_ringct::{blstrs::G1Affine, group::Curve},
+    rand::seq::SliceRandom,
+    rng, AmountSecrets, DbcBuilder, DecoyInput, Error as DbcError, Hash, KeyImage, Owner,
+    OwnerOnce, PublicKey, RingCtTransaction, SpentProof, SpentProofShare, TransactionBuilder,
 };
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
@@ -35,6 +43,86 @@ pub type WalletSpendableDbcs = BTreeMap<String, (Dbc, EntryHash)>;
 // Hence the max number of attempts set to a higher value.
 const NUM_OF_DBC_REISSUE_ATTEMPTS: u8 = 5;
 
+/// Default number of members of the ring each input DBC is mixed in when it's spent.
+pub const DEFAULT_DBC_RING_SIZE: usize = 8;
+
+// Name of the wallet entry holding the record of the reissue in progress, if there is any.
+const REISSUE_RECORD_ENTRY_NAME: &str = "reissue-in-progress";
+
+// Prefix of the name given to the change DBCs deposited in a wallet.
+const CHANGE_DBC_NAME_PREFIX: &str = "change-dbc-";
+
+// Query key of the wallet entries' URLs holding the time they were inserted at.
+const WALLET_ENTRY_TIMESTAMP_KEY: &str = "timestamp";
+
+/// Operation found in the history of a wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletHistoryEntry {
+    /// Hash of the wallet entry the operation was recorded with.
+    pub entry_hash: EntryHash,
+    /// Time the operation was recorded at, in seconds since the Unix epoch.
+    /// It's `None` for entries inserted by older versions which didn't record it.
+    pub timestamp: Option<i64>,
+    /// The operation itself.
+    pub operation: WalletOperation,
+}
+
+/// Operations recorded in a wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalletOperation {
+    /// A DBC was deposited into the wallet.
+    Deposit {
+        /// Name the DBC was deposited with.
+        name: String,
+        /// Amount of the DBC.
+        amount: Token,
+        /// Whether the DBC has been spent since.
+        spent: bool,
+    },
+    /// DBCs were reissued from the wallet.
+    Reissue {
+        /// Amount of each output DBC, along with the public key of its owner,
+        /// which is `None` for bearer DBCs.
+        outputs: Vec<(Token, Option<PublicKey>)>,
+        /// Names of the DBCs spent from the wallet.
+        inputs: Vec<String>,
+        /// Amount of the change deposited back in the wallet.
+        change: Token,
+        /// Whether the reissue was interrupted and it's yet to be resumed.
+        in_progress: bool,
+    },
+    /// The change from a reissue was deposited back into the wallet.
+    Change {
+        /// Name the change DBC was deposited with.
+        name: String,
+        /// Amount of the change DBC.
+        amount: Token,
+        /// Whether the change DBC has been spent since.
+        spent: bool,
+    },
+}
+
+/// Record of a reissue from a wallet, stored in the wallet before any of its inputs are spent,
+/// so the reissue can be resumed with the very same transaction if it gets interrupted.
+///
+/// It holds the secrets needed to spend the inputs and to own the change, thus it's stored
+/// encrypted to the DBC owner of the client, see `seal_reissue_record`.
+#[derive(Serialize, Deserialize)]
+pub(super) struct ReissueRecord {
+    // Builder of the output DBCs, with the transaction spending the inputs.
+    dbc_builder: DbcBuilder,
+    // Spent proofs and transactions of the inputs, and of their decoys.
+    spent_proofs: BTreeSet<SpentProof>,
+    spent_transactions: BTreeSet<RingCtTransaction>,
+    // Amount and owner of each output, the owner being `None` for bearer outputs.
+    outputs: Vec<(Token, Option<PublicKey>)>,
+    // Owner and amount of the change output, if there is change.
+    change_owner: Option<OwnerOnce>,
+    change_amount: Token,
+    // Entries of the input DBCs in the wallet.
+    input_entries: BTreeSet<EntryHash>,
+}
+
 /// Verifier required by sn_dbc API to check a SpentProof
 /// is signed by known sections keys.
 struct SpentProofKeyVerifier<'a> {
@@ -120,6 +208,11 @@ impl Safe {
         )?;
 
         let spendable_name = match spendable_name {
+            Some(name) if name == REISSUE_RECORD_ENTRY_NAME => {
+                return Err(Error::DbcDepositError(format!(
+                    "The name '{name}' is reserved, please choose a different name for the deposit"
+                )))
+            }
             Some(name) => name.to_string(),
             None => format!("dbc-{}", &hex::encode(dbc_to_deposit.hash())[0..8]),
         };
@@ -184,6 +277,16 @@ impl Safe {
 
     /// Fetch a wallet from a `SafeUrl` without performing any type of URL resolution
     pub(crate) async fn fetch_wallet(&self, safeurl: &SafeUrl) -> Result<WalletSpendableDbcs> {
+        let (balances, _) = self.fetch_wallet_with_reissue_record(safeurl).await?;
+        Ok(balances)
+    }
+
+    /// Fetch a wallet from a `SafeUrl` without performing any type of URL resolution,
+    /// along with the record of the reissue in progress, if there is any.
+    async fn fetch_wallet_with_reissue_record(
+        &self,
+        safeurl: &SafeUrl,
+    ) -> Result<(WalletSpendableDbcs, Option<(ReissueRecord, EntryHash)>)> {
         let entries = match self.fetch_multimap(safeurl).await {
             Ok(entries) => entries,
             Err(Error::AccessDenied(_)) => {
@@ -207,11 +310,22 @@ impl Safe {
         };
 
         let mut balances = WalletSpendableDbcs::default();
+        let mut reissue_record = None;
         for (entry_hash, (key, value)) in &entries {
             let xorurl_str = std::str::from_utf8(value)?;
             let dbc_xorurl = SafeUrl::from_xorurl(xorurl_str)?;
             let dbc_bytes = self.fetch_data(&dbc_xorurl, None).await?;
 
+            if key == REISSUE_RECORD_ENTRY_NAME.as_bytes() {
+                match self.open_reissue_record(&dbc_bytes) {
+                    Ok(record) => reissue_record = Some((record, *entry_hash)),
+                    Err(err) => {
+                        warn!("Ignoring reissue record found in wallet since it cannot be decrypted or deserialised: {:?}", err);
+                    }
+                }
+                continue;
+            }
+
             let dbc: Dbc = match rmp_serde::from_slice(&dbc_bytes) {
                 Ok(dbc) => dbc,
                 Err(err) => {
@@ -224,7 +338,7 @@ impl Safe {
             balances.insert(spendable_name, (dbc, *entry_hash));
         }
 
-        Ok(balances)
+        Ok((balances, reissue_record))
     }
 
     /// Check the total balance of a wallet found at a given XOR-URL
@@ -263,6 +377,101 @@ impl Safe {
         Ok(total_balance)
     }
 
+    /// Return the history of a wallet, i.e. all the deposits, reissues and change deposits
+    /// recorded in it, in the order they were made.
+    ///
+    /// DBCs spent from the wallet are only marked as removed from it, thus the history can be
+    /// reconstructed from all the entries of the wallet's underlying `Multimap`.
+    pub async fn wallet_history(&self, wallet_url: &str) -> Result<Vec<WalletHistoryEntry>> {
+        let safeurl = self.parse_and_resolve_url(wallet_url).await?;
+        debug!("Fetching history of wallet at {}", safeurl);
+
+        let entries = self.register_fetch_history(&safeurl).await?;
+        let current_entries: BTreeSet<EntryHash> = match self.fetch_multimap(&safeurl).await {
+            Ok(multimap) => multimap.into_iter().map(|(hash, _)| hash).collect(),
+            Err(Error::EmptyContent(_)) => BTreeSet::default(),
+            Err(err) => return Err(err),
+        };
+
+        let mut history = Vec::new();
+        let mut names = BTreeMap::<EntryHash, String>::new();
+        for (entry_hash, entry) in entries {
+            if entry == MULTIMAP_REMOVED_MARK {
+                continue;
+            }
+
+            let (key, value) = Self::decode_multimap_entry(&entry)?;
+            let data_url = SafeUrl::from_xorurl(std::str::from_utf8(&value)?)?;
+            let timestamp = data_url
+                .query_key_last(WALLET_ENTRY_TIMESTAMP_KEY)
+                .and_then(|secs| secs.parse().ok());
+            let data = self.fetch_data(&data_url, None).await?;
+            let is_current = current_entries.contains(&entry_hash);
+
+            let operation = if key == REISSUE_RECORD_ENTRY_NAME.as_bytes() {
+                let record = match self.open_reissue_record(&data) {
+                    Ok(record) => record,
+                    Err(err) => {
+                        warn!("Ignoring reissue record found in wallet history since it cannot be decrypted or deserialised: {:?}", err);
+                        continue;
+                    }
+                };
+                let mut inputs: Vec<String> = record
+                    .input_entries
+                    .iter()
+                    .filter_map(|input| names.get(input).cloned())
+                    .collect();
+                inputs.sort();
+
+                WalletOperation::Reissue {
+                    outputs: record.outputs,
+                    inputs,
+                    change: record.change_amount,
+                    in_progress: is_current,
+                }
+            } else {
+                let dbc: Dbc = match rmp_serde::from_slice(&data) {
+                    Ok(dbc) => dbc,
+                    Err(err) => {
+                        warn!("Ignoring entry found in wallet history since it cannot be deserialised as a valid DBC: {:?}", err);
+                        continue;
+                    }
+                };
+                let amount = match dbc.amount_secrets_bearer() {
+                    Ok(amount_secrets) => amount_secrets.amount(),
+                    Err(err) => {
+                        warn!("Ignoring DBC found in wallet history due to error in revealing secret amount: {:?}", err);
+                        continue;
+                    }
+                };
+                let name = std::str::from_utf8(&key)?.to_string();
+                let _ = names.insert(entry_hash, name.clone());
+
+                if name.starts_with(CHANGE_DBC_NAME_PREFIX) {
+                    WalletOperation::Change {
+                        name,
+                        amount,
+                        spent: !is_current,
+                    }
+                } else {
+                    WalletOperation::Deposit {
+                        name,
+                        amount,
+                        spent: !is_current,
+                    }
+                }
+            };
+
+            history.push(WalletHistoryEntry {
+                entry_hash,
+                timestamp,
+                operation,
+            });
+        }
+
+        Ok(history)
+    }
+
     /// Reissue a DBC from a wallet and return the output DBC.
     ///
     /// If you pass `None` for the `owner_public_key` argument, the output DBC will be a bearer. If
@@ -306,6 +515,9 @@ impl Safe {
     /// this function allows to reissue from a single wallet several output DBCs instead
     /// of a single one. If there is change from the transaction, the change DBC will be
     /// deposited in the source wallet.
+    ///
+    /// A record of the reissue is stored in the wallet before any of the input DBCs is spent,
+    /// so if the reissue gets interrupted it can be completed with `wallet_resume_reissue`.
     pub async fn wallet_reissue_many(
         &self,
         wallet_url: &str,
@@ -342,7 +554,20 @@ impl Safe {
         }
 
         let safeurl = self.parse_and_resolve_url(wallet_url).await?;
-        let spendable_dbcs = self.fetch_wallet(&safeurl).await?;
+        let (spendable_dbcs, reissue_in_progress) =
+            self.fetch_wallet_with_reissue_record(&safeurl).await?;
+
+        // Inputs of an interrupted reissue may have already been spent, so we cannot
+        // select any inputs till that reissue is completed.
+        if reissue_in_progress.is_some() {
+            return Err(Error::DbcReissueError(format!(
+                "A previous reissue from wallet at {safeurl} was interrupted, \
+                it needs to be resumed before reissuing from the wallet again"
+            )));
+        }
+
+        // Decoys for the inputs are sampled out of the outputs of transactions spent on the network.
+        let spent_txs = self.fetch_spent_transactions().await?;
 
         // We'll combine one or more input DBCs and reissue:
         // - one output DBC for the recipient,
@@ -351,7 +576,7 @@ impl Safe {
         let mut input_dbcs_entries_hash = BTreeSet::<EntryHash>::new();
         let mut total_input_amount = 0;
         let mut change_amount = total_output_amount;
-        for (name, (dbc, entry_hash)) in spendable_dbcs {
+        for (name, (dbc, entry_hash)) in &spendable_dbcs {
             let dbc_balance = match dbc.amount_secrets_bearer() {
                 Ok(amount_secrets) => amount_secrets.amount(),
                 Err(err) => {
@@ -361,8 +586,8 @@ impl Safe {
             };
 
             // Add this DBC as input to be spent
-            input_dbcs_to_spend.push(dbc);
-            input_dbcs_entries_hash.insert(entry_hash);
+            input_dbcs_to_spend.push(dbc.clone());
+            input_dbcs_entries_hash.insert(*entry_hash);
             total_input_amount += dbc_balance.as_nano();
 
             // If we've already combined input DBCs for the total output amount, then stop
@@ -388,31 +613,43 @@ impl Safe {
             ));
         }
 
-        // We can now reissue the output DBCs
-        let (output_dbcs, change_dbc) = self
-            .reissue_dbcs(input_dbcs_to_spend, outputs_owners, change_amount)
+        // We can now build the transaction reissuing the output DBCs, and record it in the wallet
+        // before spending any of the inputs, so the reissue can be resumed if it gets interrupted.
+        let record = self.prepare_reissue(
+            input_dbcs_to_spend,
+            &spent_txs,
+            outputs_owners,
+            change_amount,
+            input_dbcs_entries_hash,
+        )?;
+        let record_entry = self
+            .insert_reissue_record_into_wallet(&safeurl, &record)
             .await?;
 
-        if output_dbcs.is_empty() {
-            return Err(Error::DbcReissueError(
-                "Unexpectedly failed to generate output DBC. No balance were removed from the wallet.".to_string(),
-            ));
-        }
+        self.complete_reissue(&safeurl, &spendable_dbcs, record, record_entry)
+            .await
+    }
 
-        if let Some(change_dbc) = change_dbc {
-            self.insert_dbc_into_wallet(
-                &safeurl,
-                &change_dbc,
-                format!("change-dbc-{}", &hex::encode(change_dbc.hash())[0..8]),
-            )
-            .await?;
-        }
+    /// Resume a reissue from a wallet which was interrupted, e.g. due to a network failure,
+    /// after some or all of its input DBCs may have already been spent.
+    ///
+    /// The spends of all the input DBCs are sent to the network again, then the change DBC,
+    /// if any, is deposited in the wallet and the input DBCs removed from it, just like
+    /// `wallet_reissue_many` would have done if not interrupted. The output DBCs are returned.
+    pub async fn wallet_resume_reissue(&self, wallet_url: &str) -> Result<Vec<Dbc>> {
+        let safeurl = self.parse_and_resolve_url(wallet_url).await?;
+        let (spendable_dbcs, reissue_in_progress) =
+            self.fetch_wallet_with_reissue_record(&safeurl).await?;
 
-        // (virtually) remove input DBCs in the source wallet
-        self.multimap_remove(&safeurl.to_string(), input_dbcs_entries_hash)
-            .await?;
+        let (record, record_entry) = reissue_in_progress.ok_or_else(|| {
+            Error::DbcReissueError(format!(
+                "No interrupted reissue was found in wallet at {safeurl}"
+            ))
+        })?;
+        debug!("Resuming reissue found in wallet at {safeurl}");
 
-        Ok(output_dbcs.into_iter().map(|(dbc, _, _)| dbc).collect())
+        self.complete_reissue(&safeurl, &spendable_dbcs, record, record_entry)
+            .await
     }
 
     ///
@@ -437,7 +674,7 @@ impl Safe {
             ))
         })?);
 
-        let dbc_xorurl = self.store_bytes(dbc_bytes, None).await?;
+        let dbc_xorurl = self.store_wallet_entry_data(dbc_bytes).await?;
 
         let entry = (spendable_name.into_bytes(), dbc_xorurl.into_bytes());
         let _entry_hash = self
@@ -447,93 +684,294 @@ impl Safe {
         Ok(())
     }
 
-    /// Reissue DBCs and log the spent input DBCs on the network. Return the output DBC and the
-    /// change DBC if there is one.
-    pub(super) async fn reissue_dbcs(
+    /// Insert the record of a reissue in progress into the wallet's underlying `Multimap`.
+    async fn insert_reissue_record_into_wallet(
+        &self,
+        safeurl: &SafeUrl,
+        record: &ReissueRecord,
+    ) -> Result<EntryHash> {
+        let record_bytes = self.seal_reissue_record(record)?;
+        let record_xorurl = self.store_wallet_entry_data(record_bytes).await?;
+
+        let entry = (
+            REISSUE_RECORD_ENTRY_NAME.as_bytes().to_vec(),
+            record_xorurl.into_bytes(),
+        );
+        self.multimap_insert(&safeurl.to_string(), entry, BTreeSet::default())
+            .await
+    }
+
+    /// Serialise the record of a reissue and encrypt it to the DBC owner of the client,
+    /// since the data of the wallet entries is stored unencrypted on the network.
+    fn seal_reissue_record(&self, record: &ReissueRecord) -> Result<Bytes> {
+        let record_bytes = rmp_serde::to_vec_named(record).map_err(|err| {
+            Error::Serialisation(format!(
+                "Failed to serialise reissue record to insert it into the wallet: {:?}",
+                err
+            ))
+        })?;
+
+        let owner_pk = self.get_safe_client()?.dbc_owner().public_key();
+        let sealed_record =
+            rmp_serde::to_vec_named(&owner_pk.encrypt(record_bytes)).map_err(|err| {
+                Error::Serialisation(format!(
+                "Failed to serialise encrypted reissue record to insert it into the wallet: {:?}",
+                err
+            ))
+            })?;
+
+        Ok(Bytes::from(sealed_record))
+    }
+
+    /// Decrypt and deserialise the record of a reissue, which can only be done with the
+    /// secret key of the DBC owner of the client it was encrypted to.
+    fn open_reissue_record(&self, bytes: &[u8]) -> Result<ReissueRecord> {
+        let sealed_record: bls::Ciphertext = rmp_serde::from_slice(bytes).map_err(|err| {
+            Error::Serialisation(format!(
+                "Failed to deserialise encrypted reissue record: {:?}",
+                err
+            ))
+        })?;
+
+        let owner_sk = self
+            .get_safe_client()?
+            .dbc_owner()
+            .secret_key()
+            .map_err(|err| Error::DbcReissueError(err.to_string()))?;
+        let record_bytes = owner_sk.decrypt(&sealed_record).ok_or_else(|| {
+            Error::DbcReissueError(
+                "Reissue record cannot be decrypted with the secret key of the DBC owner"
+                    .to_string(),
+            )
+        })?;
+
+        rmp_serde::from_slice(&record_bytes).map_err(|err| {
+            Error::Serialisation(format!("Failed to deserialise reissue record: {:?}", err))
+        })
+    }
+
+    /// Store the data of a wallet entry, returning its XOR-URL with the current time set
+    /// in it, so the wallet's history can tell when the entry was inserted.
+    async fn store_wallet_entry_data(&self, bytes: Bytes) -> Result<XorUrl> {
+        let xorurl = self.store_bytes(bytes, None).await?;
+        let mut safeurl = SafeUrl::from_xorurl(&xorurl)?;
+        safeurl.set_query_key(WALLET_ENTRY_TIMESTAMP_KEY, Some(&gen_timestamp_secs()))?;
+        Ok(safeurl.encode(self.xorurl_base))
+    }
+
+    /// Complete a reissue from a wallet, spending its inputs and building the output DBCs.
+    /// The change DBC, if any, is deposited in the wallet, unless it already was, and both
+    /// the input DBCs and the record of the reissue are then removed from the wallet.
+    async fn complete_reissue(
+        &self,
+        safeurl: &SafeUrl,
+        spendable_dbcs: &WalletSpendableDbcs,
+        record: ReissueRecord,
+        record_entry: EntryHash,
+    ) -> Result<Vec<Dbc>> {
+        let mut entries_to_remove = record.input_entries.clone();
+        let _ = entries_to_remove.insert(record_entry);
+
+        let (output_dbcs, change_dbc) = match self.spend_reissue_inputs(record).await {
+            Ok(outputs) => outputs,
+            Err(Error::DbcAlreadySpent(key_image)) => {
+                // An input was spent with a different transaction, so this reissue can never
+                // be completed, thus we drop its record so the wallet is not blocked by it.
+                let _ = self
+                    .multimap_remove(&safeurl.to_string(), BTreeSet::from([record_entry]))
+                    .await?;
+                return Err(Error::DbcAlreadySpent(key_image));
+            }
+            Err(err) => return Err(err),
+        };
+
+        if output_dbcs.is_empty() {
+            return Err(Error::DbcReissueError(
+                "Unexpectedly failed to generate output DBC. No balance were removed from the wallet.".to_string(),
+            ));
+        }
+
+        if let Some(change_dbc) = change_dbc {
+            let change_name = format!(
+                "{CHANGE_DBC_NAME_PREFIX}{}",
+                &hex::encode(change_dbc.hash())[0..8]
+            );
+            // it may have been deposited already if the reissue was interrupted afterwards
+            if !spendable_dbcs.contains_key(&change_name) {
+                self.insert_dbc_into_wallet(safeurl, &change_dbc, change_name)
+                    .await?;
+            }
+        }
+
+        // (virtually) remove input DBCs, and the reissue record, from the source wallet
+        self.multimap_remove(&safeurl.to_string(), entries_to_remove)
+            .await?;
+
+        Ok(output_dbcs.into_iter().map(|(dbc, _, _)| dbc).collect())
+    }
+
+    /// Fetch a sample of the transactions spent on the network, each along with the spent proof
+    /// of one of its inputs, to sample decoys out of their outputs. Those whose spent proof
+    /// is not valid are left out. Nothing is fetched if the rings are not to hold any decoys.
+    async fn fetch_spent_transactions(&self) -> Result<Vec<(RingCtTransaction, SpentProof)>> {
+        if self.dbc_ring_size <= 1 {
+            return Ok(vec![]);
+        }
+
+        let client = self.get_safe_client()?;
+        let proof_key_verifier = SpentProofKeyVerifier { client };
+        let spent_txs = client
+            .spent_transactions_sample()
+            .await?
+            .into_iter()
+            .filter_map(|spent_tx| {
+                let tx_hash = Hash::from(spent_tx.tx.hash());
+                let key_image = *spent_tx.spent_proof_shares.first()?.key_image();
+                let spent_proof = SpentProof::try_from_proof_shares(
+                    key_image,
+                    tx_hash,
+                    spent_tx.spent_proof_shares.iter(),
+                )
+                .ok()?;
+                spent_proof.verify(tx_hash, &proof_key_verifier).ok()?;
+                Some((spent_tx.tx, spent_proof))
+            })
+            .collect();
+
+        Ok(spent_txs)
+    }
+
+    /// Build the transaction to reissue DBCs, mixing each input DBC in a ring with
+    /// `dbc_ring_size - 1` decoys sampled from the outputs of the given spent transactions.
+    /// An error is returned if they don't have enough outputs to sample from. Nothing is
+    /// sent to the network.
+    pub(super) fn prepare_reissue(
         &self,
         input_dbcs: Vec<Dbc>,
+        spent_txs: &[(RingCtTransaction, SpentProof)],
         outputs: Vec<(Token, OwnerOnce)>,
         change_amount: Token,
-    ) -> Result<(Vec<(Dbc, OwnerOnce, AmountSecrets)>, Option<Dbc>)> {
-        // TODO: enable the use of decoys
+        input_entries: BTreeSet<EntryHash>,
+    ) -> Result<ReissueRecord> {
+        let decoys_per_input = self.dbc_ring_size.saturating_sub(1);
+        let true_input_pks = input_dbcs
+            .iter()
+            .map(|dbc| Ok(dbc.as_true_input_bearer()?.public_key().to_affine()))
+            .collect::<Result<Vec<_>>>()?;
+        let (decoys, decoy_txs) = sample_decoys(
+            spent_txs,
+            &true_input_pks,
+            decoys_per_input * input_dbcs.len(),
+        )?;
+        debug!(
+            "Mixing each of the {} input DBCs with {decoys_per_input} decoys",
+            input_dbcs.len()
+        );
+
+        let outputs_owners = outputs
+            .iter()
+            .map(|(amount, owner)| {
+                let owner_pk = match owner.owner_base() {
+                    Owner::PublicKey(pk) => Some(*pk),
+                    Owner::SecretKey(_) => None,
+                };
+                (*amount, owner_pk)
+            })
+            .collect();
+
         let mut tx_builder = TransactionBuilder::default()
-            .set_decoys_per_input(0)
-            .set_require_all_decoys(false)
+            .set_decoys_per_input(decoys_per_input)
+            .set_require_all_decoys(true)
+            .add_decoy_inputs(decoys)
             .add_inputs_dbc_bearer(input_dbcs.iter())?
             .add_outputs_by_amount(outputs.into_iter().map(|(token, owner)| (token, owner)));
 
         let client = self.get_safe_client()?;
-        let change_owneronce =
-            OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
-        if change_amount.as_nano() > 0 {
+        let change_owner = if change_amount.as_nano() > 0 {
+            let change_owneronce =
+                OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
             tx_builder = tx_builder.add_output_by_amount(change_amount, change_owneronce.clone());
-        }
+            Some(change_owneronce)
+        } else {
+            None
+        };
 
+        // Elders need the spent proofs and transactions of the decoys as well as of the inputs,
+        // to verify all the members of the rings are outputs of transactions which were spent.
         let spent_proofs: BTreeSet<SpentProof> = input_dbcs
             .iter()
             .flat_map(|dbc| dbc.spent_proofs.clone())
+            .chain(decoy_txs.iter().map(|index| spent_txs[*index].1.clone()))
             .collect();
 
         let spent_transactions: BTreeSet<RingCtTransaction> = input_dbcs
             .iter()
             .flat_map(|dbc| dbc.spent_transactions.clone())
+            .chain(decoy_txs.iter().map(|index| spent_txs[*index].0.clone()))
             .collect();
 
-        let proof_key_verifier = SpentProofKeyVerifier { client };
-
         // Let's build the output DBCs
-        let mut dbc_builder = tx_builder.build(rng::thread_rng())?;
-
-        // Spend all the input DBCs, collecting the spent proof shares for each of them
-        for (key_image, tx) in dbc_builder.inputs() {
-            let tx_hash = Hash::from(tx.hash());
-            // TODO: spend DBCs concurrently spawning tasks
-            let mut attempts = 0;
-            loop {
-                attempts += 1;
-                client
-                    .spend_dbc(
-                        key_image,
-                        tx.clone(),
-                        spent_proofs.clone(),
-                        spent_transactions.clone(),
-                    )
-                    .await?;
+        let dbc_builder = tx_builder.build(rng::thread_rng())?;
+
+        Ok(ReissueRecord {
+            dbc_builder,
+            spent_proofs,
+            spent_transactions,
+            outputs: outputs_owners,
+            change_owner,
+            change_amount,
+            input_entries,
+        })
+    }
 
-                let spent_proof_shares = client.spent_proof_shares(key_image).await?;
+    /// Spend all the inputs of a reissue concurrently, and build the output DBCs out of the
+    /// spent proof shares obtained for them. Inputs already spent with the very same
+    /// transaction are simply spent again, so this can be used to resume a reissue.
+    pub(super) async fn spend_reissue_inputs(
+        &self,
+        record: ReissueRecord,
+    ) -> Result<(Vec<(Dbc, OwnerOnce, AmountSecrets)>, Option<Dbc>)> {
+        let ReissueRecord {
+            mut dbc_builder,
+            spent_proofs,
+            spent_transactions,
+            change_owner,
+            ..
+        } = record;
 
-                // TODO: we temporarilly filter the spent proof shares which correspond to the TX we
-                // are spending now. This is because current implementation of Spentbook allows
-                // double spents, so we may be retrieving spent proof shares for others spent TXs.
-                let shares_for_current_tx: HashSet<SpentProofShare> = spent_proof_shares
-                    .into_iter()
-                    .filter(|proof_share| proof_share.content.transaction_hash == tx_hash)
-                    .collect();
+        let client = self.get_safe_client()?;
+        let proof_key_verifier = SpentProofKeyVerifier { client };
 
-                match verify_spent_proof_shares_for_tx(
-                    key_image,
-                    tx_hash,
-                    shares_for_current_tx.iter(),
-                    &proof_key_verifier,
-                ) {
-                    Ok(()) => {
-                        dbc_builder = dbc_builder
-                            .add_spent_proof_shares(shares_for_current_tx.into_iter())
-                            .add_spent_transaction(tx);
+        let spends = dbc_builder.inputs().into_iter().map(|(key_image, tx)| {
+            spend_input(
+                client,
+                key_image,
+                tx,
+                &spent_proofs,
+                &spent_transactions,
+                &proof_key_verifier,
+            )
+        });
 
-                        break;
-                    }
-                    Err(err) if attempts == NUM_OF_DBC_REISSUE_ATTEMPTS => {
-                        return Err(Error::DbcReissueError(format!(
-                            "Failed to spend input, {} proof shares obtained from spentbook: {}",
-                            shares_for_current_tx.len(),
-                            err
-                        )));
-                    }
-                    Err(_) => {}
+        // All spends are awaited, even if any fails, so those which succeed
+        // won't need to be retried (but simply re-sent) when resuming.
+        let mut spend_error = None;
+        for (result, (_, tx)) in join_all(spends).await.into_iter().zip(dbc_builder.inputs()) {
+            match result {
+                Ok(shares) => {
+                    dbc_builder = dbc_builder
+                        .add_spent_proof_shares(shares)
+                        .add_spent_transaction(tx);
+                }
+                Err(err) => {
+                    warn!("Failed to spend input of reissue: {err:?}");
+                    spend_error = Some(err);
                 }
             }
         }
+        if let Some(err) = spend_error {
+            return Err(err);
+        }
 
         // Perform verifications of input TX and spentproofs,
         // as well as building the output DBCs.
@@ -541,7 +979,7 @@ impl Safe {
 
         let mut change_dbc = None;
         output_dbcs.retain(|(dbc, owneronce, _)| {
-            if owneronce == &change_owneronce && change_amount.as_nano() > 0 {
+            if Some(owneronce) == change_owner.as_ref() {
                 change_dbc = Some(dbc.clone());
                 false
             } else {
@@ -553,6 +991,111 @@ impl Safe {
     }
 }
 
+// Private helper to spend an input DBC, returning the spent proof shares obtained for it,
+// once verified they can be aggregated into a valid spent proof for the given transaction.
+async fn spend_input(
+    client: &Client,
+    key_image: KeyImage,
+    tx: RingCtTransaction,
+    spent_proofs: &BTreeSet<SpentProof>,
+    spent_transactions: &BTreeSet<RingCtTransaction>,
+    proof_key_verifier: &SpentProofKeyVerifier<'_>,
+) -> Result<HashSet<SpentProofShare>> {
+    let tx_hash = Hash::from(tx.hash());
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        client
+            .spend_dbc(
+                key_image,
+                tx.clone(),
+                spent_proofs.clone(),
+                spent_transactions.clone(),
+            )
+            .await
+            .map_err(|err| match err {
+                ClientError::DbcAlreadySpent { key_image } => Error::DbcAlreadySpent(key_image),
+                other => other.into(),
+            })?;
+
+        let spent_proof_shares = client.spent_proof_shares(key_image).await?;
+
+        // We filter the spent proof shares which correspond to the TX we are spending now,
+        // as Elders can only refuse to sign for a different TX if the Adults they check
+        // with before signing already hold a spent proof share for it.
+        let shares_for_current_tx: HashSet<SpentProofShare> = spent_proof_shares
+            .into_iter()
+            .filter(|proof_share| proof_share.content.transaction_hash == tx_hash)
+            .collect();
+
+        match verify_spent_proof_shares_for_tx(
+            key_image,
+            tx_hash,
+            shares_for_current_tx.iter(),
+            proof_key_verifier,
+        ) {
+            Ok(()) => return Ok(shares_for_current_tx),
+            Err(err) if attempts == NUM_OF_DBC_REISSUE_ATTEMPTS => {
+                return Err(Error::DbcReissueError(format!(
+                    "Failed to spend input, {} proof shares obtained from spentbook: {}",
+                    shares_for_current_tx.len(),
+                    err
+                )));
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+// Private helper to randomly sample the given number of decoys out of the outputs of the given
+// spent transactions, leaving out the outputs which are the true inputs to spend. Along with
+// the decoys, the indexes of the transactions which output them are returned.
+fn sample_decoys(
+    spent_txs: &[(RingCtTransaction, SpentProof)],
+    true_input_pks: &[G1Affine],
+    num_of_decoys: usize,
+) -> Result<(Vec<DecoyInput>, BTreeSet<usize>)> {
+    let mut outputs: Vec<(usize, DecoyInput)> = Vec::new();
+    for (index, (tx, _)) in spent_txs.iter().enumerate() {
+        for output in &tx.outputs {
+            let public_key = *output.public_key();
+            if !true_input_pks.contains(&public_key)
+                && !outputs
+                    .iter()
+                    .any(|(_, decoy)| decoy.public_key == public_key)
+            {
+                outputs.push((
+                    index,
+                    DecoyInput {
+                        public_key,
+                        commitment: output.commitment(),
+                    },
+                ));
+            }
+        }
+    }
+
+    if outputs.len() < num_of_decoys {
+        return Err(Error::NotEnoughDecoys {
+            needed: num_of_decoys,
+            available: outputs.len(),
+        });
+    }
+
+    let mut rng = rng::thread_rng();
+    let mut sampled: Vec<_> = outputs
+        .choose_multiple(&mut rng, num_of_decoys)
+        .copied()
+        .collect();
+    // the order the decoys are assigned to the inputs must be random as well
+    sampled.shuffle(&mut rng);
+
+    let decoy_txs = sampled.iter().map(|(index, _)| *index).collect();
+    let decoys = sampled.into_iter().map(|(_, decoy)| decoy).collect();
+
+    Ok((decoys, decoy_txs))
+}
+
 // Private helper to verify if a set of spent proof shares are valid for a given key_image and TX
 fn verify_spent_proof_shares_for_tx<'a>(
     key_image: KeyImage,
@@ -573,7 +1116,7 @@ mod tests {
         get_next_bearer_dbc, new_read_only_safe_instance, new_safe_instance,
         new_safe_instance_with_dbc, new_safe_instance_with_dbc_owner, GENESIS_DBC,
     };
-    use anyhow::{anyhow, Result};
+    use anyhow::{anyhow, bail, Result};
     use sn_client::{Error as ClientError, ErrorMsg};
     use sn_dbc::{Error as DbcError, Owner};
 
@@ -997,6 +1540,175 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_wallet_resume_reissue() -> Result<()> {
+        let (safe, dbc, dbc_balance) = new_safe_instance_with_dbc().await?;
+        let wallet_xorurl = safe.wallet_create().await?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-1"), &dbc, None)
+            .await?;
+        let (_, (_, entry_hash)) = safe
+            .wallet_get(&wallet_xorurl)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Couldn't read deposited DBC from wallet"))?;
+
+        match safe.wallet_resume_reissue(&wallet_xorurl).await {
+            Err(Error::DbcReissueError(_)) => {}
+            other => {
+                bail!("Expected DbcReissueError as there is nothing to resume, got: {other:?}")
+            }
+        }
+
+        // Let's simulate a reissue which is interrupted right after being recorded in the wallet
+        let owner = Owner::from_random_secret_key(&mut rng::thread_rng());
+        let output_owner = OwnerOnce::from_owner_base(owner, &mut rng::thread_rng());
+        let output_amount = Token::from_nano(1_000_000_000);
+        let change_amount = Token::from_nano(dbc_balance.as_nano() - output_amount.as_nano());
+        let spent_txs = safe.fetch_spent_transactions().await?;
+        let record = safe.prepare_reissue(
+            vec![dbc],
+            &spent_txs,
+            vec![(output_amount, output_owner)],
+            change_amount,
+            BTreeSet::from([entry_hash]),
+        )?;
+        let safeurl = SafeUrl::from_url(&wallet_xorurl)?;
+        safe.insert_reissue_record_into_wallet(&safeurl, &record)
+            .await?;
+
+        // the wallet's balance doesn't change, but nothing can be reissued from it till resumed
+        assert_eq!(safe.wallet_balance(&wallet_xorurl).await?, dbc_balance);
+        match safe.wallet_reissue(&wallet_xorurl, "1", None).await {
+            Err(Error::DbcReissueError(_)) => {}
+            other => bail!("Expected DbcReissueError as a reissue is in progress, got: {other:?}"),
+        }
+
+        let output_dbcs = safe.wallet_resume_reissue(&wallet_xorurl).await?;
+        assert_eq!(output_dbcs.len(), 1);
+        assert_eq!(
+            output_dbcs[0].amount_secrets_bearer()?.amount(),
+            output_amount
+        );
+        assert_eq!(safe.wallet_balance(&wallet_xorurl).await?, change_amount);
+
+        // and once completed, DBCs can be reissued from the wallet again
+        let _ = safe.wallet_reissue(&wallet_xorurl, "1", None).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wallet_reissue_with_decoys() -> Result<()> {
+        let (mut safe, dbc, _) = new_safe_instance_with_dbc().await?;
+        safe.dbc_ring_size = 4;
+        let wallet_xorurl = safe.wallet_create().await?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-1"), &dbc, None)
+            .await?;
+
+        // the input DBC is mixed with decoys output by transactions spent on the network
+        let output_dbc = safe.wallet_reissue(&wallet_xorurl, "1", None).await?;
+        assert_eq!(output_dbc.transaction.mlsags.len(), 1);
+        assert_eq!(output_dbc.transaction.mlsags[0].public_keys().len(), 4);
+
+        // and so is the change DBC
+        let output_dbc = safe.wallet_reissue(&wallet_xorurl, "1", None).await?;
+        assert_eq!(output_dbc.transaction.mlsags[0].public_keys().len(), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wallet_reissue_not_enough_decoys() -> Result<()> {
+        let (mut safe, dbc, dbc_balance) = new_safe_instance_with_dbc().await?;
+        // more decoys than the outputs of any sample of spent transactions
+        safe.dbc_ring_size = 10_000;
+        let wallet_xorurl = safe.wallet_create().await?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-1"), &dbc, None)
+            .await?;
+
+        match safe.wallet_reissue(&wallet_xorurl, "1", None).await {
+            Err(Error::NotEnoughDecoys { needed, available }) => {
+                assert_eq!(needed, 9_999);
+                assert!(available < needed);
+            }
+            other => bail!("Expected NotEnoughDecoys error, got: {other:?}"),
+        }
+
+        // nothing was spent nor recorded, so the DBC can still be reissued
+        assert_eq!(safe.wallet_balance(&wallet_xorurl).await?, dbc_balance);
+        safe.dbc_ring_size = 1;
+        let _ = safe.wallet_reissue(&wallet_xorurl, "1", None).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wallet_history() -> Result<()> {
+        let (safe, dbc1, dbc1_balance) = new_safe_instance_with_dbc().await?;
+        let (dbc2, dbc2_balance) = get_next_bearer_dbc().await?;
+        let wallet_xorurl = safe.wallet_create().await?;
+
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-1"), &dbc1, None)
+            .await?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-2"), &dbc2, None)
+            .await?;
+
+        // spend the first DBC, and part of the second one, for an owned output
+        let recipient_pk = bls::SecretKey::random().public_key();
+        let amount_to_reissue = Token::from_nano(dbc1_balance.as_nano() + 100);
+        let _ = safe
+            .wallet_reissue(
+                &wallet_xorurl,
+                &amount_to_reissue.to_string(),
+                Some(recipient_pk),
+            )
+            .await?;
+        let change_amount = Token::from_nano(dbc2_balance.as_nano() - 100);
+
+        let history = safe.wallet_history(&wallet_xorurl).await?;
+        assert_eq!(history.len(), 4);
+        assert!(history.iter().all(|entry| entry.timestamp.is_some()));
+
+        let operations: Vec<WalletOperation> =
+            history.into_iter().map(|entry| entry.operation).collect();
+        assert_eq!(
+            operations[0],
+            WalletOperation::Deposit {
+                name: "deposited-dbc-1".to_string(),
+                amount: dbc1_balance,
+                spent: true,
+            }
+        );
+        assert_eq!(
+            operations[1],
+            WalletOperation::Deposit {
+                name: "deposited-dbc-2".to_string(),
+                amount: dbc2_balance,
+                spent: true,
+            }
+        );
+        assert_eq!(
+            operations[2],
+            WalletOperation::Reissue {
+                outputs: vec![(amount_to_reissue, Some(recipient_pk))],
+                inputs: vec!["deposited-dbc-1".to_string(), "deposited-dbc-2".to_string()],
+                change: change_amount,
+                in_progress: false,
+            }
+        );
+        match &operations[3] {
+            WalletOperation::Change {
+                amount,
+                spent: false,
+                ..
+            } => assert_eq!(*amount, change_amount),
+            other => bail!("Unexpected operation found in wallet history: {:?}", other),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_wallet_reissue_with_persistent_dbc_owner() -> Result<()> {
         let (safe, dbc_owner) = new_safe_instance_with_dbc_owner(
@@ -1224,9 +1936,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_wallet_reissue_already_spent_dbc() -> Result<()> {
+        let (safe, dbc, _) = new_safe_instance_with_dbc().await?;
+        let wallet1_xorurl = safe.wallet_create().await?;
+        let wallet2_xorurl = safe.wallet_create().await?;
+
+        safe.wallet_deposit(&wallet1_xorurl, Some("deposited-dbc"), &dbc, None)
+            .await?;
+        let _ = safe.wallet_reissue(&wallet1_xorurl, "0.1", None).await?;
+
+        // We insert the same DBC, which is now spent, directly in another wallet,
+        // thus Elders shall refuse to sign spent proof shares for a different TX
+        safe.insert_dbc_into_wallet(
+            &SafeUrl::from_url(&wallet2_xorurl)?,
+            &dbc,
+            "spent_dbc".to_string(),
+        )
+        .await?;
+
+        match safe.wallet_reissue(&wallet2_xorurl, "0.2", None).await {
+            Err(Error::DbcAlreadySpent(key_image)) => {
+                assert_eq!(key_image, dbc.key_image_bearer()?);
+                Ok(())
+            }
+            Err(err) => Err(anyhow!("Error returned is not the expected: {:?}", err)),
+            Ok(_) => Err(anyhow!("Wallet reissue succeeded unexpectedly".to_string())),
+        }
+    }
+
     #[tokio::test]
     async fn test_wallet_is_dbc_spent() -> Result<()> {
-        let safe = new_safe_instance().await?;
+        // taking a DBC from the test funds makes sure the genesis DBC was spent
+        let (safe, _, _) = new_safe_instance_with_dbc().await?;
 
         // the api shall confirm the genesis DBC's key_image has been spent
         let is_genesis_spent = safe.is_dbc_spent(GENESIS_DBC.key_image_bearer()?).await?;
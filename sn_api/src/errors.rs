@@ -8,13 +8,13 @@
 
 use super::{
     ipc::IpcError,
-    nrs::NrsMap,
-    safeurl::{Error as UrlError, SafeUrl, XorUrl},
+    nrs::{NrsConflict, NrsMap},
+    safeurl::{Error as UrlError, XorUrl},
 };
 
 use bls::Error as BlsError;
 use sn_client::Error as ClientError;
-use sn_dbc::Error as DbcError;
+use sn_dbc::{Error as DbcError, KeyImage};
 use sn_interface::types::Error as InterfaceError;
 
 use thiserror::Error;
@@ -46,7 +46,7 @@ pub enum Error {
     AuthenticatorError(String),
     /// ConflictingNrsEntries
     #[error("ConflictingNrsEntries: {0}")]
-    ConflictingNrsEntries(String, Vec<(String, SafeUrl)>, NrsMap),
+    ConflictingNrsEntries(String, Vec<NrsConflict>, NrsMap),
     /// ConnectionError
     #[error("ConnectionError: {0}")]
     ConnectionError(String),
@@ -131,6 +131,12 @@ pub enum Error {
     /// DbcReissueError
     #[error("DbcReissueError: {0}")]
     DbcReissueError(String),
+    /// An input DBC has already been spent with a different transaction
+    #[error("DbcAlreadySpent: input DBC has already been spent, key image: {0:?}")]
+    DbcAlreadySpent(KeyImage),
+    /// Not enough outputs of spent transactions are known to mix the inputs of a reissue with
+    #[error("Not enough decoys to mix the input DBCs with, {needed} needed but only {available} available")]
+    NotEnoughDecoys { needed: usize, available: usize },
     /// Verification of DBC validly signed by a known section failed
     #[error("DBC validity verification failed: {0}")]
     DbcVerificationFailed(String),
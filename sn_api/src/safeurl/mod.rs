@@ -163,6 +163,8 @@ pub enum DataType {
     Register = 0x02,
     #[allow(missing_docs)]
     Spentbook = 0x03,
+    #[allow(missing_docs)]
+    PrivateRegister = 0x04,
 }
 
 impl std::fmt::Display for DataType {
@@ -494,6 +496,7 @@ impl SafeUrl {
             0 => DataAddress::SafeKey(xor_name),
             1 => DataAddress::Bytes(ChunkAddress(xor_name)),
             2 => DataAddress::Register(RegisterAddress::new(xor_name, type_tag)),
+            4 => DataAddress::Register(RegisterAddress::new_private(xor_name, type_tag)),
             other => {
                 return Err(Error::InvalidXorUrl(format!(
                     "Invalid data type encoded in the XOR-URL string: {other}"
@@ -547,8 +550,24 @@ impl SafeUrl {
         type_tag: u64,
         content_type: ContentType,
     ) -> Result<Self> {
+        Self::from_register_address(RegisterAddress::new(xor_name, type_tag), content_type)
+    }
+
+    pub fn from_private_register(
+        xor_name: XorName,
+        type_tag: u64,
+        content_type: ContentType,
+    ) -> Result<Self> {
+        Self::from_register_address(
+            RegisterAddress::new_private(xor_name, type_tag),
+            content_type,
+        )
+    }
+
+    fn from_register_address(address: RegisterAddress, content_type: ContentType) -> Result<Self> {
+        let type_tag = address.tag();
         SafeUrl::new(
-            DataAddress::Register(RegisterAddress::new(xor_name, type_tag)),
+            DataAddress::Register(address),
             None,
             type_tag,
             content_type,
@@ -574,6 +593,7 @@ impl SafeUrl {
     pub fn data_type(&self) -> DataType {
         match self.address {
             DataAddress::Bytes(_) => DataType::File,
+            DataAddress::Register(address) if address.is_private() => DataType::PrivateRegister,
             DataAddress::Register(_) => DataType::Register,
             DataAddress::SafeKey(_) => DataType::SafeKey,
             DataAddress::Spentbook(_) => DataType::Spentbook,
@@ -1285,6 +1305,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_url_private_register_encoding() -> Result<()> {
+        let xor_name = XorName(*b"12345678901234567890123456789012");
+        let public_url = SafeUrl::from_register(xor_name, 4_584_545, ContentType::Raw)?;
+        let private_url = SafeUrl::from_private_register(xor_name, 4_584_545, ContentType::Raw)?;
+        assert_ne!(public_url.to_string(), private_url.to_string());
+
+        let url = SafeUrl::from_url(&private_url.to_string())?;
+        assert_eq!(DataType::PrivateRegister, url.data_type());
+        assert_eq!(
+            DataAddress::Register(RegisterAddress::new_private(xor_name, 4_584_545)),
+            url.address()
+        );
+        assert_eq!(xor_name, url.xorname());
+        assert_eq!(4_584_545, url.type_tag());
+        Ok(())
+    }
+
     #[test]
     fn test_url_default_base_encoding() -> Result<()> {
         let xor_name = XorName(*b"12345678901234567890123456789012");
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+#[cfg(target_os = "linux")]
+use crate::subcommands::mount::mount_commander;
 use crate::{
     operations::auth_and_connect::connect,
     operations::config::{Config, SnLaunchToolNetworkLauncher},
@@ -14,10 +16,13 @@ use crate::{
         config::config_commander,
         dog::dog_commander,
         files::files_commander,
+        gateway::gateway_commander,
         keys::key_commander,
+        multimap::multimap_commander,
         networks::networks_commander,
         node::node_commander,
         nrs::nrs_commander,
+        register::register_commander,
         setup::setup_commander,
         update::update_commander,
         wallet::wallet_commander,
@@ -170,8 +175,13 @@ async fn process_commands(mut safe: &mut Safe, args: CmdArgs, config: &mut Confi
             match other {
                 SubCommands::Cat(cmd) => cat_commander(cmd, output_fmt, safe).await,
                 SubCommands::Dog(cmd) => dog_commander(cmd, output_fmt, safe).await,
-                SubCommands::Files(cmd) => files_commander(cmd, output_fmt, safe).await,
+                SubCommands::Files(cmd) => files_commander(cmd, output_fmt, safe, config).await,
+                SubCommands::Gateway(cmd) => gateway_commander(cmd, output_fmt, safe).await,
+                #[cfg(target_os = "linux")]
+                SubCommands::Mount(cmd) => mount_commander(cmd, output_fmt, safe).await,
+                SubCommands::Multimap(cmd) => multimap_commander(cmd, output_fmt, safe).await,
                 SubCommands::Nrs(cmd) => nrs_commander(cmd, output_fmt, safe).await,
+                SubCommands::Register(cmd) => register_commander(cmd, output_fmt, safe).await,
                 SubCommands::Wallet(cmd) => wallet_commander(cmd, output_fmt, safe, config).await,
                 _ => Err(eyre!("Unknown safe subcommand")),
             }
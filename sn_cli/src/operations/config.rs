@@ -30,6 +30,8 @@ use tracing::debug;
 use url::Url;
 
 const REMOTE_RETRY_COUNT: usize = 3;
+// Name of the folder, within the cli config dir, where the journals of resumable uploads are kept
+const UPLOAD_JOURNALS_DIR_NAME: &str = "upload_journals";
 
 /// Provides an interface for calling a launcher tool for launching and joining the network.
 ///
@@ -437,6 +439,18 @@ impl Config {
         Ok(())
     }
 
+    /// Directory where the journals of uploads to the current network are kept, so they can be
+    /// resumed if interrupted. There is one per network, named after its genesis key, as what
+    /// was stored on one network says nothing about what's stored on another.
+    pub async fn upload_journals_dir(&self) -> Result<PathBuf> {
+        let (network_contacts, _) = self.read_default_network_contacts().await?;
+        let mut dir = self.cli_config_path.clone();
+        dir.pop();
+        dir.push(UPLOAD_JOURNALS_DIR_NAME);
+        dir.push(format!("{:?}", network_contacts.genesis_key()));
+        Ok(dir)
+    }
+
     pub async fn switch_to_network(&self, name: &str) -> Result<()> {
         match self.settings.networks.get(name) {
             Some(NetworkInfo::Remote(_, genesis_key)) | Some(NetworkInfo::Local(_, genesis_key)) => {
@@ -1412,4 +1426,28 @@ mod networks {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn upload_journals_dir_should_be_specific_to_the_current_network() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let config = Config::create_config(&tmp_dir, None).await?;
+        assert!(config.upload_journals_dir().await.is_err());
+
+        let networks = config
+            .store_dummy_network_contacts_and_set_default(None, 2)
+            .await?;
+        config
+            .set_default_network_contacts(networks[0].genesis_key())
+            .await?;
+        let dir_0 = config.upload_journals_dir().await?;
+        config
+            .set_default_network_contacts(networks[1].genesis_key())
+            .await?;
+        let dir_1 = config.upload_journals_dir().await?;
+
+        assert_ne!(dir_0, dir_1);
+        assert_eq!(dir_0.parent(), dir_1.parent());
+        assert!(dir_1.ends_with(format!("{:?}", networks[1].genesis_key())));
+        Ok(())
+    }
 }
@@ -14,6 +14,7 @@ use super::{
     },
     OutputFmt,
 };
+use crate::operations::config::Config;
 use ansi_term::Colour;
 use bytes::Bytes;
 use clap::Subcommand;
@@ -105,6 +106,9 @@ pub enum FilesSubCommands {
         /// Follow symlinks
         #[clap(short = 'l', long = "follow-links")]
         follow_links: bool,
+        /// Keep a local journal of the chunks uploaded, so if the upload is interrupted, running the same command again with this flag resumes it, skipping all content already stored
+        #[clap(long = "resume")]
+        resume: bool,
     },
     /// Get a file or folder from the SAFE Network
     Get {
@@ -141,6 +145,9 @@ pub enum FilesSubCommands {
         /// Automatically update the NRS name to link to the new version of the FilesContainer. This is only allowed if an NRS URL was provided, and if the NRS name is currently linked to a specific version of the FilesContainer
         #[clap(short = 'u', long = "update-nrs")]
         update_nrs: bool,
+        /// Keep a local journal of the chunks uploaded, so if the sync is interrupted, running the same command again with this flag resumes it, skipping all content already stored
+        #[clap(long = "resume")]
+        resume: bool,
     },
     #[clap(name = "add")]
     /// Add a file to an existing FilesContainer on the network
@@ -197,7 +204,8 @@ pub enum FilesSubCommands {
 pub async fn files_commander(
     cmd: FilesSubCommands,
     output_fmt: OutputFmt,
-    safe: &Safe,
+    safe: &mut Safe,
+    config: &Config,
 ) -> Result<()> {
     match cmd {
         FilesSubCommands::Put {
@@ -205,7 +213,11 @@ pub async fn files_commander(
             dst,
             recursive,
             follow_links,
+            resume,
         } => {
+            if resume {
+                safe.upload_journals_dir = Some(config.upload_journals_dir().await?);
+            }
             // create FilesContainer from a given path to local files/folders
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
@@ -237,7 +249,11 @@ pub async fn files_commander(
             follow_links,
             delete,
             update_nrs,
+            resume,
         } => {
+            if resume {
+                safe.upload_journals_dir = Some(config.upload_journals_dir().await?);
+            }
             let target = get_from_arg_or_stdin(target, None)?;
             let mut target_url = get_target_url(&target)?;
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
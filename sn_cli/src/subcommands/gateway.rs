@@ -0,0 +1,546 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{helpers::xorname_to_hex, OutputFmt};
+use clap::Args;
+use color_eyre::{eyre::WrapErr, Result};
+use hyper::{
+    header::{
+        ACCEPT_RANGES, ALLOW, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_NONE_MATCH,
+        RANGE,
+    },
+    http::response::Builder,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use sn_api::{
+    files::{FileInfo, FilesMap},
+    resolver::{Range, SafeData},
+    Error, Safe,
+};
+use std::{collections::BTreeMap, convert::Infallible, fmt::Write, net::SocketAddr};
+use tracing::{debug, warn};
+
+// Characters to percent-encode in a path segment of the links in directory listings
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+#[derive(Args, Debug)]
+pub struct GatewayCommands {
+    /// The local address to serve the content on, which is then available at
+    /// http://<address>/<public name or XOR-URL>/<path>
+    #[clap(long = "listen", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+}
+
+pub async fn gateway_commander(
+    cmd: GatewayCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    let safe = safe.clone();
+    let make_service = make_service_fn(move |_| {
+        let safe = safe.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let safe = safe.clone();
+                async move { Ok::<_, Infallible>(handle_request(&safe, req).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&cmd.listen)
+        .wrap_err_with(|| format!("Failed to listen on {}", cmd.listen))?
+        .serve(make_service);
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "Serving Safe Network content at http://{}/<public name or XOR-URL>/<path>",
+            server.local_addr()
+        );
+    }
+
+    server.await.context("The gateway stopped serving content")
+}
+
+async fn handle_request(safe: &Safe, req: Request<Body>) -> Response<Body> {
+    debug!("Gateway request: {} {}", req.method(), req.uri());
+    let is_head = match *req.method() {
+        Method::GET => false,
+        Method::HEAD => true,
+        _ => {
+            let builder = Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(ALLOW, "GET, HEAD");
+            return build(builder, Body::empty());
+        }
+    };
+
+    let url = match safe_url_for(req.uri().path(), req.uri().query()) {
+        Some(url) => url,
+        None => {
+            return text_response(
+                StatusCode::NOT_FOUND,
+                "Provide the public name or XOR-URL of the content to fetch, \
+                e.g. /<public name>/<path>",
+            )
+        }
+    };
+
+    let range = req
+        .headers()
+        .get(RANGE)
+        .and_then(|range| range.to_str().ok())
+        .and_then(parse_range);
+
+    // Inspecting the content is enough to check if the client's copy is current, or if the
+    // range requested is within the file, before fetching the content itself
+    let inspected = if is_head || range.is_some() || req.headers().contains_key(IF_NONE_MATCH) {
+        match safe.inspect(&url).await {
+            Ok(mut chain) => chain.pop(),
+            Err(err) => {
+                warn!("Failed to inspect {}: {:?}", url, err);
+                return error_response(&err);
+            }
+        }
+    } else {
+        None
+    };
+
+    if let (Some(if_none_match), Some(etag)) = (
+        req.headers().get(IF_NONE_MATCH),
+        inspected.as_ref().and_then(etag),
+    ) {
+        if let Ok(if_none_match) = if_none_match.to_str() {
+            if etag_matches(if_none_match, &etag) {
+                let builder = Response::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .header(ETAG, etag);
+                return build(builder, Body::empty());
+            }
+        }
+    }
+
+    if let Some(size) = inspected.as_ref().and_then(file_size) {
+        if let Some(response) = unsatisfiable_range_response(range, size) {
+            return response;
+        }
+    }
+
+    // The headers of a HEAD response for a file of a known size, or a FilesContainer, are
+    // those of the inspected content, any other content is fetched as for a GET request
+    let content = match inspected {
+        Some(content @ SafeData::FilesContainer { .. }) if is_head => Ok(content),
+        Some(content @ SafeData::PublicFile { .. }) if is_head && file_size(&content).is_some() => {
+            Ok(content)
+        }
+        _ => safe.fetch(&url, range).await,
+    };
+    let response = match content {
+        Ok(content) => content_response(content, range, req.uri().path(), is_head),
+        Err(err) => {
+            warn!("Failed to fetch {}: {:?}", url, err);
+            error_response(&err)
+        }
+    };
+
+    if is_head {
+        // the headers of a GET response, including its Content-Length, without the content
+        let (parts, _) = response.into_parts();
+        Response::from_parts(parts, Body::empty())
+    } else {
+        response
+    }
+}
+
+// Build the response for the content fetched, the path requested being the base of the
+// links to the items of a directory listing. A file being inspected for a HEAD request
+// has no data, its Content-Length is then that of the range of the file a GET would serve.
+fn content_response(content: SafeData, range: Range, path: &str, is_head: bool) -> Response<Body> {
+    let etag = etag(&content);
+    let size = file_size(&content);
+    let mut builder = Response::builder();
+    if let Some(etag) = &etag {
+        builder = builder.header(ETAG, etag);
+    }
+
+    match content {
+        SafeData::PublicFile {
+            data,
+            media_type,
+            metadata,
+            ..
+        } => {
+            let content_type = metadata
+                .as_ref()
+                .and_then(|file_info| file_info.get("type"))
+                .or(media_type.as_ref())
+                .map_or("application/octet-stream", String::as_str);
+            let length = match size {
+                Some(size) if is_head => served_length(range, size),
+                _ => data.len() as u64,
+            };
+
+            builder = builder
+                .header(CONTENT_TYPE, content_type)
+                .header(ACCEPT_RANGES, "bytes")
+                .header(CONTENT_LENGTH, length);
+            if let Some((Some(start), _)) = range {
+                let total = size.map_or("*".to_string(), |size| size.to_string());
+                builder = if length == 0 {
+                    // only a file of an unknown size gets here, it has no complete length
+                    // to report in a Content-Range header
+                    builder.status(StatusCode::RANGE_NOT_SATISFIABLE)
+                } else {
+                    let last = start + length - 1;
+                    builder
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .header(CONTENT_RANGE, format!("bytes {}-{}/{}", start, last, total))
+                };
+            }
+            build(builder, Body::from(data))
+        }
+        SafeData::FilesContainer {
+            version, files_map, ..
+        } => {
+            let query = version.map_or(String::new(), |version| format!("?v={}", version));
+            let listing = render_listing(path, &query, &files_map);
+            let builder = builder
+                .header(CONTENT_TYPE, "text/html; charset=utf-8")
+                .header(CONTENT_LENGTH, listing.len());
+            build(builder, Body::from(listing))
+        }
+        other => match serde_json::to_string_pretty(&other) {
+            Ok(json) => {
+                let builder = builder
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, json.len());
+                build(builder, Body::from(json))
+            }
+            Err(err) => {
+                warn!("Failed to serialise {:?}: {:?}", other, err);
+                text_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to serialise content",
+                )
+            }
+        },
+    }
+}
+
+// The safe:// URL a request is for, given the path and query of the request's URL
+fn safe_url_for(path: &str, query: Option<&str>) -> Option<String> {
+    let location = path.trim_start_matches('/');
+    if location.is_empty() {
+        return None;
+    }
+
+    let mut url = format!("safe://{}", location);
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    Some(url)
+}
+
+// Parse the value of a Range header. Only a single range with a start is supported, for
+// any other value the header is ignored and the whole content is served.
+fn parse_range(value: &str) -> Range {
+    let (start, end) = value.trim().strip_prefix("bytes=")?.split_once('-')?;
+    let start = start.trim().parse::<u64>().ok()?;
+    let end = match end.trim() {
+        "" => None,
+        end => {
+            // the end of HTTP ranges is inclusive while it's exclusive in ours
+            let end = end.parse::<u64>().ok()?;
+            if end < start {
+                return None;
+            }
+            Some(end + 1)
+        }
+    };
+    Some((Some(start), end))
+}
+
+// Size of a file, which is only known from the FileInfo of the FilesContainer it was
+// resolved through
+fn file_size(content: &SafeData) -> Option<u64> {
+    match content {
+        SafeData::PublicFile {
+            metadata: Some(file_info),
+            ..
+        } => file_info.get("size")?.parse().ok(),
+        _ => None,
+    }
+}
+
+// Number of bytes of a file of the given size that are served for a range
+fn served_length(range: Range, size: u64) -> u64 {
+    match range {
+        Some((start, end)) => {
+            let end = end.map_or(size, |end| end.min(size));
+            end.saturating_sub(start.unwrap_or(0))
+        }
+        None => size,
+    }
+}
+
+// The 416 response to a range starting past the end of a file of the given size
+fn unsatisfiable_range_response(range: Range, size: u64) -> Option<Response<Body>> {
+    match range {
+        Some((Some(start), _)) if start >= size => {
+            let builder = Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(CONTENT_RANGE, format!("bytes */{}", size));
+            Some(build(builder, Body::empty()))
+        }
+        _ => None,
+    }
+}
+
+// Strong ETag of some content: files are content-addressed by their XorName, and the
+// content of a FilesContainer is that of the version fetched.
+fn etag(content: &SafeData) -> Option<String> {
+    match content {
+        SafeData::PublicFile { xorname, .. } => Some(format!("\"{}\"", xorname_to_hex(xorname))),
+        SafeData::FilesContainer {
+            version: Some(version),
+            ..
+        } => Some(format!("\"{}\"", version)),
+        _ => None,
+    }
+}
+
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+// Items found directly in a directory of a FilesMap, keyed by name, along with whether they are
+// directories and their FileInfo, which directories only implied by the paths of the items
+// within them don't have.
+fn dir_items(files_map: &FilesMap) -> BTreeMap<&str, (bool, Option<&FileInfo>)> {
+    let mut items = BTreeMap::new();
+    for (path, file_info) in files_map {
+        match path.trim_start_matches('/').split_once('/') {
+            Some((dir, _)) => {
+                let _ = items.entry(dir).or_insert((true, None));
+            }
+            None => {
+                let name = path.trim_start_matches('/');
+                let is_dir = file_info.get("type").map(String::as_str) == Some("inode/directory");
+                let _ = items.insert(name, (is_dir, Some(file_info)));
+            }
+        }
+    }
+    items
+}
+
+fn render_listing(path: &str, query: &str, files_map: &FilesMap) -> String {
+    let base = path.trim_end_matches('/');
+    let title = html_escape(&format!("safe://{}/", base.trim_start_matches('/')));
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Index of {title}</title></head>\n\
+        <body>\n<h1>Index of {title}</h1>\n<table>\n\
+        <tr><th>Name</th><th>Type</th><th>Size</th><th>Modified</th></tr>\n"
+    );
+    if base.matches('/').count() > 1 {
+        let parent = base.rsplit_once('/').map_or("/", |(parent, _)| parent);
+        let _ = writeln!(
+            html,
+            "<tr><td><a href=\"{}{}\">../</a></td><td></td><td></td><td></td></tr>",
+            html_escape(parent),
+            query
+        );
+    }
+
+    for (name, (is_dir, file_info)) in dir_items(files_map) {
+        let href = format!(
+            "{}/{}{}",
+            base,
+            utf8_percent_encode(name, PATH_SEGMENT),
+            query
+        );
+        let field = |key: &str| {
+            file_info
+                .and_then(|file_info| file_info.get(key))
+                .map_or(String::new(), |value| html_escape(value))
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td><a href=\"{}\">{}{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            html_escape(&href),
+            html_escape(name),
+            if is_dir { "/" } else { "" },
+            field("type"),
+            if is_dir { String::new() } else { field("size") },
+            field("modified"),
+        );
+    }
+
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn error_response(err: &Error) -> Response<Body> {
+    let status = match err {
+        Error::ContentNotFound(_)
+        | Error::ContentError(_)
+        | Error::EmptyContent(_)
+        | Error::VersionNotFound(_)
+        | Error::EntryNotFound(_)
+        | Error::HashNotFound(_) => StatusCode::NOT_FOUND,
+        Error::InvalidXorUrl(_) | Error::InvalidInput(_) | Error::UrlError(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        Error::AccessDenied(_) => StatusCode::FORBIDDEN,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    text_response(status, &err.to_string())
+}
+
+fn text_response(status: StatusCode, message: &str) -> Response<Body> {
+    let builder = Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8");
+    build(builder, Body::from(format!("{}\n", message)))
+}
+
+fn build(builder: Builder, body: Body) -> Response<Body> {
+    builder.body(body).unwrap_or_else(|err| {
+        warn!("Failed to build the gateway response: {:?}", err);
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        response
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use xor_name::XorName;
+
+    fn item(file_type: &str, size: &str) -> FileInfo {
+        BTreeMap::from([
+            ("type".to_string(), file_type.to_string()),
+            ("size".to_string(), size.to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_safe_url_for() {
+        assert_eq!(safe_url_for("/", None), None);
+        assert_eq!(
+            safe_url_for("/mysite/docs/a%20b.md", Some("v=hyryyr")),
+            Some("safe://mysite/docs/a%20b.md?v=hyryyr".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99"), Some((Some(0), Some(100))));
+        assert_eq!(parse_range("bytes=100-"), Some((Some(100), None)));
+        // suffix, multiple and invalid ranges are ignored
+        assert_eq!(parse_range("bytes=-100"), None);
+        assert_eq!(parse_range("bytes=0-1,5-6"), None);
+        assert_eq!(parse_range("bytes=10-5"), None);
+        assert_eq!(parse_range("items=0-1"), None);
+    }
+
+    #[test]
+    fn test_range_past_the_end_of_a_file() {
+        let response = unsatisfiable_range_response(Some((Some(10), None)), 10);
+        let response = response.expect("a range starting at the size should be unsatisfiable");
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[CONTENT_RANGE], "bytes */10");
+
+        assert!(unsatisfiable_range_response(Some((Some(9), None)), 10).is_none());
+        assert!(unsatisfiable_range_response(None, 0).is_none());
+    }
+
+    #[test]
+    fn test_head_of_an_inspected_file() {
+        let file = SafeData::PublicFile {
+            xorurl: String::new(),
+            xorname: XorName::default(),
+            data: Bytes::new(),
+            media_type: None,
+            metadata: Some(item("text/plain", "10")),
+            resolved_from: String::new(),
+        };
+
+        let response = content_response(file.clone(), None, "/file.txt", true);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_LENGTH], "10");
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/plain");
+
+        let response = content_response(file, Some((Some(4), Some(20))), "/file.txt", true);
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[CONTENT_LENGTH], "6");
+        assert_eq!(response.headers()[CONTENT_RANGE], "bytes 4-9/10");
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"xyz\"", "\"abc\""));
+    }
+
+    #[test]
+    fn test_dir_items() {
+        let files_map = FilesMap::from([
+            ("/a.txt".to_string(), item("text/plain", "10")),
+            ("/empty".to_string(), item("inode/directory", "0")),
+            ("/sub/b.txt".to_string(), item("text/plain", "20")),
+            ("/sub/deep/c.txt".to_string(), item("text/plain", "30")),
+        ]);
+        let items = dir_items(&files_map);
+        assert_eq!(
+            items.keys().copied().collect::<Vec<_>>(),
+            vec!["a.txt", "empty", "sub"]
+        );
+        assert_eq!(items["a.txt"], (false, files_map.get("/a.txt")));
+        assert_eq!(items["empty"], (true, files_map.get("/empty")));
+        assert_eq!(items["sub"], (true, None));
+    }
+
+    #[test]
+    fn test_render_listing_escapes_names() {
+        let files_map = FilesMap::from([("<b> & c?.txt".to_string(), item("text/plain", "5"))]);
+        let html = render_listing("/mysite/docs/", "?v=hyryyr", &files_map);
+        assert!(html.contains("<title>Index of safe://mysite/docs/</title>"));
+        assert!(html.contains(
+            "<a href=\"/mysite/docs/%3Cb%3E%20&amp;%20c%3F.txt?v=hyryyr\">&lt;b&gt; &amp; c?.txt</a>"
+        ));
+        assert!(html.contains("<a href=\"/mysite?v=hyryyr\">../</a>"));
+    }
+}
@@ -8,7 +8,7 @@
 
 use super::OutputFmt;
 use ansi_term::Style;
-use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
+use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Help, Result};
 use comfy_table::{Cell, CellAlignment, Table};
 use num_traits::Float;
 use serde::ser::Serialize;
@@ -16,10 +16,14 @@ use sn_api::{
     files::{FilesMapChange, ProcessedFiles},
     multimap::Multimap,
     nrs::NrsMap,
+    register::EntryHash,
     wallet::Dbc,
-    Safe, SafeUrl,
+    Safe, SafeUrl, VersionHash,
+};
+use std::{
+    io::{stdin, stdout, Read, Write},
+    str::FromStr,
 };
-use std::io::{stdin, stdout, Read, Write};
 use tracing::{debug, warn};
 use xor_name::XorName;
 
@@ -33,6 +37,19 @@ pub fn xorname_to_hex(xorname: &XorName) -> String {
     xorname.0.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
+// Encodes the hash of a Register entry the same way content versions are encoded in URLs
+pub fn encode_entry_hash(hash: &EntryHash) -> String {
+    VersionHash::from(hash).to_string()
+}
+
+// Decodes the hash of a Register entry, as encoded by `encode_entry_hash`
+pub fn decode_entry_hash(hash: &str) -> Result<EntryHash> {
+    VersionHash::from_str(hash)
+        .map(|version| version.entry_hash())
+        .map_err(|err| eyre!("Invalid entry hash '{}': {}", hash, err))
+        .suggestion("Entry hashes are shown when reading or writing entries.")
+}
+
 // Read the argument string from the STDIN if is not an arg provided
 pub fn get_from_arg_or_stdin(arg: Option<String>, message: Option<&str>) -> Result<String> {
     match arg {
@@ -13,11 +13,16 @@ pub mod config;
 pub mod dog;
 pub mod files;
 mod files_get;
+pub mod gateway;
 mod helpers;
 pub mod keys;
+#[cfg(target_os = "linux")]
+pub mod mount;
+pub mod multimap;
 pub mod networks;
 pub mod node;
 pub mod nrs;
+pub mod register;
 pub mod safe_id;
 pub mod setup;
 pub mod update;
@@ -83,15 +88,34 @@ pub enum SubCommands {
     )]
     /// Inspect data on the SAFE Network providing only metadata information about the content
     Dog(dog::DogCommands),
+    #[clap(
+        name = "gateway",
+        global_settings(&[AppSettings::DisableVersion]),
+    )]
+    /// Serve content from the SAFE Network over HTTP, for browsers and other HTTP clients
+    Gateway(gateway::GatewayCommands),
+    #[cfg(target_os = "linux")]
+    #[clap(
+        name = "mount",
+        global_settings(&[AppSettings::DisableVersion]),
+    )]
+    /// Mount a FilesContainer as a local directory
+    Mount(mount::MountCommands),
     #[clap(name = "files", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage files on the SAFE Network
     Files(files::FilesSubCommands),
     #[clap(name = "setup", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Perform setup tasks
     Setup(setup::SetupSubCommands),
+    #[clap(name = "multimap", subcommand, global_settings(&[AppSettings::DisableVersion]))]
+    /// Manage Multimaps on the SAFE Network
+    Multimap(multimap::MultimapSubCommands),
     #[clap(name = "nrs", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage public names on the SAFE Network
     Nrs(nrs::NrsSubCommands),
+    #[clap(name = "register", subcommand, global_settings(&[AppSettings::DisableVersion]))]
+    /// Manage Registers on the SAFE Network
+    Register(register::RegisterSubCommands),
     #[clap(name = "keys", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage keys on the SAFE Network
     Keys(keys::KeysSubCommands),
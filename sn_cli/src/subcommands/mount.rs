@@ -0,0 +1,1042 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{gen_processed_files_table, get_target_url, serialise_output},
+    OutputFmt,
+};
+use bytes::Bytes;
+use clap::Args;
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help, Result};
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory,
+    ReplyEmpty, ReplyEntry, ReplyOpen, ReplyWrite, Request, TimeOrNow, FUSE_ROOT_ID,
+};
+use libc::{c_int, EEXIST, EINVAL, EIO, EISDIR, ENOENT, ENOTDIR, ENOTEMPTY, EROFS, EXDEV};
+use sn_api::{
+    files::{FilesMap, ProcessedFiles},
+    ContentType, Safe, XorUrl,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io,
+    os::unix::fs::{FileExt, MetadataExt},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime::Handle;
+use tracing::{debug, warn};
+
+// How long the kernel can cache the attributes and entries we reply with
+const TTL: Duration = Duration::from_secs(1);
+
+#[derive(Args, Debug)]
+pub struct MountCommands {
+    /// The safe:// URL of the FilesContainer to mount, or of an NRS name linked to one
+    location: String,
+    /// The local directory to mount the FilesContainer on
+    mountpoint: PathBuf,
+    /// Allow changes to the mounted files, which are written back to the FilesContainer
+    /// as a new version when it's unmounted
+    #[clap(short = 'w', long = "write-back")]
+    write_back: bool,
+}
+
+pub async fn mount_commander(cmd: MountCommands, output_fmt: OutputFmt, safe: &Safe) -> Result<()> {
+    let mut url = get_target_url(&cmd.location)?;
+    if cmd.write_back && url.content_version().is_some() {
+        return Err(eyre!(
+            "A specific version of a FilesContainer cannot be mounted with write-back enabled"
+        ))
+        .suggestion("Remove the version from the URL to mount the latest version instead.");
+    }
+    debug!("Mounting {} on {}", url, cmd.mountpoint.display());
+
+    let (version, files_map) = match safe.files_container_get(&url.to_string()).await? {
+        Some((version, files_map)) => (Some(version), files_map),
+        None => (None, FilesMap::default()),
+    };
+    let metadata = fs::metadata(&cmd.mountpoint).wrap_err_with(|| {
+        format!(
+            "Failed to read the mountpoint directory {}",
+            cmd.mountpoint.display()
+        )
+    })?;
+
+    let staging = if cmd.write_back {
+        let dir = tempfile::tempdir()
+            .context("Failed to create the directory to stage the changes made to the files")?;
+        Some(dir)
+    } else {
+        None
+    };
+    let removed = Arc::new(Mutex::new(BTreeSet::new()));
+    let safe_fs = SafeFs {
+        safe: safe.clone(),
+        runtime: Handle::current(),
+        inodes: Inodes::from_files_map(&files_map),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        write_back: staging.as_ref().map(|dir| WriteBack {
+            staging: dir.path().to_path_buf(),
+            removed: removed.clone(),
+        }),
+    };
+
+    let mut options = vec![
+        MountOption::FSName("safe".to_string()),
+        MountOption::DefaultPermissions,
+    ];
+    if !cmd.write_back {
+        options.push(MountOption::RO);
+    }
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "FilesContainer ({}) at \"{}\" mounted on {}",
+            version.map_or("empty".to_string(), |v| format!("version {}", v)),
+            url,
+            cmd.mountpoint.display()
+        );
+        println!(
+            "Unmount it with 'fusermount -u {}'{}",
+            cmd.mountpoint.display(),
+            if cmd.write_back {
+                " to write the changes made back to the FilesContainer"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let mountpoint = cmd.mountpoint.clone();
+    tokio::task::spawn_blocking(move || fuser::mount2(safe_fs, &mountpoint, &options))
+        .await?
+        .wrap_err_with(|| {
+            format!(
+                "Failed to mount the FilesContainer on {}",
+                cmd.mountpoint.display()
+            )
+        })?;
+
+    let staging = match staging {
+        Some(staging) => staging,
+        None => return Ok(()),
+    };
+    let removed = removed
+        .lock()
+        .map_err(|_| eyre!("Failed to read the list of files removed"))?
+        .clone();
+
+    // The FilesContainer is updated through the NRS name if that's what was mounted
+    let update_nrs = url.content_type() == ContentType::NrsMapContainer;
+    url.set_path("");
+    let mut processed_files = ProcessedFiles::default();
+    let mut new_version = version;
+
+    // The changes made to the files and their removals are written back as a single new version
+    let removals = removals(&removed, &files_map);
+    let has_staged_changes = fs::read_dir(staging.path())?.next().is_some();
+    if has_staged_changes || !removals.is_empty() {
+        let result = safe
+            .files_container_sync_with_removals(
+                format!("{}/", staging.path().display()),
+                &url.to_string(),
+                &removals,
+                update_nrs,
+            )
+            .await
+            .wrap_err("Failed to write the changes made back to the FilesContainer");
+        let (content, synced) = match result {
+            Ok(synced) => synced,
+            Err(err) => {
+                // Keep the staged changes rather than deleting them with the temporary dir
+                let staging = staging.into_path();
+                let mut report: Result<()> = Err(err)
+                    .note(format!(
+                        "The files changed were kept in {}",
+                        staging.display()
+                    ))
+                    .suggestion(format!(
+                        "Write them back with 'safe files sync --recursive{} {}/ {}'",
+                        if update_nrs { " --update-nrs" } else { "" },
+                        staging.display(),
+                        url
+                    ));
+                if !removals.is_empty() {
+                    let paths: Vec<&str> = removals.iter().map(|(path, _)| path.as_str()).collect();
+                    report = report
+                        .note(format!("The paths removed were: {}", paths.join(", ")))
+                        .suggestion(
+                            "Remove them with 'safe files rm', using '--recursive' for directories",
+                        );
+                }
+                return report;
+            }
+        };
+        new_version = content.map(|(version, _)| version);
+        // Report the paths the files were synced to rather than the staged local paths
+        processed_files.extend(synced.into_iter().map(|(path, change)| {
+            let path = path.strip_prefix(staging.path()).unwrap_or(&path);
+            (Path::new("/").join(path), change)
+        }));
+    }
+
+    if OutputFmt::Pretty == output_fmt {
+        let (table, success_count) = gen_processed_files_table(&processed_files, true);
+        if success_count > 0 {
+            url.set_content_version(new_version);
+            println!(
+                "FilesContainer synced up ({}): \"{}\"",
+                new_version.map_or("empty".to_string(), |v| format!("version {}", v)),
+                url
+            );
+            println!("{table}");
+        } else {
+            println!("No changes were made to the FilesContainer at \"{}\"", url);
+        }
+    } else {
+        url.set_content_version(new_version);
+        println!(
+            "{}",
+            serialise_output(&(url.to_string(), processed_files), output_fmt)
+        );
+    }
+
+    Ok(())
+}
+
+// The removals to apply to the FilesContainer for the paths removed while it was mounted, as
+// pairs of (path, recursive). Paths within a removed directory are removed along with it.
+fn removals(removed: &BTreeSet<String>, files_map: &FilesMap) -> Vec<(String, bool)> {
+    let mut removals = Vec::new();
+    let mut removed_dirs = Vec::<String>::new();
+    for path in removed {
+        if removed_dirs.iter().any(|dir| path.starts_with(dir)) {
+            continue;
+        }
+        if files_map.contains_key(path) {
+            removals.push((path.clone(), false));
+        }
+        let dir = format!("{}/", path);
+        if files_map.keys().any(|item| item.starts_with(&dir)) {
+            removals.push((path.clone(), true));
+            removed_dirs.push(dir);
+        }
+    }
+    removals
+}
+
+#[derive(Debug, PartialEq)]
+enum NodeKind {
+    // Items in the directory by name
+    Dir(BTreeMap<String, u64>),
+    // File as published on the network, empty files have no link
+    File(Option<XorUrl>),
+    // File with a local copy staged to be written back
+    Staged,
+    Symlink(String),
+}
+
+#[derive(Debug)]
+struct Node {
+    parent: u64,
+    // Path of the item in the FilesMap, the root directory's being empty
+    path: String,
+    kind: NodeKind,
+    size: u64,
+    modified: SystemTime,
+    created: SystemTime,
+}
+
+// The tree of items of a FilesContainer, indexed by inode number
+#[derive(Debug)]
+struct Inodes {
+    nodes: BTreeMap<u64, Node>,
+    next_ino: u64,
+}
+
+impl Inodes {
+    fn from_files_map(files_map: &FilesMap) -> Self {
+        let root = Node {
+            parent: FUSE_ROOT_ID,
+            path: String::new(),
+            kind: NodeKind::Dir(BTreeMap::new()),
+            size: 0,
+            modified: UNIX_EPOCH,
+            created: UNIX_EPOCH,
+        };
+        let mut inodes = Self {
+            nodes: BTreeMap::from([(FUSE_ROOT_ID, root)]),
+            next_ino: FUSE_ROOT_ID + 1,
+        };
+
+        for (path, file_info) in files_map {
+            let mut components = path
+                .split('/')
+                .filter(|c| !c.is_empty())
+                .collect::<Vec<_>>();
+            let name = match components.pop() {
+                Some(name) => name,
+                None => continue,
+            };
+            let parent = components
+                .into_iter()
+                .fold(FUSE_ROOT_ID, |parent, dir| inodes.dir(parent, dir));
+
+            let modified = parse_timestamp(file_info.get("modified"));
+            let ino = match file_info.get("type").map(String::as_str) {
+                Some("inode/directory") => inodes.dir(parent, name),
+                Some("inode/symlink") => {
+                    let target = file_info.get("symlink_target").cloned().unwrap_or_default();
+                    let size = target.len() as u64;
+                    inodes.insert(parent, name, NodeKind::Symlink(target), size, modified)
+                }
+                _ => {
+                    let link = file_info.get("link").filter(|link| !link.is_empty());
+                    let size = file_info
+                        .get("size")
+                        .and_then(|size| size.parse().ok())
+                        .unwrap_or(0);
+                    inodes.insert(parent, name, NodeKind::File(link.cloned()), size, modified)
+                }
+            };
+            if let Some(node) = inodes.nodes.get_mut(&ino) {
+                node.modified = node.modified.max(modified);
+                node.created = parse_timestamp(file_info.get("created"));
+            }
+        }
+
+        inodes
+    }
+
+    fn get(&self, ino: u64) -> Option<&Node> {
+        self.nodes.get(&ino)
+    }
+
+    fn get_mut(&mut self, ino: u64) -> Option<&mut Node> {
+        self.nodes.get_mut(&ino)
+    }
+
+    fn child(&self, parent: u64, name: &str) -> Option<u64> {
+        match &self.get(parent)?.kind {
+            NodeKind::Dir(children) => children.get(name).copied(),
+            _ => None,
+        }
+    }
+
+    // Get the directory with the given name, adding it if it doesn't exist yet
+    fn dir(&mut self, parent: u64, name: &str) -> u64 {
+        match self.child(parent, name) {
+            Some(ino) if matches!(self.nodes[&ino].kind, NodeKind::Dir(_)) => ino,
+            _ => self.insert(parent, name, NodeKind::Dir(BTreeMap::new()), 0, UNIX_EPOCH),
+        }
+    }
+
+    // Add an item to a directory, replacing any item with the same name in it.
+    // Directories are given the modification time of the latest item within them.
+    fn insert(
+        &mut self,
+        parent: u64,
+        name: &str,
+        kind: NodeKind,
+        size: u64,
+        modified: SystemTime,
+    ) -> u64 {
+        let ino = self.next_ino;
+        self.next_ino += 1;
+        let path = format!("{}/{}", self.nodes[&parent].path, name);
+        let node = Node {
+            parent,
+            path,
+            kind,
+            size,
+            modified,
+            created: modified,
+        };
+        let _ = self.nodes.insert(ino, node);
+        self.link(ino, parent, name);
+        ino
+    }
+
+    // Remove an item from its directory, along with everything within it
+    fn remove(&mut self, parent: u64, name: &str) -> Option<Node> {
+        let ino = match self.nodes.get_mut(&parent).map(|node| &mut node.kind) {
+            Some(NodeKind::Dir(children)) => children.remove(name)?,
+            _ => return None,
+        };
+        let node = self.nodes.remove(&ino)?;
+        let mut pending = match &node.kind {
+            NodeKind::Dir(children) => children.values().copied().collect(),
+            _ => Vec::new(),
+        };
+        while let Some(ino) = pending.pop() {
+            if let Some(NodeKind::Dir(children)) = self.nodes.remove(&ino).map(|node| node.kind) {
+                pending.extend(children.into_values());
+            }
+        }
+        Some(node)
+    }
+
+    // Move a file or symlink to a directory with the given name
+    fn rename(&mut self, ino: u64, new_parent: u64, new_name: &str) {
+        let (parent, name) = match self.get(ino) {
+            Some(node) => (node.parent, item_name(&node.path).to_string()),
+            None => return,
+        };
+        if let Some(NodeKind::Dir(children)) = self.get_mut(parent).map(|node| &mut node.kind) {
+            let _ = children.remove(&name);
+        }
+        let path = format!("{}/{}", self.nodes[&new_parent].path, new_name);
+        if let Some(node) = self.get_mut(ino) {
+            node.parent = new_parent;
+            node.path = path;
+        }
+        self.link(ino, new_parent, new_name);
+    }
+
+    fn link(&mut self, ino: u64, parent: u64, name: &str) {
+        let modified = self.nodes[&ino].modified;
+        if let Some(NodeKind::Dir(children)) = self.get_mut(parent).map(|node| &mut node.kind) {
+            let _ = children.insert(name.to_string(), ino);
+        }
+
+        let mut ancestor = parent;
+        while let Some(node) = self.nodes.get_mut(&ancestor) {
+            node.modified = node.modified.max(modified);
+            if ancestor == FUSE_ROOT_ID {
+                break;
+            }
+            ancestor = node.parent;
+        }
+    }
+}
+
+// The changes made to a FilesContainer mounted with write-back enabled
+struct WriteBack {
+    // Directory where new and modified files are staged, mirroring the FilesContainer's paths
+    staging: PathBuf,
+    // Paths removed, which may have been added back after
+    removed: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl WriteBack {
+    fn staged_path(&self, path: &str) -> PathBuf {
+        self.staging.join(path.trim_start_matches('/'))
+    }
+
+    fn set_removed(&self, path: &str, removed: bool) -> Result<(), c_int> {
+        let mut paths = self.removed.lock().map_err(|_| EIO)?;
+        if removed {
+            let _ = paths.insert(path.to_string());
+        } else {
+            let _ = paths.remove(path);
+        }
+        Ok(())
+    }
+}
+
+// A FilesContainer exposed as a FUSE filesystem, reading the files' content from the network
+// on demand, and staging any change made locally if write-back is enabled
+struct SafeFs {
+    safe: Safe,
+    runtime: Handle,
+    inodes: Inodes,
+    uid: u32,
+    gid: u32,
+    write_back: Option<WriteBack>,
+}
+
+impl SafeFs {
+    fn attr(&self, ino: u64) -> Result<FileAttr, c_int> {
+        let node = self.inodes.get(ino).ok_or(ENOENT)?;
+        let (kind, perm, nlink) = match node.kind {
+            NodeKind::Dir(_) => (FileType::Directory, 0o755, 2),
+            NodeKind::File(_) | NodeKind::Staged => (FileType::RegularFile, 0o644, 1),
+            NodeKind::Symlink(_) => (FileType::Symlink, 0o777, 1),
+        };
+        let perm = if self.write_back.is_some() {
+            perm
+        } else {
+            perm & 0o555
+        };
+
+        Ok(FileAttr {
+            ino,
+            size: node.size,
+            blocks: node.size.div_ceil(512),
+            atime: node.modified,
+            mtime: node.modified,
+            ctime: node.modified,
+            crtime: node.created,
+            kind,
+            perm,
+            nlink,
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        })
+    }
+
+    fn lookup_child(&self, parent: u64, name: &OsStr) -> Result<u64, c_int> {
+        let name = name.to_str().ok_or(ENOENT)?;
+        self.inodes.child(parent, name).ok_or(ENOENT)
+    }
+
+    fn read_file(&self, ino: u64, offset: u64, size: u32) -> Result<Bytes, c_int> {
+        let node = self.inodes.get(ino).ok_or(ENOENT)?;
+        let end = node.size.min(offset + u64::from(size));
+        if offset >= end {
+            return Ok(Bytes::new());
+        }
+
+        match &node.kind {
+            NodeKind::File(Some(link)) => self
+                .runtime
+                .block_on(self.safe.files_get(link, Some((Some(offset), Some(end)))))
+                .map_err(|err| {
+                    warn!("Failed to fetch the content of {}: {:?}", node.path, err);
+                    EIO
+                }),
+            NodeKind::File(None) => Ok(Bytes::new()),
+            NodeKind::Staged => {
+                let write_back = self.write_back.as_ref().ok_or(EIO)?;
+                let file = File::open(write_back.staged_path(&node.path)).map_err(errno)?;
+                let mut data = vec![0; (end - offset) as usize];
+                file.read_exact_at(&mut data, offset).map_err(errno)?;
+                Ok(Bytes::from(data))
+            }
+            NodeKind::Dir(_) => Err(EISDIR),
+            NodeKind::Symlink(_) => Err(EINVAL),
+        }
+    }
+
+    // Make a local copy of a file so it can be modified, keeping its content only if requested
+    fn stage(&mut self, ino: u64, keep_content: bool) -> Result<(), c_int> {
+        let write_back = self.write_back.as_ref().ok_or(EROFS)?;
+        let node = self.inodes.get(ino).ok_or(ENOENT)?;
+        let link = match &node.kind {
+            NodeKind::Staged => return Ok(()),
+            NodeKind::File(link) => link.as_ref(),
+            NodeKind::Dir(_) => return Err(EISDIR),
+            NodeKind::Symlink(_) => return Err(EINVAL),
+        };
+
+        let content = match link {
+            Some(link) if keep_content => self
+                .runtime
+                .block_on(self.safe.files_get(link, None))
+                .map_err(|err| {
+                    warn!("Failed to fetch the content of {}: {:?}", node.path, err);
+                    EIO
+                })?,
+            _ => Bytes::new(),
+        };
+        let staged_path = write_back.staged_path(&node.path);
+        create_parent_dir(&staged_path)?;
+        fs::write(&staged_path, &content).map_err(errno)?;
+
+        if let Some(node) = self.inodes.get_mut(ino) {
+            node.kind = NodeKind::Staged;
+            node.size = content.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn write_file(&mut self, ino: u64, offset: u64, data: &[u8]) -> Result<u32, c_int> {
+        self.stage(ino, true)?;
+        let write_back = self.write_back.as_ref().ok_or(EROFS)?;
+        let node = self.inodes.get_mut(ino).ok_or(ENOENT)?;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .open(write_back.staged_path(&node.path))
+            .map_err(errno)?;
+        file.write_all_at(data, offset).map_err(errno)?;
+
+        node.size = node.size.max(offset + data.len() as u64);
+        node.modified = SystemTime::now();
+        Ok(data.len() as u32)
+    }
+
+    fn truncate(&mut self, ino: u64, size: u64) -> Result<(), c_int> {
+        self.stage(ino, size > 0)?;
+        let write_back = self.write_back.as_ref().ok_or(EROFS)?;
+        let node = self.inodes.get_mut(ino).ok_or(ENOENT)?;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .open(write_back.staged_path(&node.path))
+            .map_err(errno)?;
+        file.set_len(size).map_err(errno)?;
+
+        node.size = size;
+        node.modified = SystemTime::now();
+        Ok(())
+    }
+
+    fn create_node(&mut self, parent: u64, name: &OsStr, is_dir: bool) -> Result<u64, c_int> {
+        let write_back = self.write_back.as_ref().ok_or(EROFS)?;
+        let name = name.to_str().ok_or(EINVAL)?;
+        let dir = self.inodes.get(parent).ok_or(ENOENT)?;
+        match &dir.kind {
+            NodeKind::Dir(children) if children.contains_key(name) => return Err(EEXIST),
+            NodeKind::Dir(_) => {}
+            _ => return Err(ENOTDIR),
+        }
+
+        let path = format!("{}/{}", dir.path, name);
+        let staged_path = write_back.staged_path(&path);
+        let kind = if is_dir {
+            fs::create_dir_all(&staged_path).map_err(errno)?;
+            NodeKind::Dir(BTreeMap::new())
+        } else {
+            create_parent_dir(&staged_path)?;
+            let _ = File::create(&staged_path).map_err(errno)?;
+            NodeKind::Staged
+        };
+        write_back.set_removed(&path, false)?;
+
+        Ok(self.inodes.insert(parent, name, kind, 0, SystemTime::now()))
+    }
+
+    fn remove_node(&mut self, parent: u64, name: &OsStr, is_dir: bool) -> Result<(), c_int> {
+        let write_back = self.write_back.as_ref().ok_or(EROFS)?;
+        let name = name.to_str().ok_or(ENOENT)?;
+        let ino = self.inodes.child(parent, name).ok_or(ENOENT)?;
+        match (&self.inodes.get(ino).ok_or(ENOENT)?.kind, is_dir) {
+            (NodeKind::Dir(children), true) if !children.is_empty() => return Err(ENOTEMPTY),
+            (NodeKind::Dir(_), true) => {}
+            (NodeKind::Dir(_), false) => return Err(EISDIR),
+            (_, true) => return Err(ENOTDIR),
+            (_, false) => {}
+        }
+
+        let node = self.inodes.remove(parent, name).ok_or(ENOENT)?;
+        let staged_path = write_back.staged_path(&node.path);
+        let result = match node.kind {
+            NodeKind::Staged => fs::remove_file(staged_path),
+            NodeKind::Dir(_) => fs::remove_dir(staged_path),
+            _ => Ok(()),
+        };
+        match result {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(errno(err)),
+            _ => {}
+        }
+
+        write_back.set_removed(&node.path, true)
+    }
+
+    // Only files are renamed, for directories and symlinks EXDEV lets tools
+    // like 'mv' fall back to copying them and removing the originals.
+    fn rename_node(
+        &mut self,
+        parent: u64,
+        name: &OsStr,
+        new_parent: u64,
+        new_name: &OsStr,
+    ) -> Result<(), c_int> {
+        let ino = self.lookup_child(parent, name)?;
+        if !matches!(
+            self.inodes.get(ino).ok_or(ENOENT)?.kind,
+            NodeKind::File(_) | NodeKind::Staged
+        ) {
+            return Err(EXDEV);
+        }
+        match self.inodes.get(new_parent).map(|node| &node.kind) {
+            Some(NodeKind::Dir(_)) => {}
+            Some(_) => return Err(ENOTDIR),
+            None => return Err(ENOENT),
+        }
+        if let Ok(existing) = self.lookup_child(new_parent, new_name) {
+            if existing == ino {
+                return Ok(());
+            }
+            self.remove_node(new_parent, new_name, false)?;
+        }
+
+        self.stage(ino, true)?;
+        let new_name = new_name.to_str().ok_or(EINVAL)?;
+        let old_path = self.inodes.get(ino).ok_or(ENOENT)?.path.clone();
+        self.inodes.rename(ino, new_parent, new_name);
+        let new_path = self.inodes.get(ino).ok_or(ENOENT)?.path.clone();
+
+        let write_back = self.write_back.as_ref().ok_or(EROFS)?;
+        let new_staged_path = write_back.staged_path(&new_path);
+        create_parent_dir(&new_staged_path)?;
+        fs::rename(write_back.staged_path(&old_path), new_staged_path).map_err(errno)?;
+        write_back.set_removed(&old_path, true)?;
+        write_back.set_removed(&new_path, false)
+    }
+}
+
+impl Filesystem for SafeFs {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        match self
+            .lookup_child(parent, name)
+            .and_then(|ino| self.attr(ino))
+        {
+            Ok(attr) => reply.entry(&TTL, &attr, 0),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyAttr) {
+        match self.attr(ino) {
+            Ok(attr) => reply.attr(&TTL, &attr),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    // Only changing the size is supported, other attributes are left unchanged
+    fn setattr(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _mode: Option<u32>,
+        _uid: Option<u32>,
+        _gid: Option<u32>,
+        size: Option<u64>,
+        _atime: Option<TimeOrNow>,
+        _mtime: Option<TimeOrNow>,
+        _ctime: Option<SystemTime>,
+        _fh: Option<u64>,
+        _crtime: Option<SystemTime>,
+        _chgtime: Option<SystemTime>,
+        _bkuptime: Option<SystemTime>,
+        _flags: Option<u32>,
+        reply: ReplyAttr,
+    ) {
+        let result = match size {
+            Some(size) => self.truncate(ino, size),
+            None => Ok(()),
+        };
+        match result.and_then(|()| self.attr(ino)) {
+            Ok(attr) => reply.attr(&TTL, &attr),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn readlink(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyData) {
+        match self.inodes.get(ino).map(|node| &node.kind) {
+            Some(NodeKind::Symlink(target)) => reply.data(target.as_bytes()),
+            Some(_) => reply.error(EINVAL),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn mkdir(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        reply: ReplyEntry,
+    ) {
+        match self
+            .create_node(parent, name, true)
+            .and_then(|ino| self.attr(ino))
+        {
+            Ok(attr) => reply.entry(&TTL, &attr, 0),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn unlink(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.remove_node(parent, name, false) {
+            Ok(()) => reply.ok(),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn rmdir(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.remove_node(parent, name, true) {
+            Ok(()) => reply.ok(),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn rename(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        new_parent: u64,
+        new_name: &OsStr,
+        flags: u32,
+        reply: ReplyEmpty,
+    ) {
+        if flags != 0 {
+            return reply.error(EINVAL);
+        }
+        match self.rename_node(parent, name, new_parent, new_name) {
+            Ok(()) => reply.ok(),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, ino: u64, flags: i32, reply: ReplyOpen) {
+        let result = if flags & libc::O_ACCMODE == libc::O_RDONLY {
+            Ok(())
+        } else {
+            self.stage(ino, flags & libc::O_TRUNC == 0)
+        };
+        match result {
+            Ok(()) => reply.opened(0, 0),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        match self.read_file(ino, offset as u64, size) {
+            Ok(data) => reply.data(&data),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn write(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        data: &[u8],
+        _write_flags: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyWrite,
+    ) {
+        match self.write_file(ino, offset as u64, data) {
+            Ok(written) => reply.written(written),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let node = match self.inodes.get(ino) {
+            Some(node) => node,
+            None => return reply.error(ENOENT),
+        };
+        let children = match &node.kind {
+            NodeKind::Dir(children) => children,
+            _ => return reply.error(ENOTDIR),
+        };
+
+        let entries = [(ino, "."), (node.parent, "..")]
+            .into_iter()
+            .chain(children.iter().map(|(name, ino)| (*ino, name.as_str())));
+        for (index, (ino, name)) in entries.enumerate().skip(offset as usize) {
+            let kind = match self.inodes.get(ino).map(|node| &node.kind) {
+                Some(NodeKind::Dir(_)) => FileType::Directory,
+                Some(NodeKind::Symlink(_)) => FileType::Symlink,
+                _ => FileType::RegularFile,
+            };
+            // the offset given is that of the next entry to read
+            if reply.add(ino, index as i64 + 1, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn create(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _flags: i32,
+        reply: ReplyCreate,
+    ) {
+        match self
+            .create_node(parent, name, false)
+            .and_then(|ino| self.attr(ino))
+        {
+            Ok(attr) => reply.created(&TTL, &attr, 0, 0, 0),
+            Err(errno) => reply.error(errno),
+        }
+    }
+}
+
+// Items' timestamps in a FilesMap are the number of seconds since the Unix epoch
+fn parse_timestamp(timestamp: Option<&String>) -> SystemTime {
+    timestamp
+        .and_then(|secs| secs.parse().ok())
+        .map_or(UNIX_EPOCH, |secs| UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+fn item_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), c_int> {
+    match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).map_err(errno),
+        None => Ok(()),
+    }
+}
+
+fn errno(err: io::Error) -> c_int {
+    err.raw_os_error().unwrap_or(EIO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(file_type: &str, size: &str, modified: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("type".to_string(), file_type.to_string()),
+            ("size".to_string(), size.to_string()),
+            ("created".to_string(), "1000".to_string()),
+            ("modified".to_string(), modified.to_string()),
+            ("link".to_string(), format!("safe://{}", size)),
+        ])
+    }
+
+    #[test]
+    fn test_inodes_from_files_map() -> Result<()> {
+        let mut symlink = item("inode/symlink", "0", "1000");
+        let _ = symlink.insert("symlink_target".to_string(), "../a.txt".to_string());
+        let files_map = FilesMap::from([
+            ("/a.txt".to_string(), item("text/plain", "10", "1200")),
+            ("/empty".to_string(), item("inode/directory", "0", "1100")),
+            (
+                "/sub/deep/b.md".to_string(),
+                item("text/markdown", "20", "1300"),
+            ),
+            ("/sub/link".to_string(), symlink),
+        ]);
+        let inodes = Inodes::from_files_map(&files_map);
+
+        let root = FUSE_ROOT_ID;
+        let lookup = |path: &[&str]| {
+            path.iter()
+                .try_fold(root, |parent, name| inodes.child(parent, name))
+                .and_then(|ino| inodes.get(ino))
+                .ok_or_else(|| eyre!("{:?} not found", path))
+        };
+
+        let a = lookup(&["a.txt"])?;
+        assert_eq!(a.path, "/a.txt");
+        assert_eq!(a.kind, NodeKind::File(Some("safe://10".to_string())));
+        assert_eq!(a.size, 10);
+        assert_eq!(a.modified, UNIX_EPOCH + Duration::from_secs(1200));
+        assert_eq!(a.created, UNIX_EPOCH + Duration::from_secs(1000));
+
+        assert_eq!(lookup(&["empty"])?.kind, NodeKind::Dir(BTreeMap::new()));
+        assert_eq!(
+            lookup(&["sub", "link"])?.kind,
+            NodeKind::Symlink("../a.txt".to_string())
+        );
+        assert_eq!(lookup(&["sub", "deep", "b.md"])?.path, "/sub/deep/b.md");
+
+        // implied directories take the latest modification time within them
+        let sub = lookup(&["sub"])?;
+        assert!(matches!(&sub.kind, NodeKind::Dir(children) if children.len() == 2));
+        assert_eq!(sub.modified, UNIX_EPOCH + Duration::from_secs(1300));
+        assert_eq!(
+            lookup(&[])?.modified,
+            UNIX_EPOCH + Duration::from_secs(1300)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inodes_remove_and_rename() -> Result<()> {
+        let files_map = FilesMap::from([
+            ("/sub/a.txt".to_string(), item("text/plain", "10", "1200")),
+            (
+                "/sub/deep/b.txt".to_string(),
+                item("text/plain", "20", "1300"),
+            ),
+        ]);
+        let mut inodes = Inodes::from_files_map(&files_map);
+        let sub = inodes
+            .child(FUSE_ROOT_ID, "sub")
+            .ok_or_else(|| eyre!("sub not found"))?;
+        let a = inodes
+            .child(sub, "a.txt")
+            .ok_or_else(|| eyre!("a.txt not found"))?;
+
+        inodes.rename(a, FUSE_ROOT_ID, "moved.txt");
+        assert_eq!(inodes.child(sub, "a.txt"), None);
+        assert_eq!(inodes.child(FUSE_ROOT_ID, "moved.txt"), Some(a));
+        assert_eq!(
+            inodes.get(a).map(|node| node.path.as_str()),
+            Some("/moved.txt")
+        );
+
+        let removed = inodes
+            .remove(FUSE_ROOT_ID, "sub")
+            .ok_or_else(|| eyre!("sub not removed"))?;
+        assert_eq!(removed.path, "/sub");
+        // only the root directory and the moved file are left
+        assert_eq!(inodes.nodes.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_removals() {
+        let files_map = FilesMap::from([
+            ("/a.txt".to_string(), item("text/plain", "10", "1000")),
+            ("/dir".to_string(), item("inode/directory", "0", "1000")),
+            ("/dir/b.txt".to_string(), item("text/plain", "10", "1000")),
+            (
+                "/dir/sub/c.txt".to_string(),
+                item("text/plain", "10", "1000"),
+            ),
+            ("/other/d.txt".to_string(), item("text/plain", "10", "1000")),
+        ]);
+        let removed = BTreeSet::from([
+            "/a.txt".to_string(),
+            "/dir".to_string(),
+            "/dir/b.txt".to_string(),
+            "/dir/sub".to_string(),
+            "/dir/sub/c.txt".to_string(),
+            "/new.txt".to_string(),
+            "/other/d.txt".to_string(),
+        ]);
+
+        assert_eq!(
+            removals(&removed, &files_map),
+            vec![
+                ("/a.txt".to_string(), false),
+                ("/dir".to_string(), false),
+                ("/dir".to_string(), true),
+                ("/other/d.txt".to_string(), false),
+            ]
+        );
+    }
+}
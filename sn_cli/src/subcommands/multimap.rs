@@ -0,0 +1,352 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{decode_entry_hash, encode_entry_hash, get_from_arg_or_stdin, serialise_output},
+    OutputFmt,
+};
+use clap::Subcommand;
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help, Result};
+use comfy_table::Table;
+use serde::Serialize;
+use sn_api::{
+    multimap::{Multimap, MultimapKeyValue, MultimapValue},
+    register::EntryHash,
+    Error as ApiError, Safe,
+};
+use std::{collections::BTreeSet, path::PathBuf};
+use tokio::fs;
+
+/// Type tag used for Multimaps created with the CLI when none is specified
+const DEFAULT_MULTIMAP_TYPE_TAG: u64 = 25_000;
+
+#[derive(Subcommand, Debug)]
+pub enum MultimapSubCommands {
+    #[clap(name = "create")]
+    /// Create a new empty Multimap, owned by the public key configured for use with safe.
+    /// It's private unless the --public flag is set, i.e. its entries are encrypted so only
+    /// the owner can read them.
+    Create {
+        /// The type tag to create the Multimap with
+        #[clap(long = "type-tag", default_value_t = DEFAULT_MULTIMAP_TYPE_TAG)]
+        type_tag: u64,
+        /// Create a public Multimap, whose entries anyone can read
+        #[clap(long = "public")]
+        public: bool,
+    },
+    #[clap(name = "insert")]
+    /// Insert a key-value pair into a Multimap. Use the --replace argument to replace existing
+    /// entries with the new one.
+    Insert {
+        /// The URL of the Multimap to insert into
+        target: String,
+        /// The key of the entry, as a UTF-8 string
+        key: String,
+        /// The value of the entry. If neither this nor the --file argument is provided, it will
+        /// be read from stdin.
+        value: Option<String>,
+        /// Set this flag if the value is hex encoded, rather than a UTF-8 string
+        #[clap(long = "hex")]
+        hex: bool,
+        /// A path to a file to take the value of the entry from
+        #[clap(long = "file", conflicts_with_all = &["value", "hex"])]
+        file: Option<PathBuf>,
+        /// The hash of an entry the new entry replaces. It can be supplied multiple times.
+        #[clap(long = "replace")]
+        replace: Vec<String>,
+    },
+    #[clap(name = "remove")]
+    /// Remove entries from a Multimap, either by their hashes or by their key
+    Remove {
+        /// The URL of the Multimap to remove from
+        target: String,
+        /// The hashes of the entries to remove
+        hashes: Vec<String>,
+        /// Remove all the current entries with this key, as a UTF-8 string
+        #[clap(long = "key")]
+        key: Option<String>,
+    },
+    #[clap(name = "get")]
+    /// Get the current entries of a Multimap with the key provided
+    Get {
+        /// The URL of the Multimap to query
+        target: String,
+        /// The key of the entries, as a UTF-8 string
+        key: String,
+        /// Set this flag to output the values hex encoded, rather than as UTF-8 strings
+        #[clap(long = "hex")]
+        hex: bool,
+    },
+    #[clap(name = "show")]
+    /// Show the current entries of a Multimap
+    Show {
+        /// The URL of the Multimap to query
+        target: String,
+        /// Set this flag to also show the entries which were removed or replaced, and the
+        /// tombstones marking removals, in the order they were written
+        #[clap(long = "include-removed")]
+        include_removed: bool,
+        /// Set this flag to output the values hex encoded, rather than as UTF-8 strings
+        #[clap(long = "hex")]
+        hex: bool,
+    },
+}
+
+// The status of an entry of a Multimap, as listed by `safe multimap show`
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum EntryStatus {
+    Current,
+    Removed,
+    Tombstone,
+}
+
+// An entry of a Multimap, as output by `safe multimap get|show`.
+// Tombstones have no key nor value.
+#[derive(Debug, Serialize)]
+struct MultimapEntry {
+    hash: String,
+    key: Option<String>,
+    value: Option<String>,
+    status: EntryStatus,
+}
+
+impl MultimapEntry {
+    fn new(
+        hash: &EntryHash,
+        key_val: Option<&MultimapKeyValue>,
+        status: EntryStatus,
+        hex: bool,
+    ) -> Self {
+        Self {
+            hash: encode_entry_hash(hash),
+            key: key_val.map(|(key, _)| String::from_utf8_lossy(key).to_string()),
+            value: key_val.map(|(_, value)| value_to_string(value, hex)),
+            status,
+        }
+    }
+}
+
+pub async fn multimap_commander(
+    cmd: MultimapSubCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    match cmd {
+        MultimapSubCommands::Create { type_tag, public } => {
+            let xorurl = if public {
+                safe.multimap_create_public(None, type_tag).await?
+            } else {
+                safe.multimap_create(None, type_tag).await?
+            };
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Multimap created at: \"{}\"", xorurl);
+            } else {
+                println!("{}", serialise_output(&xorurl, output_fmt));
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Insert {
+            target,
+            key,
+            value,
+            hex,
+            file,
+            replace,
+        } => {
+            let value = if let Some(path) = file {
+                fs::read(&path).await.wrap_err_with(|| {
+                    format!("Failed to read the value from file {}", path.display())
+                })?
+            } else {
+                let value = get_from_arg_or_stdin(value, Some("...awaiting value from STDIN"))?;
+                if hex {
+                    hex::decode(value.trim())
+                        .map_err(|err| eyre!("Couldn't decode the hex encoded value: {}", err))
+                        .suggestion("Don't set the --hex flag to insert a UTF-8 string value.")?
+                } else {
+                    value.into_bytes()
+                }
+            };
+            let replace = replace
+                .iter()
+                .map(|hash| decode_entry_hash(hash))
+                .collect::<Result<BTreeSet<_>>>()?;
+
+            let hash = safe
+                .multimap_insert(&target, (key.clone().into_bytes(), value), replace)
+                .await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!(
+                    "Entry with key '{}' inserted into Multimap at \"{}\" with hash: {}",
+                    key,
+                    target,
+                    encode_entry_hash(&hash)
+                );
+            } else {
+                println!(
+                    "{}",
+                    serialise_output(&(target, encode_entry_hash(&hash)), output_fmt)
+                );
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Remove {
+            target,
+            hashes,
+            key,
+        } => {
+            let mut to_remove = hashes
+                .iter()
+                .map(|hash| decode_entry_hash(hash))
+                .collect::<Result<BTreeSet<_>>>()?;
+            if let Some(ref key) = key {
+                let entries =
+                    read_multimap(safe.multimap_get_by_key(&target, key.as_bytes()).await)?;
+                to_remove.extend(entries.into_iter().map(|(hash, _)| hash));
+            }
+            if to_remove.is_empty() {
+                return Err(eyre!("There are no entries to remove"))
+                    .suggestion("Provide the hashes of the entries to remove, or a key which has current entries in the Multimap.");
+            }
+
+            let hash = safe.multimap_remove(&target, to_remove.clone()).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!(
+                    "Removed {} entries from Multimap at \"{}\" with tombstone hash: {}",
+                    to_remove.len(),
+                    target,
+                    encode_entry_hash(&hash)
+                );
+            } else {
+                println!(
+                    "{}",
+                    serialise_output(&(target, encode_entry_hash(&hash)), output_fmt)
+                );
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Get { target, key, hex } => {
+            let entries = read_multimap(safe.multimap_get_by_key(&target, key.as_bytes()).await)?;
+            let entries: Vec<MultimapEntry> = entries
+                .iter()
+                .map(|(hash, key_val)| {
+                    MultimapEntry::new(hash, Some(key_val), EntryStatus::Current, hex)
+                })
+                .collect();
+
+            if OutputFmt::Pretty == output_fmt {
+                if entries.is_empty() {
+                    println!(
+                        "No entries with key '{}' found in Multimap at \"{}\"",
+                        key, target
+                    );
+                } else {
+                    println!("Entries with key '{}' of Multimap at \"{}\":", key, target);
+                    println!("{}", gen_entries_table(&entries, false));
+                }
+            } else {
+                println!("{}", serialise_output(&(target, entries), output_fmt));
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Show {
+            target,
+            include_removed,
+            hex,
+        } => {
+            let current = read_multimap(safe.multimap_read(&target).await)?;
+            let entries: Vec<MultimapEntry> = if include_removed {
+                let current_hashes: BTreeSet<EntryHash> =
+                    current.iter().map(|(hash, _)| *hash).collect();
+                safe.multimap_history(&target)
+                    .await?
+                    .iter()
+                    .map(|(hash, key_val)| {
+                        let status = match key_val {
+                            None => EntryStatus::Tombstone,
+                            Some(_) if current_hashes.contains(hash) => EntryStatus::Current,
+                            Some(_) => EntryStatus::Removed,
+                        };
+                        MultimapEntry::new(hash, key_val.as_ref(), status, hex)
+                    })
+                    .collect()
+            } else {
+                current
+                    .iter()
+                    .map(|(hash, key_val)| {
+                        MultimapEntry::new(hash, Some(key_val), EntryStatus::Current, hex)
+                    })
+                    .collect()
+            };
+
+            if OutputFmt::Pretty == output_fmt {
+                if entries.is_empty() {
+                    println!("Multimap at \"{}\" is empty", target);
+                } else {
+                    println!("Entries of Multimap at \"{}\":", target);
+                    println!("{}", gen_entries_table(&entries, include_removed));
+                }
+            } else {
+                println!("{}", serialise_output(&(target, entries), output_fmt));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+// An empty Multimap is reported as an error by the API, which we treat as having no entries
+fn read_multimap(result: Result<Multimap, ApiError>) -> Result<Multimap> {
+    match result {
+        Ok(multimap) => Ok(multimap),
+        Err(ApiError::EmptyContent(_)) => Ok(Multimap::default()),
+        Err(err) => Err(eyre!(err)),
+    }
+}
+
+fn gen_entries_table(entries: &[MultimapEntry], with_status: bool) -> Table {
+    let mut table = Table::new();
+    if with_status {
+        table.add_row(vec!["Entry hash", "Key", "Value", "Status"]);
+    } else {
+        table.add_row(vec!["Entry hash", "Key", "Value"]);
+    }
+    for entry in entries {
+        let mut row = vec![
+            entry.hash.clone(),
+            entry.key.clone().unwrap_or_default(),
+            entry.value.clone().unwrap_or_default(),
+        ];
+        if with_status {
+            let status = match entry.status {
+                EntryStatus::Current => "current",
+                EntryStatus::Removed => "removed",
+                EntryStatus::Tombstone => "tombstone",
+            };
+            row.push(status.to_string());
+        }
+        table.add_row(row);
+    }
+    table
+}
+
+fn value_to_string(value: &MultimapValue, hex: bool) -> String {
+    if hex {
+        hex::encode(value)
+    } else {
+        String::from_utf8_lossy(value).to_string()
+    }
+}
@@ -16,7 +16,7 @@ use clap::Subcommand;
 use color_eyre::{eyre::eyre, Help, Result};
 use comfy_table::Table;
 use sn_api::Error::{InvalidInput, NetDataError, NrsNameAlreadyExists, UnversionedContentError};
-use sn_api::{Safe, SafeUrl};
+use sn_api::{Safe, SafeUrl, VersionHash};
 
 #[derive(Subcommand, Debug)]
 pub enum NrsSubCommands {
@@ -58,6 +58,16 @@ pub enum NrsSubCommands {
         /// The name to remove
         name: String,
     },
+    #[clap(name = "resolve")]
+    /// List the links a public name was associated to concurrently, or resolve the conflict by
+    /// choosing one of them
+    Resolve {
+        /// The public name in conflict
+        public_name: String,
+        /// The version of the conflicting entry whose link the public name should keep
+        #[clap(long = "keep")]
+        keep: Option<VersionHash>,
+    },
 }
 
 pub async fn nrs_commander(cmd: NrsSubCommands, output_fmt: OutputFmt, safe: &Safe) -> Result<()> {
@@ -72,6 +82,9 @@ pub async fn nrs_commander(cmd: NrsSubCommands, output_fmt: OutputFmt, safe: &Sa
             default,
         } => run_add_subcommand(name, link, register_top_name, default, safe, output_fmt).await,
         NrsSubCommands::Remove { name } => run_remove_subcommand(name, safe, output_fmt).await,
+        NrsSubCommands::Resolve { public_name, keep } => {
+            run_resolve_subcommand(public_name, keep, safe, output_fmt).await
+        }
     }
 }
 
@@ -224,6 +237,72 @@ async fn run_remove_subcommand(name: String, safe: &Safe, output_fmt: OutputFmt)
     }
 }
 
+async fn run_resolve_subcommand(
+    public_name: String,
+    keep: Option<VersionHash>,
+    safe: &Safe,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let version = match keep {
+        Some(version) => version,
+        None => {
+            let conflicts = safe.nrs_get_conflicts(&public_name).await?;
+            let links = conflicts
+                .into_iter()
+                .find(|conflict| conflict.public_name == public_name)
+                .map(|conflict| conflict.links)
+                .unwrap_or_default();
+            if OutputFmt::Pretty == output_fmt {
+                if links.is_empty() {
+                    println!("No conflicting links found for \"{}\"", public_name);
+                } else {
+                    println!(
+                        "\"{}\" was associated to different links concurrently:",
+                        public_name
+                    );
+                    let mut table = Table::new();
+                    table.add_row(vec!["Version", "Link"]);
+                    for (version, link) in &links {
+                        table.add_row(vec![version.to_string(), link.to_string()]);
+                    }
+                    println!("{table}");
+                    println!("Resolve the conflict with 'safe nrs resolve {public_name} --keep <version>'");
+                }
+            } else {
+                println!("{}", serialise_output(&(public_name, links), output_fmt));
+            }
+            return Ok(());
+        }
+    };
+
+    match safe.nrs_resolve_conflict(&public_name, version).await {
+        Ok(url) => {
+            let version = url
+                .content_version()
+                .ok_or_else(|| eyre!("Content version not set for returned NRS SafeUrl"))?
+                .to_string();
+            let (link, _) = safe.nrs_get(&public_name, None).await?;
+            let link = link.map(|link| link.to_string()).unwrap_or_default();
+            print_summary(
+                output_fmt,
+                &format!("NRS Map conflict resolved (version {})", version),
+                "".to_string(),
+                &SafeUrl::from_url(&format!("safe://{}", url.top_name()))?.to_xorurl_string(),
+                &url,
+                ("+", &public_name, &link),
+            );
+            Ok(())
+        }
+        Err(error) => match error {
+            InvalidInput(_) => Err(eyre!(error).suggestion(format!(
+                "Run 'safe nrs resolve {}' to list the versions in conflict.",
+                public_name
+            ))),
+            _ => Err(eyre!(error)),
+        },
+    }
+}
+
 async fn associate_url_with_public_name(
     public_name: &str,
     safe: &Safe,
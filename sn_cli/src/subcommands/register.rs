@@ -0,0 +1,255 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{decode_entry_hash, encode_entry_hash, get_from_arg_or_stdin, serialise_output},
+    OutputFmt,
+};
+use clap::Subcommand;
+use color_eyre::Result;
+use comfy_table::Table;
+use sn_api::{
+    register::{Action, Entry, EntryHash, Policy, User},
+    ContentType, Safe,
+};
+use std::collections::BTreeSet;
+
+/// Type tag used for Registers created with the CLI when none is specified
+const DEFAULT_REGISTER_TYPE_TAG: u64 = 25_000;
+
+#[derive(Subcommand, Debug)]
+pub enum RegisterSubCommands {
+    #[clap(name = "create")]
+    /// Create a new empty Register, owned by the public key configured for use with safe.
+    /// It's private unless the --public flag is set, i.e. its entries are encrypted so only
+    /// the owner can read them.
+    Create {
+        /// The type tag to create the Register with
+        #[clap(long = "type-tag", default_value_t = DEFAULT_REGISTER_TYPE_TAG)]
+        type_tag: u64,
+        /// Create a public Register, whose entries anyone can read
+        #[clap(long = "public")]
+        public: bool,
+    },
+    #[clap(name = "read")]
+    /// Read the latest entries of a Register, i.e. the heads of each of its branches
+    Read {
+        /// The URL of the Register to read from
+        target: String,
+    },
+    #[clap(name = "write")]
+    /// Write an entry to a Register. By default the new entry succeeds all the latest entries,
+    /// merging any branches. Use the --parent argument to choose which entries it succeeds
+    /// instead, e.g. to create a new branch.
+    Write {
+        /// The URL of the Register to write to
+        target: String,
+        /// The data to write. If not provided, it will be read from stdin.
+        data: Option<String>,
+        /// The hash of an entry the new entry succeeds. It can be supplied multiple times.
+        #[clap(long = "parent")]
+        parents: Vec<String>,
+    },
+    #[clap(name = "entry")]
+    /// Read an entry of a Register by its hash
+    Entry {
+        /// The URL of the Register to read from
+        target: String,
+        /// The hash of the entry to read
+        hash: String,
+    },
+    #[clap(name = "policy")]
+    /// Show the owner of a Register and the users' permissions
+    Policy {
+        /// The URL of the Register to query
+        target: String,
+    },
+}
+
+pub async fn register_commander(
+    cmd: RegisterSubCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    match cmd {
+        RegisterSubCommands::Create { type_tag, public } => {
+            let xorurl = if public {
+                safe.register_create_public(None, type_tag, ContentType::Raw)
+                    .await?
+            } else {
+                safe.register_create(None, type_tag, ContentType::Raw)
+                    .await?
+            };
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Register created at: \"{}\"", xorurl);
+            } else {
+                println!("{}", serialise_output(&xorurl, output_fmt));
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Read { target } => {
+            let (entries, skipped) = safe.register_read_with_skipped(&target).await?;
+            if !skipped.is_empty() {
+                let hashes: Vec<String> = skipped.iter().map(encode_entry_hash).collect();
+                eprintln!(
+                    "Warning: {} latest entries of Register at \"{}\" were left out as you \
+                    aren't one of their readers: {}",
+                    skipped.len(),
+                    target,
+                    hashes.join(", ")
+                );
+            }
+
+            if OutputFmt::Pretty == output_fmt {
+                if entries.is_empty() {
+                    println!("Register at \"{}\" is empty", target);
+                } else {
+                    println!("Latest entries of Register at \"{}\":", target);
+                    println!("{}", gen_entries_table(&entries));
+                }
+            } else {
+                let entries: Vec<(String, String)> = entries
+                    .iter()
+                    .map(|(hash, entry)| (encode_entry_hash(hash), entry_to_string(entry)))
+                    .collect();
+                println!("{}", serialise_output(&(target, entries), output_fmt));
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Write {
+            target,
+            data,
+            parents,
+        } => {
+            let data = get_from_arg_or_stdin(data, Some("...awaiting data from STDIN"))?;
+            let parents = if parents.is_empty() {
+                safe.register_read(&target)
+                    .await?
+                    .into_iter()
+                    .map(|(hash, _)| hash)
+                    .collect()
+            } else {
+                parents
+                    .iter()
+                    .map(|hash| decode_entry_hash(hash))
+                    .collect::<Result<BTreeSet<_>>>()?
+            };
+
+            let hash = safe
+                .register_write(&target, data.into_bytes(), parents)
+                .await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!(
+                    "Entry written to Register at \"{}\" with hash: {}",
+                    target,
+                    encode_entry_hash(&hash)
+                );
+            } else {
+                println!(
+                    "{}",
+                    serialise_output(&(target, encode_entry_hash(&hash)), output_fmt)
+                );
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Entry { target, hash } => {
+            let entry = safe
+                .register_read_entry(&target, decode_entry_hash(&hash)?)
+                .await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("{}", entry_to_string(&entry));
+            } else {
+                println!(
+                    "{}",
+                    serialise_output(&(target, hash, entry_to_string(&entry)), output_fmt)
+                );
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Policy { target } => {
+            let policy = safe.register_policy(&target).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Policy of Register at \"{}\":", target);
+                println!("Owner: {}", user_to_string(&policy.owner));
+                println!("{}", gen_policy_table(&policy));
+            } else {
+                // Users can't be used as keys of serialised maps, thus we output them as strings
+                let permissions: Vec<(String, Option<bool>, Option<bool>)> = policy
+                    .permissions
+                    .iter()
+                    .map(|(user, perms)| {
+                        (
+                            user_to_string(user),
+                            perms.is_allowed(Action::Write),
+                            perms.is_allowed(Action::ManagePolicy),
+                        )
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    serialise_output(
+                        &(target, user_to_string(&policy.owner), permissions),
+                        output_fmt
+                    )
+                );
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn gen_entries_table(entries: &BTreeSet<(EntryHash, Entry)>) -> Table {
+    let mut table = Table::new();
+    table.add_row(vec!["Entry hash", "Data"]);
+    for (hash, entry) in entries {
+        table.add_row(vec![encode_entry_hash(hash), entry_to_string(entry)]);
+    }
+    table
+}
+
+fn gen_policy_table(policy: &Policy) -> Table {
+    let mut table = Table::new();
+    table.add_row(vec!["User", "Write", "Manage policy"]);
+    for (user, perms) in &policy.permissions {
+        table.add_row(vec![
+            user_to_string(user),
+            permission_to_string(perms.is_allowed(Action::Write)),
+            permission_to_string(perms.is_allowed(Action::ManagePolicy)),
+        ]);
+    }
+    table
+}
+
+fn permission_to_string(allowed: Option<bool>) -> String {
+    match allowed {
+        Some(true) => "allowed",
+        Some(false) => "denied",
+        None => "default",
+    }
+    .to_string()
+}
+
+fn entry_to_string(entry: &Entry) -> String {
+    String::from_utf8_lossy(entry).to_string()
+}
+
+fn user_to_string(user: &User) -> String {
+    match user {
+        User::Anyone => "Anyone".to_string(),
+        User::Key(public_key) => format!("{:x}", public_key),
+    }
+}
@@ -14,8 +14,12 @@ use crate::operations::config::Config;
 use bls::{PublicKey, SecretKey};
 use clap::Subcommand;
 use color_eyre::{eyre::eyre, eyre::Error, Help, Result};
-use sn_api::{Error as ApiError, Safe};
-use sn_dbc::{Dbc, Error as DbcError};
+use comfy_table::{Cell, CellAlignment, Table};
+use sn_api::{
+    wallet::{WalletHistoryEntry, WalletOperation},
+    Error as ApiError, Safe,
+};
+use sn_dbc::{Dbc, Error as DbcError, Token};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
@@ -30,6 +34,12 @@ pub enum WalletSubCommands {
         /// The URL of wallet to query
         target: Option<String>,
     },
+    #[clap(name = "history")]
+    /// List the deposits, reissues and change deposits recorded in a wallet
+    History {
+        /// The URL of wallet to query
+        target: Option<String>,
+    },
     #[clap(name = "deposit")]
     /// Deposit a spendable DBC in a wallet. If the DBC is not bearer, we will try to deposit using
     /// the secret key configured for use with safe. If you wish to use a different key, use the
@@ -74,13 +84,25 @@ pub enum WalletSubCommands {
         /// A file path to store the content of the reissued DBC.
         #[clap(long = "save")]
         save: Option<PathBuf>,
+        /// Number of members of the ring each input DBC is mixed in, i.e. the DBC itself plus
+        /// decoys, to hide which DBCs are being spent. Decoys are sampled from the outputs of
+        /// transactions spent on the network, the reissue fails if not enough are available.
+        #[clap(long = "ring-size")]
+        ring_size: Option<usize>,
+    },
+    #[clap(name = "resume")]
+    /// Resume a reissue from a wallet which was interrupted, e.g. due to a network failure,
+    /// outputting the reissued DBCs once completed.
+    Resume {
+        /// The URL of the wallet the reissue was interrupted from
+        wallet_url: String,
     },
 }
 
 pub async fn wallet_commander(
     cmd: WalletSubCommands,
     output_fmt: OutputFmt,
-    safe: &Safe,
+    safe: &mut Safe,
     config: &Config,
 ) -> Result<()> {
     match cmd {
@@ -114,6 +136,23 @@ pub async fn wallet_commander(
 
             Ok(())
         }
+        WalletSubCommands::History { target } => {
+            let target = get_from_arg_or_stdin(
+                target,
+                Some("...awaiting wallet address/location from STDIN stream..."),
+            )?;
+
+            let history = safe.wallet_history(&target).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("History of wallet at \"{}\":", target);
+                println!("{}", gen_wallet_history_table(&history));
+            } else {
+                println!("{}", serialise_output(&history, output_fmt));
+            }
+
+            Ok(())
+        }
         WalletSubCommands::Deposit {
             wallet_url,
             name,
@@ -207,6 +246,7 @@ pub async fn wallet_commander(
             save,
             to,
             owned,
+            ring_size,
         } => {
             if owned && to.is_some() {
                 return Err(eyre!(
@@ -231,6 +271,9 @@ pub async fn wallet_commander(
             } else {
                 None
             };
+            if let Some(ring_size) = ring_size {
+                safe.dbc_ring_size = ring_size;
+            }
             let dbc = safe.wallet_reissue(&from, &amount, pk).await?;
             let dbc_hex = dbc.to_hex()?;
 
@@ -271,11 +314,103 @@ pub async fn wallet_commander(
                 println!("{}", dbc_hex);
             }
 
+            Ok(())
+        }
+        WalletSubCommands::Resume { wallet_url } => {
+            let dbcs = safe.wallet_resume_reissue(&wallet_url).await?;
+            let dbcs_hex = dbcs
+                .iter()
+                .map(|dbc| dbc.to_hex())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!(
+                    "Reissue from wallet at \"{}\" completed, {} DBC(s) reissued.",
+                    wallet_url,
+                    dbcs_hex.len()
+                );
+                for dbc_hex in dbcs_hex {
+                    println!("-------- DBC DATA --------");
+                    println!("{}", dbc_hex);
+                    println!("--------------------------");
+                }
+            } else {
+                println!("{}", serialise_output(&dbcs_hex, output_fmt));
+            }
+
             Ok(())
         }
     }
 }
 
+/// Helper to list the operations found in a wallet's history in a table ready to print out.
+fn gen_wallet_history_table(history: &[WalletHistoryEntry]) -> Table {
+    let mut table = Table::new();
+    table.add_row(vec!["Timestamp", "Operation", "Amount", "Details"]);
+
+    for entry in history {
+        let timestamp = entry
+            .timestamp
+            .map(|secs| secs.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let spent_status = |spent: bool| if spent { "spent" } else { "unspent" };
+        let (operation, amount, details) = match &entry.operation {
+            WalletOperation::Deposit {
+                name,
+                amount,
+                spent,
+            } => (
+                "deposit",
+                *amount,
+                format!("'{}' ({})", name, spent_status(*spent)),
+            ),
+            WalletOperation::Change {
+                name,
+                amount,
+                spent,
+            } => (
+                "change",
+                *amount,
+                format!("'{}' ({})", name, spent_status(*spent)),
+            ),
+            WalletOperation::Reissue {
+                outputs,
+                inputs,
+                change,
+                in_progress,
+            } => {
+                let mut details: Vec<String> = outputs
+                    .iter()
+                    .map(|(amount, owner)| match owner {
+                        Some(pk) => format!("{} to {}", amount, pk.to_hex()),
+                        None => format!("{} as bearer", amount),
+                    })
+                    .collect();
+                details.push(format!("inputs: {}", inputs.join(", ")));
+                details.push(format!("change: {}", change));
+                if *in_progress {
+                    details.push("interrupted, needs to be resumed".to_string());
+                }
+
+                let total = outputs.iter().fold(Token::zero(), |total, (amount, _)| {
+                    total.checked_add(*amount).unwrap_or(total)
+                });
+                ("reissue", total, details.join("\n"))
+            }
+        };
+
+        table.add_row(vec![
+            Cell::new(timestamp),
+            Cell::new(operation),
+            Cell::new(amount).set_alignment(CellAlignment::Right),
+            Cell::new(details),
+        ]);
+    }
+
+    table
+}
+
 /// Helper to get the secret key from the credentials that are configured for use with safe.
 ///
 /// Different error and suggestion messages need to be provided depending on the context in which
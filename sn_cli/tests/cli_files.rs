@@ -104,6 +104,38 @@ fn files_put_should_upload_directory_when_recursive_arg_is_used() -> Result<()>
     Ok(())
 }
 
+#[test]
+fn files_put_should_remove_upload_journals_when_resume_arg_is_used() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    safe_cmd(
+        &config_dir,
+        [
+            "files",
+            "put",
+            TEST_FOLDER,
+            "--recursive",
+            "--resume",
+            "--json",
+        ],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains(r#"Added"#).count(12))
+    .success();
+
+    // all uploads completed, so there should be no journals left behind
+    config_dir
+        .child("cli/upload_journals")
+        .assert(
+            predicate::path::missing().or(predicate::function(|path: &Path| {
+                std::fs::read_dir(path)
+                    .map(|mut entries| entries.next().is_none())
+                    .unwrap_or(false)
+            })),
+        );
+    Ok(())
+}
+
 #[test]
 fn files_put_should_create_sub_folder_in_container_when_destination_is_used() -> Result<()> {
     let config_dir = use_isolated_safe_config_dir()?;
@@ -0,0 +1,234 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use color_eyre::Result;
+use predicates::prelude::*;
+use serde_json::json;
+use sn_cmd_test_utilities::util::{
+    parse_multimap_create_output, parse_multimap_insert_output, parse_multimap_show_output,
+    safe_cmd, safe_cmd_stdout, use_isolated_safe_config_dir,
+};
+
+#[test]
+fn multimap_create_should_create_a_multimap() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    safe_cmd(&config_dir, ["multimap", "create"], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("Multimap created at"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn multimap_insert_should_accept_utf8_hex_and_file_values() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["multimap", "create", "--json"], Some(0))?;
+    let multimap_xorurl = parse_multimap_create_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "insert",
+            &multimap_xorurl,
+            "utf8",
+            "value",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, utf8_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "insert",
+            &multimap_xorurl,
+            "hex",
+            "68657876616c7565",
+            "--hex",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, hex_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let value_file = tmp_data_dir.child("value");
+    value_file.write_str("filevalue")?;
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "insert",
+            &multimap_xorurl,
+            "file",
+            "--file",
+            &value_file.path().display().to_string(),
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, file_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "show", &multimap_xorurl, "--json"],
+        Some(0),
+    )?;
+    let (_, mut entries) = parse_multimap_show_output(&json_output)?;
+    entries.sort_by_key(|entry| entry["key"].to_string());
+    assert_eq!(
+        entries,
+        vec![
+            json!({"hash": file_hash, "key": "file", "value": "filevalue", "status": "current"}),
+            json!({"hash": hex_hash, "key": "hex", "value": "hexvalue", "status": "current"}),
+            json!({"hash": utf8_hash, "key": "utf8", "value": "value", "status": "current"}),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn multimap_insert_should_replace_the_entries_provided() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["multimap", "create", "--json"], Some(0))?;
+    let multimap_xorurl = parse_multimap_create_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "insert",
+            &multimap_xorurl,
+            "key",
+            "first",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, first_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "insert",
+            &multimap_xorurl,
+            "key",
+            "second",
+            "--replace",
+            &first_hash,
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, second_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "get", &multimap_xorurl, "key", "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_multimap_show_output(&json_output)?;
+    assert_eq!(
+        entries,
+        vec![json!({"hash": second_hash, "key": "key", "value": "second", "status": "current"})]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn multimap_show_should_include_removed_entries_when_requested() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["multimap", "create", "--json"], Some(0))?;
+    let multimap_xorurl = parse_multimap_create_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "insert",
+            &multimap_xorurl,
+            "key",
+            "value",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, value_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "remove",
+            &multimap_xorurl,
+            "--key",
+            "key",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, tombstone_hash) = parse_multimap_insert_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "show", &multimap_xorurl, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_multimap_show_output(&json_output)?;
+    assert!(entries.is_empty());
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "multimap",
+            "show",
+            &multimap_xorurl,
+            "--include-removed",
+            "--hex",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, entries) = parse_multimap_show_output(&json_output)?;
+    assert_eq!(
+        entries,
+        vec![
+            json!({"hash": value_hash, "key": "key", "value": "76616c7565", "status": "removed"}),
+            json!({"hash": tombstone_hash, "key": null, "value": null, "status": "tombstone"}),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn multimap_remove_should_fail_when_there_are_no_entries_to_remove() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["multimap", "create", "--json"], Some(0))?;
+    let multimap_xorurl = parse_multimap_create_output(&json_output)?;
+
+    safe_cmd(
+        &config_dir,
+        ["multimap", "remove", &multimap_xorurl, "--key", "missing"],
+        Some(1),
+    )?
+    .assert()
+    .stderr(predicate::str::contains("There are no entries to remove"))
+    .failure();
+
+    Ok(())
+}
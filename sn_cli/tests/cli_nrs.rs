@@ -571,3 +571,36 @@ fn nrs_remove_should_return_an_error_for_a_non_existent_topname() -> Result<()>
         )));
     Ok(())
 }
+
+#[test]
+fn nrs_resolve_should_report_no_conflicts_for_a_name_without_them() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let test_md_file = tmp_data_path.child("test.md");
+    let (files_container_xor, _processed_files, _) = upload_path(&config_dir, test_md_file, false)?;
+    let mut url = SafeUrl::from_url(&files_container_xor)?;
+    url.set_path("test.md");
+
+    let public_name = format!("test.{}", get_random_string());
+    safe_cmd(
+        &config_dir,
+        [
+            "nrs",
+            "add",
+            &public_name,
+            "--link",
+            &url.to_string(),
+            "--register-top-name",
+        ],
+        Some(0),
+    )?;
+    safe_cmd(&config_dir, ["nrs", "resolve", &public_name], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "No conflicting links found for \"{}\"",
+            public_name
+        )));
+
+    Ok(())
+}
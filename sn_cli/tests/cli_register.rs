@@ -0,0 +1,228 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use assert_cmd::prelude::*;
+use color_eyre::Result;
+use predicates::prelude::*;
+use sn_api::{DataType, SafeUrl};
+use sn_cmd_test_utilities::util::{
+    parse_register_create_output, parse_register_read_output, parse_register_write_output,
+    safe_cmd, safe_cmd_stdout, use_isolated_safe_config_dir,
+};
+
+#[test]
+fn register_create_should_create_a_register() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    safe_cmd(&config_dir, ["register", "create"], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("Register created at"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn register_create_should_use_the_type_tag_provided() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "create", "--type-tag", "30000", "--json"],
+        Some(0),
+    )?;
+    let register_xorurl = parse_register_create_output(&json_output)?;
+
+    let url = SafeUrl::from_url(&register_xorurl)?;
+    assert_eq!(url.data_type(), DataType::PrivateRegister);
+    assert_eq!(url.type_tag(), 30_000);
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "read", &register_xorurl, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_register_read_output(&json_output)?;
+    assert!(entries.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn register_create_should_create_a_public_register_when_requested() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "create", "--public", "--json"],
+        Some(0),
+    )?;
+    let register_xorurl = parse_register_create_output(&json_output)?;
+
+    let url = SafeUrl::from_url(&register_xorurl)?;
+    assert_eq!(url.data_type(), DataType::Register);
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &register_xorurl, "public", "--json"],
+        Some(0),
+    )?;
+    let (_, hash) = parse_register_write_output(&json_output)?;
+
+    // anyone can read the entries of a public Register
+    let other_config_dir = use_isolated_safe_config_dir()?;
+    safe_cmd(
+        &other_config_dir,
+        ["register", "entry", &register_xorurl, &hash],
+        Some(0),
+    )?
+    .assert()
+    .stdout("public\n")
+    .success();
+
+    Ok(())
+}
+
+#[test]
+fn register_write_should_succeed_the_latest_entries() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["register", "create", "--json"], Some(0))?;
+    let register_xorurl = parse_register_create_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &register_xorurl, "first", "--json"],
+        Some(0),
+    )?;
+    let (_, first_hash) = parse_register_write_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &register_xorurl, "second", "--json"],
+        Some(0),
+    )?;
+    let (_, second_hash) = parse_register_write_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "read", &register_xorurl, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_register_read_output(&json_output)?;
+    assert_eq!(entries, vec![(second_hash, "second".to_string())]);
+
+    // the first entry is still available by its hash
+    safe_cmd(
+        &config_dir,
+        ["register", "entry", &register_xorurl, &first_hash],
+        Some(0),
+    )?
+    .assert()
+    .stdout("first\n")
+    .success();
+
+    Ok(())
+}
+
+#[test]
+fn register_write_should_branch_from_the_parents_provided() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["register", "create", "--json"], Some(0))?;
+    let register_xorurl = parse_register_create_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &register_xorurl, "root", "--json"],
+        Some(0),
+    )?;
+    let (_, root_hash) = parse_register_write_output(&json_output)?;
+
+    let mut branch_hashes = vec![];
+    for data in ["left", "right"] {
+        let json_output = safe_cmd_stdout(
+            &config_dir,
+            [
+                "register",
+                "write",
+                &register_xorurl,
+                data,
+                "--parent",
+                &root_hash,
+                "--json",
+            ],
+            Some(0),
+        )?;
+        let (_, hash) = parse_register_write_output(&json_output)?;
+        branch_hashes.push(hash);
+    }
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "read", &register_xorurl, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_register_read_output(&json_output)?;
+    let mut heads: Vec<String> = entries.into_iter().map(|(hash, _)| hash).collect();
+    heads.sort();
+    branch_hashes.sort();
+    assert_eq!(heads, branch_hashes);
+
+    // writing with no parents merges both branches
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &register_xorurl, "merged", "--json"],
+        Some(0),
+    )?;
+    let (_, merged_hash) = parse_register_write_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "read", &register_xorurl, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_register_read_output(&json_output)?;
+    assert_eq!(entries, vec![(merged_hash, "merged".to_string())]);
+
+    Ok(())
+}
+
+#[test]
+fn register_entry_should_fail_with_an_invalid_hash() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["register", "create", "--json"], Some(0))?;
+    let register_xorurl = parse_register_create_output(&json_output)?;
+
+    safe_cmd(
+        &config_dir,
+        ["register", "entry", &register_xorurl, "invalid-hash"],
+        Some(1),
+    )?
+    .assert()
+    .stderr(predicate::str::contains(
+        "Invalid entry hash 'invalid-hash'",
+    ))
+    .failure();
+
+    Ok(())
+}
+
+#[test]
+fn register_policy_should_show_the_owner() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["register", "create", "--json"], Some(0))?;
+    let register_xorurl = parse_register_create_output(&json_output)?;
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "policy", &register_xorurl, "--json"],
+        Some(0),
+    )?;
+    type PolicyOutput = (String, String, Vec<(String, Option<bool>, Option<bool>)>);
+    let (url, owner, permissions): PolicyOutput = serde_json::from_str(&json_output)?;
+    assert_eq!(url, register_xorurl);
+    assert_eq!(permissions, vec![(owner, Some(true), None)]);
+
+    Ok(())
+}
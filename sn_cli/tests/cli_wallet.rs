@@ -1125,3 +1125,81 @@ async fn wallet_deposit_dbc_reissued_to_file() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn wallet_resume_should_fail_when_no_reissue_was_interrupted() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["wallet", "create", "--json"], Some(0))?;
+    let wallet_xorurl = parse_wallet_create_output(&json_output)?;
+
+    safe_cmd(&config_dir, ["wallet", "resume", &wallet_xorurl], Some(1))?
+        .assert()
+        .stderr(predicate::str::contains(
+            "No interrupted reissue was found in wallet",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn wallet_history_should_list_deposits_and_reissues() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let json_output = safe_cmd_stdout(&config_dir, ["wallet", "create", "--json"], Some(0))?;
+    let wallet_xorurl = parse_wallet_create_output(&json_output)?;
+
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let (dbc_file_path, _, _) = get_bearer_dbc_on_file(&tmp_data_dir).await?;
+
+    safe_cmd(
+        &config_dir,
+        [
+            "wallet",
+            "deposit",
+            "--name",
+            "my-first-dbc",
+            "--dbc",
+            &dbc_file_path.path().display().to_string(),
+            &wallet_xorurl,
+        ],
+        Some(0),
+    )?
+    .assert()
+    .success();
+
+    safe_cmd(
+        &config_dir,
+        ["wallet", "reissue", "1", "--from", &wallet_xorurl],
+        Some(0),
+    )?
+    .assert()
+    .success();
+
+    let json_output = safe_cmd_stdout(
+        &config_dir,
+        ["wallet", "history", &wallet_xorurl, "--json"],
+        Some(0),
+    )?;
+    let history: serde_json::Value = serde_json::from_str(&json_output)?;
+    let operations = history
+        .as_array()
+        .ok_or_else(|| eyre!("Wallet history is not a list of operations"))?
+        .iter()
+        .map(|entry| entry["operation"].clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(operations.len(), 3);
+    assert_eq!(operations[0]["Deposit"]["name"], "my-first-dbc");
+    assert_eq!(operations[0]["Deposit"]["spent"], true);
+    assert_eq!(operations[1]["Reissue"]["inputs"][0], "my-first-dbc");
+    assert_eq!(operations[2]["Change"]["spent"], false);
+
+    safe_cmd(&config_dir, ["wallet", "history", &wallet_xorurl], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("deposit"))
+        .stdout(predicate::str::contains("reissue"))
+        .stdout(predicate::str::contains("change"))
+        .success();
+
+    Ok(())
+}
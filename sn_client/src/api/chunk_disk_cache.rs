@@ -0,0 +1,257 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Result;
+
+use sn_interface::types::Chunk;
+
+use bytes::Bytes;
+use std::{
+    fs::{self as std_fs, File},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+use tempfile::NamedTempFile;
+use tokio::{fs, sync::Mutex, task};
+use tracing::{trace, warn};
+use xor_name::XorName;
+
+/// Content-addressed cache of Chunks kept on disk, so they outlive the client.
+///
+/// Each Chunk is stored in a file named after its hex-encoded name. Files are written
+/// to a temporary file and then moved into place, so the cache can be shared by several
+/// clients, even from different processes, without any of them reading a partial Chunk.
+///
+/// The least recently used Chunks, as per the modification time of their files, are
+/// evicted when the total size of the cache goes over its maximum size.
+#[derive(Clone, Debug)]
+pub(crate) struct ChunkDiskCache {
+    dir: PathBuf,
+    max_size: usize,
+    // Size of the cache as of the last time it was scanned, plus the size of the
+    // Chunks we've inserted since then. Chunks inserted by other clients sharing
+    // the cache are only accounted for the next time it's scanned.
+    size: Arc<Mutex<usize>>,
+}
+
+impl ChunkDiskCache {
+    /// Opens the cache at the given directory, creating it if it doesn't exist yet,
+    /// and evicting Chunks from it if it's larger than the given maximum size.
+    pub(crate) async fn open(dir: PathBuf, max_size: usize) -> Result<Self> {
+        fs::create_dir_all(&dir).await?;
+        let size = evict(dir.clone(), max_size).await?;
+        trace!(
+            "Chunks disk cache opened at {} with {size} bytes",
+            dir.display()
+        );
+
+        Ok(Self {
+            dir,
+            max_size,
+            size: Arc::new(Mutex::new(size)),
+        })
+    }
+
+    /// Returns the Chunk with the given name if it's found in the cache.
+    ///
+    /// Chunks are self-authenticating, so a Chunk whose content doesn't match its name,
+    /// e.g. if the file was corrupted, is removed from the cache and not returned.
+    pub(crate) async fn get(&self, name: &XorName) -> Option<Chunk> {
+        let path = self.chunk_path(name);
+        let bytes = match fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return None,
+            Err(err) => {
+                warn!("Failed to read Chunk {name:?} from disk cache: {err}");
+                return None;
+            }
+        };
+
+        let chunk = Chunk::new(Bytes::from(bytes));
+        if chunk.name() != name {
+            warn!("Chunk {name:?} found corrupted in disk cache, removing it");
+            if let Err(err) = fs::remove_file(&path).await {
+                warn!("Failed to remove corrupted Chunk {name:?} from disk cache: {err}");
+            }
+            return None;
+        }
+
+        // let's flag it as recently used so it's evicted after those unused for longer
+        if let Err(err) = touch(&path) {
+            trace!("Failed to update last use of Chunk {name:?} in disk cache: {err}");
+        }
+
+        Some(chunk)
+    }
+
+    /// Stores the Chunk in the cache, unless it's already there, evicting
+    /// the least recently used Chunks if the cache goes over its maximum size.
+    pub(crate) async fn insert(&self, chunk: &Chunk) -> Result<()> {
+        let path = self.chunk_path(chunk.name());
+        if path.exists() {
+            return Ok(());
+        }
+
+        let dir = self.dir.clone();
+        let value = chunk.value().clone();
+        task::spawn_blocking(move || -> io::Result<()> {
+            let mut file = NamedTempFile::new_in(dir)?;
+            file.write_all(&value)?;
+            // if another client stored the same Chunk meanwhile we just replace it,
+            // it's exactly the same content since it's addressed by it
+            let _ = file.persist(path).map_err(|err| err.error)?;
+            Ok(())
+        })
+        .await
+        .map_err(io::Error::other)??;
+
+        let mut size = self.size.lock().await;
+        *size += chunk.value().len();
+        if *size > self.max_size {
+            *size = evict(self.dir.clone(), self.max_size).await?;
+        }
+
+        Ok(())
+    }
+
+    fn chunk_path(&self, name: &XorName) -> PathBuf {
+        self.dir.join(hex::encode(name))
+    }
+}
+
+// Scans the cache evicting the least recently used Chunks until it fits
+// within its maximum size, returning the size of the cache afterwards.
+async fn evict(dir: PathBuf, max_size: usize) -> Result<usize> {
+    let size = task::spawn_blocking(move || evict_lru(&dir, max_size))
+        .await
+        .map_err(io::Error::other)??;
+    Ok(size)
+}
+
+fn evict_lru(dir: &Path, max_size: usize) -> io::Result<usize> {
+    let mut files = Vec::new();
+    let mut total_size = 0;
+    for entry in std_fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = match entry.metadata() {
+            Ok(meta) if meta.is_file() => meta,
+            Ok(_) => continue,
+            // it may have just been evicted by another client sharing the cache
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        let size = meta.len() as usize;
+        total_size += size;
+        files.push((meta.modified()?, size, entry.path()));
+    }
+
+    if total_size <= max_size {
+        return Ok(total_size);
+    }
+
+    files.sort();
+    for (_, size, path) in files {
+        if total_size <= max_size {
+            break;
+        }
+        match std_fs::remove_file(&path) {
+            Ok(()) => trace!("Evicted {} from disk cache", path.display()),
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        total_size -= size;
+    }
+
+    Ok(total_size)
+}
+
+// Sets the modification time of the file to now
+fn touch(path: &Path) -> io::Result<()> {
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkDiskCache;
+
+    use sn_interface::types::{utils::random_bytes, Chunk};
+
+    use eyre::{eyre, Result};
+    use std::{fs, time::Duration};
+    use tempfile::tempdir;
+    use tokio::time::sleep;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cached_chunks_are_found_by_any_client() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = ChunkDiskCache::open(dir.path().to_path_buf(), 10_000).await?;
+
+        let chunk = Chunk::new(random_bytes(1_000));
+        assert!(cache.get(chunk.name()).await.is_none());
+        cache.insert(&chunk).await?;
+        assert_eq!(cache.get(chunk.name()).await, Some(chunk.clone()));
+
+        // the cache outlives the client which populated it
+        drop(cache);
+        let cache = ChunkDiskCache::open(dir.path().to_path_buf(), 10_000).await?;
+        assert_eq!(cache.get(chunk.name()).await, Some(chunk));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn corrupted_chunks_are_removed() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = ChunkDiskCache::open(dir.path().to_path_buf(), 10_000).await?;
+
+        let chunk = Chunk::new(random_bytes(1_000));
+        cache.insert(&chunk).await?;
+
+        let path = dir.path().join(hex::encode(chunk.name()));
+        fs::write(&path, random_bytes(1_000))?;
+
+        assert!(cache.get(chunk.name()).await.is_none());
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn least_recently_used_chunks_are_evicted() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = ChunkDiskCache::open(dir.path().to_path_buf(), 3_000).await?;
+
+        let chunks = (0..4)
+            .map(|_| Chunk::new(random_bytes(1_000)))
+            .collect::<Vec<_>>();
+        for chunk in &chunks[..3] {
+            cache.insert(chunk).await?;
+            // make sure files don't share the same modification time
+            sleep(Duration::from_millis(20)).await;
+        }
+
+        // using the first chunk makes the second one the least recently used
+        let first = chunks.first().ok_or_else(|| eyre!("no chunks"))?;
+        assert!(cache.get(first.name()).await.is_some());
+        sleep(Duration::from_millis(20)).await;
+
+        cache.insert(&chunks[3]).await?;
+
+        assert!(cache.get(chunks[0].name()).await.is_some());
+        assert!(cache.get(chunks[1].name()).await.is_none());
+        assert!(cache.get(chunks[2].name()).await.is_some());
+        assert!(cache.get(chunks[3].name()).await.is_some());
+
+        Ok(())
+    }
+}
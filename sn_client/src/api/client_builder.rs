@@ -14,6 +14,7 @@
 //! # Ok(())
 //! # }
 //! ```
+use super::ChunkDiskCache;
 use crate::{sessions::Session, Client, Error, DEFAULT_NETWORK_CONTACTS_FILE_NAME};
 
 use qp2p::Config as Qp2pConfig;
@@ -36,6 +37,12 @@ pub const ENV_MAX_BACKOFF_INTERVAL: &str = "SN_MAX_BACKOFF_INTERVAL";
 pub const ENV_CMD_TIMEOUT: &str = "SN_CMD_TIMEOUT";
 /// Environment variable used to convert into [`ClientBuilder::cmd_ack_wait`] (seconds)
 pub const ENV_AE_WAIT: &str = "SN_AE_WAIT";
+/// Environment variable used to convert into [`ClientBuilder::chunks_disk_cache_dir`]
+pub const ENV_CHUNKS_DISK_CACHE_DIR: &str = "SN_CHUNKS_DISK_CACHE_DIR";
+/// Environment variable used to convert into [`ClientBuilder::chunks_disk_cache_max_size`] (bytes)
+pub const ENV_CHUNKS_DISK_CACHE_MAX_SIZE: &str = "SN_CHUNKS_DISK_CACHE_MAX_SIZE";
+/// Environment variable used to convert into [`ClientBuilder::register_read_repair`] (`true`/`false`)
+pub const ENV_REGISTER_READ_REPAIR: &str = "SN_REGISTER_READ_REPAIR";
 
 /// Bind by default to all network interfaces on a OS assigned port
 pub const DEFAULT_LOCAL_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::UNSPECIFIED, 0);
@@ -43,6 +50,8 @@ pub const DEFAULT_LOCAL_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::UNSPECIFIED, 0);
 pub const DEFAULT_QUERY_CMD_TIMEOUT: Duration = Duration::from_secs(90);
 /// Max amount of time for an operation backoff (time between attempts). In Seconds.
 pub const DEFAULT_MAX_QUERY_CMD_BACKOFF_INTERVAL: Duration = Duration::from_secs(3);
+/// Default maximum size of the Chunks disk cache, in bytes.
+pub const DEFAULT_CHUNKS_DISK_CACHE_MAX_SIZE: usize = 1024 * 1024 * 1024;
 
 // Default interval at which to send (QUIC) keep-alives msgs to maintain otherwise idle connections.
 const DEFAULT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(3);
@@ -62,6 +71,9 @@ pub struct ClientBuilder {
     cmd_timeout: Option<Duration>,
     cmd_ack_wait: Option<Duration>,
     network_contacts: Option<SectionTree>,
+    chunks_disk_cache_dir: Option<PathBuf>,
+    chunks_disk_cache_max_size: Option<usize>,
+    register_read_repair: Option<bool>,
 }
 
 impl ClientBuilder {
@@ -127,11 +139,35 @@ impl ClientBuilder {
         self
     }
 
+    /// Directory where to keep a cache of the Chunks retrieved from the network, so they
+    /// don't need to be retrieved again, not even by other clients using the same directory.
+    /// Chunks are only cached in memory if it's not set.
+    pub fn chunks_disk_cache_dir(mut self, dir: impl Into<Option<PathBuf>>) -> Self {
+        self.chunks_disk_cache_dir = dir.into();
+        self
+    }
+
+    /// Max size of the Chunks disk cache, in bytes, beyond which the least recently used Chunks are evicted
+    pub fn chunks_disk_cache_max_size(mut self, max_size: impl Into<Option<usize>>) -> Self {
+        self.chunks_disk_cache_max_size = max_size.into();
+        self
+    }
+
+    /// Whether to push the edits of a Register back to those of its replicas which are found
+    /// to be lagging behind the others when the Register is read.
+    pub fn register_read_repair(mut self, enabled: impl Into<Option<bool>>) -> Self {
+        self.register_read_repair = enabled.into();
+        self
+    }
+
     /// Read options from environment variables:
     /// - [`Self::query_timeout()`] from [`ENV_QUERY_TIMEOUT`]
     /// - [`Self::max_backoff_interval()`] from [`ENV_MAX_BACKOFF_INTERVAL`]
     /// - [`Self::cmd_timeout()`] from [`ENV_CMD_TIMEOUT`]
     /// - [`Self::cmd_ack_wait()`] from [`ENV_AE_WAIT`]
+    /// - [`Self::chunks_disk_cache_dir()`] from [`ENV_CHUNKS_DISK_CACHE_DIR`]
+    /// - [`Self::chunks_disk_cache_max_size()`] from [`ENV_CHUNKS_DISK_CACHE_MAX_SIZE`]
+    /// - [`Self::register_read_repair()`] from [`ENV_REGISTER_READ_REPAIR`]
     pub fn from_env(mut self) -> Self {
         if let Ok(Some(v)) = env_parse(ENV_QUERY_TIMEOUT) {
             self.query_timeout = Some(Duration::from_secs(v));
@@ -145,6 +181,15 @@ impl ClientBuilder {
         if let Ok(Some(v)) = env_parse(ENV_AE_WAIT) {
             self.cmd_ack_wait = Some(Duration::from_secs(v));
         }
+        if let Ok(Some(v)) = env_parse(ENV_CHUNKS_DISK_CACHE_DIR) {
+            self.chunks_disk_cache_dir = Some(v);
+        }
+        if let Ok(Some(v)) = env_parse(ENV_CHUNKS_DISK_CACHE_MAX_SIZE) {
+            self.chunks_disk_cache_max_size = Some(v);
+        }
+        if let Ok(Some(v)) = env_parse(ENV_REGISTER_READ_REPAIR) {
+            self.register_read_repair = Some(v);
+        }
 
         self
     }
@@ -157,6 +202,9 @@ impl ClientBuilder {
     /// - `[Self::max_backoff_interval`] defaults to [`DEFAULT_MAX_QUERY_CMD_BACKOFF_INTERVAL`]
     /// - [`qp2p::Config`] will default to it's [`Default`] impl
     /// - Network contacts file will be read from a standard location
+    /// - Chunks are not cached on disk unless [`Self::chunks_disk_cache_dir`] is set, in which case
+    ///   [`Self::chunks_disk_cache_max_size`] defaults to [`DEFAULT_CHUNKS_DISK_CACHE_MAX_SIZE`]
+    /// - Register replicas are not repaired upon reads unless [`Self::register_read_repair`] is enabled
     pub async fn build(self) -> Result<Client, Error> {
         let max_backoff_interval = self
             .max_backoff_interval
@@ -196,6 +244,16 @@ impl ClientBuilder {
             .dbc_owner
             .unwrap_or_else(|| Owner::from_random_secret_key(&mut rand::thread_rng()));
 
+        let chunks_disk_cache = match self.chunks_disk_cache_dir {
+            Some(dir) => {
+                let max_size = self
+                    .chunks_disk_cache_max_size
+                    .unwrap_or(DEFAULT_CHUNKS_DISK_CACHE_MAX_SIZE);
+                Some(ChunkDiskCache::open(dir, max_size).await?)
+            }
+            None => None,
+        };
+
         let client = Client {
             keypair,
             dbc_owner,
@@ -204,6 +262,8 @@ impl ClientBuilder {
             max_backoff_interval,
             cmd_timeout,
             chunks_cache: Arc::new(RwLock::new(Default::default())),
+            chunks_disk_cache,
+            register_read_repair: self.register_read_repair.unwrap_or(false),
         };
         client.connect().await?;
 
@@ -25,6 +25,18 @@ pub(crate) enum DataMapLevel {
     // resulting from chunking up a previous level data map.
     // This happens when that previous level data map was too big to fit in a chunk itself.
     Additional(DataMap),
+    // Holds the segments a streamed file was split into, each of them
+    // self-encrypted and stored as an independent file.
+    Segments(Vec<StreamSegment>),
+}
+
+/// A segment of a file which was uploaded from a stream.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct StreamSegment {
+    /// Address of the segment, which is readable as a file on its own.
+    pub(crate) address: XorName,
+    /// Size of the segment's content.
+    pub(crate) size: usize,
 }
 
 #[allow(unused)]
@@ -46,6 +58,35 @@ pub(crate) fn pack(
     data_map: DataMap,
     encrypted_chunks: Vec<EncryptedChunk>,
 ) -> Result<(XorName, Vec<Chunk>)> {
+    let (address, additional_chunks) = pack_level(DataMapLevel::First(data_map))?;
+
+    let expected_total = encrypted_chunks.len() + additional_chunks.len();
+    let all_chunks: Vec<_> = encrypted_chunks
+        .par_iter()
+        .map(|c| to_chunk(c.content.clone())) // no need to encrypt what is self-encrypted
+        .chain(additional_chunks)
+        .collect();
+
+    if expected_total > all_chunks.len() {
+        // as we flatten above, we need to check outcome here
+        return Err(Error::NotAllDataWasChunked {
+            expected: expected_total,
+            chunked: all_chunks.len(),
+        });
+    }
+
+    Ok((address, all_chunks))
+}
+
+/// Returns the top-most chunk address through which all the segments
+/// of a streamed file can be accessed, and the chunks holding the list of segments.
+pub(crate) fn pack_segments(segments: Vec<StreamSegment>) -> Result<(XorName, Vec<Chunk>)> {
+    pack_level(DataMapLevel::Segments(segments))
+}
+
+// Packs a data map level into a chunk, adding as many additional levels as needed
+// for it to fit in a chunk. Returns the address of the top-most level, and all the chunks.
+fn pack_level(level: DataMapLevel) -> Result<(XorName, Vec<Chunk>)> {
     // Produces a chunk out of the first secret key, which is validated for its size.
     // If the chunk is too big, it is self-encrypted and the resulting (additional level) secret key is put into a chunk.
     // The above step is repeated as many times as required until the chunk size is valid.
@@ -53,9 +94,9 @@ pub(crate) fn pack(
     // self encrypted into additional chunks, and now we have a new secret key
     // which points to all of those additional chunks.. and so on.
     let mut chunks = vec![];
-    let mut chunk_content = pack_data_map(DataMapLevel::First(data_map))?;
+    let mut chunk_content = pack_data_map(level)?;
 
-    let (address, additional_chunks) = loop {
+    loop {
         let chunk = to_chunk(chunk_content);
         // If datamap chunk is less that 1MB return it so it can be directly sent to the network
         if chunk.validate_size() {
@@ -63,7 +104,7 @@ pub(crate) fn pack(
             chunks.reverse();
             chunks.push(chunk);
             // returns the address of the last datamap, and all the chunks produced
-            break (name, chunks);
+            break Ok((name, chunks));
         } else {
             let serialized_chunk = Bytes::from(serialize(&chunk)?);
             let (data_map, next_encrypted_chunks) = self_encryption::encrypt(serialized_chunk)?;
@@ -74,24 +115,7 @@ pub(crate) fn pack(
                 .collect();
             chunk_content = pack_data_map(DataMapLevel::Additional(data_map))?;
         }
-    };
-
-    let expected_total = encrypted_chunks.len() + additional_chunks.len();
-    let all_chunks: Vec<_> = encrypted_chunks
-        .par_iter()
-        .map(|c| to_chunk(c.content.clone())) // no need to encrypt what is self-encrypted
-        .chain(additional_chunks)
-        .collect();
-
-    if expected_total > all_chunks.len() {
-        // as we flatten above, we need to check outcome here
-        return Err(Error::NotAllDataWasChunked {
-            expected: expected_total,
-            chunked: all_chunks.len(),
-        });
     }
-
-    Ok((address, all_chunks))
 }
 
 pub(crate) fn to_chunk(chunk_content: Bytes) -> Chunk {
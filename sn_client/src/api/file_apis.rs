@@ -7,8 +7,8 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    data::{encrypt_large, to_chunk, LargeFile, SmallFile},
-    Client,
+    data::{encrypt_large, pack_segments, to_chunk, LargeFile, SmallFile, StreamSegment},
+    Client, UploadJournal,
 };
 use crate::{api::data::DataMapLevel, Error, Result};
 
@@ -18,17 +18,38 @@ use sn_interface::{
 };
 
 use bincode::deserialize;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use futures::future::join_all;
 use itertools::Itertools;
-use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk};
-use tokio::{task, time::sleep};
-use tracing::trace;
+use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk, MAX_CHUNK_SIZE};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    task,
+    time::sleep,
+};
+use tracing::{trace, warn};
 use xor_name::XorName;
 
 // Maximum number of concurrent chunks to be uploaded/retrieved for a file
 const CHUNKS_BATCH_MAX_SIZE: usize = 5;
 
+// Size of the segments a stream is split into when uploaded, and of the
+// portions of a file fetched at a time when downloaded to a writer.
+// This is what bounds the memory used when streaming files of any size.
+const STREAM_SEGMENT_SIZE: usize = 8 * MAX_CHUNK_SIZE;
+
+// Segments are uploaded as any other file, so they cannot exceed the upload size limit
+#[cfg(feature = "limit-client-upload-size")]
+const _: () = assert!(STREAM_SEGMENT_SIZE <= LargeFile::CLIENT_UPLOAD_SIZE_LIMIT);
+
+// What a file's head chunk maps to, once unpacked
+enum FileMap {
+    // The data map of a self-encrypted file
+    DataMap(DataMap),
+    // The segments of a file uploaded from a stream
+    Segments(Vec<StreamSegment>),
+}
+
 impl Client {
     #[instrument(skip(self), level = "debug")]
     /// Reads [`Bytes`] from the network, whose contents are contained within on or more chunks.
@@ -36,14 +57,59 @@ impl Client {
         let chunk = self.get_chunk(&address).await?;
 
         // first try to deserialize a LargeFile, if it works, we go and seek it
-        if let Ok(data_map) = self.unpack_chunk(chunk.clone()).await {
-            self.read_all(data_map).await
-        } else {
+        match self.unpack_chunk(chunk.clone()).await {
+            Ok(FileMap::DataMap(data_map)) => self.read_all(data_map).await,
+            Ok(FileMap::Segments(segments)) => self.read_segments(&segments, 0, usize::MAX).await,
             // if an error occurs, we assume it's a SmallFile
-            Ok(chunk.value().clone())
+            Err(_) => Ok(chunk.value().clone()),
         }
     }
 
+    /// Reads a file from the network, writing its contents to the provided writer as the
+    /// chunks are retrieved and decrypted, a portion at a time, so that files of any size
+    /// can be read without holding them in memory.
+    /// Returns the number of bytes written.
+    #[instrument(skip(self, writer), level = "debug")]
+    pub async fn download_to_writer(
+        &self,
+        address: XorName,
+        mut writer: impl AsyncWrite + Unpin,
+    ) -> Result<usize> {
+        let chunk = self.get_chunk(&address).await?;
+
+        let mut written = 0;
+        match self.unpack_chunk(chunk.clone()).await {
+            Ok(FileMap::DataMap(data_map)) => {
+                let file_size = data_map.file_size();
+                while written < file_size {
+                    let bytes = self
+                        .seek(data_map.clone(), written, STREAM_SEGMENT_SIZE)
+                        .await?;
+                    if bytes.is_empty() {
+                        break;
+                    }
+                    writer.write_all(&bytes).await?;
+                    written += bytes.len();
+                }
+            }
+            Ok(FileMap::Segments(segments)) => {
+                for segment in segments {
+                    let bytes = self.read_segment(segment.address, 0, segment.size).await?;
+                    writer.write_all(&bytes).await?;
+                    written += bytes.len();
+                }
+            }
+            // if an error occurs, we assume it's a SmallFile
+            Err(_) => {
+                writer.write_all(chunk.value()).await?;
+                written = chunk.value().len();
+            }
+        }
+
+        writer.flush().await?;
+        Ok(written)
+    }
+
     /// Read bytes from the network. The contents are spread across
     /// multiple chunks in the network. This function invokes the self-encryptor and returns
     /// the data that was initially stored.
@@ -62,8 +128,13 @@ impl Client {
 
         // First try to deserialize a LargeFile, if it works, we go and seek it.
         // If an error occurs, we consider it to be a SmallFile.
-        if let Ok(data_map) = self.unpack_chunk(chunk.clone()).await {
-            return self.seek(data_map, position, length).await;
+        if let Ok(file_map) = self.unpack_chunk(chunk.clone()).await {
+            return match file_map {
+                FileMap::DataMap(data_map) => self.seek(data_map, position, length).await,
+                FileMap::Segments(segments) => {
+                    self.read_segments(&segments, position, length).await
+                }
+            };
         }
 
         // The error above is ignored to avoid leaking the storage format detail of SmallFiles and LargeFiles.
@@ -90,6 +161,15 @@ impl Client {
             return Ok(chunk.clone());
         }
 
+        // then check if we (or any other client sharing it) have it in our disk cache
+        if let Some(disk_cache) = &self.chunks_disk_cache {
+            if let Some(chunk) = disk_cache.get(name).await {
+                trace!("Chunk retrieved from disk cache: {name:?}");
+                let _ = self.chunks_cache.write().await.insert(chunk.clone());
+                return Ok(chunk);
+            }
+        }
+
         let query = DataQueryVariant::GetChunk(ChunkAddress(*name));
         let res = self.send_query(query.clone()).await?;
 
@@ -101,6 +181,11 @@ impl Client {
         }?;
 
         let _ = self.chunks_cache.write().await.insert(chunk.clone());
+        if let Some(disk_cache) = &self.chunks_disk_cache {
+            if let Err(err) = disk_cache.insert(&chunk).await {
+                warn!("Failed to store Chunk {name:?} in disk cache: {err:?}");
+            }
+        }
 
         Ok(chunk)
     }
@@ -152,6 +237,44 @@ impl Client {
         self.upload_bytes(bytes, true).await
     }
 
+    /// Writes the contents read from the provided reader to the network in the form
+    /// of immutable chunks. The contents are read, self-encrypted and uploaded a segment
+    /// at a time, so there is no limit in the size of the contents that can be uploaded.
+    ///
+    /// Contents which fit in a single segment are stored exactly as with `upload`.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn upload_from_reader(&self, reader: impl AsyncRead + Unpin) -> Result<XorName> {
+        self.upload_stream(reader, false).await
+    }
+
+    /// Writes the contents read from the provided reader to the network in the form
+    /// of immutable chunks, a segment at a time, as `upload_from_reader` does.
+    /// It also attempts to verify that all the data was uploaded to the network before returning.
+    #[instrument(skip_all, level = "trace")]
+    pub async fn upload_from_reader_and_verify(
+        &self,
+        reader: impl AsyncRead + Unpin,
+    ) -> Result<XorName> {
+        self.upload_stream(reader, true).await
+    }
+
+    /// Writes [`Bytes`] to the network in the form of immutable chunks, verifying they are stored,
+    /// as `upload_and_verify` does, but recording in the given journal each chunk as it's stored.
+    ///
+    /// Any chunk already recorded in the journal is not uploaded again, thus if a previous
+    /// attempt to upload the same bytes with the same journal was interrupted, e.g. due to a
+    /// network error, the upload is resumed from the chunks that were not stored yet.
+    #[instrument(skip_all, level = "trace")]
+    pub async fn upload_and_verify_with_journal(
+        &self,
+        bytes: Bytes,
+        journal: &mut UploadJournal,
+    ) -> Result<XorName> {
+        let (head_address, all_chunks) = Self::chunk_bytes(bytes)?;
+        self.upload_chunks(&all_chunks, true, Some(journal)).await?;
+        Ok(head_address)
+    }
+
     /// Calculates a LargeFile's/SmallFile's address from self encrypted chunks,
     /// without storing them onto the network.
     #[instrument(skip(bytes), level = "debug")]
@@ -175,12 +298,72 @@ impl Client {
         }
     }
 
+    #[instrument(skip_all, level = "trace")]
+    async fn upload_stream(
+        &self,
+        mut reader: impl AsyncRead + Unpin,
+        verify: bool,
+    ) -> Result<XorName> {
+        let mut segment = read_next_segment(&mut reader).await?;
+        if segment.len() < STREAM_SEGMENT_SIZE {
+            // all contents fit in a single segment, so it's stored as any other file
+            return self.upload_bytes(segment, verify).await;
+        }
+
+        let mut segments = vec![];
+        while !segment.is_empty() {
+            let size = segment.len();
+            let address = self.upload_bytes(segment, verify).await?;
+            trace!(
+                "Segment #{} ({size} bytes) uploaded at {address:?}",
+                segments.len()
+            );
+            segments.push(StreamSegment { address, size });
+
+            segment = read_next_segment(&mut reader).await?;
+        }
+
+        let (head_address, chunks) = pack_segments(segments)?;
+        self.upload_chunks(&chunks, verify, None).await?;
+
+        Ok(head_address)
+    }
+
     /// Directly writes a [`LargeFile`] to the network in the
     /// form of immutable self encrypted chunks, without any batching.
     #[instrument(skip_all, level = "trace")]
     async fn upload_large(&self, large: LargeFile, verify: bool) -> Result<XorName> {
         let (head_address, all_chunks) = Self::encrypt_large(large)?;
-        for next_batch in all_chunks.chunks(CHUNKS_BATCH_MAX_SIZE) {
+        self.upload_chunks(&all_chunks, verify, None).await?;
+        Ok(head_address)
+    }
+
+    // Writes the chunks to the network, in batches of concurrent uploads.
+    // If a journal is provided, chunks already recorded in it are skipped, and those
+    // successfully stored are recorded in it as each batch completes.
+    async fn upload_chunks(
+        &self,
+        all_chunks: &[Chunk],
+        verify: bool,
+        mut journal: Option<&mut UploadJournal>,
+    ) -> Result<()> {
+        let pending_chunks = all_chunks
+            .iter()
+            .filter(|chunk| match &journal {
+                Some(journal) => !journal.is_acked(chunk.name()),
+                None => true,
+            })
+            .cloned()
+            .collect_vec();
+        if pending_chunks.len() < all_chunks.len() {
+            trace!(
+                "Resuming upload, {} out of {} chunks were already stored",
+                all_chunks.len() - pending_chunks.len(),
+                all_chunks.len()
+            );
+        }
+
+        for next_batch in pending_chunks.chunks(CHUNKS_BATCH_MAX_SIZE) {
             // Connect to all relevant elders before we fire off all msgs...
             self.session
                 .setup_connections_to_relevant_nodes(next_batch.iter().map(|c| *c.name()).collect())
@@ -190,11 +373,16 @@ impl Client {
                 let client_clone = self.clone();
                 task::spawn(async move {
                     let chunk_addr = *chunk.address().name();
-                    client_clone.send_cmd(DataCmd::StoreChunk(chunk)).await?;
+                    client_clone
+                        .send_cmd(DataCmd::StoreChunk {
+                            chunk,
+                            payment: None,
+                        })
+                        .await?;
                     if verify {
                         client_clone.verify_chunk_is_stored(chunk_addr).await?;
                     }
-                    Ok::<(), Error>(())
+                    Ok::<XorName, Error>(chunk_addr)
                 })
             });
 
@@ -204,13 +392,23 @@ impl Client {
                 .flatten() // swallows errors
                 .collect_vec();
 
+            // record the chunks which were stored before failing with any error,
+            // so they don't need to be uploaded again when resuming
+            if let Some(journal) = journal.as_deref_mut() {
+                let stored = respones
+                    .iter()
+                    .filter_map(|res| res.as_ref().ok().copied())
+                    .collect_vec();
+                journal.record(&stored).await?;
+            }
+
             for res in respones {
                 // fail with any issue here
-                res?;
+                let _ = res?;
             }
         }
 
-        Ok(head_address)
+        Ok(())
     }
 
     /// Directly writes a [`SmallFile`] to the network in the
@@ -219,7 +417,11 @@ impl Client {
     async fn upload_small(&self, small: SmallFile, verify: bool) -> Result<XorName> {
         let chunk = Self::package_small(small)?;
         let address = *chunk.name();
-        self.send_cmd(DataCmd::StoreChunk(chunk)).await?;
+        self.send_cmd(DataCmd::StoreChunk {
+            chunk,
+            payment: None,
+        })
+        .await?;
 
         if verify {
             self.verify_chunk_is_stored(address).await?;
@@ -286,6 +488,51 @@ impl Client {
         Ok(bytes)
     }
 
+    // Reads `len` bytes starting at given `pos` of a file uploaded from a stream,
+    // from the segments which cover that range.
+    #[instrument(skip_all, level = "trace")]
+    async fn read_segments(
+        &self,
+        segments: &[StreamSegment],
+        pos: usize,
+        len: usize,
+    ) -> Result<Bytes> {
+        let end = pos.saturating_add(len);
+        let mut bytes = BytesMut::new();
+        let mut segment_start = 0;
+        for segment in segments {
+            let segment_end = segment_start + segment.size;
+            if segment_end > pos && segment_start < end {
+                let relative_pos = pos.saturating_sub(segment_start);
+                let relative_len = end.min(segment_end) - segment_start - relative_pos;
+                let segment_bytes = self
+                    .read_segment(segment.address, relative_pos, relative_len)
+                    .await?;
+                bytes.extend_from_slice(&segment_bytes);
+            }
+            segment_start = segment_end;
+        }
+
+        Ok(bytes.freeze())
+    }
+
+    // Reads `len` bytes starting at given `pos` of a segment of a file uploaded from a stream,
+    // segments are stored as any other file, but they cannot be split into segments themselves.
+    async fn read_segment(&self, address: XorName, pos: usize, len: usize) -> Result<Bytes> {
+        let chunk = self.get_chunk(&address).await?;
+        match self.unpack_chunk(chunk.clone()).await {
+            Ok(FileMap::DataMap(data_map)) => self.seek(data_map, pos, len).await,
+            Ok(FileMap::Segments(_)) => Err(Error::InvalidStreamSegment(address)),
+            // if an error occurs, we assume it's a SmallFile
+            Err(_) => {
+                let mut bytes = chunk.value().clone();
+                let _ = bytes.split_to(pos);
+                bytes.truncate(len);
+                Ok(bytes)
+            }
+        }
+    }
+
     #[instrument(skip_all, level = "trace")]
     async fn try_get_chunks(
         client: &Self,
@@ -330,13 +577,17 @@ impl Client {
 
     /// Extracts a file DataMapLevel from a chunk.
     /// If the DataMapLevel is not the first level mapping directly to the user's contents,
-    /// the process repeats itself until it obtains the first level DataMapLevel.
+    /// or to the segments of a file uploaded from a stream, the process repeats itself
+    /// until it obtains such a DataMapLevel.
     #[instrument(skip_all, level = "trace")]
-    async fn unpack_chunk(&self, mut chunk: Chunk) -> Result<DataMap> {
+    async fn unpack_chunk(&self, mut chunk: Chunk) -> Result<FileMap> {
         loop {
             match deserialize(chunk.value())? {
                 DataMapLevel::First(data_map) => {
-                    return Ok(data_map);
+                    return Ok(FileMap::DataMap(data_map));
+                }
+                DataMapLevel::Segments(segments) => {
+                    return Ok(FileMap::Segments(segments));
                 }
                 DataMapLevel::Additional(data_map) => {
                     let serialized_chunk = self.read_all(data_map).await?;
@@ -347,9 +598,20 @@ impl Client {
     }
 }
 
+// Reads the next segment of contents from the reader,
+// which is only smaller than a full segment at the end of the stream.
+async fn read_next_segment(reader: &mut (impl AsyncRead + Unpin)) -> Result<Bytes> {
+    let mut segment = Vec::with_capacity(STREAM_SEGMENT_SIZE);
+    let _ = reader
+        .take(STREAM_SEGMENT_SIZE as u64)
+        .read_to_end(&mut segment)
+        .await?;
+    Ok(Bytes::from(segment))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::LargeFile;
+    use super::{LargeFile, STREAM_SEGMENT_SIZE};
     use crate::{
         utils::test_utils::{create_test_client, init_logger, try_create_test_client},
         Client,
@@ -360,6 +622,7 @@ mod tests {
     use bytes::Bytes;
     use eyre::{eyre, Result};
     use futures::future::join_all;
+    use std::io::Cursor;
     use tokio::time::Instant;
     use tracing::{instrument::Instrumented, Instrument};
     use xor_name::XorName;
@@ -660,6 +923,31 @@ mod tests {
         store_and_read(&client, 20 * 1024 * 1024).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_read_streamed_file() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("store_and_read_streamed_file").entered();
+        let client = create_test_client().await?;
+
+        // contents spanning a few segments, the last one being a partial segment
+        let bytes = random_bytes(2 * STREAM_SEGMENT_SIZE + MIN_ENCRYPTABLE_BYTES);
+        let address = client
+            .upload_from_reader_and_verify(Cursor::new(bytes.clone()))
+            .await?;
+
+        let mut downloaded = vec![];
+        let written = client.download_to_writer(address, &mut downloaded).await?;
+        assert_eq!(written, bytes.len());
+        compare(bytes.clone(), Bytes::from(downloaded));
+
+        // read a range which spans two segments
+        let pos = STREAM_SEGMENT_SIZE - 512;
+        let read_data = client.read_from(address, pos, 1024).await?;
+        compare(bytes.slice(pos..pos + 1024), read_data);
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[ignore = "too heavy for CI"]
     async fn store_and_read_40mb() {
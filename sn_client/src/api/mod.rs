@@ -6,31 +6,37 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod chunk_disk_cache;
 /// A [`Client`] builder
 pub mod client_builder;
 mod cmds;
 mod data;
 mod file_apis;
+mod payment_apis;
 mod queries;
 mod register_apis;
 mod spentbook_apis;
+mod upload_journal;
 
 pub use client_builder::ClientBuilder;
-pub use register_apis::RegisterWriteAheadLog;
+pub use register_apis::{RegisterHeads, RegisterWriteAheadLog};
+pub use upload_journal::UploadJournal;
 
 use crate::{errors::Error, sessions::Session};
+use chunk_disk_cache::ChunkDiskCache;
 
 use sn_dbc::Owner;
 use sn_interface::{
     messaging::data::{DataQueryVariant, RegisterQuery},
     network_knowledge::SectionTree,
-    types::{Chunk, Keypair, PublicKey, RegisterAddress},
+    types::{Chunk, Error as DtError, Keypair, PublicKey, RegisterAddress},
 };
 
 use std::sync::Arc;
 use tokio::{sync::RwLock, time::Duration};
 use tracing::debug;
 use uluru::LRUCache;
+use xor_name::XorName;
 
 /// Name of the default network contacts file the Client uses. The file is
 /// expected to be found at user's OS home directory, e.g. in Linux this
@@ -57,6 +63,8 @@ pub struct Client {
     pub(crate) max_backoff_interval: Duration,
     pub(crate) cmd_timeout: Duration,
     chunks_cache: Arc<RwLock<ChunksCache>>,
+    chunks_disk_cache: Option<ChunkDiskCache>,
+    register_read_repair: bool,
 }
 
 /// Easily manage connections to/from The Safe Network with the client and its APIs.
@@ -75,10 +83,10 @@ impl Client {
         // trigger the AE flows.
 
         // Generate a random query to send a dummy message
-        let query = DataQueryVariant::Register(RegisterQuery::Get(RegisterAddress {
-            name: xor_name::rand::random(),
-            tag: 1,
-        }));
+        let query = DataQueryVariant::Register(RegisterQuery::Get(RegisterAddress::new(
+            xor_name::rand::random(),
+            1,
+        )));
         debug!(
             "Making initial contact with network. Our public addr: {:?}. Probe msg: {query:?}",
             self.session.endpoint.public_addr()
@@ -131,6 +139,17 @@ impl Client {
         self.keypair().public_key()
     }
 
+    /// Return the key used to decrypt the entries of the private Registers we are a reader of.
+    ///
+    /// It's derived from the client's keypair, thus the same keypair can always read them back.
+    pub fn register_encryption_key(&self) -> Result<bls::SecretKey, Error> {
+        let secret_key = bincode::serialize(&self.keypair.secret_key()?)?;
+        let mut bytes = XorName::from_content_parts(&[b"register-encryption", &secret_key]).0;
+        // clear the top bits so the bytes are always within the range of a BLS secret key
+        bytes[0] &= 0x3f;
+        Ok(bls::SecretKey::from_bytes(bytes).map_err(DtError::from)?)
+    }
+
     /// Return the client's DBC owner, which will be a secret key.
     ///
     /// This can then be used to sign output DBCs during a DBC reissue.
@@ -0,0 +1,62 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+
+use crate::Error;
+
+use sn_interface::{
+    messaging::data::{DataCmd, DataQueryVariant, PaymentProof, QueryResponse, StoreCostQuote},
+    types::{Chunk, DataAddress},
+};
+
+impl Client {
+    /// Get a quote of the cost of storing the given amount of bytes at the given address,
+    /// as signed by one of the Elders of the section responsible for it.
+    ///
+    /// The quoted price is paid by reissuing a DBC to the quote's payment owner
+    /// (see [`StoreCostQuote::payment_owner`]), and the resulting [`PaymentProof`]
+    /// is then attached to the cmd storing the data.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_store_cost(
+        &self,
+        address: DataAddress,
+        bytes: usize,
+    ) -> Result<StoreCostQuote, Error> {
+        let query = DataQueryVariant::GetStoreCost { address, bytes };
+        let query_result = self.send_query(query.clone()).await?;
+        match query_result.response {
+            QueryResponse::GetStoreCost(res) => {
+                let quote = res.map_err(|err| Error::ErrorMsg { source: err })?;
+                quote
+                    .verify()
+                    .map_err(|err| Error::ErrorMsg { source: err })?;
+                Ok(quote)
+            }
+            other => Err(Error::UnexpectedQueryResponse {
+                query,
+                response: other,
+            }),
+        }
+    }
+
+    /// Store a Chunk on the network, attaching the proof of the payment made for storing it.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn store_chunk_with_payment(
+        &self,
+        chunk: Chunk,
+        payment: PaymentProof,
+    ) -> Result<(), Error> {
+        debug!("Storing Chunk {:?} with payment", chunk.name());
+        self.send_cmd(DataCmd::StoreChunk {
+            chunk,
+            payment: Some(payment),
+        })
+        .await
+    }
+}
@@ -25,6 +25,9 @@ use bytes::Bytes;
 use tokio::time::sleep;
 use tracing::{debug, info_span};
 
+// Result of a query sent to each of the data replicas, along with the index of the Adult holding it.
+type ReplicasQueryResults = Vec<(Result<QueryResult, Error>, usize)>;
+
 impl Client {
     /// Send a Query to the network and await a response.
     /// Queries are automatically retried using exponential backoff if the timeout is hit.
@@ -168,17 +171,14 @@ impl Client {
             .await
     }
 
-    /// Send a Query to the network and await a response.
-    /// Queries are sent once per each replica, i.e. it sends the query targetting
-    /// all Adults replicas (using `query_index`) to make sure the piece of content
-    /// is stored in each and all of the expected data replicas at section Adults.
-    #[cfg(feature = "check-replicas")]
+    /// Send a Query to each of the Adults holding a replica of the data, concurrently,
+    /// and await all their responses. Each response is returned along with the
+    /// index of the Adult it was received from.
     #[instrument(skip(self), level = "debug")]
-    pub async fn send_query(&self, query: DataQueryVariant) -> Result<QueryResult, Error> {
-        use crate::errors::DataReplicasCheckError;
-        let span = info_span!("Attempting a query");
-        let _ = span.enter();
-
+    pub(crate) async fn send_query_to_all_replicas(
+        &self,
+        query: DataQueryVariant,
+    ) -> Result<ReplicasQueryResults, Error> {
         let client_pk = self.public_key();
         let dst = query.dst_name();
 
@@ -216,7 +216,22 @@ impl Client {
         }
 
         // Let's await for all queries to be sent
-        let results = futures::future::join_all(tasks).await;
+        Ok(futures::future::join_all(tasks).await)
+    }
+
+    /// Send a Query to the network and await a response.
+    /// Queries are sent once per each replica, i.e. it sends the query targetting
+    /// all Adults replicas (using `query_index`) to make sure the piece of content
+    /// is stored in each and all of the expected data replicas at section Adults.
+    #[cfg(feature = "check-replicas")]
+    #[instrument(skip(self), level = "debug")]
+    pub async fn send_query(&self, query: DataQueryVariant) -> Result<QueryResult, Error> {
+        use crate::errors::DataReplicasCheckError;
+        let span = info_span!("Attempting a query");
+        let _ = span.enter();
+
+        let num_of_replicas = data_copy_count();
+        let results = self.send_query_to_all_replicas(query.clone()).await?;
 
         let mut errors = vec![];
         let mut responses = vec![];
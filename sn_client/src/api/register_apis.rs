@@ -12,12 +12,13 @@ use crate::Error;
 
 use sn_interface::{
     messaging::data::{
-        CreateRegister, DataCmd, DataQueryVariant, EditRegister, QueryResponse, RegisterCmd,
-        RegisterQuery, SignedRegisterCreate, SignedRegisterEdit,
+        CreateRegister, DataCmd, DataQueryVariant, EditRegister, Error as ErrorMsg, PaymentProof,
+        QueryResponse, RegisterCmd, RegisterQuery, SignedRegisterCreate, SignedRegisterEdit,
+        SignedRegisterPolicyUpdate, UpdateRegisterPolicy,
     },
     types::{
-        register::{Action, Entry, EntryHash, Permissions, Policy, Register, User},
-        RegisterAddress as Address,
+        register::{Action, Entry, EntryHash, Permissions, Policy, Register, SealedEntry, User},
+        DataAddress, Error as DtError, RegisterAddress as Address,
     },
 };
 
@@ -32,6 +33,10 @@ use xor_name::XorName;
 /// Batches can be republished without duplication risks thanks to the CRDT nature of registers.
 pub type RegisterWriteAheadLog = Vec<DataCmd>;
 
+/// Latest entries read from a Register, along with the hashes of those of a private Register
+/// which were left out, as they couldn't be decrypted.
+pub type RegisterHeads = (BTreeSet<(EntryHash, Entry)>, BTreeSet<EntryHash>);
+
 impl Client {
     //----------------------
     // Write Operations
@@ -55,6 +60,8 @@ impl Client {
     ///
     /// A tag must be supplied.
     /// A xorname must be supplied, this can be random or deterministic as per your apps needs.
+    ///
+    /// No payment is attached to the cmd, see `create_register_with_payment`.
     #[instrument(skip(self), level = "debug")]
     pub async fn create_register(
         &self,
@@ -62,9 +69,74 @@ impl Client {
         tag: u64,
         policy: Policy,
     ) -> Result<(Address, RegisterWriteAheadLog), Error> {
-        let address = Address { name, tag };
+        self.create_register_cmd(Address::new(name, tag), policy, None)
+    }
+
+    /// Creates a private Register which can then be written to.
+    ///
+    /// The entries of a private Register are encrypted before being written, so only its
+    /// readers can decrypt them. Private Registers live in a separate address space from
+    /// public ones, i.e. they don't clash with a public Register with the same name and tag.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    ///
+    /// No payment is attached to the cmd, see `create_private_register_with_payment`.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn create_private_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        policy: Policy,
+    ) -> Result<(Address, RegisterWriteAheadLog), Error> {
+        self.create_register_cmd(Address::new_private(name, tag), policy, None)
+    }
 
-        let op = CreateRegister { name, tag, policy };
+    /// Creates a Register which can then be written to, attaching the proof
+    /// of the payment made for storing it to the cmd creating it.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self, payment), level = "debug")]
+    pub async fn create_register_with_payment(
+        &self,
+        name: XorName,
+        tag: u64,
+        policy: Policy,
+        payment: PaymentProof,
+    ) -> Result<(Address, RegisterWriteAheadLog), Error> {
+        self.create_register_cmd(Address::new(name, tag), policy, Some(payment))
+    }
+
+    /// Creates a private Register which can then be written to, attaching the proof
+    /// of the payment made for storing it to the cmd creating it.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self, payment), level = "debug")]
+    pub async fn create_private_register_with_payment(
+        &self,
+        name: XorName,
+        tag: u64,
+        policy: Policy,
+        payment: PaymentProof,
+    ) -> Result<(Address, RegisterWriteAheadLog), Error> {
+        self.create_register_cmd(Address::new_private(name, tag), policy, Some(payment))
+    }
+
+    // Builds the cmd for creating a Register, signed by us
+    fn create_register_cmd(
+        &self,
+        address: Address,
+        policy: Policy,
+        payment: Option<PaymentProof>,
+    ) -> Result<(Address, RegisterWriteAheadLog), Error> {
+        let op = CreateRegister {
+            name: address.name,
+            tag: address.tag,
+            policy,
+            private: address.private,
+        };
         let signature = self.keypair.sign(&bincode::serialize(&op)?);
 
         let cmd = DataCmd::Register(RegisterCmd::Create {
@@ -75,7 +147,8 @@ impl Client {
                     signature,
                 },
             },
-            section_sig: section_sig(), // obtained after presenting a valid payment to the network
+            section_sig: payment.as_ref().and_then(PaymentProof::section_sig),
+            payment,
         });
 
         debug!("Creating Register: {:?}", cmd);
@@ -85,6 +158,9 @@ impl Client {
 
     /// Write to Register
     ///
+    /// If the Register is private, the entry is encrypted to the readers of the entries it
+    /// succeeds, and to ourselves.
+    ///
     /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
     /// network until the WAL is published with `publish_register_ops`
     #[instrument(skip(self, children), level = "debug")]
@@ -93,6 +169,24 @@ impl Client {
         address: Address,
         entry: Entry,
         children: BTreeSet<EntryHash>,
+    ) -> Result<(EntryHash, RegisterWriteAheadLog), Error> {
+        self.write_to_local_private_register(address, entry, children, BTreeSet::new())
+            .await
+    }
+
+    /// Write to a private Register, encrypting the entry to the given readers, on top of the
+    /// readers of the entries it succeeds and ourselves. Readers added this way are then kept
+    /// by the entries written on top of this one.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self, children, readers), level = "debug")]
+    pub async fn write_to_local_private_register(
+        &self,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+        readers: BTreeSet<bls::PublicKey>,
     ) -> Result<(EntryHash, RegisterWriteAheadLog), Error> {
         // First we fetch it so we can get the causality info,
         // either from local CRDT replica or from the network if not found
@@ -104,9 +198,21 @@ impl Client {
         let public_key = self.keypair.public_key();
         register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
 
+        let entry = if address.is_private() {
+            self.seal_register_entry(&register, &entry, &children, readers)?
+        } else if readers.is_empty() {
+            entry
+        } else {
+            return Err(Error::NotAPrivateRegister(address));
+        };
+
         // We can now write the entry to the Register
         let (hash, op) = register.write(entry, children)?;
-        let op = EditRegister { address, edit: op };
+        let op = EditRegister {
+            address,
+            edit: op,
+            policy_version: register.policy_version(),
+        };
 
         let signature = self.keypair.sign(&bincode::serialize(&op)?);
 
@@ -124,20 +230,265 @@ impl Client {
         Ok((hash, batch))
     }
 
+    /// Update the policy of a Register, i.e. its owner and users' permissions.
+    /// Only the owner and users allowed to manage the policy can update it.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn update_register_policy(
+        &self,
+        address: Address,
+        policy: Policy,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        let register = self.get_register(address).await?;
+        self.update_register_policy_cmd(&register, policy)
+    }
+
+    /// Grant a user permissions on a Register, replacing any permissions previously set for them.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn grant_register_permissions(
+        &self,
+        address: Address,
+        user: User,
+        permissions: Permissions,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        let register = self.get_register(address).await?;
+        let mut policy = register.policy().clone();
+        let _ = policy.permissions.insert(user, permissions);
+        self.update_register_policy_cmd(&register, policy)
+    }
+
+    /// Revoke all permissions of a user on a Register. They are explicitly denied, rather than
+    /// removed, so the user doesn't fall back to the permissions granted to `User::Anyone`.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn revoke_register_permissions(
+        &self,
+        address: Address,
+        user: User,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        let permissions = Permissions::new(false).with_manage_policy(false);
+        self.grant_register_permissions(address, user, permissions)
+            .await
+    }
+
+    // Builds the cmd for replacing the policy of the given Register, signed by us
+    fn update_register_policy_cmd(
+        &self,
+        register: &Register,
+        policy: Policy,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        // Let's check the policy/permissions to make sure this operation is allowed,
+        // otherwise it will fail when the operation is applied on the network replica.
+        let public_key = self.keypair.public_key();
+        register.check_permissions(Action::ManagePolicy, Some(User::Key(public_key)))?;
+
+        let op = UpdateRegisterPolicy {
+            address: *register.address(),
+            version: register.policy_version() + 1,
+            policy,
+        };
+        let signature = self.keypair.sign(&bincode::serialize(&op)?);
+
+        let update = SignedRegisterPolicyUpdate {
+            op,
+            auth: sn_interface::messaging::ClientAuth {
+                public_key,
+                signature,
+            },
+        };
+
+        debug!("Updating Register policy: {:?}", update);
+
+        Ok(vec![DataCmd::Register(RegisterCmd::UpdatePolicy(update))])
+    }
+
     //----------------------
     // Get Register
     //---------------------
 
-    /// Get the entire Register from the Network
+    /// Get the entire Register from the Network.
+    ///
+    /// The Register is retrieved from each of the Adults holding a replica of it, and all the
+    /// replicas are merged into one, so a replica lagging behind doesn't make us miss any entry.
+    /// If read-repair is enabled, lagging replicas are then sent the edits they are missing.
     #[instrument(skip(self), level = "debug")]
     pub async fn get_register(&self, address: Address) -> Result<Register, Error> {
-        // Let's fetch the Register from the network
         let query = DataQueryVariant::Register(RegisterQuery::Get(address));
+        let results = self.send_query_to_all_replicas(query.clone()).await?;
+
+        let mut merged: Option<Register> = None;
+        let mut diverged = false;
+        let mut last_error = data_not_found(address);
+        for (result, adult_index) in results {
+            match result.map(|query_result| query_result.response) {
+                Ok(QueryResponse::GetRegister(Ok(register))) => match merged.as_mut() {
+                    Some(merged) if *merged != register => {
+                        debug!("Register replica at Adult-#{adult_index} diverged from others");
+                        diverged = true;
+                        merged.merge(register)?;
+                    }
+                    Some(_) => {}
+                    None => merged = Some(register),
+                },
+                Ok(QueryResponse::GetRegister(Err(err))) => {
+                    if let ErrorMsg::DataNotFound(_) = err {
+                        debug!("Register replica not found at Adult-#{adult_index}");
+                        diverged = true;
+                    }
+                    last_error = Error::ErrorMsg { source: err };
+                }
+                Ok(other) => {
+                    last_error = Error::UnexpectedQueryResponse {
+                        query: query.clone(),
+                        response: other,
+                    }
+                }
+                Err(err) => last_error = err,
+            }
+        }
+
+        match merged {
+            Some(register) => {
+                if diverged && self.register_read_repair {
+                    if let Err(err) = self.repair_register(address).await {
+                        warn!("Failed to repair replicas of Register at {address:?}: {err:?}");
+                    }
+                }
+                Ok(register)
+            }
+            None => Err(last_error),
+        }
+    }
+
+    /// Get the latest entry (or entries if branching)
+    ///
+    /// The entries are read from each of the Adults holding a replica of the Register.
+    /// If the replicas don't agree, the whole Register is retrieved and merged instead.
+    ///
+    /// The latest entries of a private Register which we aren't a reader of are left out,
+    /// see `read_register_with_skipped` to find out which ones.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn read_register(
+        &self,
+        address: Address,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
+        let (entries, skipped) = self.read_register_with_skipped(address).await?;
+        if !skipped.is_empty() {
+            warn!(
+                "Left out {} latest entries of the Register at {address:?} which we can't decrypt",
+                skipped.len()
+            );
+        }
+        Ok(entries)
+    }
+
+    /// Get the latest entry (or entries if branching), along with the hashes of the latest
+    /// entries of a private Register which were left out as we can't decrypt them, i.e. we
+    /// aren't one of their readers. It fails if none of them can be decrypted.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn read_register_with_skipped(
+        &self,
+        address: Address,
+    ) -> Result<RegisterHeads, Error> {
+        let query = DataQueryVariant::Register(RegisterQuery::Read(address));
+        let results = self.send_query_to_all_replicas(query.clone()).await?;
+
+        let mut entries: Option<BTreeSet<(EntryHash, Entry)>> = None;
+        let mut diverged = false;
+        let mut last_error = data_not_found(address);
+        for (result, _) in results {
+            match result.map(|query_result| query_result.response) {
+                Ok(QueryResponse::ReadRegister(Ok(replica_entries))) => match &entries {
+                    Some(entries) => diverged |= *entries != replica_entries,
+                    None => entries = Some(replica_entries),
+                },
+                Ok(QueryResponse::ReadRegister(Err(err))) => {
+                    diverged |= matches!(err, ErrorMsg::DataNotFound(_));
+                    last_error = Error::ErrorMsg { source: err };
+                }
+                Ok(other) => {
+                    last_error = Error::UnexpectedQueryResponse {
+                        query: query.clone(),
+                        response: other,
+                    }
+                }
+                Err(err) => last_error = err,
+            }
+        }
+
+        let entries = match entries {
+            Some(_) if diverged => self.get_register(address).await?.read(),
+            Some(entries) => entries,
+            None => return Err(last_error),
+        };
+        self.open_register_heads(address, entries)
+    }
+
+    // Sends to all the replicas of a Register all the cmds held by any of them, including the
+    // one creating it, so those lagging behind or missing it altogether catch up with the rest.
+    // Cmds already applied by a replica are simply ignored by it, thanks to the CRDT nature of registers.
+    async fn repair_register(&self, address: Address) -> Result<(), Error> {
+        let query = DataQueryVariant::Register(RegisterQuery::GetOpLog(address));
+        let mut op_logs = vec![];
+        for (result, adult_index) in self.send_query_to_all_replicas(query).await? {
+            match result.map(|query_result| query_result.response) {
+                Ok(QueryResponse::GetRegisterOpLog(Ok(op_log))) => op_logs.push(op_log),
+                other => debug!("No Register op log obtained from Adult-#{adult_index}: {other:?}"),
+            }
+        }
+
+        let cmds = cmds_to_repair(op_logs);
+        debug!(
+            "Repairing replicas of Register at {address:?} with {} cmds",
+            cmds.len()
+        );
+        let wal = cmds.into_iter().map(DataCmd::Register).collect();
+        self.publish_register_ops(wal).await
+    }
+
+    /// Get an entry from a Register on the Network by its hash
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_register_entry(
+        &self,
+        address: Address,
+        hash: EntryHash,
+    ) -> Result<Entry, Error> {
+        let query = DataQueryVariant::Register(RegisterQuery::GetEntry { address, hash });
         let query_result = self.send_query(query.clone()).await?;
+        match query_result.response {
+            QueryResponse::GetRegisterEntry(res) => {
+                let entry = res.map_err(|err| Error::ErrorMsg { source: err })?;
+                self.open_register_entry(address, entry)
+            }
+            other => Err(Error::UnexpectedQueryResponse {
+                query,
+                response: other,
+            }),
+        }
+    }
 
-        debug!("get_register result is; {query_result:?}");
+    /// Get all the entries a Register entry was written on top of, either directly
+    /// or transitively, in causal order.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_register_entry_ancestors(
+        &self,
+        address: Address,
+        hash: EntryHash,
+    ) -> Result<Vec<(EntryHash, Entry)>, Error> {
+        let query = DataQueryVariant::Register(RegisterQuery::GetEntryAncestors { address, hash });
+        let query_result = self.send_query(query.clone()).await?;
         match query_result.response {
-            QueryResponse::GetRegister(res) => res.map_err(|err| Error::ErrorMsg { source: err }),
+            QueryResponse::GetRegisterEntryAncestors(res) => {
+                let entries = res.map_err(|err| Error::ErrorMsg { source: err })?;
+                self.open_register_entries(address, entries)
+            }
             other => Err(Error::UnexpectedQueryResponse {
                 query,
                 response: other,
@@ -145,16 +496,20 @@ impl Client {
         }
     }
 
-    /// Get the latest entry (or entries if branching)
+    /// Get the full history of a Register, i.e. all its entries in causal order,
+    /// where concurrent entries are ordered by their hash.
     #[instrument(skip(self), level = "debug")]
-    pub async fn read_register(
+    pub async fn get_register_history(
         &self,
         address: Address,
-    ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
-        let query = DataQueryVariant::Register(RegisterQuery::Read(address));
+    ) -> Result<Vec<(EntryHash, Entry)>, Error> {
+        let query = DataQueryVariant::Register(RegisterQuery::GetHistory(address));
         let query_result = self.send_query(query.clone()).await?;
         match query_result.response {
-            QueryResponse::ReadRegister(res) => res.map_err(|err| Error::ErrorMsg { source: err }),
+            QueryResponse::GetRegisterHistory(res) => {
+                let entries = res.map_err(|err| Error::ErrorMsg { source: err })?;
+                self.open_register_entries(address, entries)
+            }
             other => Err(Error::UnexpectedQueryResponse {
                 query,
                 response: other,
@@ -162,18 +517,21 @@ impl Client {
         }
     }
 
-    /// Get an entry from a Register on the Network by its hash
+    /// Read the entries of a Register which were the current ones as of the entry with the
+    /// given hash, i.e. the entry, along with any concurrent heads which come before it in the
+    /// history of the Register. Entries which come after it in the history are ignored.
     #[instrument(skip(self), level = "debug")]
-    pub async fn get_register_entry(
+    pub async fn read_register_as_of(
         &self,
         address: Address,
         hash: EntryHash,
-    ) -> Result<Entry, Error> {
-        let query = DataQueryVariant::Register(RegisterQuery::GetEntry { address, hash });
+    ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
+        let query = DataQueryVariant::Register(RegisterQuery::ReadAsOf { address, hash });
         let query_result = self.send_query(query.clone()).await?;
         match query_result.response {
-            QueryResponse::GetRegisterEntry(res) => {
-                res.map_err(|err| Error::ErrorMsg { source: err })
+            QueryResponse::ReadRegisterAsOf(res) => {
+                let entries = res.map_err(|err| Error::ErrorMsg { source: err })?;
+                self.open_register_entries(address, entries)
             }
             other => Err(Error::UnexpectedQueryResponse {
                 query,
@@ -243,33 +601,124 @@ impl Client {
     }
 }
 
-// temp dummy
-fn section_sig() -> sn_interface::messaging::SectionSig {
-    use sn_interface::messaging::system::SectionSig;
+impl Client {
+    // Encrypts an entry of a private Register to the readers of the entries it succeeds,
+    // the additional readers provided, and ourselves.
+    fn seal_register_entry(
+        &self,
+        register: &Register,
+        entry: &[u8],
+        children: &BTreeSet<EntryHash>,
+        mut readers: BTreeSet<bls::PublicKey>,
+    ) -> Result<Entry, Error> {
+        let _ = readers.insert(self.register_encryption_key()?.public_key());
+        for hash in children {
+            let sealed = SealedEntry::from_entry(register.get(*hash)?)?;
+            readers.extend(sealed.readers());
+        }
+
+        Ok(SealedEntry::seal(entry, &readers).to_entry()?)
+    }
+
+    // Decrypts an entry read from a private Register, entries of public ones are returned as is
+    fn open_register_entry(&self, address: Address, entry: Entry) -> Result<Entry, Error> {
+        if address.is_private() {
+            let sealed = SealedEntry::from_entry(&entry)?;
+            Ok(sealed.open(&self.register_encryption_key()?)?)
+        } else {
+            Ok(entry)
+        }
+    }
+
+    // Decrypts the latest entries read from a private Register, leaving out those we can't
+    // decrypt as long as there's any we can, rather than failing the whole read
+    fn open_register_heads(
+        &self,
+        address: Address,
+        entries: BTreeSet<(EntryHash, Entry)>,
+    ) -> Result<RegisterHeads, Error> {
+        let mut opened = BTreeSet::new();
+        let mut skipped = BTreeSet::new();
+        for (hash, entry) in entries {
+            match self.open_register_entry(address, entry) {
+                Ok(entry) => {
+                    let _ = opened.insert((hash, entry));
+                }
+                Err(Error::NetworkDataError(DtError::EntryDecryption)) => {
+                    let _ = skipped.insert(hash);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        if opened.is_empty() && !skipped.is_empty() {
+            return Err(Error::NetworkDataError(DtError::EntryDecryption));
+        }
+        Ok((opened, skipped))
+    }
+
+    // Decrypts all the entries read from a private Register
+    fn open_register_entries<C>(
+        &self,
+        address: Address,
+        entries: impl IntoIterator<Item = (EntryHash, Entry)>,
+    ) -> Result<C, Error>
+    where
+        C: FromIterator<(EntryHash, Entry)>,
+    {
+        entries
+            .into_iter()
+            .map(|(hash, entry)| Ok((hash, self.open_register_entry(address, entry)?)))
+            .collect()
+    }
+}
+
+fn data_not_found(address: Address) -> Error {
+    Error::ErrorMsg {
+        source: ErrorMsg::DataNotFound(DataAddress::Register(address)),
+    }
+}
 
-    let sk = bls::SecretKey::random();
-    let public_key = sk.public_key();
-    let data = "hello".to_string();
-    let signature = sk.sign(data);
-    SectionSig {
-        public_key,
-        signature,
+// Merges the op logs of the replicas of a Register into the cmds to send them in order to repair
+// them. The cmd creating the Register goes first, so it can be reconstructed by replicas missing it,
+// followed by the policy updates, in the order of their versions, so edits allowed by any of
+// the policies the Register went through are accepted.
+fn cmds_to_repair(op_logs: Vec<Vec<RegisterCmd>>) -> Vec<RegisterCmd> {
+    let mut cmds: Vec<RegisterCmd> = vec![];
+    for cmd in op_logs.into_iter().flatten() {
+        if !cmds.contains(&cmd) {
+            cmds.push(cmd);
+        }
     }
+
+    cmds.sort_by_key(|cmd| match cmd {
+        RegisterCmd::Create { .. } => (0, 0),
+        RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate { op, .. }) => (1, op.version),
+        RegisterCmd::Edit(_) => (2, 0),
+    });
+    cmds
 }
 
 #[cfg(test)]
 mod tests {
+    use super::cmds_to_repair;
     use crate::{
         utils::test_utils::{create_test_client, init_logger},
         Error,
     };
 
     use sn_interface::{
-        messaging::data::Error as ErrorMsg,
+        messaging::{
+            data::{
+                CreateRegister, EditRegister, Error as ErrorMsg, RegisterCmd, SignedRegisterCreate,
+                SignedRegisterEdit, SignedRegisterPolicyUpdate, UpdateRegisterPolicy,
+            },
+            ClientAuth,
+        },
         types::{
             log_markers::LogMarker,
-            register::{Action, EntryHash, Permissions, Policy, User},
-            Keypair,
+            register::{Action, EntryHash, Permissions, Policy, Register, User},
+            Error as DtError, Keypair, RegisterAddress,
         },
     };
 
@@ -617,6 +1066,172 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_private_entries_readers() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("test__register_private_entries_readers").entered();
+
+        let owner_client = create_test_client().await?;
+        let reader_client = create_test_client().await?;
+        let other_client = create_test_client().await?;
+
+        let name = xor_name::rand::random();
+        let tag = 10;
+        let owner = User::Key(owner_client.public_key());
+
+        let (address, batch) = owner_client
+            .create_private_register(name, tag, policy(owner))
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+        assert!(address.is_private());
+
+        // the entry is only stored encrypted, and the owner can decrypt it
+        let entry = random_register_entry();
+        let (hash, batch) = owner_client
+            .write_to_local_register(address, entry.clone(), BTreeSet::new())
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+        assert_eq!(owner_client.get_register_entry(address, hash).await?, entry);
+        let register = owner_client.get_register(address).await?;
+        assert_ne!(register.get(hash)?, &entry);
+
+        match reader_client.get_register_entry(address, hash).await {
+            Err(Error::NetworkDataError(DtError::EntryDecryption)) => {}
+            other => bail!("Decryption should have failed: {:?}", other),
+        }
+
+        // a new entry with an additional reader, which the next entries keep
+        let reader_key = reader_client.register_encryption_key()?.public_key();
+        let shared_entry = random_register_entry();
+        let (shared_hash, batch) = owner_client
+            .write_to_local_private_register(
+                address,
+                shared_entry.clone(),
+                BTreeSet::from([hash]),
+                BTreeSet::from([reader_key]),
+            )
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+
+        let next_entry = random_register_entry();
+        let (next_hash, batch) = owner_client
+            .write_to_local_register(address, next_entry.clone(), BTreeSet::from([shared_hash]))
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+
+        assert_eq!(
+            reader_client
+                .get_register_entry(address, shared_hash)
+                .await?,
+            shared_entry
+        );
+        assert_eq!(
+            reader_client.read_register(address).await?,
+            BTreeSet::from([(next_hash, next_entry.clone())])
+        );
+
+        // a branch the reader isn't a reader of is left out of what it reads, and reported
+        let branch_entry = random_register_entry();
+        let (branch_hash, batch) = owner_client
+            .write_to_local_register(address, branch_entry.clone(), BTreeSet::from([hash]))
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+
+        assert_eq!(
+            reader_client.read_register_with_skipped(address).await?,
+            (
+                BTreeSet::from([(next_hash, next_entry.clone())]),
+                BTreeSet::from([branch_hash])
+            )
+        );
+        assert_eq!(
+            owner_client.read_register_with_skipped(address).await?,
+            (
+                BTreeSet::from([(next_hash, next_entry), (branch_hash, branch_entry)]),
+                BTreeSet::new()
+            )
+        );
+        match other_client.read_register(address).await {
+            Err(Error::NetworkDataError(DtError::EntryDecryption)) => Ok(()),
+            other => bail!("Decryption should have failed: {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_grant_and_revoke_permissions() -> Result<()> {
+        init_logger();
+        let _outer_span =
+            tracing::info_span!("test__register_grant_and_revoke_permissions").entered();
+
+        let owner_client = create_test_client().await?;
+        let other_client = create_test_client().await?;
+
+        let name = xor_name::rand::random();
+        let tag = 10;
+        let owner = User::Key(owner_client.public_key());
+        let other_user = User::Key(other_client.public_key());
+
+        let (address, batch) = owner_client
+            .create_register(name, tag, policy(owner))
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+
+        // the other user can neither write nor manage the policy yet
+        match other_client
+            .write_to_local_register(address, random_register_entry(), BTreeSet::new())
+            .await
+        {
+            Err(Error::NetworkDataError(_)) => {}
+            other => bail!("Write should have been denied: {:?}", other),
+        }
+        match other_client
+            .grant_register_permissions(address, other_user, Permissions::new(true))
+            .await
+        {
+            Err(Error::NetworkDataError(_)) => {}
+            other => bail!("Policy update should have been denied: {:?}", other),
+        }
+
+        // once granted, the other user can write, without needing to recreate the Register
+        let batch = owner_client
+            .grant_register_permissions(address, other_user, Permissions::new(true))
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+
+        let permissions = owner_client
+            .get_register_permissions_for_user(address, other_user)
+            .await?;
+        assert_eq!(Some(true), permissions.is_allowed(Action::Write));
+        assert_eq!(None, permissions.is_allowed(Action::ManagePolicy));
+
+        let entry = random_register_entry();
+        let (hash, batch) = other_client
+            .write_to_local_register(address, entry.clone(), BTreeSet::new())
+            .await?;
+        other_client.publish_register_ops(batch).await?;
+        assert_eq!(owner_client.get_register_entry(address, hash).await?, entry);
+
+        // after revoking, the other user can no longer write
+        let batch = owner_client
+            .revoke_register_permissions(address, other_user)
+            .await?;
+        owner_client.publish_register_ops(batch).await?;
+
+        let register = owner_client.get_register(address).await?;
+        assert_eq!(register.policy_version(), 2);
+        assert_eq!(
+            register.permissions(other_user)?,
+            Permissions::new(false).with_manage_policy(false)
+        );
+        match other_client
+            .write_to_local_register(address, random_register_entry(), BTreeSet::new())
+            .await
+        {
+            Err(Error::NetworkDataError(_)) => Ok(()),
+            other => bail!("Write should have been denied: {:?}", other),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_owner() -> Result<()> {
         init_logger();
@@ -669,6 +1284,69 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn repair_cmds_let_replicas_missing_the_register_create_it() -> Result<()> {
+        let keypair = Keypair::new_ed25519();
+        let owner = User::Key(keypair.public_key());
+        let auth = |payload: Vec<u8>| ClientAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&payload),
+        };
+        let name = xor_name::rand::random();
+        let tag = 15000;
+        let address = RegisterAddress::new(name, tag);
+
+        let op = CreateRegister {
+            name,
+            tag,
+            policy: policy(owner),
+            private: false,
+        };
+        let create = RegisterCmd::Create {
+            cmd: SignedRegisterCreate {
+                auth: auth(bincode::serialize(&op)?),
+                op,
+            },
+            section_sig: None,
+            payment: None,
+        };
+
+        let mut register = Register::new(owner, name, tag, policy(owner));
+        let mut edit = || -> Result<RegisterCmd> {
+            let (_, edit) = register.write(random_register_entry(), BTreeSet::new())?;
+            let op = EditRegister {
+                address,
+                edit,
+                policy_version: register.policy_version(),
+            };
+            Ok(RegisterCmd::Edit(SignedRegisterEdit {
+                auth: auth(bincode::serialize(&op)?),
+                op,
+            }))
+        };
+        let (edit1, edit2) = (edit()?, edit()?);
+
+        let op = UpdateRegisterPolicy {
+            address,
+            version: 1,
+            policy: none_policy(owner),
+        };
+        let update = RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate {
+            auth: auth(bincode::serialize(&op)?),
+            op,
+        });
+
+        // one replica holds the whole Register, while the other one is missing it,
+        // having only received an edit which it cannot apply without the create cmd
+        let cmds = cmds_to_repair(vec![
+            vec![edit1.clone(), update.clone(), create.clone()],
+            vec![edit2.clone(), edit1.clone()],
+        ]);
+        assert_eq!(cmds, vec![create, update, edit1, edit2]);
+
+        Ok(())
+    }
+
     fn random_register_entry() -> Vec<u8> {
         let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
         random_bytes.to_vec()
@@ -13,8 +13,8 @@ use crate::Error;
 use sn_dbc::{KeyImage, RingCtTransaction, SpentProof, SpentProofShare};
 use sn_interface::{
     messaging::data::{
-        DataCmd, DataQueryVariant, Error as NetworkDataError, QueryResponse, SpentbookCmd,
-        SpentbookQuery,
+        DataCmd, DataQueryVariant, Error as NetworkDataError, QueryResponse, SpentTransaction,
+        SpentbookCmd, SpentbookQuery,
     },
     types::SpentbookAddress,
 };
@@ -40,6 +40,9 @@ impl Client {
     ///
     /// When the request is resubmitted, it gets sent along with a proof chain and a signed SAP
     /// that the section can use to update itself.
+    ///
+    /// If the key image was already spent with a different transaction the request is refused,
+    /// and `Error::DbcAlreadySpent` is returned without retrying.
     #[instrument(skip(self, tx, spent_proofs, spent_transactions), level = "debug")]
     pub async fn spend_dbc(
         &self,
@@ -94,6 +97,13 @@ impl Client {
 
                 network_knowledge = Some((proof_chain, signed_sap.clone()));
                 attempts += 1;
+            } else if let Err(Error::CmdError {
+                source: NetworkDataError::DoubleSpendAttempt(_),
+                ..
+            }) = result
+            {
+                error!("DBC spend request refused, key_image was already spent: {key_image:?}");
+                return Err(Error::DbcAlreadySpent { key_image });
             } else {
                 return result;
             }
@@ -123,30 +133,45 @@ impl Client {
             }),
         }
     }
+
+    /// Return a sample of the transactions spent on the network, each along with the spent
+    /// proof shares of one of its inputs, so decoys can be picked out of their outputs.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn spent_transactions_sample(&self) -> Result<Vec<SpentTransaction>, Error> {
+        // any Adults can provide a sample, so we query those holding a random spentbook
+        let address = SpentbookAddress::new(xor_name::rand::random());
+        let query = DataQueryVariant::Spentbook(SpentbookQuery::SpentTransactions(address));
+        let query_result = self.send_query(query.clone()).await?;
+        match query_result.response {
+            QueryResponse::SpentTransactions(res) => {
+                res.map_err(|err| Error::ErrorMsg { source: err })
+            }
+            other => Err(Error::UnexpectedQueryResponse {
+                query,
+                response: other,
+            }),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::utils::test_utils::{
-        create_test_client_with, init_logger, read_genesis_dbc_from_first_node,
+        create_test_client_with, get_dbc_from_test_funds, init_logger, reissue_dbcs,
     };
     use crate::Client;
 
-    use sn_dbc::{rng, Hash, OwnerOnce, RingCtTransaction, TransactionBuilder};
+    use sn_dbc::{rng, Hash, OwnerOnce, RingCtTransaction, Token, TransactionBuilder};
     use sn_interface::messaging::data::Error as ErrorMsg;
 
     use eyre::{bail, Result};
-    use std::collections::{BTreeSet, HashSet};
     use tokio::time::Duration;
 
     const MAX_ATTEMPTS: u8 = 5;
     const SLEEP_DURATION: Duration = Duration::from_secs(3);
 
-    // Number of attempts to make trying to spend inputs when reissuing DBCs
-    // As the spend and query cmds are cascaded closely, there is high chance
-    // that the first two query attempts could both be failed.
-    // Hence the max number of attempts set to a higher value.
-    const NUM_OF_DBC_REISSUE_ATTEMPTS: u8 = 5;
+    // Amount of the DBC each test takes from the test funds to spend
+    const TEST_DBC_AMOUNT: u64 = 1_000_000;
 
     async fn verify_spent_proof_share(
         key_image: bls::PublicKey,
@@ -162,11 +187,7 @@ mod tests {
             // Get spent proof shares for the key_image.
             let spent_proof_shares = client.spent_proof_shares(key_image).await?;
 
-            // Note this test could have been run more than once thus the genesis DBC
-            // could have been spent a few times already, so we filter
-            // the SpentProofShares that belong to the TX we just spent in this run.
-            // TODO: once we have our Spentbook which prevents double spents
-            // we shouldnt't need this filtering.
+            // We filter the SpentProofShares that belong to the TX we just spent.
             let num_of_spent_proof_shares = spent_proof_shares
                 .iter()
                 .filter(|proof| proof.content.transaction_hash == Hash::from(tx.hash()))
@@ -190,28 +211,63 @@ mod tests {
         init_logger();
         let _outer_span = tracing::info_span!("test__spentbook_spend_dbc").entered();
 
-        let (
-            client,
-            SpendDetails {
-                key_image,
-                genesis_dbc,
-                tx,
-            },
-        ) = setup(false).await?;
+        let (client, SpendDetails { key_image, dbc, tx }) = setup(false).await?;
 
         // Spend the key_image.
         client
             .spend_dbc(
                 key_image,
                 tx.clone(),
-                genesis_dbc.spent_proofs,
-                genesis_dbc.spent_transactions,
+                dbc.spent_proofs,
+                dbc.spent_transactions,
             )
             .await?;
 
         verify_spent_proof_share(key_image, tx, &client).await
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn spentbook_spend_with_a_different_tx_should_return_already_spent_error() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!(
+            "test__spentbook_spend_with_a_different_tx_should_return_already_spent_error"
+        )
+        .entered();
+
+        let (client, SpendDetails { key_image, dbc, tx }) = setup(false).await?;
+        let other_tx = spend_tx(&client, &dbc)?;
+
+        client
+            .spend_dbc(
+                key_image,
+                tx,
+                dbc.spent_proofs.clone(),
+                dbc.spent_transactions.clone(),
+            )
+            .await?;
+
+        // Try spend the key_image with a different TX.
+        let result = client
+            .spend_dbc(
+                key_image,
+                other_tx,
+                dbc.spent_proofs,
+                dbc.spent_transactions,
+            )
+            .await;
+
+        match result {
+            Ok(_) => bail!("We expected an error to be returned"),
+            Err(crate::Error::DbcAlreadySpent {
+                key_image: spent_key_image,
+            }) => {
+                assert_eq!(spent_key_image, key_image);
+                Ok(())
+            }
+            Err(error) => bail!("We expected a different error to be returned. Actual: {error:?}"),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn spentbook_spend_spent_proof_with_invalid_pk_should_return_spentbook_error(
     ) -> Result<()> {
@@ -221,18 +277,11 @@ mod tests {
         )
         .entered();
 
-        let (
-            client,
-            SpendDetails {
-                key_image,
-                genesis_dbc,
-                tx,
-            },
-        ) = setup(false).await?;
+        let (client, SpendDetails { key_image, dbc, tx }) = setup(false).await?;
 
         // insert the invalid pk to proofs
         let invalid_pk = bls::SecretKey::random().public_key();
-        let invalid_spent_proofs = genesis_dbc
+        let invalid_spent_proofs = dbc
             .spent_proofs
             .into_iter()
             .map(|mut proof| {
@@ -247,7 +296,7 @@ mod tests {
                 key_image,
                 tx.clone(),
                 invalid_spent_proofs,
-                genesis_dbc.spent_transactions,
+                dbc.spent_transactions,
             )
             .await;
 
@@ -275,24 +324,17 @@ mod tests {
         init_logger();
         let _outer_span = tracing::info_span!("test__spentbook_spend_spent_proof_with_key_not_in_section_chain_should_return_cmd_error_response").entered();
 
-        let (
-            client,
-            SpendDetails {
-                key_image,
-                genesis_dbc,
-                tx,
-            },
-        ) = setup(true).await?; // pass in true, for getting an invalid genesis
+        let (client, SpendDetails { key_image, dbc, tx }) = setup(true).await?; // pass in true, for getting a DBC of an invalid genesis
 
-        let genesis_dbc_owner_pk = genesis_dbc.owner_base().public_key();
+        let dbc_owner_pk = dbc.owner_base().public_key();
 
         // Try spend the key_image.
         let result = client
             .spend_dbc(
                 key_image,
                 tx.clone(),
-                genesis_dbc.spent_proofs,
-                genesis_dbc.spent_transactions,
+                dbc.spent_proofs,
+                dbc.spent_transactions,
             )
             .await;
 
@@ -300,8 +342,8 @@ mod tests {
             Ok(_) => bail!("We expected an error to be returned"),
             Err(crate::Error::SectionsDagKeyNotFound(section_key)) => {
                 assert_eq!(
-                    section_key, genesis_dbc_owner_pk,
-                    "We expected {genesis_dbc_owner_pk:?} in the error but got {section_key:?}"
+                    section_key, dbc_owner_pk,
+                    "We expected {dbc_owner_pk:?} in the error but got {section_key:?}"
                 );
                 Ok(())
             }
@@ -315,27 +357,24 @@ mod tests {
         init_logger();
         let _outer_span = tracing::info_span!("test__spentbook_spend_spent_proofs_do_not_relate_to_input_dbcs_should_return_spentbook_error").entered();
 
-        let (client, SpendDetails { genesis_dbc, .. }) = setup(false).await?;
+        let (client, SpendDetails { dbc, .. }) = setup(false).await?;
 
         // The idea for this test case is to pass the wrong spent proofs and transactions for
         // the key image we're trying to spend. To do so, we reissue `output_dbc_1` from
-        // `genesis_dbc`, then reissue `output_dbc_2` from `output_dbc_1`, then when we try to spend
-        // `output_dbc_2`, we use the spent proofs/transactions from `genesis_dbc`. This should
+        // `dbc`, then reissue `output_dbc_2` from `output_dbc_1`, then when we try to spend
+        // `output_dbc_2`, we use the spent proofs/transactions from `dbc`. This should
         // not be permitted. The correct way would be to pass the spent proofs/transactions
         // from `output_dbc_1`, which was our input to `output_dbc_2`.
 
         let spend_amount_1 = 10;
         let recipient_owneronce_1 =
             OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
-        let outputs_1 = vec![(
-            sn_dbc::Token::from_nano(spend_amount_1),
-            recipient_owneronce_1,
-        )];
+        let outputs_1 = vec![(Token::from_nano(spend_amount_1), recipient_owneronce_1)];
         let (output_dbcs_1, _change_dbc_1) = reissue_dbcs(
             &client,
-            vec![genesis_dbc.clone()],
+            vec![dbc.clone()],
             outputs_1,
-            sn_dbc::Token::from_nano(sn_interface::dbcs::GENESIS_DBC_AMOUNT - spend_amount_1),
+            Token::from_nano(TEST_DBC_AMOUNT - spend_amount_1),
         )
         .await?;
 
@@ -344,15 +383,12 @@ mod tests {
         let spend_amount_2 = 5;
         let recipient_owneronce_2 =
             OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
-        let outputs_2 = vec![(
-            sn_dbc::Token::from_nano(spend_amount_2),
-            recipient_owneronce_2,
-        )];
+        let outputs_2 = vec![(Token::from_nano(spend_amount_2), recipient_owneronce_2)];
         let (output_dbcs_2, _change_dbc_2) = reissue_dbcs(
             &client,
             vec![output_dbc_1],
             outputs_2,
-            sn_dbc::Token::from_nano(spend_amount_1 - spend_amount_2),
+            Token::from_nano(spend_amount_1 - spend_amount_2),
         )
         .await?;
 
@@ -363,8 +399,8 @@ mod tests {
             .spend_dbc(
                 output_owneronce_2.as_owner().public_key(),
                 output_dbc_2.transaction.clone(),
-                genesis_dbc.spent_proofs.clone(),
-                genesis_dbc.spent_transactions,
+                dbc.spent_proofs.clone(),
+                dbc.spent_transactions,
             )
             .await;
 
@@ -394,12 +430,7 @@ mod tests {
         )
         .entered();
 
-        let (
-            client,
-            SpendDetails {
-                genesis_dbc, tx, ..
-            },
-        ) = setup(false).await?;
+        let (client, SpendDetails { dbc, tx, .. }) = setup(false).await?;
 
         // generate the random key image
         let random_key_image = bls::SecretKey::random().public_key();
@@ -409,8 +440,8 @@ mod tests {
             .spend_dbc(
                 random_key_image,
                 tx.clone(),
-                genesis_dbc.spent_proofs.clone(),
-                genesis_dbc.spent_transactions,
+                dbc.spent_proofs.clone(),
+                dbc.spent_transactions,
             )
             .await;
 
@@ -433,33 +464,39 @@ mod tests {
     }
 
     struct SpendDetails {
-        genesis_dbc: sn_dbc::Dbc,
+        dbc: sn_dbc::Dbc,
         tx: RingCtTransaction,
         key_image: sn_dbc::PublicKey,
     }
 
-    // returns a client which is the owner to the genesis dbc,
-    // we can do this since our genesis dbc is currently generated as a bearer dbc, and stored locally
-    // so we can fetch that owner key from the first node, and pass it to the client
-    async fn setup(invalid_genesis_dbc: bool) -> Result<(Client, SpendDetails)> {
+    // returns a client which is the owner of an unspent bearer dbc, taken from the
+    // test funds, along with the tx spending it, or of the dbc of a random genesis,
+    // thus not known to the network, when an invalid dbc is asked for
+    async fn setup(invalid_dbc: bool) -> Result<(Client, SpendDetails)> {
         init_logger();
 
-        let genesis_dbc = if invalid_genesis_dbc {
+        let dbc = if invalid_dbc {
             let sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
             sn_interface::dbcs::gen_genesis_dbc(&sk_set, &sk_set.secret_key())?
         } else {
-            read_genesis_dbc_from_first_node()?
+            get_dbc_from_test_funds(Token::from_nano(TEST_DBC_AMOUNT)).await?
         };
-        let dbc_owner = genesis_dbc.owner_base().clone();
-        let client = create_test_client_with(None, Some(dbc_owner.clone()), None).await?;
+        let client = create_test_client_with(None, Some(dbc.owner_base().clone()), None).await?;
+
+        let tx = spend_tx(&client, &dbc)?;
+        let key_image = dbc.key_image_bearer()?;
 
-        let genesis_key_image = genesis_dbc.key_image_bearer()?;
+        Ok((client, SpendDetails { dbc, tx, key_image }))
+    }
 
-        let output_owner = OwnerOnce::from_owner_base(dbc_owner, &mut rng::thread_rng());
+    // builds a tx spending the whole amount of the dbc to the client's dbc owner
+    fn spend_tx(client: &Client, dbc: &sn_dbc::Dbc) -> Result<RingCtTransaction> {
+        let output_owner =
+            OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
         let dbc_builder = TransactionBuilder::default()
             .set_decoys_per_input(0)
             .set_require_all_decoys(false)
-            .add_input_dbc_bearer(&genesis_dbc)?;
+            .add_input_dbc_bearer(dbc)?;
 
         let inputs_amount_sum = dbc_builder.inputs_amount_sum();
         let dbc_builder = dbc_builder
@@ -468,159 +505,8 @@ mod tests {
 
         assert_eq!(dbc_builder.inputs().len(), 1);
         let (key_image, tx) = dbc_builder.inputs()[0].clone();
-        assert_eq!(genesis_key_image, key_image);
-
-        Ok((
-            client,
-            SpendDetails {
-                genesis_dbc,
-                tx,
-                key_image,
-            },
-        ))
-    }
-
-    // Reissue DBCs and log the spent input DBCs on the network. Return the output DBC and the
-    // change DBC if there is one.
-    async fn reissue_dbcs(
-        client: &Client,
-        input_dbcs: Vec<sn_dbc::Dbc>,
-        outputs: Vec<(sn_dbc::Token, OwnerOnce)>,
-        change_amount: sn_dbc::Token,
-    ) -> Result<(
-        Vec<(sn_dbc::Dbc, OwnerOnce, sn_dbc::AmountSecrets)>,
-        Option<sn_dbc::Dbc>,
-    )> {
-        // TODO: enable the use of decoys
-        let mut tx_builder = TransactionBuilder::default()
-            .set_decoys_per_input(0)
-            .set_require_all_decoys(false)
-            .add_inputs_dbc_bearer(input_dbcs.iter())?
-            .add_outputs_by_amount(outputs.into_iter().map(|(token, owner)| (token, owner)));
-
-        let change_owneronce =
-            OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
-        if change_amount.as_nano() > 0 {
-            tx_builder = tx_builder.add_output_by_amount(change_amount, change_owneronce.clone());
-        }
-
-        let spent_proofs: BTreeSet<sn_dbc::SpentProof> = input_dbcs
-            .iter()
-            .flat_map(|dbc| dbc.spent_proofs.clone())
-            .collect();
-
-        let spent_transactions: BTreeSet<RingCtTransaction> = input_dbcs
-            .iter()
-            .flat_map(|dbc| dbc.spent_transactions.clone())
-            .collect();
-
-        let proof_key_verifier = SpentProofKeyVerifier { client };
-
-        // Let's build the output DBCs
-        let mut dbc_builder = tx_builder.build(rng::thread_rng())?;
+        assert_eq!(dbc.key_image_bearer()?, key_image);
 
-        // Spend all the input DBCs, collecting the spent proof shares for each of them
-        for (key_image, tx) in dbc_builder.inputs() {
-            let tx_hash = Hash::from(tx.hash());
-            // TODO: spend DBCs concurrently spawning tasks
-            let mut attempts = 0;
-            loop {
-                attempts += 1;
-                client
-                    .spend_dbc(
-                        key_image,
-                        tx.clone(),
-                        spent_proofs.clone(),
-                        spent_transactions.clone(),
-                    )
-                    .await?;
-
-                let spent_proof_shares = client.spent_proof_shares(key_image).await?;
-
-                // TODO: we temporarilly filter the spent proof shares which correspond to the TX we
-                // are spending now. This is because current implementation of Spentbook allows
-                // double spents, so we may be retrieving spent proof shares for others spent TXs.
-                let shares_for_current_tx: HashSet<sn_dbc::SpentProofShare> = spent_proof_shares
-                    .into_iter()
-                    .filter(|proof_share| proof_share.content.transaction_hash == tx_hash)
-                    .collect();
-
-                match verify_spent_proof_shares_for_tx(
-                    key_image,
-                    tx_hash,
-                    shares_for_current_tx.iter(),
-                    &proof_key_verifier,
-                ) {
-                    Ok(()) => {
-                        dbc_builder = dbc_builder
-                            .add_spent_proof_shares(shares_for_current_tx.into_iter())
-                            .add_spent_transaction(tx);
-
-                        break;
-                    }
-                    Err(err) if attempts == NUM_OF_DBC_REISSUE_ATTEMPTS => {
-                        bail!(format!(
-                            "Failed to spend input, {} proof shares obtained from spentbook: {}",
-                            shares_for_current_tx.len(),
-                            err
-                        ))
-                        // return Err(crate::Error::DbcSpendRetryAttemptsExceeded {
-                        //     attempts,
-                        //     key_image,
-                        // });
-                    }
-                    Err(_) => {}
-                }
-            }
-        }
-
-        // Perform verifications of input TX and spentproofs,
-        // as well as building the output DBCs.
-        let mut output_dbcs = dbc_builder.build(&proof_key_verifier)?;
-
-        let mut change_dbc = None;
-        output_dbcs.retain(|(dbc, owneronce, _)| {
-            if owneronce == &change_owneronce && change_amount.as_nano() > 0 {
-                change_dbc = Some(dbc.clone());
-                false
-            } else {
-                true
-            }
-        });
-
-        Ok((output_dbcs, change_dbc))
-    }
-
-    // Private helper to verify if a set of spent proof shares are valid for a given key_image and TX
-    fn verify_spent_proof_shares_for_tx<'a>(
-        key_image: sn_dbc::KeyImage,
-        tx_hash: Hash,
-        proof_shares: impl Iterator<Item = &'a sn_dbc::SpentProofShare>,
-        proof_key_verifier: &SpentProofKeyVerifier,
-    ) -> Result<()> {
-        sn_dbc::SpentProof::try_from_proof_shares(key_image, tx_hash, proof_shares)
-            .and_then(|spent_proof| spent_proof.verify(tx_hash, proof_key_verifier))?;
-
-        Ok(())
-    }
-
-    /// Verifier required by test to check a SpentProof
-    /// is signed by known sections keys.
-    struct SpentProofKeyVerifier<'a> {
-        client: &'a Client,
-    }
-
-    impl sn_dbc::SpentProofKeyVerifier for SpentProofKeyVerifier<'_> {
-        type Error = crate::Error;
-
-        // Called by test when it needs to verify a SpentProof is signed by a known key,
-        // we check if the key is any of the network sections keys we are aware of
-        fn verify_known_key(&self, key: &sn_dbc::PublicKey) -> crate::Result<()> {
-            if !futures::executor::block_on(self.client.is_known_section_key(key)) {
-                Err(crate::Error::SectionsDagKeyNotFound(*key))
-            } else {
-                Ok(())
-            }
-        }
+        Ok(tx)
     }
 }
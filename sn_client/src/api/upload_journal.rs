@@ -0,0 +1,203 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Result;
+
+use hex::FromHex;
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+};
+use tracing::{trace, warn};
+use xor_name::XorName;
+
+/// Local record of the chunks of an upload which were already acknowledged by the network.
+///
+/// The journal is kept in a file, with the hex-encoded name of a chunk per line, which is
+/// appended to as batches of chunks get stored. Since self-encryption is deterministic,
+/// uploading the same content again with the same journal skips all chunks recorded in it,
+/// thus resuming an upload which was previously interrupted.
+#[derive(Debug)]
+pub struct UploadJournal {
+    path: PathBuf,
+    acked: BTreeSet<XorName>,
+    // Whether the file ends with a partially written line, which needs to be terminated
+    partial_line: bool,
+}
+
+impl UploadJournal {
+    /// Opens the journal kept at the given path, or starts a new empty one
+    /// if there is no file there yet. The file is only created once a chunk is recorded.
+    ///
+    /// Any line which cannot be parsed, e.g. the last one if we were interrupted
+    /// while writing it, is ignored, so the chunk it refers to is simply uploaded again.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut acked = BTreeSet::new();
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            match <[u8; 32]>::from_hex(line) {
+                Ok(name) => {
+                    let _ = acked.insert(XorName(name));
+                }
+                Err(err) => warn!(
+                    "Ignoring invalid entry in upload journal at {}: {err}",
+                    path.display()
+                ),
+            }
+        }
+
+        trace!(
+            "Upload journal opened at {} with {} chunk/s already stored",
+            path.display(),
+            acked.len()
+        );
+        let partial_line = !contents.is_empty() && !contents.ends_with('\n');
+        Ok(Self {
+            path,
+            acked,
+            partial_line,
+        })
+    }
+
+    /// Path of the file the journal is kept at.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the chunk with the given name was already stored by a previous attempt.
+    pub fn is_acked(&self, name: &XorName) -> bool {
+        self.acked.contains(name)
+    }
+
+    /// Number of chunks recorded as stored in the journal.
+    pub fn acked_count(&self) -> usize {
+        self.acked.len()
+    }
+
+    /// Removes the journal file, once the upload it recorded has completed.
+    pub async fn remove(self) -> Result<()> {
+        match fs::remove_file(&self.path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // Records the given chunks as stored, persisting them to the journal file
+    // before returning, so they are not lost if we are interrupted right after.
+    pub(crate) async fn record(&mut self, names: &[XorName]) -> Result<()> {
+        let new_names = names
+            .iter()
+            .filter(|name| !self.acked.contains(name))
+            .collect::<BTreeSet<_>>();
+        if new_names.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+
+        let mut lines = if self.partial_line {
+            "\n".to_string()
+        } else {
+            String::new()
+        };
+        lines.extend(
+            new_names
+                .iter()
+                .map(|name| format!("{}\n", hex::encode(name))),
+        );
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(lines.as_bytes()).await?;
+        file.sync_data().await?;
+
+        self.partial_line = false;
+        self.acked.extend(new_names);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadJournal;
+
+    use eyre::Result;
+    use tempfile::tempdir;
+    use tokio::fs;
+    use xor_name::{rand::random, XorName};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn journal_is_persisted_and_reloaded() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("journals").join("upload");
+
+        let mut journal = UploadJournal::open(&path).await?;
+        assert_eq!(journal.acked_count(), 0);
+        assert!(!path.exists());
+
+        let names = (0..3).map(|_| random()).collect::<Vec<XorName>>();
+        journal.record(&names[..2]).await?;
+        // recording already acked chunks doesn't duplicate entries
+        journal.record(&names[..1]).await?;
+
+        let mut journal = UploadJournal::open(&path).await?;
+        assert_eq!(journal.acked_count(), 2);
+        assert!(journal.is_acked(&names[0]));
+        assert!(journal.is_acked(&names[1]));
+        assert!(!journal.is_acked(&names[2]));
+
+        journal.record(&names[2..]).await?;
+        let journal = UploadJournal::open(&path).await?;
+        assert_eq!(journal.acked_count(), 3);
+
+        journal.remove().await?;
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn journal_ignores_partially_written_entries() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("upload");
+
+        let name: XorName = random();
+        let partial = &hex::encode(random::<XorName>())[..20];
+        fs::write(&path, format!("{}\n{partial}", hex::encode(name))).await?;
+
+        let mut journal = UploadJournal::open(&path).await?;
+        assert_eq!(journal.acked_count(), 1);
+        assert!(journal.is_acked(&name));
+
+        // new entries are not mixed up with the partially written one
+        let other_name: XorName = random();
+        journal.record(&[other_name]).await?;
+        let journal = UploadJournal::open(&path).await?;
+        assert_eq!(journal.acked_count(), 2);
+        assert!(journal.is_acked(&other_name));
+
+        Ok(())
+    }
+}
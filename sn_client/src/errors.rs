@@ -14,7 +14,7 @@ use sn_interface::{
         system::NodeMsg,
         Error as MessagingError, MsgId, MsgType,
     },
-    types::{Error as DtError, Peer},
+    types::{Error as DtError, Peer, RegisterAddress},
 };
 
 use bls::PublicKey;
@@ -201,6 +201,9 @@ pub enum Error {
         /// Unexpected msg received
         msg: MsgType,
     },
+    /// Readers can only be given to the entries of private Registers.
+    #[error("Register at {0:?} is not private, thus its entries cannot be encrypted to readers")]
+    NotAPrivateRegister(RegisterAddress),
     /// Other types errors
     #[error(transparent)]
     NetworkDataError(#[from] DtError),
@@ -280,6 +283,9 @@ pub enum Error {
         /// Number of Chunks generated
         chunked: usize,
     },
+    /// Occurs if a segment of a streamed file is itself a streamed file.
+    #[error("Segment {0} of a streamed file is not a file on its own.")]
+    InvalidStreamSegment(XorName),
     /// Occurs if a signed SAP cannot be obtained for a section key.
     #[error("A signed section authority provider was not found for section key {0:?}")]
     SignedSapNotFound(PublicKey),
@@ -291,6 +297,15 @@ pub enum Error {
         /// The key_image that was attempted to spend
         key_image: KeyImage,
     },
+    /// Occurs if a DBC spend command is refused as its key image was already spent
+    /// with a different transaction.
+    #[error(
+        "The DBC has already been spent with a different transaction, key_image: {key_image:?}"
+    )]
+    DbcAlreadySpent {
+        /// The key_image that was attempted to spend
+        key_image: KeyImage,
+    },
     /// Occurs if a section key is not found when searching the sections DAG.
     #[error("Section key {0:?} was not found in the sections DAG")]
     SectionsDagKeyNotFound(PublicKey),
@@ -314,10 +314,15 @@ impl Session {
                 }
                 QueryResponse::GetRegister(Err(_))
                 | QueryResponse::ReadRegister(Err(_))
+                | QueryResponse::GetRegisterHistory(Err(_))
+                | QueryResponse::GetRegisterEntryAncestors(Err(_))
+                | QueryResponse::ReadRegisterAsOf(Err(_))
+                | QueryResponse::GetRegisterOpLog(Err(_))
                 | QueryResponse::GetRegisterPolicy(Err(_))
                 | QueryResponse::GetRegisterOwner(Err(_))
                 | QueryResponse::GetRegisterUserPermissions(Err(_))
-                | QueryResponse::GetChunk(Err(_)) => {
+                | QueryResponse::GetChunk(Err(_))
+                | QueryResponse::GetStoreCost(Err(_)) => {
                     debug!(
                         "QueryResponse error #{discarded_responses} for {msg_id:?} received \
                         from {peer_address:?} (but may be overridden by a non-error response \
@@ -327,17 +332,19 @@ impl Session {
                     error_response = Some(*response);
                     discarded_responses += 1;
                 }
-                QueryResponse::GetRegister(Ok(ref register)) => {
+                QueryResponse::GetRegister(Ok(register)) => {
                     debug!("okay got register from {peer_address:?}");
-                    // TODO: properly merge all registers
-                    if let Some(QueryResponse::GetRegister(Ok(prior_response))) = &valid_response {
-                        if register.size() > prior_response.size() {
-                            debug!("longer register");
-                            // keep this new register
-                            valid_response = Some(*response);
+                    // replicas may have diverged, so let's merge them all into one
+                    match valid_response {
+                        Some(QueryResponse::GetRegister(Ok(ref mut merged))) => {
+                            if let Err(err) = merged.merge(register) {
+                                warn!(
+                                    "Discarding register received from {peer_address:?}: {err:?}"
+                                );
+                                discarded_responses += 1;
+                            }
                         }
-                    } else {
-                        valid_response = Some(*response);
+                        _ => valid_response = Some(QueryResponse::GetRegister(Ok(register))),
                     }
                 }
                 QueryResponse::ReadRegister(Ok(_)) => {
@@ -346,6 +353,19 @@ impl Session {
                         valid_response = Some(*response);
                     }
                 }
+                QueryResponse::GetRegisterOpLog(Ok(op_log)) => {
+                    debug!("okay got register op log from {peer_address:?}");
+                    match valid_response {
+                        Some(QueryResponse::GetRegisterOpLog(Ok(ref mut all_cmds))) => {
+                            for cmd in op_log {
+                                if !all_cmds.contains(&cmd) {
+                                    all_cmds.push(cmd);
+                                }
+                            }
+                        }
+                        _ => valid_response = Some(QueryResponse::GetRegisterOpLog(Ok(op_log))),
+                    }
+                }
                 QueryResponse::SpentProofShares(Ok(ref spentproof_set)) => {
                     debug!("okay _read_ spentproofs from {peer_address:?}");
                     // TODO: properly merge all registers
@@ -361,6 +381,31 @@ impl Session {
                         valid_response = Some(*response);
                     }
                 }
+                QueryResponse::SpentTransactions(Ok(sample)) => {
+                    debug!("okay got spent transactions from {peer_address:?}");
+                    // each Elder may have got a different sample, so let's keep them all
+                    match valid_response {
+                        Some(QueryResponse::SpentTransactions(Ok(ref mut all_spent_txs))) => {
+                            for spent_tx in sample {
+                                if !all_spent_txs.iter().any(|known| known.tx == spent_tx.tx) {
+                                    all_spent_txs.push(spent_tx);
+                                }
+                            }
+                        }
+                        _ => valid_response = Some(QueryResponse::SpentTransactions(Ok(sample))),
+                    }
+                }
+                QueryResponse::GetStoreCost(Ok(ref quote)) => {
+                    debug!("okay got store cost quote from {peer_address:?}");
+                    // each Elder signs its own quote, let's keep the cheapest one
+                    if let Some(QueryResponse::GetStoreCost(Ok(prior_quote))) = &valid_response {
+                        if quote.price < prior_quote.price {
+                            valid_response = Some(*response);
+                        }
+                    } else {
+                        valid_response = Some(*response);
+                    }
+                }
                 response => {
                     // we got a valid response
                     valid_response = Some(response)
@@ -10,14 +10,24 @@
 /// Utility functions for testing clients
 pub mod test_client;
 
+#[cfg(any(test, feature = "test-utils"))]
+/// Utility functions for obtaining DBCs in tests
+pub mod test_dbcs;
+
 #[cfg(test)]
 pub use test_client::{
     create_test_client, create_test_client_with, get_dbc_owner_from_secret_key_hex,
     try_create_test_client,
 };
 
+#[cfg(test)]
+pub use test_dbcs::reissue_dbcs;
+
 #[cfg(any(test, feature = "test-utils"))]
 pub use test_client::read_genesis_dbc_from_first_node;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub use test_dbcs::get_dbc_from_test_funds;
+
 #[cfg(test)]
 pub use sn_interface::init_logger;
@@ -64,17 +64,7 @@ pub fn get_dbc_owner_from_secret_key_hex(secret_key_hex: &str) -> Result<Owner>
 
 /// Helper utility to read the genesis DBC generated by the first node in a testnet
 pub fn read_genesis_dbc_from_first_node() -> Result<Dbc> {
-    let path = match env::var(TEST_ENV_GENESIS_DBC_PATH) {
-        Ok(dir) => PathBuf::from(&dir),
-        Err(_) => {
-            let mut path = dirs_next::home_dir()
-                .ok_or_else(|| eyre!("Failed to obtain user's home path".to_string()))?;
-
-            path.push(DEFAULT_TEST_GENESIS_DBC_PATH);
-            path
-        }
-    };
-
+    let path = genesis_dbc_path()?;
     let dbc_data = read_to_string(path.clone()).map_err(|err| {
         eyre!(
             "Failed to read genesis DBC file from '{}'. \
@@ -93,3 +83,19 @@ pub fn read_genesis_dbc_from_first_node() -> Result<Dbc> {
         )
     })
 }
+
+// Path of the genesis DBC file generated by the first node in a testnet
+pub(super) fn genesis_dbc_path() -> Result<PathBuf> {
+    let path = match env::var(TEST_ENV_GENESIS_DBC_PATH) {
+        Ok(dir) => PathBuf::from(&dir),
+        Err(_) => {
+            let mut path = dirs_next::home_dir()
+                .ok_or_else(|| eyre!("Failed to obtain user's home path".to_string()))?;
+
+            path.push(DEFAULT_TEST_GENESIS_DBC_PATH);
+            path
+        }
+    };
+
+    Ok(path)
+}
@@ -0,0 +1,242 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::test_client::{
+    create_test_client_with, genesis_dbc_path, read_genesis_dbc_from_first_node,
+};
+use crate::Client;
+
+use sn_dbc::{
+    rng, AmountSecrets, Dbc, Hash, Owner, OwnerOnce, RingCtTransaction, SpentProof,
+    SpentProofShare, Token, TransactionBuilder,
+};
+
+use eyre::{bail, eyre, Result};
+use fs2::FileExt;
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs::{self, File},
+    path::PathBuf,
+};
+
+// Number of attempts to make trying to spend inputs when reissuing DBCs
+// As the spend and query cmds are cascaded closely, there is high chance
+// that the first two query attempts could both be failed.
+// Hence the max number of attempts set to a higher value.
+const NUM_OF_DBC_REISSUE_ATTEMPTS: u8 = 5;
+
+// Name of the file, next to the genesis DBC, used to lock the test funds
+const TEST_FUNDS_LOCK_FILE_NAME: &str = "test_funds.lock";
+
+/// Returns an unspent bearer DBC with the given amount, taken from the funds of the testnet
+/// genesis DBC.
+///
+/// The genesis DBC can only be spent once, thus the first test to need funds reissues it,
+/// and its change is kept in a file next to the genesis DBC, for the following tests to
+/// reissue their DBCs from, be it in this test process or any other, and from any later run.
+pub async fn get_dbc_from_test_funds(amount: Token) -> Result<Dbc> {
+    let genesis_dbc = read_genesis_dbc_from_first_node()?;
+    let dir = genesis_dbc_path()?
+        .parent()
+        .map(PathBuf::from)
+        .ok_or_else(|| eyre!("Failed to obtain the dir of the genesis DBC file"))?;
+
+    // funds of a former testnet, with another genesis DBC, are not to be used
+    let genesis_key_image = genesis_dbc.key_image_bearer()?.to_bytes();
+    let funds_path = dir.join(format!(
+        "test_funds_{}",
+        hex::encode(&genesis_key_image[..8])
+    ));
+
+    // the lock is released when the file is dropped
+    let lock_path = dir.join(TEST_FUNDS_LOCK_FILE_NAME);
+    let _lock = tokio::task::spawn_blocking(move || -> Result<File> {
+        let file = File::create(lock_path)?;
+        file.lock_exclusive()?;
+        Ok(file)
+    })
+    .await??;
+
+    let funds_dbc = if funds_path.exists() {
+        Dbc::from_hex(fs::read_to_string(&funds_path)?.trim())?
+    } else {
+        genesis_dbc
+    };
+    let balance = funds_dbc.amount_secrets_bearer()?.amount();
+    let change = balance
+        .checked_sub(amount)
+        .ok_or_else(|| eyre!("Not enough test funds left for {amount}, only {balance}"))?;
+
+    let client = create_test_client_with(
+        None,
+        Some(Owner::from_random_secret_key(&mut rng::thread_rng())),
+        None,
+    )
+    .await?;
+    let owner = OwnerOnce::from_owner_base(
+        Owner::from_random_secret_key(&mut rng::thread_rng()),
+        &mut rng::thread_rng(),
+    );
+    let (output_dbcs, change_dbc) =
+        reissue_dbcs(&client, vec![funds_dbc], vec![(amount, owner)], change).await?;
+
+    if let Some(change_dbc) = change_dbc {
+        // the funds are replaced at once, so they're never left half written
+        let tmp_path = funds_path.with_extension("tmp");
+        fs::write(&tmp_path, change_dbc.to_hex()?)?;
+        fs::rename(tmp_path, funds_path)?;
+    }
+
+    output_dbcs
+        .into_iter()
+        .next()
+        .map(|(dbc, _, _)| dbc)
+        .ok_or_else(|| eyre!("The DBC reissued from the test funds was not found"))
+}
+
+/// Reissue DBCs and log the spent input DBCs on the network. Return the output DBC and the
+/// change DBC if there is one.
+pub async fn reissue_dbcs(
+    client: &Client,
+    input_dbcs: Vec<Dbc>,
+    outputs: Vec<(Token, OwnerOnce)>,
+    change_amount: Token,
+) -> Result<(Vec<(Dbc, OwnerOnce, AmountSecrets)>, Option<Dbc>)> {
+    // TODO: enable the use of decoys
+    let mut tx_builder = TransactionBuilder::default()
+        .set_decoys_per_input(0)
+        .set_require_all_decoys(false)
+        .add_inputs_dbc_bearer(input_dbcs.iter())?
+        .add_outputs_by_amount(outputs);
+
+    let change_owneronce =
+        OwnerOnce::from_owner_base(client.dbc_owner().clone(), &mut rng::thread_rng());
+    if change_amount.as_nano() > 0 {
+        tx_builder = tx_builder.add_output_by_amount(change_amount, change_owneronce.clone());
+    }
+
+    let spent_proofs: BTreeSet<SpentProof> = input_dbcs
+        .iter()
+        .flat_map(|dbc| dbc.spent_proofs.clone())
+        .collect();
+
+    let spent_transactions: BTreeSet<RingCtTransaction> = input_dbcs
+        .iter()
+        .flat_map(|dbc| dbc.spent_transactions.clone())
+        .collect();
+
+    let proof_key_verifier = SpentProofKeyVerifier { client };
+
+    // Let's build the output DBCs
+    let mut dbc_builder = tx_builder.build(rng::thread_rng())?;
+
+    // Spend all the input DBCs concurrently, collecting the spent proof shares for each of them
+    let spends = dbc_builder.inputs().into_iter().map(|(key_image, tx)| {
+        let spent_proofs = spent_proofs.clone();
+        let spent_transactions = spent_transactions.clone();
+        let proof_key_verifier = &proof_key_verifier;
+        async move {
+            let tx_hash = Hash::from(tx.hash());
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                client
+                    .spend_dbc(
+                        key_image,
+                        tx.clone(),
+                        spent_proofs.clone(),
+                        spent_transactions.clone(),
+                    )
+                    .await?;
+
+                let spent_proof_shares = client.spent_proof_shares(key_image).await?;
+
+                // TODO: we temporarilly filter the spent proof shares which correspond to the TX we
+                // are spending now. This is because current implementation of Spentbook allows
+                // double spents, so we may be retrieving spent proof shares for others spent TXs.
+                let shares_for_current_tx: HashSet<SpentProofShare> = spent_proof_shares
+                    .into_iter()
+                    .filter(|proof_share| proof_share.content.transaction_hash == tx_hash)
+                    .collect();
+
+                match verify_spent_proof_shares_for_tx(
+                    key_image,
+                    tx_hash,
+                    shares_for_current_tx.iter(),
+                    proof_key_verifier,
+                ) {
+                    Ok(()) => break Ok((shares_for_current_tx, tx)),
+                    Err(err) if attempts == NUM_OF_DBC_REISSUE_ATTEMPTS => {
+                        bail!(format!(
+                            "Failed to spend input, {} proof shares obtained from spentbook: {}",
+                            shares_for_current_tx.len(),
+                            err
+                        ))
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+    });
+
+    for result in futures::future::join_all(spends).await {
+        let (shares, tx) = result?;
+        dbc_builder = dbc_builder
+            .add_spent_proof_shares(shares)
+            .add_spent_transaction(tx);
+    }
+
+    // Perform verifications of input TX and spentproofs,
+    // as well as building the output DBCs.
+    let mut output_dbcs = dbc_builder.build(&proof_key_verifier)?;
+
+    let mut change_dbc = None;
+    output_dbcs.retain(|(dbc, owneronce, _)| {
+        if owneronce == &change_owneronce && change_amount.as_nano() > 0 {
+            change_dbc = Some(dbc.clone());
+            false
+        } else {
+            true
+        }
+    });
+
+    Ok((output_dbcs, change_dbc))
+}
+
+// Private helper to verify if a set of spent proof shares are valid for a given key_image and TX
+fn verify_spent_proof_shares_for_tx<'a>(
+    key_image: sn_dbc::KeyImage,
+    tx_hash: Hash,
+    proof_shares: impl Iterator<Item = &'a SpentProofShare>,
+    proof_key_verifier: &SpentProofKeyVerifier,
+) -> Result<()> {
+    SpentProof::try_from_proof_shares(key_image, tx_hash, proof_shares)
+        .and_then(|spent_proof| spent_proof.verify(tx_hash, proof_key_verifier))?;
+
+    Ok(())
+}
+
+/// Verifier required by test to check a SpentProof
+/// is signed by known sections keys.
+struct SpentProofKeyVerifier<'a> {
+    client: &'a Client,
+}
+
+impl sn_dbc::SpentProofKeyVerifier for SpentProofKeyVerifier<'_> {
+    type Error = crate::Error;
+
+    // Called by test when it needs to verify a SpentProof is signed by a known key,
+    // we check if the key is any of the network sections keys we are aware of
+    fn verify_known_key(&self, key: &sn_dbc::PublicKey) -> crate::Result<()> {
+        if !futures::executor::block_on(self.client.is_known_section_key(key)) {
+            Err(crate::Error::SectionsDagKeyNotFound(*key))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -387,6 +387,36 @@ pub mod util {
             .map_err(|_| eyre!("Failed to parse output of `safe wallet create` (Perhaps RUST_LOG is polluting output?): {}", output))
     }
 
+    pub fn parse_multimap_create_output(output: &str) -> Result<String> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe multimap create` (Perhaps RUST_LOG is polluting output?): {}", output))
+    }
+
+    pub fn parse_multimap_insert_output(output: &str) -> Result<(String, String)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe multimap insert` (Perhaps RUST_LOG is polluting output?): {}", output))
+    }
+
+    pub fn parse_multimap_show_output(output: &str) -> Result<(String, Vec<serde_json::Value>)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe multimap show` (Perhaps RUST_LOG is polluting output?): {}", output))
+    }
+
+    pub fn parse_register_create_output(output: &str) -> Result<String> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe register create` (Perhaps RUST_LOG is polluting output?): {}", output))
+    }
+
+    pub fn parse_register_read_output(output: &str) -> Result<(String, Vec<(String, String)>)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe register read` (Perhaps RUST_LOG is polluting output?): {}", output))
+    }
+
+    pub fn parse_register_write_output(output: &str) -> Result<(String, String)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe register write` (Perhaps RUST_LOG is polluting output?): {}", output))
+    }
+
     pub fn parse_xorurl_output(output: &str) -> Result<Vec<(String, String)>> {
         serde_json::from_str(output)
             .map_err(|_| eyre!("Failed to parse output of `safe xorurl` (Perhaps RUST_LOG is polluting output?): {}", output))
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{Error, RegisterCmd, SpentbookCmd};
+use super::{Error, PaymentProof, RegisterCmd, SpentbookCmd};
 use crate::{
     messaging::data::CmdResponse,
     types::{Chunk, DataAddress},
@@ -27,7 +27,12 @@ pub enum DataCmd {
     /// [`Chunk`] write operation.
     ///
     /// [`Chunk`]: crate::types::Chunk
-    StoreChunk(Chunk),
+    StoreChunk {
+        /// The Chunk to be stored.
+        chunk: Chunk,
+        /// Proof of the payment made for storing the Chunk, if any.
+        payment: Option<PaymentProof>,
+    },
     #[cfg(feature = "registers")]
     /// [`Register`] write operation.
     ///
@@ -42,7 +47,7 @@ impl DataCmd {
     /// Returns the address of the corresponding variant.
     pub fn address(&self) -> DataAddress {
         match self {
-            Self::StoreChunk(chunk) => DataAddress::Bytes(*chunk.address()),
+            Self::StoreChunk { chunk, .. } => DataAddress::Bytes(*chunk.address()),
             Self::Register(register_cmd) => DataAddress::Register(register_cmd.dst_address()),
             Self::Spentbook(spentbook_cmd) => DataAddress::Spentbook(spentbook_cmd.dst_address()),
         }
@@ -53,7 +58,7 @@ impl DataCmd {
         use DataCmd::*;
         match self {
             #[cfg(feature = "chunks")]
-            StoreChunk(c) => *c.name(),
+            StoreChunk { chunk, .. } => *chunk.name(),
             #[cfg(feature = "registers")]
             Register(c) => c.name(), // TODO: c.dst_id(), as to not co-locate private and public and different tags of same name.
             #[cfg(feature = "spentbook")]
@@ -67,7 +72,7 @@ impl DataCmd {
         use DataCmd::*;
         match self {
             #[cfg(feature = "chunks")]
-            StoreChunk(_) => CmdResponse::StoreChunk(Err(error)),
+            StoreChunk { .. } => CmdResponse::StoreChunk(Err(error)),
             #[cfg(feature = "registers")]
             Register(c) => c.to_error_response(error),
             #[cfg(feature = "spentbook")]
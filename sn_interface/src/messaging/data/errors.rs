@@ -11,6 +11,7 @@ use crate::types::{
     DataAddress,
 };
 use serde::{Deserialize, Serialize};
+use sn_dbc::KeyImage;
 use std::result;
 use thiserror::Error;
 use xor_name::Prefix;
@@ -57,6 +58,13 @@ pub enum Error {
     /// the section that signed one of the input spent proofs.
     #[error("Spent proof is signed by section key {0:?} that is unknown to the current section")]
     SpentProofUnknownSectionKey(bls::PublicKey),
+    /// A DBC spend request was refused since its key image has already been spent
+    /// with a different transaction, i.e. it'd be a double spend.
+    #[error("Key image has already been spent with a different transaction: {0:?}")]
+    DoubleSpendAttempt(KeyImage),
+    /// The payment attached to a cmd is not valid for storing its data.
+    #[error("Invalid payment: {0}")]
+    InvalidPayment(String),
     #[error("Trying to produce a CmdResponse error for a data type not resulting from a cmd")]
     NoCorrespondingCmdError,
 }
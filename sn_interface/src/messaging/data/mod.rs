@@ -11,20 +11,28 @@
 mod cmd;
 mod data_exchange;
 mod errors;
+mod payment;
 mod query;
 mod register;
+mod register_legacy;
 mod spentbook;
 
 pub use self::{
     cmd::DataCmd,
     data_exchange::{MetadataExchange, StorageLevel},
     errors::{Error, Result},
+    payment::{PaymentProof, StoreCostQuote},
     query::{DataQuery, DataQueryVariant},
     register::{
         CreateRegister, EditRegister, RegisterCmd, RegisterQuery, SignedRegisterCreate,
-        SignedRegisterEdit,
+        SignedRegisterEdit, SignedRegisterPolicyUpdate, UpdateRegisterPolicy,
     },
-    spentbook::{SpentbookCmd, SpentbookQuery},
+    register_legacy::{
+        LegacyCreateRegister, LegacyEditRegister, LegacyPermissions, LegacyPolicy,
+        LegacyRegisterAddress, LegacyRegisterCmd, LegacyRegisterOp, LegacySignedRegisterCreate,
+        LegacySignedRegisterEdit,
+    },
+    spentbook::{SpentTransaction, SpentbookCmd, SpentbookQuery},
 };
 
 use crate::network_knowledge::SectionTreeUpdate;
@@ -177,11 +185,28 @@ pub enum QueryResponse {
     GetRegisterPolicy(Result<Policy>),
     /// Response to [`RegisterQuery::GetUserPermissions`].
     GetRegisterUserPermissions(Result<Permissions>),
+    /// Response to [`RegisterQuery::GetEntryAncestors`].
+    GetRegisterEntryAncestors(Result<Vec<(EntryHash, Entry)>>),
+    /// Response to [`RegisterQuery::GetHistory`].
+    GetRegisterHistory(Result<Vec<(EntryHash, Entry)>>),
+    /// Response to [`RegisterQuery::ReadAsOf`].
+    ReadRegisterAsOf(Result<BTreeSet<(EntryHash, Entry)>>),
+    /// Response to [`RegisterQuery::GetOpLog`].
+    GetRegisterOpLog(Result<Vec<RegisterCmd>>),
     //
     // ===== Spentbook Data =====
     //
     /// Response to [`SpentbookQuery::SpentProofShares`].
     SpentProofShares(Result<Vec<SpentProofShare>>),
+    /// Response to [`SpentbookQuery::SpentTransactions`].
+    SpentTransactions(Result<Vec<SpentTransaction>>),
+    //
+    // ===== Payments =====
+    //
+    /// Response to [`GetStoreCost`].
+    ///
+    /// [`GetStoreCost`]: crate::messaging::data::DataQueryVariant::GetStoreCost
+    GetStoreCost(Result<StoreCostQuote>),
 }
 
 impl QueryResponse {
@@ -197,7 +222,13 @@ impl QueryResponse {
                 | ReadRegister(Ok(_))
                 | GetRegisterPolicy(Ok(_))
                 | GetRegisterUserPermissions(Ok(_))
+                | GetRegisterEntryAncestors(Ok(_))
+                | GetRegisterHistory(Ok(_))
+                | ReadRegisterAsOf(Ok(_))
+                | GetRegisterOpLog(Ok(_))
                 | SpentProofShares(Ok(_))
+                | SpentTransactions(Ok(_))
+                | GetStoreCost(Ok(_))
         )
     }
 
@@ -217,7 +248,14 @@ impl QueryResponse {
                 | GetRegisterPolicy(Err(Error::NoSuchUser(_)))
                 | GetRegisterUserPermissions(Err(Error::DataNotFound(_)))
                 | GetRegisterUserPermissions(Err(Error::NoSuchUser(_)))
+                | GetRegisterEntryAncestors(Err(Error::DataNotFound(_)))
+                | GetRegisterEntryAncestors(Err(Error::NoSuchEntry(_)))
+                | GetRegisterHistory(Err(Error::DataNotFound(_)))
+                | ReadRegisterAsOf(Err(Error::DataNotFound(_)))
+                | ReadRegisterAsOf(Err(Error::NoSuchEntry(_)))
+                | GetRegisterOpLog(Err(Error::DataNotFound(_)))
                 | SpentProofShares(Err(Error::DataNotFound(_)))
+                | SpentTransactions(Err(Error::DataNotFound(_)))
         )
     }
 }
@@ -238,6 +276,8 @@ pub enum CmdResponse {
     CreateRegister(Result<()>),
     /// Response to RegisterCmd::Edit.
     EditRegister(Result<()>),
+    /// Response to RegisterCmd::UpdatePolicy.
+    UpdateRegisterPolicy(Result<()>),
     //
     // ===== Spentbook Data =====
     //
@@ -256,7 +296,10 @@ impl CmdResponse {
             ReplicatedData::RegisterWrite(RegisterCmd::Edit { .. }) => {
                 CmdResponse::EditRegister(Ok(()))
             }
-            ReplicatedData::SpentbookWrite(_) => CmdResponse::SpendKey(Ok(())),
+            ReplicatedData::RegisterWrite(RegisterCmd::UpdatePolicy { .. }) => {
+                CmdResponse::UpdateRegisterPolicy(Ok(()))
+            }
+            ReplicatedData::SpentbookWrite(..) => CmdResponse::SpendKey(Ok(())),
             ReplicatedData::RegisterLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `RegisterLog` is not resulting from a cmd.
             ReplicatedData::SpentbookLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `SpentbookLog` is not resulting from a cmd.
         };
@@ -273,7 +316,10 @@ impl CmdResponse {
             ReplicatedData::RegisterWrite(RegisterCmd::Edit { .. }) => {
                 CmdResponse::EditRegister(Err(err))
             }
-            ReplicatedData::SpentbookWrite(_) => CmdResponse::SpendKey(Err(err)),
+            ReplicatedData::RegisterWrite(RegisterCmd::UpdatePolicy { .. }) => {
+                CmdResponse::UpdateRegisterPolicy(Err(err))
+            }
+            ReplicatedData::SpentbookWrite(..) => CmdResponse::SpendKey(Err(err)),
             ReplicatedData::RegisterLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `RegisterLog` is not resulting from a cmd.
             ReplicatedData::SpentbookLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `SpentbookLog` is not resulting from a cmd.
         };
@@ -292,6 +338,7 @@ impl CmdResponse {
             StoreChunk(result)
             | CreateRegister(result)
             | EditRegister(result)
+            | UpdateRegisterPolicy(result)
             | SpendKey(result) => result,
         }
     }
@@ -415,7 +462,10 @@ mod tests {
 
         for chunk in chunks {
             let (original_msg, serialised_cmd) = {
-                let msg = ClientMsg::Cmd(DataCmd::StoreChunk(chunk));
+                let msg = ClientMsg::Cmd(DataCmd::StoreChunk {
+                    chunk,
+                    payment: None,
+                });
                 let bytes = WireMsg::serialize_msg_payload(&msg)?;
                 (msg, bytes)
             };
@@ -0,0 +1,305 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Error, Result};
+use crate::messaging::SectionSig;
+use crate::types::{keys::ed25519, DataAddress, PublicKey, Signature};
+
+use ed25519_dalek::Keypair;
+use serde::{Deserialize, Serialize};
+use sn_dbc::{
+    bls_ringct::{bls_bulletproofs::PedersenGens, group::Curve},
+    AmountSecrets, Dbc, Owner, OwnerOnce, RevealedCommitment, RingCtTransaction, SpentProof, Token,
+};
+use std::collections::BTreeSet;
+use tiny_keccak::{Hasher, Sha3};
+
+/// The price quoted by an Elder for storing some data in its section.
+///
+/// The quote is signed by the Elder, and it's only honoured by the section
+/// while the Elder which issued it is still one of its Elders.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct StoreCostQuote {
+    /// Address of the data to be stored.
+    pub address: DataAddress,
+    /// Size of the data to be stored, in bytes.
+    pub bytes: usize,
+    /// Price for storing the data.
+    pub price: Token,
+    /// Section key the payment is to be made to.
+    pub payee: bls::PublicKey,
+    /// Elder which issued the quote.
+    pub elder: PublicKey,
+    /// Elder's signature over the quote.
+    pub signature: Signature,
+}
+
+impl StoreCostQuote {
+    /// Creates a quote signed with the given Elder's keypair.
+    #[allow(clippy::result_large_err)]
+    pub fn new(
+        address: DataAddress,
+        bytes: usize,
+        price: Token,
+        payee: bls::PublicKey,
+        keypair: &Keypair,
+    ) -> Result<Self> {
+        let bytes_to_sign = Self::bytes_to_sign(&address, bytes, price, &payee)?;
+        let signature = ed25519::sign(&bytes_to_sign, keypair);
+
+        Ok(Self {
+            address,
+            bytes,
+            price,
+            payee,
+            elder: PublicKey::Ed25519(keypair.public),
+            signature: Signature::Ed25519(signature),
+        })
+    }
+
+    /// Verifies the quote was signed by the Elder it claims to be issued by.
+    #[allow(clippy::result_large_err)]
+    pub fn verify(&self) -> Result<()> {
+        let bytes = Self::bytes_to_sign(&self.address, self.bytes, self.price, &self.payee)?;
+        self.elder
+            .verify(&self.signature, bytes)
+            .map_err(|_| Error::InvalidPayment("Store cost quote signature is invalid".to_string()))
+    }
+
+    /// One-time owner the payment for this quote is to be made to.
+    ///
+    /// It's derived from the section key using the hash of the quote, so a payment
+    /// made for a quote cannot be presented for storing any other data.
+    #[allow(clippy::result_large_err)]
+    pub fn payment_owner(&self) -> Result<OwnerOnce> {
+        let bytes = bincode::serialize(self).map_err(|err| {
+            Error::InvalidOperation(format!("Failed to serialise store cost quote: {err}"))
+        })?;
+        let mut hasher = Sha3::v256();
+        let mut derivation_index = [0; 32];
+        hasher.update(&bytes);
+        hasher.finalize(&mut derivation_index);
+
+        Ok(OwnerOnce {
+            owner_base: Owner::from(self.payee),
+            derivation_index,
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn bytes_to_sign(
+        address: &DataAddress,
+        bytes: usize,
+        price: Token,
+        payee: &bls::PublicKey,
+    ) -> Result<Vec<u8>> {
+        bincode::serialize(&(address, bytes, price, payee)).map_err(|err| {
+            Error::InvalidOperation(format!("Failed to serialise store cost quote: {err}"))
+        })
+    }
+}
+
+/// Proof of the payment made for a [`StoreCostQuote`], to be attached to the cmd storing the data.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PaymentProof {
+    /// The quote which was paid.
+    pub quote: StoreCostQuote,
+    /// Transaction with an output to the quote's payment owner.
+    pub tx: RingCtTransaction,
+    /// Spent proofs of all the inputs of the transaction.
+    pub spent_proofs: BTreeSet<SpentProof>,
+    /// Amount secrets of the payment output, revealing the amount paid.
+    pub amount_secrets: AmountSecrets,
+}
+
+impl PaymentProof {
+    /// Creates the proof of payment for a quote out of the DBC
+    /// which was reissued to the quote's payment owner.
+    pub fn new(quote: StoreCostQuote, dbc: &Dbc, amount_secrets: AmountSecrets) -> Self {
+        Self {
+            quote,
+            tx: dbc.transaction.clone(),
+            spent_proofs: dbc.spent_proofs.clone(),
+            amount_secrets,
+        }
+    }
+
+    /// Section signature over the spend of the first input of the payment transaction,
+    /// i.e. the network's evidence that the payment was made.
+    ///
+    /// Note this doesn't verify the signature, nor that the spent proof is for the transaction.
+    pub fn section_sig(&self) -> Option<SectionSig> {
+        self.spent_proofs.iter().next().map(|proof| SectionSig {
+            public_key: proof.spentbook_pub_key,
+            signature: proof.spentbook_sig.clone(),
+        })
+    }
+
+    /// Returns the amount paid to the quote's payment owner,
+    /// once checked it matches the amount committed to in the transaction.
+    ///
+    /// Note this doesn't verify the transaction itself nor its spent proofs,
+    /// which requires knowledge of the sections which signed them.
+    #[allow(clippy::result_large_err)]
+    pub fn paid_amount(&self) -> Result<Token> {
+        let owner = self.quote.payment_owner()?.as_owner().public_key();
+        let output = self
+            .tx
+            .outputs
+            .iter()
+            .find(|output| owner.eq(output.public_key()))
+            .ok_or_else(|| {
+                Error::InvalidPayment(
+                    "Transaction has no output to the quote's payment owner".to_string(),
+                )
+            })?;
+
+        let revealed_commitment = RevealedCommitment::from(self.amount_secrets.clone());
+        let commitment = revealed_commitment
+            .commit(&PedersenGens::default())
+            .to_affine();
+        if commitment != output.commitment() {
+            return Err(Error::InvalidPayment(
+                "Amount secrets don't match the payment output commitment".to_string(),
+            ));
+        }
+
+        Ok(self.amount_secrets.amount())
+    }
+}
+
+// `AmountSecrets` doesn't implement `Eq`, so we compare them by their serialised form.
+impl PartialEq for PaymentProof {
+    fn eq(&self, other: &Self) -> bool {
+        self.quote == other.quote
+            && self.tx == other.tx
+            && self.spent_proofs == other.spent_proofs
+            && self.amount_secrets.to_bytes() == other.amount_secrets.to_bytes()
+    }
+}
+
+impl Eq for PaymentProof {}
+
+#[cfg(test)]
+mod tests {
+    use super::{PaymentProof, StoreCostQuote};
+    use crate::{
+        dbcs::gen_genesis_dbc,
+        messaging::data::Error,
+        types::{ChunkAddress, DataAddress, Keypair},
+    };
+
+    use eyre::{eyre, Result};
+    use sn_dbc::{
+        bls_ringct::{bls_bulletproofs::PedersenGens, group::Curve},
+        rng, Hash, IndexedSignatureShare, OwnerOnce, RevealedCommitment, SpentProofContent,
+        SpentProofShare, Token, TransactionBuilder,
+    };
+    use xor_name::rand::random;
+
+    fn new_quote(price: Token) -> Result<StoreCostQuote> {
+        let keypair = match Keypair::new_ed25519() {
+            Keypair::Ed25519(keypair) => keypair,
+            _ => return Err(eyre!("Unexpected keypair type")),
+        };
+        let address = DataAddress::Bytes(ChunkAddress(random()));
+        let payee = bls::SecretKey::random().public_key();
+        Ok(StoreCostQuote::new(
+            address,
+            1024,
+            price,
+            payee,
+            keypair.as_ref(),
+        )?)
+    }
+
+    // Reissues a new genesis DBC, paying the given amount to the quote's payment owner.
+    fn pay(quote: &StoreCostQuote, amount: Token) -> Result<PaymentProof> {
+        let sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+        let payment_owner = quote.payment_owner()?;
+        let change_owner =
+            OwnerOnce::from_owner_base(genesis_dbc.owner_base().clone(), &mut rng::thread_rng());
+
+        let tx_builder = TransactionBuilder::default()
+            .set_decoys_per_input(0)
+            .set_require_all_decoys(false)
+            .add_input_dbc_bearer(&genesis_dbc)?;
+        let change = tx_builder
+            .inputs_amount_sum()
+            .checked_sub(amount)
+            .ok_or_else(|| eyre!("Not enough funds"))?;
+        let mut dbc_builder = tx_builder
+            .add_output_by_amount(amount, payment_owner.clone())
+            .add_output_by_amount(change, change_owner)
+            .build(rng::thread_rng())?;
+
+        let genesis_commitment = RevealedCommitment::from(genesis_dbc.amount_secrets_bearer()?)
+            .commit(&PedersenGens::default())
+            .to_affine();
+        for (key_image, tx) in dbc_builder.inputs() {
+            let content = SpentProofContent {
+                key_image,
+                transaction_hash: Hash::from(tx.hash()),
+                public_commitments: vec![genesis_commitment],
+            };
+            let sig_share = sk_set.secret_key_share(0).sign(content.hash().as_ref());
+            dbc_builder = dbc_builder
+                .add_spent_proof_share(SpentProofShare {
+                    content,
+                    spentbook_pks: sk_set.public_keys(),
+                    spentbook_sig_share: IndexedSignatureShare::new(0, sig_share),
+                })
+                .add_spent_transaction(tx);
+        }
+
+        let (dbc, _, amount_secrets) = dbc_builder
+            .build_without_verifying()?
+            .into_iter()
+            .find(|(_, owner, _)| owner == &payment_owner)
+            .ok_or_else(|| eyre!("Payment output not found"))?;
+
+        Ok(PaymentProof::new(quote.clone(), &dbc, amount_secrets))
+    }
+
+    #[test]
+    fn quote_signature_is_verified() -> Result<()> {
+        let quote = new_quote(Token::from_nano(1_000))?;
+        quote.verify()?;
+
+        let mut tampered = quote;
+        tampered.price = Token::from_nano(1);
+        assert!(matches!(tampered.verify(), Err(Error::InvalidPayment(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn paid_amount_is_revealed() -> Result<()> {
+        let quote = new_quote(Token::from_nano(1_000))?;
+        let payment = pay(&quote, Token::from_nano(1_500))?;
+
+        assert_eq!(payment.paid_amount()?, Token::from_nano(1_500));
+
+        Ok(())
+    }
+
+    #[test]
+    fn payment_cannot_be_used_for_another_quote() -> Result<()> {
+        let quote = new_quote(Token::from_nano(1_000))?;
+        let mut payment = pay(&quote, Token::from_nano(1_000))?;
+
+        payment.quote = new_quote(Token::from_nano(1_000))?;
+        assert!(matches!(
+            payment.paid_amount(),
+            Err(Error::InvalidPayment(_))
+        ));
+
+        Ok(())
+    }
+}
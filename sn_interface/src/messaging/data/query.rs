@@ -45,6 +45,18 @@ pub enum DataQueryVariant {
     #[cfg(feature = "spentbook")]
     /// Spentbook read operation.
     Spentbook(SpentbookQuery),
+    /// Retrieve a quote of the cost of storing some data, which is provided
+    /// by the Elders of the section responsible for the data.
+    ///
+    /// This should eventually lead to a [`GetStoreCost`] response.
+    ///
+    /// [`GetStoreCost`]: QueryResponse::GetStoreCost
+    GetStoreCost {
+        /// Address of the data to be stored.
+        address: DataAddress,
+        /// Size of the data to be stored, in bytes.
+        bytes: usize,
+    },
 }
 
 impl DataQueryVariant {
@@ -59,6 +71,7 @@ impl DataQueryVariant {
             Register(q) => q.to_error_response(error),
             #[cfg(feature = "spentbook")]
             Spentbook(q) => q.to_error_response(error),
+            GetStoreCost { .. } => QueryResponse::GetStoreCost(Err(error)),
         }
     }
 
@@ -72,6 +85,7 @@ impl DataQueryVariant {
             Register(q) => q.dst_name(),
             #[cfg(feature = "spentbook")]
             Spentbook(q) => q.dst_name(),
+            GetStoreCost { address, .. } => *address.name(),
         }
     }
 
@@ -86,6 +100,7 @@ impl DataQueryVariant {
             Self::Spentbook(read) => {
                 DataAddress::Spentbook(SpentbookAddress::new(*read.dst_address().name()))
             }
+            Self::GetStoreCost { address, .. } => *address,
         }
     }
 }
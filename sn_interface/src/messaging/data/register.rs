@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{CmdResponse, Error, QueryResponse};
+use super::{CmdResponse, Error, PaymentProof, QueryResponse};
 
 use crate::messaging::{ClientAuth, SectionSig};
 #[allow(unused_imports)] // needed by rustdocs links
@@ -69,6 +69,45 @@ pub enum RegisterQuery {
     ///
     /// [`GetRegisterOwner`]: QueryResponse::GetRegisterOwner
     GetOwner(RegisterAddress),
+    /// Retrieve all the entries the given entry of a [`Register`] was written on top of,
+    /// either directly or transitively, in causal order.
+    ///
+    /// This should eventually lead to a [`GetRegisterEntryAncestors`] response.
+    ///
+    /// [`GetRegisterEntryAncestors`]: QueryResponse::GetRegisterEntryAncestors
+    GetEntryAncestors {
+        /// Register address.
+        address: RegisterAddress,
+        /// The hash of the entry.
+        hash: EntryHash,
+    },
+    /// Retrieve all the entries of the [`Register`] at the given address, in causal order.
+    ///
+    /// This should eventually lead to a [`GetRegisterHistory`] response.
+    ///
+    /// [`GetRegisterHistory`]: QueryResponse::GetRegisterHistory
+    GetHistory(RegisterAddress),
+    /// Retrieve the entries which were the current ones in the [`Register`]
+    /// at the given address as of the given entry, i.e. the entry along with any concurrent
+    /// heads which come before it in the history of the Register.
+    ///
+    /// This should eventually lead to a [`ReadRegisterAsOf`] response.
+    ///
+    /// [`ReadRegisterAsOf`]: QueryResponse::ReadRegisterAsOf
+    ReadAsOf {
+        /// Register address.
+        address: RegisterAddress,
+        /// The hash of the entry.
+        hash: EntryHash,
+    },
+    /// Retrieve all the cmds stored in the log of the [`Register`] at the given address,
+    /// including the one creating it, so they can be republished to holders which are
+    /// lagging behind or missing the Register altogether (read-repair).
+    ///
+    /// This should eventually lead to a [`GetRegisterOpLog`] response.
+    ///
+    /// [`GetRegisterOpLog`]: QueryResponse::GetRegisterOpLog
+    GetOpLog(RegisterAddress),
 }
 
 /// A [`Register`] cmd that is stored in a log on Adults.
@@ -79,12 +118,20 @@ pub enum RegisterCmd {
     Create {
         /// The user signed op.
         cmd: SignedRegisterCreate,
-        /// Section signature over the operation,
-        /// verifying that it was paid for.
-        section_sig: SectionSig,
+        /// Section signature over the spend which paid for the Register, as found among the
+        /// spent proofs of its payment (see [`PaymentProof::section_sig`]).
+        ///
+        /// It's kept by Adults along with the Register, as evidence that it was paid for.
+        section_sig: Option<SectionSig>,
+        /// Proof of the payment made for creating the Register, if any.
+        ///
+        /// It's verified by the Elders, and not kept with the Register by Adults.
+        payment: Option<PaymentProof>,
     },
     /// Edit the [`Register`].
     Edit(SignedRegisterEdit),
+    /// Update the policy of the [`Register`], i.e. its owner and users' permissions.
+    UpdatePolicy(SignedRegisterPolicyUpdate),
 }
 
 impl RegisterCmd {
@@ -94,6 +141,7 @@ impl RegisterCmd {
         match self {
             Self::Create { .. } => CmdResponse::CreateRegister(Err(error)),
             Self::Edit(_) => CmdResponse::EditRegister(Err(error)),
+            Self::UpdatePolicy(_) => CmdResponse::UpdateRegisterPolicy(Err(error)),
         }
     }
 }
@@ -108,6 +156,8 @@ pub struct CreateRegister {
     pub tag: u64,
     /// The policy of the [`Register`].
     pub policy: Policy,
+    /// Whether the [`Register`] is private, i.e. its entries are encrypted to its readers' keys.
+    pub private: bool,
 }
 
 impl CreateRegister {
@@ -121,6 +171,7 @@ impl CreateRegister {
         RegisterAddress {
             name: self.name,
             tag: self.tag,
+            private: self.private,
         }
     }
 }
@@ -132,6 +183,21 @@ pub struct EditRegister {
     pub address: RegisterAddress,
     /// The operation to perform.
     pub edit: RegisterOp<Entry>,
+    /// The version of the [`Register`]'s policy the edit was authored under,
+    /// i.e. the policy it's checked against when replicated.
+    pub policy_version: u64,
+}
+
+/// Replace the policy of a [`Register`].
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct UpdateRegisterPolicy {
+    /// The address of the [`Register`] to update.
+    pub address: RegisterAddress,
+    /// The version of the new policy, which must be greater than the current one's
+    /// for the update to take effect.
+    pub version: u64,
+    /// The new policy of the [`Register`].
+    pub policy: Policy,
 }
 
 /// A signed cmd to create a [`Register`].
@@ -156,6 +222,17 @@ pub struct SignedRegisterEdit {
     pub auth: ClientAuth,
 }
 
+/// A [`Register`] policy update signed by the requester.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SignedRegisterPolicyUpdate {
+    /// The operation to perform.
+    pub op: UpdateRegisterPolicy,
+    /// A signature carrying authority to perform the operation.
+    ///
+    /// This will be verified against the register's owner and permissions.
+    pub auth: ClientAuth,
+}
+
 impl SignedRegisterCreate {
     /// Returns the dst address of the register.
     pub fn dst_address(&self) -> RegisterAddress {
@@ -170,6 +247,13 @@ impl SignedRegisterEdit {
     }
 }
 
+impl SignedRegisterPolicyUpdate {
+    /// Returns the dst address of the register.
+    pub fn dst_address(&self) -> RegisterAddress {
+        self.op.address
+    }
+}
+
 impl RegisterQuery {
     /// Creates a Response containing an error, with the Response variant corresponding to the
     /// Request variant.
@@ -183,6 +267,10 @@ impl RegisterQuery {
             }
             Self::GetEntry { .. } => QueryResponse::GetRegisterEntry(Err(error)),
             Self::GetOwner(_) => QueryResponse::GetRegisterOwner(Err(error)),
+            Self::GetEntryAncestors { .. } => QueryResponse::GetRegisterEntryAncestors(Err(error)),
+            Self::GetHistory(_) => QueryResponse::GetRegisterHistory(Err(error)),
+            Self::ReadAsOf { .. } => QueryResponse::ReadRegisterAsOf(Err(error)),
+            Self::GetOpLog(_) => QueryResponse::GetRegisterOpLog(Err(error)),
         }
     }
 
@@ -194,7 +282,11 @@ impl RegisterQuery {
             | Self::GetPolicy(ref address)
             | Self::GetUserPermissions { ref address, .. }
             | Self::GetEntry { ref address, .. }
-            | Self::GetOwner(ref address) => *address,
+            | Self::GetOwner(ref address)
+            | Self::GetEntryAncestors { ref address, .. }
+            | Self::GetHistory(ref address)
+            | Self::ReadAsOf { ref address, .. }
+            | Self::GetOpLog(ref address) => *address,
         }
     }
 
@@ -206,7 +298,11 @@ impl RegisterQuery {
             | Self::GetPolicy(ref address)
             | Self::GetUserPermissions { ref address, .. }
             | Self::GetEntry { ref address, .. }
-            | Self::GetOwner(ref address) => *address.name(),
+            | Self::GetOwner(ref address)
+            | Self::GetEntryAncestors { ref address, .. }
+            | Self::GetHistory(ref address)
+            | Self::ReadAsOf { ref address, .. }
+            | Self::GetOpLog(ref address) => *address.name(),
         }
     }
 }
@@ -223,6 +319,7 @@ impl RegisterCmd {
         match self {
             Self::Create { cmd, .. } => cmd.dst_address(),
             Self::Edit(cmd) => cmd.dst_address(),
+            Self::UpdatePolicy(cmd) => cmd.dst_address(),
         }
     }
 
@@ -0,0 +1,356 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Layout [`RegisterCmd`]s were serialised with before private Registers, policy management
+//! permissions and payment proofs were added to them.
+//!
+//! Register logs stored with this layout are read back through these types, and clients which
+//! signed cmds in this layout still have their signatures verified against it. Any cmd without
+//! the new features can be represented in this layout, and it's then identified by it, so
+//! existing public Registers keep the very same ids they were stored with.
+
+use super::{CreateRegister, EditRegister, RegisterCmd, SignedRegisterCreate, SignedRegisterEdit};
+
+use crate::messaging::{ClientAuth, SectionSig};
+use crate::types::{
+    register::{Action, Entry, Permissions, Policy, RegisterOp, User},
+    RegisterAddress, Signature,
+};
+
+use crdts::merkle_reg::Node;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use xor_name::XorName;
+
+/// A [`RegisterCmd`] in the layout it was serialised with before the format was versioned.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub enum LegacyRegisterCmd {
+    /// Create a new Register on the network.
+    Create {
+        /// The user signed op.
+        cmd: LegacySignedRegisterCreate,
+        /// Section signature over the operation.
+        section_sig: SectionSig,
+    },
+    /// Edit the Register.
+    Edit(LegacySignedRegisterEdit),
+}
+
+/// A [`SignedRegisterCreate`] in its legacy layout.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LegacySignedRegisterCreate {
+    /// Create a Register.
+    pub op: LegacyCreateRegister,
+    /// A signature carrying authority to perform the operation.
+    pub auth: ClientAuth,
+}
+
+/// A [`SignedRegisterEdit`] in its legacy layout.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LegacySignedRegisterEdit {
+    /// The operation to perform.
+    pub op: LegacyEditRegister,
+    /// A signature carrying authority to perform the operation.
+    pub auth: ClientAuth,
+}
+
+/// A [`CreateRegister`] in its legacy layout, i.e. without the `private` flag.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LegacyCreateRegister {
+    /// The name of the Register.
+    pub name: XorName,
+    /// The tag on the Register.
+    pub tag: u64,
+    /// The policy of the Register.
+    pub policy: LegacyPolicy,
+}
+
+/// An [`EditRegister`] in its legacy layout.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LegacyEditRegister {
+    /// The address of the Register to edit.
+    pub address: LegacyRegisterAddress,
+    /// The operation to perform.
+    pub edit: LegacyRegisterOp,
+}
+
+/// A [`RegisterOp`] in its legacy layout.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LegacyRegisterOp {
+    /// Address of the Register.
+    pub address: LegacyRegisterAddress,
+    /// The data operation to apply.
+    pub crdt_op: Node<Entry>,
+    /// The user who generated the operation.
+    pub source: User,
+    /// The signature of source on the crdt_top.
+    pub signature: Option<Signature>,
+}
+
+/// A [`RegisterAddress`] in its legacy layout, i.e. of a public Register.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LegacyRegisterAddress {
+    /// Name.
+    pub name: XorName,
+    /// Tag.
+    pub tag: u64,
+}
+
+/// A [`Policy`] in its legacy layout, i.e. with write permissions only.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LegacyPolicy {
+    /// Owner of the Register.
+    pub owner: User,
+    /// Map of users to their permission to write.
+    pub permissions: BTreeMap<User, LegacyPermissions>,
+}
+
+/// A [`Permissions`] set in its legacy layout, i.e. with the permission to write only.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LegacyPermissions {
+    /// `Some(true)` if the user can write.
+    pub write: Option<bool>,
+}
+
+impl LegacyRegisterCmd {
+    /// Returns the cmd in its legacy layout, if it can be represented in it.
+    pub fn from_cmd(cmd: &RegisterCmd) -> Option<Self> {
+        match cmd {
+            RegisterCmd::Create {
+                cmd,
+                section_sig: Some(section_sig),
+                payment: None,
+            } => Some(Self::Create {
+                cmd: LegacySignedRegisterCreate {
+                    op: LegacyCreateRegister::from_op(&cmd.op)?,
+                    auth: cmd.auth.clone(),
+                },
+                section_sig: section_sig.clone(),
+            }),
+            RegisterCmd::Edit(cmd) => Some(Self::Edit(LegacySignedRegisterEdit {
+                op: LegacyEditRegister::from_op(&cmd.op)?,
+                auth: cmd.auth.clone(),
+            })),
+            RegisterCmd::Create { .. } | RegisterCmd::UpdatePolicy(_) => None,
+        }
+    }
+}
+
+impl From<LegacyRegisterCmd> for RegisterCmd {
+    fn from(cmd: LegacyRegisterCmd) -> Self {
+        match cmd {
+            LegacyRegisterCmd::Create { cmd, section_sig } => Self::Create {
+                cmd: SignedRegisterCreate {
+                    op: cmd.op.into(),
+                    auth: cmd.auth,
+                },
+                section_sig: Some(section_sig),
+                payment: None,
+            },
+            LegacyRegisterCmd::Edit(cmd) => Self::Edit(SignedRegisterEdit {
+                op: cmd.op.into(),
+                auth: cmd.auth,
+            }),
+        }
+    }
+}
+
+impl LegacyCreateRegister {
+    /// Returns the op in its legacy layout, if it can be represented in it.
+    pub fn from_op(op: &CreateRegister) -> Option<Self> {
+        if op.private {
+            return None;
+        }
+
+        Some(Self {
+            name: op.name,
+            tag: op.tag,
+            policy: LegacyPolicy::from_policy(&op.policy)?,
+        })
+    }
+}
+
+impl From<LegacyCreateRegister> for CreateRegister {
+    fn from(op: LegacyCreateRegister) -> Self {
+        Self {
+            name: op.name,
+            tag: op.tag,
+            policy: op.policy.into(),
+            private: false,
+        }
+    }
+}
+
+impl LegacyEditRegister {
+    /// Returns the op in its legacy layout, if it can be represented in it.
+    pub fn from_op(op: &EditRegister) -> Option<Self> {
+        // legacy edits were all authored before policies could be updated
+        if op.policy_version != 0 {
+            return None;
+        }
+
+        let edit = &op.edit;
+        Some(Self {
+            address: LegacyRegisterAddress::from_address(&op.address)?,
+            edit: LegacyRegisterOp {
+                address: LegacyRegisterAddress::from_address(&edit.address)?,
+                crdt_op: edit.crdt_op.clone(),
+                source: edit.source,
+                signature: edit.signature.clone(),
+            },
+        })
+    }
+}
+
+impl From<LegacyEditRegister> for EditRegister {
+    fn from(op: LegacyEditRegister) -> Self {
+        let edit = op.edit;
+        Self {
+            address: op.address.into(),
+            edit: RegisterOp {
+                address: edit.address.into(),
+                crdt_op: edit.crdt_op,
+                source: edit.source,
+                signature: edit.signature,
+            },
+            policy_version: 0,
+        }
+    }
+}
+
+impl LegacyRegisterAddress {
+    /// Returns the address in its legacy layout, i.e. if it's the address of a public Register.
+    pub fn from_address(address: &RegisterAddress) -> Option<Self> {
+        if address.is_private() {
+            return None;
+        }
+
+        Some(Self {
+            name: address.name,
+            tag: address.tag,
+        })
+    }
+}
+
+impl From<LegacyRegisterAddress> for RegisterAddress {
+    fn from(address: LegacyRegisterAddress) -> Self {
+        Self::new(address.name, address.tag)
+    }
+}
+
+impl LegacyPolicy {
+    /// Returns the policy in its legacy layout, i.e. if it grants no permissions to
+    /// manage the policy to any user.
+    pub fn from_policy(policy: &Policy) -> Option<Self> {
+        let permissions = policy
+            .permissions
+            .iter()
+            .map(
+                |(user, perms)| match perms.is_allowed(Action::ManagePolicy) {
+                    None => Some((
+                        *user,
+                        LegacyPermissions {
+                            write: perms.is_allowed(Action::Write),
+                        },
+                    )),
+                    Some(_) => None,
+                },
+            )
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            owner: policy.owner,
+            permissions,
+        })
+    }
+}
+
+impl From<LegacyPolicy> for Policy {
+    fn from(policy: LegacyPolicy) -> Self {
+        Self {
+            owner: policy.owner,
+            permissions: policy
+                .permissions
+                .into_iter()
+                .map(|(user, perms)| (user, Permissions::new(perms.write)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LegacyRegisterCmd;
+    use crate::messaging::{
+        data::{CreateRegister, RegisterCmd, SignedRegisterCreate},
+        ClientAuth, SectionSig,
+    };
+    use crate::types::{
+        register::{Permissions, Policy, User},
+        Keypair,
+    };
+
+    use eyre::{eyre, Result};
+    use std::collections::BTreeMap;
+    use xor_name::XorName;
+
+    #[test]
+    fn legacy_cmds_convert_back_and_forth() -> Result<()> {
+        let keypair = Keypair::new_ed25519();
+        let owner = User::Key(keypair.public_key());
+        let writer = User::Key(Keypair::new_ed25519().public_key());
+        let op = CreateRegister {
+            name: XorName::random(&mut rand::thread_rng()),
+            tag: 15000,
+            policy: Policy {
+                owner,
+                permissions: BTreeMap::from([(writer, Permissions::new(true))]),
+            },
+            private: false,
+        };
+        let sk = bls::SecretKey::random();
+        let cmd = RegisterCmd::Create {
+            cmd: SignedRegisterCreate {
+                auth: ClientAuth {
+                    public_key: keypair.public_key(),
+                    signature: keypair.sign(&bincode::serialize(&op)?),
+                },
+                op,
+            },
+            section_sig: Some(SectionSig {
+                public_key: sk.public_key(),
+                signature: sk.sign("section-sig"),
+            }),
+            payment: None,
+        };
+
+        let legacy_cmd = LegacyRegisterCmd::from_cmd(&cmd)
+            .ok_or_else(|| eyre!("The cmd should be representable in the legacy layout"))?;
+        assert_eq!(RegisterCmd::from(legacy_cmd.clone()), cmd);
+
+        // cmds using any of the new features cannot be represented in the legacy layout
+        let mut cmd = RegisterCmd::from(legacy_cmd);
+        if let RegisterCmd::Create { cmd, .. } = &mut cmd {
+            cmd.op.private = true;
+        }
+        assert_eq!(LegacyRegisterCmd::from_cmd(&cmd), None);
+
+        if let RegisterCmd::Create { cmd, .. } = &mut cmd {
+            cmd.op.private = false;
+            let _ = cmd
+                .op
+                .policy
+                .permissions
+                .insert(writer, Permissions::new(true).with_manage_policy(true));
+        }
+        assert_eq!(LegacyRegisterCmd::from_cmd(&cmd), None);
+
+        Ok(())
+    }
+}
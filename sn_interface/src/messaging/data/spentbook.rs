@@ -13,7 +13,7 @@ use crate::network_knowledge::{SectionAuthorityProvider, SectionsDAG};
 use crate::types::SpentbookAddress;
 
 use serde::{Deserialize, Serialize};
-use sn_dbc::{KeyImage, RingCtTransaction, SpentProof};
+use sn_dbc::{KeyImage, RingCtTransaction, SpentProof, SpentProofShare};
 use std::collections::BTreeSet;
 use xor_name::XorName;
 
@@ -23,6 +23,19 @@ use xor_name::XorName;
 pub enum SpentbookQuery {
     /// Query the set of spent proofs if the provided key image has already been spent with a Tx
     SpentProofShares(SpentbookAddress),
+    /// Query a sample of the transactions spent on the network, as stored by the Adults
+    /// holding the provided spentbook, to pick decoys out of their outputs.
+    SpentTransactions(SpentbookAddress),
+}
+
+/// A transaction spent on the network, along with the spent proof shares of one of its inputs.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, custom_debug::Debug)]
+pub struct SpentTransaction {
+    /// The transaction the input was spent with.
+    #[debug(skip)]
+    pub tx: RingCtTransaction,
+    /// The spent proof shares stored for the input, all of them for the transaction.
+    pub spent_proof_shares: Vec<SpentProofShare>,
 }
 
 /// A Spentbook cmd.
@@ -65,13 +78,14 @@ impl SpentbookQuery {
     pub fn to_error_response(&self, error: Error) -> QueryResponse {
         match self {
             Self::SpentProofShares(_) => QueryResponse::SpentProofShares(Err(error)),
+            Self::SpentTransactions(_) => QueryResponse::SpentTransactions(Err(error)),
         }
     }
 
     /// Returns the dst address for the request.
     pub fn dst_address(&self) -> SpentbookAddress {
         match self {
-            Self::SpentProofShares(address) => *address,
+            Self::SpentProofShares(address) | Self::SpentTransactions(address) => *address,
         }
     }
 
@@ -35,6 +35,12 @@ pub enum NodeDataCmd {
     ReplicateData(Vec<ReplicatedData>),
     /// Tells an Adult to fetch and replicate data from the sender
     SendAnyMissingRelevantData(Vec<DataAddress>),
+    /// Pushes the sender's replicas of some data to an Adult, asking it to confirm it holds all
+    /// their content, sent by an Adult before removing data it's no longer responsible for
+    ConfirmDataHeld(Vec<ReplicatedData>),
+    /// Tells an Adult which of the replicas it pushed with `ConfirmDataHeld` the sender holds
+    /// all the content of, along with the digest of the content of each of them
+    DataHeld(Vec<(DataAddress, [u8; 32])>),
     /// Sent to all promoted nodes (also sibling if any) after
     /// a completed transition to a new constellation.
     ReceiveMetadata {
@@ -18,12 +18,28 @@ pub struct RegisterAddress {
     pub name: XorName,
     /// Tag.
     pub tag: u64,
+    /// Whether the Register is private, i.e. its entries are encrypted to its readers' keys.
+    /// Private Registers live in a separate address space from public ones.
+    pub private: bool,
 }
 
 impl RegisterAddress {
-    /// Constructs a new `RegisterAddress` given `name` and `tag`.
+    /// Constructs a new `RegisterAddress` of a public Register given `name` and `tag`.
     pub fn new(name: XorName, tag: u64) -> Self {
-        Self { name, tag }
+        Self {
+            name,
+            tag,
+            private: false,
+        }
+    }
+
+    /// Constructs a new `RegisterAddress` of a private Register given `name` and `tag`.
+    pub fn new_private(name: XorName, tag: u64) -> Self {
+        Self {
+            name,
+            tag,
+            private: true,
+        }
     }
 
     /// Returns the name.
@@ -36,4 +52,9 @@ impl RegisterAddress {
     pub fn tag(&self) -> u64 {
         self.tag
     }
+
+    /// Returns true if this is the address of a private Register.
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
 }
@@ -33,6 +33,19 @@ pub enum Error {
     /// Entry is too big to fit inside a register
     #[error("Entry is too big to fit inside a register: {size}, max: {max}")]
     EntryTooBig { size: usize, max: usize },
+    /// Entry is too big to fit inside a private register once encrypted to its readers
+    #[error(
+        "Entry is too big to fit inside a private register once encrypted to its {readers} \
+        reader(s): {size}, max: {max}"
+    )]
+    SealedEntryTooBig {
+        size: usize,
+        readers: usize,
+        max: usize,
+    },
+    /// The entry of a private register couldn't be decrypted with the key provided.
+    #[error("Entry of private register could not be decrypted with the key provided")]
+    EntryDecryption,
     /// Cannot add another entry since the register entry cap has been reached.
     #[error("Cannot add another entry since the register entry cap has been reached: {0}")]
     TooManyEntries(usize),
@@ -53,15 +53,27 @@ pub enum LogMarker {
     DataResponseReceviedFromAdult,
     ChunkQueryReceviedAtElder,
     ChunkQueryReceviedAtAdult,
+    // Storage
+    UsedSpaceDriftCorrected,
+    CorruptedDataQuarantined,
     // Data reorganisation
     RequestForAnyMissingData,
     DataReorganisationUnderway,
     QueuingMissingReplicatedData,
     SendingMissingReplicatedData,
+    // Data pruning
+    DataPruningUnderway,
+    DataNoLongerResponsibleForRemoved,
     // Register
     RegisterWrite,
     RegisterQueryReceivedAtElder,
     RegisterQueryReceivedAtAdult,
+    // Spentbook
+    SpentProofShareStored,
+    SpentbookDoubleSpendRejected,
+    // Payments
+    StoreCostQuoted,
+    PaymentVerified,
     // Routing cmds
     DispatchHandleMsgCmd,
     CmdHandlingSpawned,
@@ -109,6 +121,12 @@ pub enum LogMarker {
     ConnectionClosed,
     ReceiveCompleted,
     ConnectionReused,
+    // Spam protection
+    IncomingConnThrottled,
+    ClientMsgThrottled,
+    MalformedMsgReceived,
+    PeerBanned,
+    RejectedBannedPeer,
     // Relocation
     RelocateStart,
     RelocateEnd,
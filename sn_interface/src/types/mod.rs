@@ -43,13 +43,11 @@ pub use keys::{
 pub use peer::Peer;
 
 use serde::{Deserialize, Serialize};
+use sn_dbc::{RingCtTransaction, SpentProofShare};
 use xor_name::XorName;
 
-// TODO: temporary type tag for spentbook since its underlying data type is
-// still not implemented, it uses a Public Register for now.
-pub const SPENTBOOK_TYPE_TAG: u64 = 0;
-
 const REGISTER_CMD_SIZE: usize = 300;
+const SPENT_PROOF_SHARE_SIZE: usize = 500;
 
 /// Register data exchange.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -60,6 +58,17 @@ pub struct ReplicatedRegisterLog {
     pub op_log: Vec<RegisterCmd>,
 }
 
+/// Spentbook data exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicatedSpentbookLog {
+    /// Address of the spentbook, derived from the key image the spent proof shares are for.
+    pub address: SpentbookAddress,
+    /// All the spent proof shares stored for the key image.
+    pub spent_proof_shares: Vec<SpentProofShare>,
+    /// The transaction the key image was spent with, if it's stored.
+    pub spent_transaction: Option<RingCtTransaction>,
+}
+
 ///
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -70,10 +79,10 @@ pub enum ReplicatedData {
     RegisterWrite(RegisterCmd),
     /// An entire op log of a register.
     RegisterLog(ReplicatedRegisterLog),
-    /// A single cmd for a spentbook.
-    SpentbookWrite(RegisterCmd),
-    /// An entire op log of a spentbook.
-    SpentbookLog(ReplicatedRegisterLog),
+    /// A single spent proof share for a spentbook, along with the transaction it was spent with.
+    SpentbookWrite(SpentProofShare, RingCtTransaction),
+    /// All the spent proof shares of a spentbook.
+    SpentbookLog(ReplicatedSpentbookLog),
 }
 
 impl ReplicatedData {
@@ -83,7 +92,7 @@ impl ReplicatedData {
             Self::RegisterLog(log) => *log.address.name(),
             Self::RegisterWrite(cmd) => *cmd.dst_address().name(),
             Self::SpentbookLog(log) => *log.address.name(),
-            Self::SpentbookWrite(cmd) => *cmd.dst_address().name(),
+            Self::SpentbookWrite(share, _) => XorName::from_content(&share.key_image().to_bytes()),
         }
     }
 
@@ -92,22 +101,18 @@ impl ReplicatedData {
             Self::Chunk(chunk) => DataAddress::Bytes(*chunk.address()),
             Self::RegisterLog(log) => DataAddress::Register(log.address),
             Self::RegisterWrite(cmd) => DataAddress::Register(cmd.dst_address()),
-            Self::SpentbookLog(log) => {
-                DataAddress::Spentbook(SpentbookAddress::new(*log.address.name()))
-            }
-            Self::SpentbookWrite(cmd) => {
-                DataAddress::Spentbook(SpentbookAddress::new(*cmd.dst_address().name()))
-            }
+            Self::SpentbookLog(log) => DataAddress::Spentbook(log.address),
+            Self::SpentbookWrite(..) => DataAddress::Spentbook(SpentbookAddress::new(self.name())),
         }
     }
 
     pub fn size(&self) -> u64 {
         let length = match self {
             Self::Chunk(chunk) => chunk.payload_size(),
-            Self::RegisterWrite(_) | Self::SpentbookWrite(_) => REGISTER_CMD_SIZE,
-            Self::RegisterLog(log) | Self::SpentbookLog(log) => {
-                REGISTER_CMD_SIZE * log.op_log.len()
-            }
+            Self::RegisterWrite(_) => REGISTER_CMD_SIZE,
+            Self::RegisterLog(log) => REGISTER_CMD_SIZE * log.op_log.len(),
+            Self::SpentbookWrite(..) => SPENT_PROOF_SHARE_SIZE,
+            Self::SpentbookLog(log) => SPENT_PROOF_SHARE_SIZE * log.spent_proof_shares.len(),
         };
         length as u64
     }
@@ -121,7 +126,10 @@ impl ReplicatedData {
             Self::RegisterWrite(RegisterCmd::Edit { .. }) => {
                 Ok(CmdResponse::EditRegister(Err(error)))
             }
-            Self::SpentbookWrite(_) => Ok(CmdResponse::SpendKey(Err(error))),
+            Self::RegisterWrite(RegisterCmd::UpdatePolicy { .. }) => {
+                Ok(CmdResponse::UpdateRegisterPolicy(Err(error)))
+            }
+            Self::SpentbookWrite(..) => Ok(CmdResponse::SpendKey(Err(error))),
             Self::SpentbookLog(_) => Err(Error::NoCmdResponseForTheVariant), // should be unreachable, since `SpentbookLog` is not resulting from a cmd.
             Self::RegisterLog(_) => Err(Error::NoCmdResponseForTheVariant), // should be unreachable, since `RegisterLog` is not resulting from a cmd.,
         }
@@ -11,8 +11,10 @@
 pub enum Action {
     /// Read from the data.
     Read,
-    /// Write to the data.
+    /// Write to the data, i.e. append entries to it.
     Write,
+    /// Manage the policy of the data, i.e. change its owner and the users' permissions.
+    ManagePolicy,
 }
 
 /// An entry in a Register (note that the `vec<u8>` is size limited: `MAX_REG_ENTRY_SIZE`)
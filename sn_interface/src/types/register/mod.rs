@@ -9,10 +9,12 @@
 mod metadata;
 mod policy;
 mod reg_crdt;
+mod sealed_entry;
 
 pub use metadata::{Action, Entry};
 pub use policy::{Permissions, Policy, User};
 pub use reg_crdt::EntryHash;
+pub use sealed_entry::SealedEntry;
 
 pub(crate) use reg_crdt::{CrdtOperation, RegisterCrdt};
 
@@ -41,16 +43,22 @@ pub struct Register {
     authority: User,
     pub(super) crdt: RegisterCrdt, // Temporarily exposed to 'super' till spentbook fully implemented.
     policy: Policy,
+    policy_version: u64,
 }
 
 impl Register {
     ///
     pub fn new(authority: User, name: XorName, tag: u64, policy: Policy) -> Self {
-        let address = RegisterAddress { name, tag };
+        Self::with_address(authority, RegisterAddress::new(name, tag), policy)
+    }
+
+    /// Create a new Register at the given address, which can be that of a private Register.
+    pub fn with_address(authority: User, address: RegisterAddress, policy: Policy) -> Self {
         Self {
             authority,
             crdt: RegisterCrdt::new(address),
             policy,
+            policy_version: 0,
         }
     }
 
@@ -111,6 +119,25 @@ impl Register {
         self.crdt.read()
     }
 
+    /// Return all the entries, in causal order, i.e. each entry comes after all those it was
+    /// written on top of. Concurrent entries are ordered by their hash.
+    pub fn history(&self) -> Vec<(EntryHash, Entry)> {
+        self.crdt.history()
+    }
+
+    /// Return all the entries the entry corresponding to the provided 'hash' was written
+    /// on top of, either directly or transitively, in causal order.
+    pub fn ancestors(&self, hash: EntryHash) -> Result<Vec<(EntryHash, Entry)>> {
+        self.crdt.ancestors(hash)
+    }
+
+    /// Read the entries which were the current ones as of the entry corresponding to the
+    /// provided 'hash', i.e. the heads of the register made of the entries up to it in its
+    /// history: the entry, along with any concurrent heads which come before it.
+    pub fn read_as_of(&self, hash: EntryHash) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        self.crdt.read_as_of(hash)
+    }
+
     /// Return user permissions, if applicable.
     pub fn permissions(&self, user: User) -> Result<Permissions> {
         self.policy.permissions(user).ok_or(Error::NoSuchUser(user))
@@ -139,6 +166,30 @@ impl Register {
         self.crdt.apply_op(op)
     }
 
+    /// Return the version of the policy, i.e. the number of updates it went through.
+    pub fn policy_version(&self) -> u64 {
+        self.policy_version
+    }
+
+    /// Update the policy, replacing the current one if the given version is newer.
+    /// Concurrent updates, i.e. with the same version, are resolved by keeping the greatest
+    /// policy, so all replicas converge regardless of the order updates are applied in.
+    /// Permissions to manage the policy are expected to be checked by the caller.
+    pub fn update_policy(&mut self, version: u64, policy: Policy) {
+        if (version, &policy) > (self.policy_version, &self.policy) {
+            self.policy_version = version;
+            self.policy = policy;
+        }
+    }
+
+    /// Merge another replica of this Register into this one, e.g. when replicas
+    /// retrieved from different holders have diverged. The newest policy of the two is kept.
+    pub fn merge(&mut self, other: Register) -> Result<()> {
+        self.crdt.merge(other.crdt)?;
+        self.update_policy(other.policy_version, other.policy);
+        Ok(())
+    }
+
     // Private helper to check the given Entry's size is within define limit,
     // as well as check the Register hasn't already reached the maximum number of entries.
     fn check_entry_and_reg_sizes(&self, entry: &Entry) -> Result<()> {
@@ -173,7 +224,7 @@ impl Register {
 #[cfg(test)]
 mod tests {
     use super::super::{
-        register::{Entry, EntryHash, Permissions, Register, RegisterOp, User},
+        register::{Action, Entry, EntryHash, Permissions, Register, RegisterOp, User},
         utils, Error, Keypair, Result,
     };
     use crate::types::register::MAX_REG_NUM_ENTRIES;
@@ -354,6 +405,177 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_history_and_ancestors() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+
+        let entry1 = random_register_entry();
+        let entry2 = random_register_entry();
+        let entry3 = random_register_entry();
+        let entry4 = random_register_entry();
+
+        // entry2 and entry3 are concurrent writes on top of entry1,
+        // and entry4 merges them back
+        let (entry1_hash, _) = register.write(entry1.clone(), BTreeSet::new())?;
+        let (entry2_hash, _) = register.write(entry2.clone(), BTreeSet::from([entry1_hash]))?;
+        let (entry3_hash, _) = register.write(entry3.clone(), BTreeSet::from([entry1_hash]))?;
+        let (entry4_hash, _) =
+            register.write(entry4.clone(), BTreeSet::from([entry2_hash, entry3_hash]))?;
+
+        let (first_fork, second_fork) = if entry2_hash < entry3_hash {
+            ((entry2_hash, entry2.clone()), (entry3_hash, entry3.clone()))
+        } else {
+            ((entry3_hash, entry3.clone()), (entry2_hash, entry2.clone()))
+        };
+
+        assert_eq!(
+            register.history(),
+            vec![
+                (entry1_hash, entry1.clone()),
+                first_fork.clone(),
+                second_fork.clone(),
+                (entry4_hash, entry4),
+            ]
+        );
+
+        assert_eq!(
+            register.ancestors(entry4_hash)?,
+            vec![(entry1_hash, entry1.clone()), first_fork, second_fork]
+        );
+        assert_eq!(
+            register.ancestors(entry2_hash)?,
+            vec![(entry1_hash, entry1)]
+        );
+        assert!(register.ancestors(entry1_hash)?.is_empty());
+
+        let non_existing_hash = EntryHash::default();
+        assert_eq!(
+            register.ancestors(non_existing_hash),
+            Err(Error::NoSuchEntry(non_existing_hash))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_read_as_of() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+
+        let entry1 = random_register_entry();
+        let entry2 = random_register_entry();
+        let entry3 = random_register_entry();
+
+        let (entry1_hash, _) = register.write(entry1.clone(), BTreeSet::new())?;
+        let (entry2_hash, _) = register.write(entry2.clone(), BTreeSet::from([entry1_hash]))?;
+        let (entry3_hash, _) = register.write(entry3.clone(), BTreeSet::from([entry2_hash]))?;
+
+        assert_eq!(
+            register.read_as_of(entry1_hash)?,
+            BTreeSet::from([(entry1_hash, entry1.clone())])
+        );
+        assert_eq!(
+            register.read_as_of(entry2_hash)?,
+            BTreeSet::from([(entry2_hash, entry2.clone())])
+        );
+        assert_eq!(register.read_as_of(entry3_hash)?, register.read());
+
+        let non_existing_hash = EntryHash::default();
+        assert_eq!(
+            register.read_as_of(non_existing_hash),
+            Err(Error::NoSuchEntry(non_existing_hash))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_read_as_of_concurrent_branches() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+
+        // two concurrent entries written on top of the same one
+        let entry1 = random_register_entry();
+        let (entry1_hash, _) = register.write(entry1.clone(), BTreeSet::new())?;
+        let mut branches = Vec::new();
+        for _ in 0..2 {
+            let entry = random_register_entry();
+            let (hash, _) = register.write(entry.clone(), BTreeSet::from([entry1_hash]))?;
+            branches.push((hash, entry));
+        }
+        branches.sort();
+        let (first, second) = (branches[0].clone(), branches[1].clone());
+        assert_eq!(
+            register.history(),
+            vec![(entry1_hash, entry1.clone()), first.clone(), second.clone()]
+        );
+
+        assert_eq!(
+            register.read_as_of(entry1_hash)?,
+            BTreeSet::from([(entry1_hash, entry1)])
+        );
+        assert_eq!(
+            register.read_as_of(first.0)?,
+            BTreeSet::from([first.clone()])
+        );
+        // the concurrent head which came first in the history is still current
+        assert_eq!(
+            register.read_as_of(second.0)?,
+            BTreeSet::from([first.clone(), second.clone()])
+        );
+        assert_eq!(register.read_as_of(second.0)?, register.read());
+
+        // an entry written on top of both heads is the only current one as of it,
+        // while the heads as of the former ones are unaffected
+        let entry4 = random_register_entry();
+        let (entry4_hash, _) =
+            register.write(entry4.clone(), BTreeSet::from([first.0, second.0]))?;
+        assert_eq!(
+            register.read_as_of(entry4_hash)?,
+            BTreeSet::from([(entry4_hash, entry4)])
+        );
+        assert_eq!(
+            register.read_as_of(second.0)?,
+            BTreeSet::from([first, second])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_merge_replicas() -> eyre::Result<()> {
+        let mut replicas = create_reg_replicas(2);
+        let (_, mut replica2) = replicas.remove(1);
+        let (_, mut replica1) = replicas.remove(0);
+
+        let (entry1_hash, _) = replica1.write(random_register_entry(), BTreeSet::new())?;
+        let (entry2_hash, _) = replica2.write(random_register_entry(), BTreeSet::new())?;
+
+        replica1.merge(replica2.clone())?;
+        assert_eq!(replica1.size(), 2);
+        assert_eq!(
+            replica1
+                .read()
+                .into_iter()
+                .map(|(hash, _)| hash)
+                .collect::<BTreeSet<_>>(),
+            BTreeSet::from([entry1_hash, entry2_hash])
+        );
+
+        // merging is idempotent, and converges regardless of the order
+        replica2.merge(replica1.clone())?;
+        replica1.merge(replica2.clone())?;
+        assert_eq!(replica1.size(), 2);
+        assert_eq!(replica1.read(), replica2.read());
+
+        // replicas of another Register cannot be merged
+        let (_, other_register) = &create_reg_replicas(1)[0];
+        assert_eq!(
+            replica1.merge(other_register.clone()),
+            Err(Error::CrdtWrongAddress(*other_register.address()))
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn register_query_public_policy() -> eyre::Result<()> {
         let name = xor_name::rand::random();
@@ -415,6 +637,105 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_manage_policy_permissions() -> eyre::Result<()> {
+        let (owner_keypair, register) = &create_reg_replicas(1)[0];
+        let owner = User::Key(owner_keypair.public_key());
+        let writer = User::Key(Keypair::new_ed25519().public_key());
+        let manager = User::Key(Keypair::new_ed25519().public_key());
+
+        let mut perms = BTreeMap::default();
+        let _prev = perms.insert(writer, Permissions::new(true));
+        let _prev = perms.insert(manager, Permissions::new(None).with_manage_policy(true));
+        let mut register = register.clone();
+        register.update_policy(
+            1,
+            Policy {
+                owner,
+                permissions: perms,
+            },
+        );
+
+        // the owner can do anything, regardless of the permissions set
+        assert!(register
+            .check_permissions(Action::Write, Some(owner))
+            .is_ok());
+        assert!(register
+            .check_permissions(Action::ManagePolicy, Some(owner))
+            .is_ok());
+
+        // appending and managing the policy are granted separately
+        assert!(register
+            .check_permissions(Action::Write, Some(writer))
+            .is_ok());
+        assert_eq!(
+            register.check_permissions(Action::ManagePolicy, Some(writer)),
+            Err(Error::AccessDenied(writer))
+        );
+        assert_eq!(
+            register.check_permissions(Action::Write, Some(manager)),
+            Err(Error::AccessDenied(manager))
+        );
+        assert!(register
+            .check_permissions(Action::ManagePolicy, Some(manager))
+            .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_policy_updates_converge() -> eyre::Result<()> {
+        let (owner_keypair, register) = &create_reg_replicas(1)[0];
+        let owner = User::Key(owner_keypair.public_key());
+        let user1 = User::Key(Keypair::new_ed25519().public_key());
+        let user2 = User::Key(Keypair::new_ed25519().public_key());
+
+        let policy_granting = |user| {
+            let mut permissions = BTreeMap::default();
+            let _prev = permissions.insert(user, Permissions::new(true));
+            Policy { owner, permissions }
+        };
+        let policy1 = policy_granting(user1);
+        let policy2 = policy_granting(user2);
+        let policy3 = policy_granting(User::Anyone);
+
+        // two concurrent updates, and a later one, applied in different orders
+        let updates = [
+            (1, policy1.clone()),
+            (1, policy2.clone()),
+            (2, policy3.clone()),
+        ];
+        let mut replica1 = register.clone();
+        let mut replica2 = register.clone();
+        for (version, policy) in updates.iter().cloned() {
+            replica1.update_policy(version, policy);
+        }
+        for (version, policy) in updates.iter().rev().cloned() {
+            replica2.update_policy(version, policy);
+        }
+
+        assert_eq!(replica1.policy_version(), 2);
+        assert_eq!(replica1.policy(), &policy3);
+        assert_eq!(replica1, replica2);
+
+        // concurrent updates resolve to the same policy no matter the order
+        let mut replica1 = register.clone();
+        let mut replica2 = register.clone();
+        replica1.update_policy(1, policy1.clone());
+        replica1.update_policy(1, policy2.clone());
+        replica2.update_policy(1, policy2);
+        replica2.update_policy(1, policy1);
+        assert_eq!(replica1, replica2);
+
+        // merging keeps the newest policy
+        let mut replica3 = register.clone();
+        replica3.merge(replica1.clone())?;
+        assert_eq!(replica3.policy_version(), 1);
+        assert_eq!(replica3.policy(), replica1.policy());
+
+        Ok(())
+    }
+
     #[test]
     fn exceeding_max_reg_entries_errors() -> eyre::Result<()> {
         let name = xor_name::rand::random();
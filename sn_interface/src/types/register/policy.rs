@@ -18,27 +18,45 @@ pub struct Permissions {
     /// `Some(false)` explicitly denies this permission (even if `Anyone` has permissions).
     /// Use permissions for `Anyone` if `None`.
     write: Option<bool>,
+    /// `Some(true)` if the user can manage the policy, i.e. change the owner and permissions.
+    /// `Some(false)` explicitly denies this permission (even if `Anyone` has permissions).
+    /// Use permissions for `Anyone` if `None`.
+    manage_policy: Option<bool>,
 }
 
 impl Permissions {
-    /// Constructs a new public permission set.
+    /// Constructs a new public permission set, with the permission to manage the policy
+    /// left to the permissions for `Anyone`.
     pub fn new(write: impl Into<Option<bool>>) -> Self {
         Self {
             write: write.into(),
+            manage_policy: None,
         }
     }
 
+    /// Returns this permission set with the permission to manage the policy set.
+    pub fn with_manage_policy(mut self, manage_policy: impl Into<Option<bool>>) -> Self {
+        self.manage_policy = manage_policy.into();
+        self
+    }
+
     /// Sets permissions.
     pub fn set_perms(&mut self, write: impl Into<Option<bool>>) {
         self.write = write.into();
     }
 
+    /// Sets the permission to manage the policy.
+    pub fn set_manage_policy_perms(&mut self, manage_policy: impl Into<Option<bool>>) {
+        self.manage_policy = manage_policy.into();
+    }
+
     /// Returns `Some(true)` if `action` is allowed and `Some(false)` if it's not permitted.
     /// `None` means that default permissions should be applied.
     pub fn is_allowed(self, action: Action) -> Option<bool> {
         match action {
             Action::Read => Some(true), // It's public data, so it's always allowed to read it.
             Action::Write => self.write,
+            Action::ManagePolicy => self.manage_policy,
         }
     }
 }
@@ -95,7 +113,7 @@ impl Policy {
             // i.e. it won't be possible to circumvent the semantics of `owner`
             // by setting some other permissions for the user.
             // the permissions can still be kept in the state though, so that switching owners gives an immediate permission update as well
-            Some(Permissions::new(true))
+            Some(Permissions::new(true).with_manage_policy(true))
         } else {
             self.permissions.get(&user).copied()
         }
@@ -14,12 +14,13 @@ use super::{
     User,
 };
 use crdts::{
-    merkle_reg::{MerkleReg, Node},
-    CmRDT,
+    merkle_reg::{Hash as CrdtHash, MerkleReg, Node},
+    CmRDT, CvRDT,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeSet,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
     fmt::{self, Debug, Display, Formatter, Result as FmtResult},
     hash::Hash,
 };
@@ -140,6 +141,17 @@ impl RegisterCrdt {
         Ok(())
     }
 
+    /// Merge another replica of the `RegisterCrdt` into this one.
+    pub(crate) fn merge(&mut self, other: Self) -> Result<()> {
+        if self.address != other.address {
+            return Err(Error::CrdtWrongAddress(other.address));
+        }
+
+        self.data.merge(other.data);
+
+        Ok(())
+    }
+
     /// Get the entry corresponding to the provided `hash` if it exists.
     pub(crate) fn get(&self, hash: EntryHash) -> Option<&Entry> {
         self.data.node(hash.0).map(|node| &node.value)
@@ -153,6 +165,104 @@ impl RegisterCrdt {
             .map(|(hash, node)| (EntryHash(hash), node.value.clone()))
             .collect()
     }
+
+    /// Returns all the entries in the register, in causal order.
+    pub(crate) fn history(&self) -> Vec<(EntryHash, Entry)> {
+        let hashes = self.data.all_nodes().map(|node| node.hash()).collect();
+        self.causal_order(hashes)
+    }
+
+    /// Returns all the entries the entry corresponding to the provided `hash` was
+    /// written on top of, either directly or transitively, in causal order.
+    pub(crate) fn ancestors(&self, hash: EntryHash) -> Result<Vec<(EntryHash, Entry)>> {
+        let node = self.data.node(hash.0).ok_or(Error::NoSuchEntry(hash))?;
+
+        let mut ancestors = BTreeSet::new();
+        let mut to_visit = node.children.iter().copied().collect::<Vec<_>>();
+        while let Some(hash) = to_visit.pop() {
+            if ancestors.insert(hash) {
+                if let Some(node) = self.data.node(hash) {
+                    to_visit.extend(node.children.iter().copied());
+                }
+            }
+        }
+
+        Ok(self.causal_order(ancestors))
+    }
+
+    /// Read the entries which were the current ones as of the entry corresponding to the
+    /// provided `hash`, i.e. the heads of the register made of the entries up to it in its
+    /// `history`: the entry, along with those concurrent to it which come before it and which
+    /// no other entry up to it was written on top of.
+    pub(crate) fn read_as_of(&self, hash: EntryHash) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        let history = self.history();
+        let position = history
+            .iter()
+            .position(|(entry_hash, _)| *entry_hash == hash)
+            .ok_or(Error::NoSuchEntry(hash))?;
+        let as_of = &history[..=position];
+
+        // a prefix of the history holds all the entries each of its entries was written on top of
+        let written_on = as_of
+            .iter()
+            .filter_map(|(entry_hash, _)| self.data.node(entry_hash.0))
+            .flat_map(|node| node.children.iter().copied())
+            .collect::<BTreeSet<_>>();
+
+        Ok(as_of
+            .iter()
+            .filter(|(entry_hash, _)| !written_on.contains(&entry_hash.0))
+            .cloned()
+            .collect())
+    }
+
+    // Orders the entries corresponding to the provided hashes so each one comes after all
+    // of those it was written on top of, using Kahn's algorithm. Of the entries ready to be
+    // ordered, the one with the lowest hash always goes first, so all replicas agree on the
+    // same order.
+    fn causal_order(&self, hashes: BTreeSet<CrdtHash>) -> Vec<(EntryHash, Entry)> {
+        let nodes = hashes
+            .into_iter()
+            .filter_map(|hash| self.data.node(hash).map(|node| (hash, node)))
+            .collect::<BTreeMap<_, _>>();
+
+        // for each entry, the number of entries it was written on top of which are yet to be
+        // ordered, and the entries written on top of it
+        let mut in_degrees = BTreeMap::new();
+        let mut successors = BTreeMap::<_, Vec<_>>::new();
+        for (hash, node) in &nodes {
+            let mut in_degree = 0;
+            for child in node.children.iter().filter(|c| nodes.contains_key(*c)) {
+                successors.entry(*child).or_default().push(*hash);
+                in_degree += 1;
+            }
+            let _ = in_degrees.insert(*hash, in_degree);
+        }
+
+        let mut ready = in_degrees
+            .iter()
+            .filter(|(_, in_degree)| **in_degree == 0)
+            .map(|(hash, _)| Reverse(*hash))
+            .collect::<BinaryHeap<_>>();
+        let mut ordered = Vec::with_capacity(nodes.len());
+        while let Some(Reverse(hash)) = ready.pop() {
+            if let Some(node) = nodes.get(&hash) {
+                ordered.push((EntryHash(hash), node.value.clone()));
+            }
+            for successor in successors.get(&hash).into_iter().flatten() {
+                if let Some(in_degree) = in_degrees.get_mut(successor) {
+                    *in_degree -= 1;
+                    if *in_degree == 0 {
+                        ready.push(Reverse(*successor));
+                    }
+                }
+            }
+        }
+
+        // entries left out, if any, are in a cycle, which cannot happen as long as hashes are
+        // not forged, since the entries form a DAG
+        ordered
+    }
 }
 
 #[cfg(test)]
@@ -164,14 +274,8 @@ mod tests {
     #[test]
     fn creating_entry_hash() -> Result<()> {
         let mut rng = rand::thread_rng();
-        let address_1 = RegisterAddress {
-            name: XorName::random(&mut rng),
-            tag: 0,
-        };
-        let address_2 = RegisterAddress {
-            name: XorName::random(&mut rng),
-            tag: 0,
-        };
+        let address_1 = RegisterAddress::new(XorName::random(&mut rng), 0);
+        let address_2 = RegisterAddress::new(XorName::random(&mut rng), 0);
 
         let mut crdt_1 = RegisterCrdt::new(address_1);
         let mut crdt_2 = RegisterCrdt::new(address_2);
@@ -0,0 +1,145 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::super::{Error, Result};
+use super::{Entry, MAX_REG_ENTRY_SIZE};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An entry of a private Register, encrypted so only its readers can decrypt it.
+///
+/// The entry is encrypted with a random content key, which is in turn encrypted
+/// to each of the readers' public keys. The readers are kept along with the entry,
+/// so whoever writes on top of it can encrypt the new entry to the same readers.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct SealedEntry {
+    content_keys: BTreeMap<bls::PublicKey, bls::Ciphertext>,
+    content: bls::Ciphertext,
+}
+
+impl SealedEntry {
+    /// Encrypt an entry so it can only be decrypted by the given readers.
+    pub fn seal(entry: &[u8], readers: &BTreeSet<bls::PublicKey>) -> Self {
+        let content_key = bls::SecretKey::random();
+        let content = content_key.public_key().encrypt(entry);
+        let content_keys = readers
+            .iter()
+            .map(|reader| (*reader, reader.encrypt(content_key.to_bytes())))
+            .collect();
+
+        Self {
+            content_keys,
+            content,
+        }
+    }
+
+    /// Decrypt the entry, provided the secret key of one of its readers.
+    pub fn open(&self, secret_key: &bls::SecretKey) -> Result<Entry> {
+        let content_key = self
+            .content_keys
+            .get(&secret_key.public_key())
+            .and_then(|content_key| secret_key.decrypt(content_key))
+            .and_then(|bytes| bytes.try_into().ok())
+            .and_then(|bytes| bls::SecretKey::from_bytes(bytes).ok())
+            .ok_or(Error::EntryDecryption)?;
+
+        content_key
+            .decrypt(&self.content)
+            .ok_or(Error::EntryDecryption)
+    }
+
+    /// Returns the public keys of the readers of the entry.
+    pub fn readers(&self) -> BTreeSet<bls::PublicKey> {
+        self.content_keys.keys().copied().collect()
+    }
+
+    /// Deserialise a sealed entry from an entry of a private Register.
+    pub fn from_entry(entry: &Entry) -> Result<Self> {
+        bincode::deserialize(entry).map_err(|_| Error::EntryDecryption)
+    }
+
+    /// Serialise the sealed entry, to be written to a private Register.
+    ///
+    /// The encryption adds to the size of the entry, on top of some more for each reader,
+    /// which all has to fit within the maximum size of an entry.
+    pub fn to_entry(&self) -> Result<Entry> {
+        let entry = bincode::serialize(self)?;
+        if entry.len() > MAX_REG_ENTRY_SIZE {
+            return Err(Error::SealedEntryTooBig {
+                size: entry.len(),
+                readers: self.content_keys.len(),
+                max: MAX_REG_ENTRY_SIZE,
+            });
+        }
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SealedEntry;
+    use crate::types::Error;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn sealed_entry_can_only_be_opened_by_its_readers() -> eyre::Result<()> {
+        let reader1 = bls::SecretKey::random();
+        let reader2 = bls::SecretKey::random();
+        let outsider = bls::SecretKey::random();
+        let readers = BTreeSet::from([reader1.public_key(), reader2.public_key()]);
+
+        let entry = b"private entry".to_vec();
+        let sealed = SealedEntry::seal(&entry, &readers);
+        assert_eq!(sealed.readers(), readers);
+
+        // it survives being written to and read from a Register
+        let sealed = SealedEntry::from_entry(&sealed.to_entry()?)?;
+        assert_eq!(sealed.open(&reader1)?, entry);
+        assert_eq!(sealed.open(&reader2)?, entry);
+        assert_eq!(sealed.open(&outsider), Err(Error::EntryDecryption));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sealed_entry_must_fit_in_a_register_entry() -> eyre::Result<()> {
+        let readers = BTreeSet::from([bls::SecretKey::random().public_key()]);
+        assert!(SealedEntry::seal(&[0; 512], &readers).to_entry().is_ok());
+
+        // an entry within the limit of public registers can be too big once sealed
+        let readers = (0..3)
+            .map(|_| bls::SecretKey::random().public_key())
+            .collect();
+        match SealedEntry::seal(&[0; 1000], &readers).to_entry() {
+            Err(Error::SealedEntryTooBig { readers, max, .. }) => {
+                assert_eq!(readers, 3);
+                assert_eq!(max, 1024);
+            }
+            other => eyre::bail!("The sealed entry should have been too big: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sealed_entry_hides_the_entry() -> eyre::Result<()> {
+        let reader = bls::SecretKey::random();
+        let readers = BTreeSet::from([reader.public_key()]);
+
+        let entry = b"private entry".to_vec();
+        let sealed = SealedEntry::seal(&entry, &readers).to_entry()?;
+        assert!(!sealed
+            .windows(entry.len())
+            .any(|window| window == entry.as_slice()));
+
+        // the same entry is sealed differently each time
+        assert_ne!(SealedEntry::seal(&entry, &readers).to_entry()?, sealed);
+
+        Ok(())
+    }
+}
@@ -11,12 +11,12 @@ use sn_interface::{
     test_utils::TestKeys,
     types::{
         register::{Policy, Register, User},
-        Chunk, Keypair, PublicKey, RegisterCmd, ReplicatedData, SectionSig,
+        Chunk, Keypair, RegisterCmd, ReplicatedData, SectionSig,
     },
 };
 use sn_node::{
     node::{cfg::config_handler::Config, DataStorage},
-    UsedSpace,
+    StorageBackendKind, UsedSpace,
 };
 
 use bytes::{Bytes, BytesMut};
@@ -28,7 +28,8 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     path::Path,
 };
-use tempfile::tempdir;
+use strum::IntoEnumIterator;
+use tempfile::{tempdir, TempDir};
 use tokio::runtime::Runtime;
 
 // sample size is _NOT_ the number of times the command is run...
@@ -97,9 +98,6 @@ fn main() -> Result<()> {
 }
 
 fn bench_data_storage_writes(c: &mut Criterion) -> Result<()> {
-    let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-    let keypair = Keypair::new_ed25519();
-
     let mut group = c.benchmark_group("write-sampling");
 
     let runtime = Runtime::new().unwrap();
@@ -107,125 +105,123 @@ fn bench_data_storage_writes(c: &mut Criterion) -> Result<()> {
 
     let size_ranges = [100, 1_000, 4_000];
 
-    for size in &size_ranges {
-        let data_set: Vec<_> = (0..*size)
-            .map(|_| create_random_register_replicated_data())
-            .collect();
-        group.bench_with_input(
-            BenchmarkId::new("register_writes", size),
-            &(size, &data_set),
-            |b, (size, data_set)| {
-                let storage = get_new_data_store()
-                    .context("Could not create a temp data store")
-                    .unwrap();
-                b.to_async(&runtime).iter(|| async {
-                    for i in 0..**size {
-                        let _ = storage
-                            .clone()
-                            .store(&data_set[i], pk, keypair.clone())
-                            .await;
-                    }
-                })
-            },
-        );
-    }
-
-    for size in &size_ranges {
-        let seed = random_vector(NONSENSE_CHUNK_SIZE);
-        group.bench_with_input(
-            BenchmarkId::new("chunk writes", size),
-            &(size, &seed),
-            |b, (size, seed)| {
-                let storage = get_new_data_store()
-                    .context("Could not create a temp data store")
-                    .unwrap();
-                b.to_async(&runtime).iter(|| async {
-                    for _ in 0..**size {
-                        let random_data =
-                            ReplicatedData::Chunk(Chunk::new(grows_vec_to_bytes(seed)));
-                        storage
-                            .clone()
-                            .store(&random_data, pk, keypair.clone())
-                            .await
-                            .expect("failed to write chunk {i}");
-                    }
-                })
-            },
-        );
+    for kind in StorageBackendKind::iter() {
+        for size in &size_ranges {
+            let data_set: Vec<_> = (0..*size)
+                .map(|_| create_random_register_replicated_data())
+                .collect();
+            group.bench_with_input(
+                BenchmarkId::new(format!("register_writes {kind:?}"), size),
+                &(size, &data_set),
+                |b, (size, data_set)| {
+                    let (_root_dir, storage) = get_new_data_store(kind)
+                        .context("Could not create a temp data store")
+                        .unwrap();
+                    b.to_async(&runtime).iter(|| async {
+                        for i in 0..**size {
+                            let _ = storage.clone().store(&data_set[i], &BTreeSet::new()).await;
+                        }
+                    })
+                },
+            );
+        }
+
+        for size in &size_ranges {
+            let seed = random_vector(NONSENSE_CHUNK_SIZE);
+            group.bench_with_input(
+                BenchmarkId::new(format!("chunk writes {kind:?}"), size),
+                &(size, &seed),
+                |b, (size, seed)| {
+                    let (_root_dir, storage) = get_new_data_store(kind)
+                        .context("Could not create a temp data store")
+                        .unwrap();
+                    b.to_async(&runtime).iter(|| async {
+                        for _ in 0..**size {
+                            let random_data =
+                                ReplicatedData::Chunk(Chunk::new(grows_vec_to_bytes(seed)));
+                            storage
+                                .clone()
+                                .store(&random_data, &BTreeSet::new())
+                                .await
+                                .expect("failed to write chunk {i}");
+                        }
+                    })
+                },
+            );
+        }
     }
 
     Ok(())
 }
 
 fn bench_data_storage_register_edits(c: &mut Criterion) -> Result<()> {
-    let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
     let mut group = c.benchmark_group("register-edit-sampling");
 
     let runtime = Runtime::new().unwrap();
 
     let size_ranges = [1000];
 
-    for size in &size_ranges {
-        let (keypair, op) = create_random_register_register_op();
-        let address = op.address();
-        // the actual register we'll be editing
-        let mut register = Register::new(
-            op.owner(),
-            *address.name(),
-            address.tag(),
-            op.policy.clone(),
-        );
-
-        let data_set: Vec<_> = (0..*size)
-            .map(|_| create_random_register_replicated_data_edit(&keypair, &mut register))
-            .collect();
-
-        group.bench_with_input(
-            BenchmarkId::new("register_edits", size),
-            &(size, &data_set),
-            |b, (size, data_set)| {
-                let storage = get_new_data_store()
-                    .context("Could not create a temp data store")
-                    .unwrap();
-                let signature =
-                    keypair.sign(&bincode::serialize(&op.clone()).expect("could not serialize op"));
-
-                let reg_cmd = RegisterCmd::Create {
-                    cmd: SignedRegisterCreate {
-                        op: op.clone(),
-                        auth: sn_interface::messaging::ClientAuth {
-                            public_key: keypair.public_key(),
-                            signature,
+    for kind in StorageBackendKind::iter() {
+        for size in &size_ranges {
+            let (keypair, op) = create_random_register_register_op();
+            let address = op.address();
+            // the actual register we'll be editing
+            let mut register = Register::new(
+                op.owner(),
+                *address.name(),
+                address.tag(),
+                op.policy.clone(),
+            );
+
+            let data_set: Vec<_> = (0..*size)
+                .map(|_| create_random_register_replicated_data_edit(&keypair, &mut register))
+                .collect();
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("register_edits {kind:?}"), size),
+                &(size, &data_set),
+                |b, (size, data_set)| {
+                    let (_root_dir, storage) = get_new_data_store(kind)
+                        .context("Could not create a temp data store")
+                        .unwrap();
+                    let signature = keypair
+                        .sign(&bincode::serialize(&op.clone()).expect("could not serialize op"));
+
+                    let reg_cmd = RegisterCmd::Create {
+                        cmd: SignedRegisterCreate {
+                            op: op.clone(),
+                            auth: sn_interface::messaging::ClientAuth {
+                                public_key: keypair.public_key(),
+                                signature,
+                            },
                         },
-                    },
-                    section_sig: section_sig(), // obtained after presenting a valid payment to the network
-                };
-
-                let first_write = ReplicatedData::RegisterWrite(reg_cmd);
-                runtime
-                    .block_on(storage.store(&first_write, pk, keypair.clone()))
-                    .expect("Could not store initial register");
-
-                b.to_async(&runtime).iter(|| async {
-                    for i in 0..**size {
-                        storage
-                            .clone()
-                            .store(&data_set[i], pk, keypair.clone())
-                            .await
-                            .expect("failed to write data storage edit");
-                    }
-                })
-            },
-        );
+                        section_sig: Some(section_sig()),
+                        payment: None,
+                    };
+
+                    let first_write = ReplicatedData::RegisterWrite(reg_cmd);
+                    runtime
+                        .block_on(storage.store(&first_write, &BTreeSet::new()))
+                        .expect("Could not store initial register");
+
+                    b.to_async(&runtime).iter(|| async {
+                        for i in 0..**size {
+                            storage
+                                .clone()
+                                .store(&data_set[i], &BTreeSet::new())
+                                .await
+                                .expect("failed to write data storage edit");
+                        }
+                    })
+                },
+            );
+        }
     }
 
     Ok(())
 }
 
 fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
-    let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-    let keypair = Keypair::new_ed25519();
-
     let mut group = c.benchmark_group("read-sampling");
 
     let runtime = Runtime::new().unwrap();
@@ -233,50 +229,60 @@ fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
 
     let size_ranges = [100, 1_000, 4_000];
 
-    for size in &size_ranges {
-        group.bench_with_input(BenchmarkId::new("register_keys", size), size, |b, &size| {
-            let storage = get_new_data_store()
-                .context("Could not create a temp data store")
-                .unwrap();
-
-            for _ in 0..size {
-                let random_data = create_random_register_replicated_data();
-
-                if let Err(error) = runtime
-                    .block_on(storage.clone().store(&random_data, pk, keypair.clone()))
-                    .context("could not store register")
-                {
-                    panic!("Error storing register {random_data:?}: {error:?}");
-                }
-            }
-
-            b.iter(|| {
-                let _keys = runtime.block_on(storage.data_addrs());
-            })
-        });
-    }
+    for kind in StorageBackendKind::iter() {
+        for size in &size_ranges {
+            group.bench_with_input(
+                BenchmarkId::new(format!("register_keys {kind:?}"), size),
+                size,
+                |b, &size| {
+                    let (_root_dir, storage) = get_new_data_store(kind)
+                        .context("Could not create a temp data store")
+                        .unwrap();
+
+                    for _ in 0..size {
+                        let random_data = create_random_register_replicated_data();
+
+                        if let Err(error) = runtime
+                            .block_on(storage.clone().store(&random_data, &BTreeSet::new()))
+                            .context("could not store register")
+                        {
+                            panic!("Error storing register {random_data:?}: {error:?}");
+                        }
+                    }
 
-    for size in &size_ranges {
-        group.bench_with_input(BenchmarkId::new("chunk keys", size), size, |b, &size| {
-            let storage = get_new_data_store()
-                .context("Could not create a temp data store")
-                .unwrap();
-
-            for _ in 0..size {
-                let file = sn_interface::types::utils::random_bytes(NONSENSE_CHUNK_SIZE);
-                let random_data = ReplicatedData::Chunk(Chunk::new(file));
-                if let Err(error) = runtime
-                    .block_on(storage.store(&random_data, pk, keypair.clone()))
-                    .context("could not store chunk")
-                {
-                    panic!("Error storing chunk {error:?}");
-                };
-            }
-
-            b.iter(|| {
-                let _keys = runtime.block_on(storage.data_addrs());
-            })
-        });
+                    b.iter(|| {
+                        let _keys = runtime.block_on(storage.data_addrs());
+                    })
+                },
+            );
+        }
+
+        for size in &size_ranges {
+            group.bench_with_input(
+                BenchmarkId::new(format!("chunk keys {kind:?}"), size),
+                size,
+                |b, &size| {
+                    let (_root_dir, storage) = get_new_data_store(kind)
+                        .context("Could not create a temp data store")
+                        .unwrap();
+
+                    for _ in 0..size {
+                        let file = sn_interface::types::utils::random_bytes(NONSENSE_CHUNK_SIZE);
+                        let random_data = ReplicatedData::Chunk(Chunk::new(file));
+                        if let Err(error) = runtime
+                            .block_on(storage.store(&random_data, &BTreeSet::new()))
+                            .context("could not store chunk")
+                        {
+                            panic!("Error storing chunk {error:?}");
+                        };
+                    }
+
+                    b.iter(|| {
+                        let _keys = runtime.block_on(storage.data_addrs());
+                    })
+                },
+            );
+        }
     }
 
     Ok(())
@@ -304,7 +310,15 @@ pub fn create_random_register_register_op() -> (Keypair, CreateRegister) {
     let owner = User::Key(keypair.public_key());
     let policy = public_policy(owner);
 
-    (keypair, CreateRegister { name, tag, policy })
+    (
+        keypair,
+        CreateRegister {
+            name,
+            tag,
+            policy,
+            private: false,
+        },
+    )
 }
 
 /// create random edits to an existing register
@@ -321,6 +335,7 @@ pub fn create_random_register_replicated_data_edit(
     let edit_register_op = EditRegister {
         address: *register.address(),
         edit,
+        policy_version: register.policy_version(),
     };
     let signature =
         keypair.sign(&bincode::serialize(&edit_register_op).expect("could not serialize op"));
@@ -349,13 +364,15 @@ pub fn create_random_register_replicated_data() -> ReplicatedData {
                 signature,
             },
         },
-        section_sig: section_sig(), // obtained after presenting a valid payment to the network
+        section_sig: Some(section_sig()),
+        payment: None,
     };
 
     ReplicatedData::RegisterWrite(reg_cmd)
 }
 
-fn get_new_data_store() -> Result<DataStorage> {
+// The temp dir is returned so it's not removed while the store is in use
+fn get_new_data_store(kind: StorageBackendKind) -> Result<(TempDir, DataStorage)> {
     let random_filename: String = thread_rng()
         .sample_iter(&Alphanumeric)
         .take(7)
@@ -369,7 +386,7 @@ fn get_new_data_store() -> Result<DataStorage> {
     let max_capacity = config.max_capacity();
 
     let used_space = UsedSpace::new(max_capacity);
-    let store = DataStorage::new(&storage_dir, used_space)?;
+    let store = DataStorage::with_backend(&storage_dir, used_space, kind)?;
 
-    Ok(store)
+    Ok((root_dir, store))
 }
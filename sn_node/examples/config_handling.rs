@@ -147,6 +147,65 @@ async fn main() -> Result<()> {
         )
     }
 
+    assert_eq!(command_line_args.storage_backend, config.storage_backend);
+
+    if command_line_args.conns_per_ip_per_sec.is_some() {
+        assert_eq!(
+            command_line_args.conns_per_ip_per_sec,
+            config.conns_per_ip_per_sec
+        )
+    } else {
+        assert_eq!(
+            file_config.conns_per_ip_per_sec,
+            config.conns_per_ip_per_sec
+        )
+    }
+
+    if command_line_args.client_msgs_per_ip_per_sec.is_some() {
+        assert_eq!(
+            command_line_args.client_msgs_per_ip_per_sec,
+            config.client_msgs_per_ip_per_sec
+        )
+    } else {
+        assert_eq!(
+            file_config.client_msgs_per_ip_per_sec,
+            config.client_msgs_per_ip_per_sec
+        )
+    }
+
+    if command_line_args.client_msgs_per_key_per_sec.is_some() {
+        assert_eq!(
+            command_line_args.client_msgs_per_key_per_sec,
+            config.client_msgs_per_key_per_sec
+        )
+    } else {
+        assert_eq!(
+            file_config.client_msgs_per_key_per_sec,
+            config.client_msgs_per_key_per_sec
+        )
+    }
+
+    if command_line_args.malformed_msgs_before_ban.is_some() {
+        assert_eq!(
+            command_line_args.malformed_msgs_before_ban,
+            config.malformed_msgs_before_ban
+        )
+    } else {
+        assert_eq!(
+            file_config.malformed_msgs_before_ban,
+            config.malformed_msgs_before_ban
+        )
+    }
+
+    if command_line_args.ban_duration_secs.is_some() {
+        assert_eq!(
+            command_line_args.ban_duration_secs,
+            config.ban_duration_secs
+        )
+    } else {
+        assert_eq!(file_config.ban_duration_secs, config.ban_duration_secs)
+    }
+
     clear_disk_config().await?;
 
     Ok(())
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::MsgFromPeer;
+use super::{rate_limiter::RateLimiter, MsgFromPeer};
 
 use sn_interface::{
     messaging::{MsgKind, WireMsg},
@@ -37,16 +37,19 @@ pub(crate) enum ListenerEvent {
 pub(crate) struct MsgListener {
     connection_events: mpsc::Sender<ListenerEvent>,
     receive_msg: mpsc::Sender<MsgFromPeer>,
+    rate_limiter: RateLimiter,
 }
 
 impl MsgListener {
     pub(crate) fn new(
         connection_events: mpsc::Sender<ListenerEvent>,
         receive_msg: mpsc::Sender<MsgFromPeer>,
+        rate_limiter: RateLimiter,
     ) -> Self {
         Self {
             connection_events,
             receive_msg,
+            rate_limiter,
         }
     }
 
@@ -78,17 +81,40 @@ impl MsgListener {
                         "New msg arrived over conn_id={conn_id} from {remote_address:?}{stream_info}"
                     );
 
+                    // the IP could have been banned while this connection was open
+                    if self.rate_limiter.is_banned(&remote_address.ip()) {
+                        debug!("Closing conn_id={conn_id} with banned {remote_address:?}");
+                        conn.close(Some("Banned".to_string()));
+                        break;
+                    }
+
                     let wire_msg = match WireMsg::from(msg_bytes) {
                         Ok(wire_msg) => wire_msg,
                         Err(error) => {
-                            // TODO: should perhaps rather drop this connection.. as it is a spam vector
                             debug!("Failed to deserialize message received from {remote_address:?}{stream_info}: {error:?}");
+                            // this is a spam vector, repeated offenders get banned
+                            if self.rate_limiter.record_malformed_msg(remote_address.ip()) {
+                                conn.close(Some("Banned".to_string()));
+                                break;
+                            }
                             continue;
                         }
                     };
                     let mut is_from_client = false;
                     let src_name = match wire_msg.kind() {
                         MsgKind::Client(auth) => {
+                            // the signature is yet to be verified, so only the budget of the
+                            // IP is charged here, the one of the client key is charged once verified
+                            if !self
+                                .rate_limiter
+                                .allow_client_msg_from_ip(remote_address.ip())
+                            {
+                                debug!(
+                                    "Dropping msg {:?} from client {remote_address:?}{stream_info}, rate limit exceeded",
+                                    wire_msg.msg_id()
+                                );
+                                continue;
+                            }
                             is_from_client = true;
                             auth.public_key.into()
                         }
@@ -9,11 +9,19 @@
 mod link;
 mod listener;
 mod peer_session;
+mod rate_limiter;
 
 use self::{
     link::Link,
     listener::{ListenerEvent, MsgListener},
     peer_session::{PeerSession, SendStatus, SendWatcher},
+    rate_limiter::{RateLimiter, RateLimiterStats},
+};
+
+pub(crate) use self::rate_limiter::{
+    Rate, RateLimits, DEFAULT_BAN_DURATION, DEFAULT_CLIENT_MSGS_PER_IP_PER_SEC,
+    DEFAULT_CLIENT_MSGS_PER_KEY_PER_SEC, DEFAULT_CONNS_PER_IP_PER_SEC,
+    DEFAULT_MALFORMED_MSGS_BEFORE_BAN,
 };
 
 use crate::node::{Error, Result, STANDARD_CHANNEL_SIZE};
@@ -21,7 +29,7 @@ use qp2p::{Connection, SendStream, UsrMsgBytes};
 
 use sn_interface::{
     messaging::{MsgId, WireMsg},
-    types::Peer,
+    types::{Peer, PublicKey},
 };
 
 use dashmap::DashMap;
@@ -41,6 +49,7 @@ pub(crate) struct Comm {
     pub(crate) our_endpoint: Endpoint,
     msg_listener: MsgListener,
     sessions: Arc<DashMap<Peer, PeerSession>>,
+    rate_limiter: RateLimiter,
 }
 
 impl Comm {
@@ -48,6 +57,7 @@ impl Comm {
     pub(crate) async fn new(
         local_addr: SocketAddr,
         config: qp2p::Config,
+        rate_limits: RateLimits,
         incoming_msg_pipe: Sender<MsgFromPeer>,
     ) -> Result<Self> {
         // Doesn't bootstrap, just creates an endpoint to listen to
@@ -55,7 +65,12 @@ impl Comm {
         let (our_endpoint, incoming_connections, _) =
             Endpoint::new_peer(local_addr, Default::default(), config).await?;
 
-        let (comm, _) = setup_comms(our_endpoint, incoming_connections, incoming_msg_pipe);
+        let (comm, _) = setup_comms(
+            our_endpoint,
+            incoming_connections,
+            RateLimiter::new(rate_limits),
+            incoming_msg_pipe,
+        );
 
         Ok(comm)
     }
@@ -64,6 +79,16 @@ impl Comm {
         self.our_endpoint.public_addr()
     }
 
+    /// Whether a `ClientMsg` whose signature by the client key has been verified shall be handled.
+    pub(crate) fn allow_client_msg_from(&self, client: PublicKey) -> bool {
+        self.rate_limiter.allow_client_msg_from_key(client)
+    }
+
+    /// Counters of the incoming traffic rejected so far by our spam protection.
+    pub(crate) fn rate_limiter_stats(&self) -> RateLimiterStats {
+        self.rate_limiter.stats()
+    }
+
     /// Fake function used as replacement for testing only.
     #[cfg(test)]
     pub(crate) async fn is_reachable(&self, _peer: &SocketAddr) -> Result<(), Error> {
@@ -335,25 +360,31 @@ impl Comm {
 fn setup_comms(
     our_endpoint: Endpoint,
     incoming_connections: IncomingConnections,
+    rate_limiter: RateLimiter,
     incoming_msg_pipe: Sender<MsgFromPeer>,
 ) -> (Comm, MsgListener) {
-    let (comm, msg_listener) = setup(our_endpoint, incoming_msg_pipe);
+    let (comm, msg_listener) = setup(our_endpoint, rate_limiter.clone(), incoming_msg_pipe);
 
-    listen_for_incoming_msgs(msg_listener.clone(), incoming_connections);
+    listen_for_incoming_msgs(msg_listener.clone(), rate_limiter, incoming_connections);
 
     (comm, msg_listener)
 }
 
 #[tracing::instrument(skip_all)]
-fn setup(our_endpoint: Endpoint, receive_msg: Sender<MsgFromPeer>) -> (Comm, MsgListener) {
+fn setup(
+    our_endpoint: Endpoint,
+    rate_limiter: RateLimiter,
+    receive_msg: Sender<MsgFromPeer>,
+) -> (Comm, MsgListener) {
     let (add_connection, conn_events_recv) = mpsc::channel(STANDARD_CHANNEL_SIZE);
 
-    let msg_listener = MsgListener::new(add_connection, receive_msg);
+    let msg_listener = MsgListener::new(add_connection, receive_msg, rate_limiter.clone());
 
     let comm = Comm {
         our_endpoint,
         msg_listener: msg_listener.clone(),
         sessions: Arc::new(DashMap::new()),
+        rate_limiter,
     };
 
     let _ = task::spawn(receive_conns(comm.clone(), conn_events_recv));
@@ -378,6 +409,7 @@ async fn receive_conns(comm: Comm, mut conn_events_recv: Receiver<ListenerEvent>
 #[tracing::instrument(skip_all)]
 fn listen_for_incoming_msgs(
     msg_listener: MsgListener,
+    rate_limiter: RateLimiter,
     mut incoming_connections: IncomingConnections,
 ) {
     let _ = task::spawn(async move {
@@ -388,6 +420,16 @@ fn listen_for_incoming_msgs(
                 connection.id()
             );
 
+            if !rate_limiter.allow_conn(connection.remote_address().ip()) {
+                debug!(
+                    "Rejecting connection {:?} from {:?}",
+                    connection.id(),
+                    connection.remote_address()
+                );
+                connection.close(Some("Rate limit exceeded".to_string()));
+                continue;
+            }
+
             msg_listener.listen(Arc::new(connection), incoming_msgs);
         }
     });
@@ -413,6 +455,7 @@ mod tests {
     };
 
     use assert_matches::assert_matches;
+    use bytes::Bytes;
     use eyre::Result;
     use futures::future;
     use qp2p::Config;
@@ -424,7 +467,7 @@ mod tests {
     #[tokio::test]
     async fn successful_send() -> Result<()> {
         let (tx, _rx) = mpsc::channel(1);
-        let comm = Comm::new(local_addr(), Config::default(), tx).await?;
+        let comm = Comm::new(local_addr(), Config::default(), RateLimits::default(), tx).await?;
 
         let (peer0, mut rx0) = new_peer().await?;
         let (peer1, mut rx1) = new_peer().await?;
@@ -459,6 +502,7 @@ mod tests {
                 idle_timeout: Some(Duration::from_millis(1)),
                 ..Config::default()
             },
+            RateLimits::default(),
             tx,
         )
         .await?;
@@ -478,7 +522,8 @@ mod tests {
     #[tokio::test]
     async fn send_after_reconnect() -> Result<()> {
         let (tx, _rx) = mpsc::channel(1);
-        let send_comm = Comm::new(local_addr(), Config::default(), tx).await?;
+        let send_comm =
+            Comm::new(local_addr(), Config::default(), RateLimits::default(), tx).await?;
 
         let (recv_endpoint, mut incoming_connections, _) =
             Endpoint::new_peer(local_addr(), &[], Config::default()).await?;
@@ -527,10 +572,16 @@ mod tests {
     #[tokio::test]
     async fn incoming_connection_lost() -> Result<()> {
         let (tx, mut rx0) = mpsc::channel(1);
-        let comm0 = Comm::new(local_addr(), Config::default(), tx.clone()).await?;
+        let comm0 = Comm::new(
+            local_addr(),
+            Config::default(),
+            RateLimits::default(),
+            tx.clone(),
+        )
+        .await?;
         let addr0 = comm0.socket_addr();
 
-        let comm1 = Comm::new(local_addr(), Config::default(), tx).await?;
+        let comm1 = Comm::new(local_addr(), Config::default(), RateLimits::default(), tx).await?;
 
         let peer = Peer::new(xor_name::rand::random(), addr0);
         let msg = new_test_msg(dst(peer))?;
@@ -549,6 +600,128 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn client_msgs_over_the_rate_limit_are_dropped() -> Result<()> {
+        let rate_limits = RateLimits {
+            client_msgs_per_ip: Rate {
+                per_sec: 1,
+                burst: 2,
+            },
+            ..RateLimits::default()
+        };
+        let (tx, mut rx0) = mpsc::channel(10);
+        let comm0 = Comm::new(local_addr(), Config::default(), rate_limits, tx).await?;
+        let peer = Peer::new(xor_name::rand::random(), comm0.socket_addr());
+
+        let (tx, _rx1) = mpsc::channel(1);
+        let comm1 = Comm::new(local_addr(), Config::default(), RateLimits::default(), tx).await?;
+
+        // all msgs come from the same IP, even if signed by different clients
+        for _ in 0..5 {
+            let msg = new_test_msg(dst(peer))?;
+            comm1
+                .send_out_bytes(peer, msg.msg_id(), msg.serialize()?, None)
+                .await?;
+        }
+
+        let mut received = 0;
+        while let Ok(Some(_)) = time::timeout(TIMEOUT, rx0.recv()).await {
+            received += 1;
+        }
+        assert_eq!(received, 2);
+        assert_eq!(comm0.rate_limiter_stats().client_msgs_throttled, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn forged_client_msgs_dont_use_up_the_client_budget() -> Result<()> {
+        let rate_limits = RateLimits {
+            client_msgs_per_key: Rate {
+                per_sec: 1,
+                burst: 2,
+            },
+            ..RateLimits::default()
+        };
+        let (tx, mut rx0) = mpsc::channel(10);
+        let comm0 = Comm::new(local_addr(), Config::default(), rate_limits, tx).await?;
+        let peer = Peer::new(xor_name::rand::random(), comm0.socket_addr());
+
+        let (tx, _rx1) = mpsc::channel(1);
+        let comm1 = Comm::new(local_addr(), Config::default(), RateLimits::default(), tx).await?;
+
+        // msgs claiming to be signed by the client, but signed by someone else
+        let client = Keypair::new_ed25519();
+        let forger = Keypair::new_ed25519();
+        for _ in 0..5 {
+            let msg = new_test_msg_from(&forger, dst(peer))?;
+            let forged_auth = ClientAuth {
+                public_key: client.public_key(),
+                signature: forger.sign(&msg.payload),
+            };
+            let msg = WireMsg::new_msg(
+                msg.msg_id(),
+                msg.payload,
+                MsgKind::Client(forged_auth),
+                dst(peer),
+            );
+            comm1
+                .send_out_bytes(peer, msg.msg_id(), msg.serialize()?, None)
+                .await?;
+        }
+
+        // they are handed over to be verified, without charging the budget of the client
+        let mut received = 0;
+        while let Ok(Some(_)) = time::timeout(TIMEOUT, rx0.recv()).await {
+            received += 1;
+        }
+        assert_eq!(received, 5);
+        assert!(comm0.allow_client_msg_from(client.public_key()));
+        assert!(comm0.allow_client_msg_from(client.public_key()));
+        assert!(!comm0.allow_client_msg_from(client.public_key()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn malformed_msgs_get_the_sender_banned() -> Result<()> {
+        let rate_limits = RateLimits {
+            malformed_msgs_before_ban: 2,
+            ..RateLimits::default()
+        };
+        let (tx, mut rx) = mpsc::channel(1);
+        let comm = Comm::new(local_addr(), Config::default(), rate_limits, tx).await?;
+        let addr = comm.socket_addr();
+
+        let endpoint = Endpoint::new_client(local_addr(), Config::default())?;
+        let (conn, _incoming_msgs) = endpoint.connect_to(&addr).await?;
+        for _ in 0..2 {
+            conn.send((
+                Bytes::from_static(b"spam"),
+                Bytes::from_static(b"spam"),
+                Bytes::from_static(b"spam"),
+            ))
+            .await?;
+        }
+
+        time::timeout(TIMEOUT, async {
+            while comm.rate_limiter_stats().bans == 0 {
+                time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+
+        // now even valid msgs over new connections are rejected
+        let msg = new_test_msg(dst(Peer::new(xor_name::rand::random(), addr)))?;
+        if let Ok((conn, _incoming_msgs)) = endpoint.connect_to(&addr).await {
+            let _ = conn.send(msg.serialize()?).await;
+        }
+        assert_matches!(time::timeout(TIMEOUT, rx.recv()).await, Err(_));
+        assert!(comm.rate_limiter_stats().rejected_while_banned > 0);
+
+        Ok(())
+    }
+
     fn dst(peer: Peer) -> Dst {
         Dst {
             name: peer.name(),
@@ -557,8 +730,10 @@ mod tests {
     }
 
     fn new_test_msg(dst: Dst) -> Result<WireMsg> {
-        let src_keypair = Keypair::new_ed25519();
+        new_test_msg_from(&Keypair::new_ed25519(), dst)
+    }
 
+    fn new_test_msg_from(src_keypair: &Keypair, dst: Dst) -> Result<WireMsg> {
         let query = DataQueryVariant::GetChunk(ChunkAddress(xor_name::rand::random()));
         let query = DataQuery {
             adult_index: 0,
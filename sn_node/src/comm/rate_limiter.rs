@@ -0,0 +1,447 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::types::{log_markers::LogMarker, PublicKey};
+
+use dashmap::DashMap;
+use std::{
+    hash::Hash,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Default sustained rate of new incoming connections allowed from a single IP.
+pub(crate) const DEFAULT_CONNS_PER_IP_PER_SEC: u32 = 50;
+/// Default sustained rate of `ClientMsg`s allowed from a single IP.
+pub(crate) const DEFAULT_CLIENT_MSGS_PER_IP_PER_SEC: u32 = 500;
+/// Default sustained rate of `ClientMsg`s allowed from a single client key.
+pub(crate) const DEFAULT_CLIENT_MSGS_PER_KEY_PER_SEC: u32 = 100;
+/// Default number of undecodable msgs we tolerate from an IP before banning it.
+pub(crate) const DEFAULT_MALFORMED_MSGS_BEFORE_BAN: u32 = 5;
+/// Default duration of a ban.
+pub(crate) const DEFAULT_BAN_DURATION: Duration = Duration::from_secs(600);
+
+// Number of seconds worth of traffic a bucket can absorb in a burst.
+const BURST_SECS: u32 = 5;
+// Beyond this number of tracked IPs/keys we drop the idle ones,
+// so the limiter itself can't be used to exhaust our memory.
+const MAX_TRACKED_ENTRIES: usize = 10_000;
+// A bucket untouched for this long is full again, thus it's safe to forget about it.
+const IDLE_ENTRY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Limits applied to the traffic received by `Comm`.
+/// A rate of 0 disables the corresponding limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RateLimits {
+    pub(crate) conns_per_ip: Rate,
+    pub(crate) client_msgs_per_ip: Rate,
+    pub(crate) client_msgs_per_key: Rate,
+    /// Number of undecodable msgs after which the sender IP is banned. 0 disables bans.
+    pub(crate) malformed_msgs_before_ban: u32,
+    pub(crate) ban_duration: Duration,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            conns_per_ip: Rate::per_sec(DEFAULT_CONNS_PER_IP_PER_SEC),
+            client_msgs_per_ip: Rate::per_sec(DEFAULT_CLIENT_MSGS_PER_IP_PER_SEC),
+            client_msgs_per_key: Rate::per_sec(DEFAULT_CLIENT_MSGS_PER_KEY_PER_SEC),
+            malformed_msgs_before_ban: DEFAULT_MALFORMED_MSGS_BEFORE_BAN,
+            ban_duration: DEFAULT_BAN_DURATION,
+        }
+    }
+}
+
+/// Sustained rate and burst capacity of a token bucket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rate {
+    pub(crate) per_sec: u32,
+    pub(crate) burst: u32,
+}
+
+impl Rate {
+    /// A rate allowing bursts of a few seconds worth of traffic.
+    pub(crate) fn per_sec(per_sec: u32) -> Self {
+        Self {
+            per_sec,
+            burst: per_sec.saturating_mul(BURST_SECS),
+        }
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.per_sec == 0
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: Rate, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(rate.burst),
+            last_refill: now,
+        }
+    }
+
+    /// Refills the bucket for the time elapsed and then tries to take a token from it.
+    fn try_take(&mut self, rate: Rate, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * f64::from(rate.per_sec)).min(f64::from(rate.burst));
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Counters of the traffic rejected by the `RateLimiter`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RateLimiterStats {
+    pub(crate) conns_throttled: u64,
+    pub(crate) client_msgs_throttled: u64,
+    pub(crate) malformed_msgs: u64,
+    pub(crate) bans: u64,
+    pub(crate) rejected_while_banned: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    conns_throttled: AtomicU64,
+    client_msgs_throttled: AtomicU64,
+    malformed_msgs: AtomicU64,
+    bans: AtomicU64,
+    rejected_while_banned: AtomicU64,
+}
+
+impl Counters {
+    fn incr(counter: &AtomicU64) -> u64 {
+        counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Per-IP and per-client-key token buckets guarding the node's listener,
+/// plus temporary bans of IPs sending us msgs we can't even deserialise.
+#[derive(Clone, Debug)]
+pub(crate) struct RateLimiter {
+    limits: RateLimits,
+    conn_buckets: Arc<DashMap<IpAddr, TokenBucket>>,
+    ip_msg_buckets: Arc<DashMap<IpAddr, TokenBucket>>,
+    key_msg_buckets: Arc<DashMap<PublicKey, TokenBucket>>,
+    malformed_msgs: Arc<DashMap<IpAddr, u32>>,
+    bans: Arc<DashMap<IpAddr, Instant>>,
+    counters: Arc<Counters>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            conn_buckets: Arc::new(DashMap::new()),
+            ip_msg_buckets: Arc::new(DashMap::new()),
+            key_msg_buckets: Arc::new(DashMap::new()),
+            malformed_msgs: Arc::new(DashMap::new()),
+            bans: Arc::new(DashMap::new()),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Current values of the rejection counters.
+    pub(crate) fn stats(&self) -> RateLimiterStats {
+        let c = &self.counters;
+        RateLimiterStats {
+            conns_throttled: c.conns_throttled.load(Ordering::Relaxed),
+            client_msgs_throttled: c.client_msgs_throttled.load(Ordering::Relaxed),
+            malformed_msgs: c.malformed_msgs.load(Ordering::Relaxed),
+            bans: c.bans.load(Ordering::Relaxed),
+            rejected_while_banned: c.rejected_while_banned.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Is the IP currently banned? Expired bans are lifted here.
+    pub(crate) fn is_banned(&self, ip: &IpAddr) -> bool {
+        self.is_banned_at(ip, Instant::now())
+    }
+
+    fn is_banned_at(&self, ip: &IpAddr, now: Instant) -> bool {
+        let expired = match self.bans.get(ip) {
+            None => return false,
+            Some(until) => *until <= now,
+        };
+
+        if expired {
+            let _ = self.bans.remove(ip);
+            info!("Ban of {ip} has expired");
+            false
+        } else {
+            let total = Counters::incr(&self.counters.rejected_while_banned);
+            trace!(
+                "{} from {ip} (total: {total})",
+                LogMarker::RejectedBannedPeer
+            );
+            true
+        }
+    }
+
+    /// Whether a new incoming connection from the IP shall be accepted.
+    pub(crate) fn allow_conn(&self, ip: IpAddr) -> bool {
+        self.allow_conn_at(ip, Instant::now())
+    }
+
+    fn allow_conn_at(&self, ip: IpAddr, now: Instant) -> bool {
+        if self.is_banned_at(&ip, now) {
+            return false;
+        }
+
+        let allowed = take_token(&self.conn_buckets, ip, self.limits.conns_per_ip, now);
+        if !allowed {
+            let total = Counters::incr(&self.counters.conns_throttled);
+            debug!(
+                "{} from {ip} (total: {total})",
+                LogMarker::IncomingConnThrottled
+            );
+        }
+        allowed
+    }
+
+    /// Whether a `ClientMsg` received from the IP shall be handled. This is checked before
+    /// the msg signature is verified, thus the client key it claims cannot be trusted yet.
+    pub(crate) fn allow_client_msg_from_ip(&self, ip: IpAddr) -> bool {
+        self.allow_client_msg_from_ip_at(ip, Instant::now())
+    }
+
+    fn allow_client_msg_from_ip_at(&self, ip: IpAddr, now: Instant) -> bool {
+        let allowed = take_token(
+            &self.ip_msg_buckets,
+            ip,
+            self.limits.client_msgs_per_ip,
+            now,
+        );
+
+        if !allowed {
+            let total = Counters::incr(&self.counters.client_msgs_throttled);
+            debug!(
+                "{} from {ip} (total: {total})",
+                LogMarker::ClientMsgThrottled
+            );
+        }
+        allowed
+    }
+
+    /// Whether a `ClientMsg` signed by the client key shall be handled. This shall only be
+    /// checked once the msg signature has been verified, otherwise anyone could use up the
+    /// budget of a client by sending msgs claiming to be signed by its key.
+    pub(crate) fn allow_client_msg_from_key(&self, client: PublicKey) -> bool {
+        self.allow_client_msg_from_key_at(client, Instant::now())
+    }
+
+    fn allow_client_msg_from_key_at(&self, client: PublicKey, now: Instant) -> bool {
+        let allowed = take_token(
+            &self.key_msg_buckets,
+            client,
+            self.limits.client_msgs_per_key,
+            now,
+        );
+
+        if !allowed {
+            let total = Counters::incr(&self.counters.client_msgs_throttled);
+            debug!(
+                "{} with key {client:?} (total: {total})",
+                LogMarker::ClientMsgThrottled
+            );
+        }
+        allowed
+    }
+
+    /// Records a msg from the IP which failed to deserialise.
+    /// Returns `true` if the IP got banned as a result of it.
+    pub(crate) fn record_malformed_msg(&self, ip: IpAddr) -> bool {
+        self.record_malformed_msg_at(ip, Instant::now())
+    }
+
+    fn record_malformed_msg_at(&self, ip: IpAddr, now: Instant) -> bool {
+        let total = Counters::incr(&self.counters.malformed_msgs);
+        debug!(
+            "{} from {ip} (total: {total})",
+            LogMarker::MalformedMsgReceived
+        );
+
+        let threshold = self.limits.malformed_msgs_before_ban;
+        if threshold == 0 {
+            return false;
+        }
+
+        let count = {
+            let mut count = self.malformed_msgs.entry(ip).or_insert(0);
+            *count += 1;
+            *count
+        };
+
+        if count < threshold {
+            return false;
+        }
+
+        let _ = self.malformed_msgs.remove(&ip);
+        let _ = self.bans.insert(ip, now + self.limits.ban_duration);
+        let total = Counters::incr(&self.counters.bans);
+        warn!(
+            "{} {ip} for {:?} after {count} malformed msgs (total bans: {total}), stats: {:?}",
+            LogMarker::PeerBanned,
+            self.limits.ban_duration,
+            self.stats()
+        );
+        true
+    }
+}
+
+// Takes a token from the bucket of the given key, creating it if it doesn't exist yet.
+fn take_token<K: Eq + Hash + Copy>(
+    buckets: &DashMap<K, TokenBucket>,
+    key: K,
+    rate: Rate,
+    now: Instant,
+) -> bool {
+    if rate.is_disabled() {
+        return true;
+    }
+
+    if buckets.len() >= MAX_TRACKED_ENTRIES && !buckets.contains_key(&key) {
+        buckets.retain(|_, bucket| {
+            now.saturating_duration_since(bucket.last_refill) < IDLE_ENTRY_TIMEOUT
+        });
+    }
+
+    buckets
+        .entry(key)
+        .or_insert_with(|| TokenBucket::new(rate, now))
+        .try_take(rate, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sn_interface::types::Keypair;
+    use std::net::Ipv4Addr;
+
+    fn limits(per_sec: u32, burst: u32) -> RateLimits {
+        let rate = Rate { per_sec, burst };
+        RateLimits {
+            conns_per_ip: rate,
+            client_msgs_per_ip: rate,
+            client_msgs_per_key: rate,
+            malformed_msgs_before_ban: 3,
+            ban_duration: Duration::from_secs(10),
+        }
+    }
+
+    fn ip(last_octet: u8) -> IpAddr {
+        Ipv4Addr::new(10, 0, 0, last_octet).into()
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_refills_over_time() {
+        let limiter = RateLimiter::new(limits(2, 4));
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            assert!(limiter.allow_conn_at(ip(1), now));
+        }
+        assert!(!limiter.allow_conn_at(ip(1), now));
+
+        // other IPs have their own budget
+        assert!(limiter.allow_conn_at(ip(2), now));
+
+        // after a second two more tokens are available
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.allow_conn_at(ip(1), later));
+        assert!(limiter.allow_conn_at(ip(1), later));
+        assert!(!limiter.allow_conn_at(ip(1), later));
+
+        // the bucket never holds more than the burst
+        let much_later = now + Duration::from_secs(3600);
+        for _ in 0..4 {
+            assert!(limiter.allow_conn_at(ip(1), much_later));
+        }
+        assert!(!limiter.allow_conn_at(ip(1), much_later));
+
+        assert_eq!(limiter.stats().conns_throttled, 3);
+    }
+
+    #[test]
+    fn client_msgs_are_limited_per_key_and_per_ip() {
+        let limiter = RateLimiter::new(limits(1, 2));
+        let now = Instant::now();
+        let client = Keypair::new_ed25519().public_key();
+
+        // the key budget applies regardless of the IP the msgs come from
+        assert!(limiter.allow_client_msg_from_key_at(client, now));
+        assert!(limiter.allow_client_msg_from_key_at(client, now));
+        assert!(!limiter.allow_client_msg_from_key_at(client, now));
+
+        // while a different key is still fine
+        let other_client = Keypair::new_ed25519().public_key();
+        assert!(limiter.allow_client_msg_from_key_at(other_client, now));
+
+        // the IP budget is independent from the keys budget
+        assert!(limiter.allow_client_msg_from_ip_at(ip(1), now));
+        assert!(limiter.allow_client_msg_from_ip_at(ip(1), now));
+        assert!(!limiter.allow_client_msg_from_ip_at(ip(1), now));
+        assert!(limiter.allow_client_msg_from_ip_at(ip(2), now));
+        assert_eq!(limiter.stats().client_msgs_throttled, 2);
+    }
+
+    #[test]
+    fn zero_rate_disables_limit() {
+        let limiter = RateLimiter::new(limits(0, 0));
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(limiter.allow_conn_at(ip(1), now));
+        }
+        assert_eq!(limiter.stats(), RateLimiterStats::default());
+    }
+
+    #[test]
+    fn malformed_msgs_lead_to_temporary_ban() {
+        let limiter = RateLimiter::new(limits(100, 100));
+        let now = Instant::now();
+
+        assert!(!limiter.record_malformed_msg_at(ip(1), now));
+        assert!(!limiter.record_malformed_msg_at(ip(1), now));
+        assert!(!limiter.is_banned_at(&ip(1), now));
+        assert!(limiter.record_malformed_msg_at(ip(1), now));
+
+        assert!(limiter.is_banned_at(&ip(1), now));
+        assert!(!limiter.allow_conn_at(ip(1), now));
+        assert!(!limiter.is_banned_at(&ip(2), now));
+
+        // the ban is lifted once expired
+        let later = now + Duration::from_secs(10);
+        assert!(!limiter.is_banned_at(&ip(1), later));
+        assert!(limiter.allow_conn_at(ip(1), later));
+
+        let stats = limiter.stats();
+        assert_eq!(stats.malformed_msgs, 3);
+        assert_eq!(stats.bans, 1);
+        assert_eq!(stats.rejected_while_banned, 2);
+    }
+}
@@ -7,12 +7,9 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::comm::Comm;
-use crate::{
-    node::{
-        flow_ctrl::{cmds::Cmd, dysfunction::DysCmds},
-        Error, MyNode, Result,
-    },
-    UsedSpace,
+use crate::node::{
+    flow_ctrl::{cmds::Cmd, dysfunction::DysCmds},
+    DataStorage, Error, MyNode, Result,
 };
 
 use sn_interface::{
@@ -33,7 +30,7 @@ impl MyNode {
     pub(crate) async fn first_node(
         comm: Comm,
         keypair: Arc<Keypair>,
-        used_space: UsedSpace,
+        data_storage: DataStorage,
         root_storage_dir: PathBuf,
         genesis_sk_set: bls::SecretKeySet,
         dysfunction_cmds_sender: mpsc::Sender<DysCmds>,
@@ -55,7 +52,7 @@ impl MyNode {
             keypair.clone(),
             network_knowledge,
             Some(section_key_share),
-            used_space,
+            data_storage,
             root_storage_dir,
             dysfunction_cmds_sender,
         )
@@ -6,13 +6,22 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{Error, NetworkConfig, Result};
+use crate::{
+    comm::{
+        Rate, RateLimits, DEFAULT_BAN_DURATION, DEFAULT_CLIENT_MSGS_PER_IP_PER_SEC,
+        DEFAULT_CLIENT_MSGS_PER_KEY_PER_SEC, DEFAULT_CONNS_PER_IP_PER_SEC,
+        DEFAULT_MALFORMED_MSGS_BEFORE_BAN,
+    },
+    node::{Error, NetworkConfig, Result},
+    StorageBackendKind,
+};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::{
     io::{self},
     net::SocketAddr,
     path::PathBuf,
+    time::Duration,
 };
 use tokio::{
     fs::{self, File},
@@ -131,6 +140,38 @@ pub struct Config {
     /// Duration of a UPnP port mapping.
     #[clap(long)]
     pub upnp_lease_duration: Option<u32>,
+    /// Backend used to store the data this node is responsible for.
+    #[clap(long, value_enum, default_value = "file-system")]
+    #[serde(default)]
+    pub storage_backend: StorageBackendKind,
+    /// Maximum sustained rate of new incoming connections per second accepted from a single IP
+    /// address, bursts of a few seconds worth of connections are tolerated. If none supplied
+    /// we'll default to the documented constant.
+    ///
+    /// A value of 0 disables this limit.
+    #[clap(long)]
+    pub conns_per_ip_per_sec: Option<u32>,
+    /// Maximum sustained rate of client messages per second handled from a single IP address.
+    /// If none supplied we'll default to the documented constant.
+    ///
+    /// A value of 0 disables this limit.
+    #[clap(long)]
+    pub client_msgs_per_ip_per_sec: Option<u32>,
+    /// Maximum sustained rate of client messages per second handled from a single client key.
+    /// If none supplied we'll default to the documented constant.
+    ///
+    /// A value of 0 disables this limit.
+    #[clap(long)]
+    pub client_msgs_per_key_per_sec: Option<u32>,
+    /// Number of messages which cannot be deserialised we tolerate from an IP address before
+    /// temporarily banning it. If none supplied we'll default to the documented constant.
+    ///
+    /// A value of 0 disables bans.
+    #[clap(long)]
+    pub malformed_msgs_before_ban: Option<u32>,
+    /// Duration of a ban, in seconds. If none supplied we'll default to the documented constant.
+    #[clap(long)]
+    pub ban_duration_secs: Option<u64>,
     #[clap(skip)]
     #[allow(missing_docs)]
     pub network_config: NetworkConfig,
@@ -256,6 +297,24 @@ impl Config {
         if let Some(keep_alive_interval_msec) = config.keep_alive_interval_msec {
             self.keep_alive_interval_msec = Some(keep_alive_interval_msec);
         }
+
+        self.storage_backend = config.storage_backend;
+
+        if let Some(rate) = config.conns_per_ip_per_sec {
+            self.conns_per_ip_per_sec = Some(rate);
+        }
+        if let Some(rate) = config.client_msgs_per_ip_per_sec {
+            self.client_msgs_per_ip_per_sec = Some(rate);
+        }
+        if let Some(rate) = config.client_msgs_per_key_per_sec {
+            self.client_msgs_per_key_per_sec = Some(rate);
+        }
+        if let Some(count) = config.malformed_msgs_before_ban {
+            self.malformed_msgs_before_ban = Some(count);
+        }
+        if let Some(secs) = config.ban_duration_secs {
+            self.ban_duration_secs = Some(secs);
+        }
     }
 
     /// The address to be credited when this node farms `SafeCoin`.
@@ -278,6 +337,11 @@ impl Config {
         DEFAULT_MAX_CAPACITY
     }
 
+    /// Backend used to store the data this node is responsible for.
+    pub fn storage_backend(&self) -> StorageBackendKind {
+        self.storage_backend
+    }
+
     /// Root directory for dbs and cached state. If not set, it defaults to
     /// `DEFAULT_ROOT_DIR_NAME` within the project's data directory (see `Config::root_dir` for the
     /// directories on each platform).
@@ -320,6 +384,31 @@ impl Config {
         &self.network_config
     }
 
+    /// Limits applied to the incoming connections and client messages.
+    pub(crate) fn rate_limits(&self) -> RateLimits {
+        RateLimits {
+            conns_per_ip: Rate::per_sec(
+                self.conns_per_ip_per_sec
+                    .unwrap_or(DEFAULT_CONNS_PER_IP_PER_SEC),
+            ),
+            client_msgs_per_ip: Rate::per_sec(
+                self.client_msgs_per_ip_per_sec
+                    .unwrap_or(DEFAULT_CLIENT_MSGS_PER_IP_PER_SEC),
+            ),
+            client_msgs_per_key: Rate::per_sec(
+                self.client_msgs_per_key_per_sec
+                    .unwrap_or(DEFAULT_CLIENT_MSGS_PER_KEY_PER_SEC),
+            ),
+            malformed_msgs_before_ban: self
+                .malformed_msgs_before_ban
+                .unwrap_or(DEFAULT_MALFORMED_MSGS_BEFORE_BAN),
+            ban_duration: self
+                .ban_duration_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_BAN_DURATION),
+        }
+    }
+
     /// Set network configuration options.
     pub fn set_network_config(&mut self, config: NetworkConfig) {
         self.network_config = config;
@@ -456,7 +545,7 @@ fn smoke() -> Result<()> {
     // NOTE: IF this value is being changed due to a change in the config,
     // the change in config also be handled in Config::merge()
     // and in examples/config_handling.rs
-    let expected_size = 56;
+    let expected_size = 65;
 
     assert_eq!(bincode::serialize(&Config::default())?.len(), expected_size);
     Ok(())
@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::{Prefix, XorName};
+use sn_dbc::Token;
 use sn_interface::messaging::data::StorageLevel;
 
 use itertools::Itertools;
@@ -15,6 +16,21 @@ use std::collections::{BTreeMap, BTreeSet};
 // The number of separate copies of a chunk which should be maintained.
 pub(crate) const MIN_LEVEL_WHEN_FULL: u8 = 9; // considered full when >= 90 %.
 
+// Price, in nanos, of storing up to a KiB of data in a section with no used space.
+const BASE_PRICE_PER_KIB: u64 = 1_000;
+const KIB: usize = 1024;
+
+/// Price of storing the given amount of bytes in a section with the given avg usage
+/// (a value between 0 and 10), which is doubled for every 10 % of used space.
+pub(crate) fn store_cost(bytes: usize, avg_usage: u8) -> Token {
+    let kibs = bytes.div_ceil(KIB).max(1) as u64;
+    let multiplier = 1_u64 << avg_usage.min(10);
+    Token::from_nano(
+        kibs.saturating_mul(BASE_PRICE_PER_KIB)
+            .saturating_mul(multiplier),
+    )
+}
+
 /// A util for sharing the info on data capacity among the
 /// chunk storing nodes in the section.
 #[derive(Default)]
@@ -109,3 +125,38 @@ impl Capacity {
         self.adult_levels.retain(|name, _| members.contains(name))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{store_cost, BASE_PRICE_PER_KIB};
+
+    use sn_dbc::Token;
+
+    #[test]
+    fn store_cost_is_charged_per_started_kib() {
+        assert_eq!(store_cost(0, 0), Token::from_nano(BASE_PRICE_PER_KIB));
+        assert_eq!(store_cost(1024, 0), Token::from_nano(BASE_PRICE_PER_KIB));
+        assert_eq!(
+            store_cost(1025, 0),
+            Token::from_nano(2 * BASE_PRICE_PER_KIB)
+        );
+    }
+
+    #[test]
+    fn store_cost_grows_with_used_space() {
+        assert_eq!(
+            store_cost(1024, 1),
+            Token::from_nano(2 * BASE_PRICE_PER_KIB)
+        );
+        assert_eq!(
+            store_cost(1024, 5),
+            Token::from_nano(32 * BASE_PRICE_PER_KIB)
+        );
+        assert_eq!(
+            store_cost(1024, 10),
+            Token::from_nano(1024 * BASE_PRICE_PER_KIB)
+        );
+        // usage is capped to the section being full
+        assert_eq!(store_cost(1024, 20), store_cost(1024, 10));
+    }
+}
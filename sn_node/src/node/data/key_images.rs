@@ -0,0 +1,108 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_dbc::{Hash, KeyImage};
+
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Tracks, as an Elder, the transaction we signed a spent proof share for, for each key image,
+/// so we never sign a share for a different transaction spending the same key image.
+///
+/// A record is only kept until the share is stored by the Adults holding the spentbook of
+/// the key image, as they are checked for any later spend of it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SignedKeyImages {
+    records: Arc<DashMap<KeyImage, Arc<Mutex<Option<Hash>>>>>,
+}
+
+impl SignedKeyImages {
+    /// Locks the record of the key image, waiting for any other spend of it being processed,
+    /// so no two different transactions spending it can be checked and signed concurrently.
+    /// The record holds the hash of the transaction we signed a share for, if any.
+    pub(crate) async fn lock(&self, key_image: KeyImage) -> OwnedMutexGuard<Option<Hash>> {
+        let record = self.records.entry(key_image).or_default().clone();
+        record.lock_owned().await
+    }
+
+    /// Drops the record of the key image once the spent proof share we signed for it
+    /// is stored by the Adults holding its spentbook.
+    pub(crate) fn release_stored(&self, key_image: &KeyImage) {
+        let _ = self.records.remove(key_image);
+    }
+
+    /// Drops the record of the key image if we signed no share for it, e.g. its spend was
+    /// refused, unless another spend of it is being processed.
+    pub(crate) fn release_unsigned(&self, key_image: &KeyImage) {
+        let _ = self.records.remove_if(key_image, |_, record| {
+            // the map holds the only reference when no spend of it is being processed
+            Arc::strong_count(record) == 1
+                && record
+                    .try_lock()
+                    .map(|signed| signed.is_none())
+                    .unwrap_or(false)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedKeyImages;
+
+    use sn_dbc::Hash;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn spends_of_a_key_image_are_processed_one_at_a_time() {
+        let signed = SignedKeyImages::default();
+        let key_image = bls::SecretKey::random().public_key();
+
+        let mut record = signed.lock(key_image).await;
+        assert_eq!(*record, None);
+
+        // the record of the key image cannot be locked while a spend of it is being processed
+        assert!(timeout(Duration::from_millis(100), signed.lock(key_image))
+            .await
+            .is_err());
+        // unlike those of other key images
+        let other_key_image = bls::SecretKey::random().public_key();
+        assert!(
+            timeout(Duration::from_millis(100), signed.lock(other_key_image))
+                .await
+                .is_ok()
+        );
+
+        *record = Some(Hash::from([1; 32]));
+        drop(record);
+
+        assert_eq!(*signed.lock(key_image).await, Some(Hash::from([1; 32])));
+    }
+
+    #[tokio::test]
+    async fn records_are_released_once_not_needed() {
+        let signed = SignedKeyImages::default();
+        let key_image = bls::SecretKey::random().public_key();
+
+        // a record without a signed share is released, unless a spend is being processed
+        let record = signed.lock(key_image).await;
+        signed.release_unsigned(&key_image);
+        assert_eq!(signed.records.len(), 1);
+        drop(record);
+        signed.release_unsigned(&key_image);
+        assert!(signed.records.is_empty());
+
+        // a record with a signed share is kept until the share is stored
+        *signed.lock(key_image).await = Some(Hash::from([1; 32]));
+        signed.release_unsigned(&key_image);
+        assert_eq!(signed.records.len(), 1);
+        signed.release_stored(&key_image);
+        assert!(signed.records.is_empty());
+    }
+}
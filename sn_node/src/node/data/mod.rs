@@ -7,6 +7,10 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod capacity;
+mod key_images;
+mod pruning;
 mod records;
 
-pub(crate) use self::capacity::{Capacity, MIN_LEVEL_WHEN_FULL};
+pub(crate) use self::capacity::{store_cost, Capacity, MIN_LEVEL_WHEN_FULL};
+pub(crate) use self::key_images::SignedKeyImages;
+pub(crate) use self::pruning::{replica_digest, DataPruning, ReplicaDigest};
@@ -0,0 +1,210 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::Result;
+
+use sn_interface::types::{DataAddress, ReplicatedData};
+
+use std::collections::{BTreeMap, BTreeSet};
+use tiny_keccak::{Hasher, Sha3};
+use xor_name::XorName;
+
+/// Digest of the content of a data replica.
+pub(crate) type ReplicaDigest = [u8; 32];
+
+/// Tracks the data we hold but are no longer responsible for, till all the Adults now
+/// responsible for it have confirmed they hold all the content of our replica, so it can be removed.
+#[derive(Debug, Default)]
+pub(crate) struct DataPruning {
+    // Digest of our replica of each data item, and its holders which are yet to confirm they hold it
+    pending: BTreeMap<DataAddress, (ReplicaDigest, BTreeSet<XorName>)>,
+}
+
+impl DataPruning {
+    /// Starts tracking a new set of data to be removed once confirmed by their holders.
+    /// Anything pending from a previous round is dropped, as it was
+    /// computed against what is now an outdated set of Adults.
+    pub(crate) fn reset(
+        &mut self,
+        pending: BTreeMap<DataAddress, (ReplicaDigest, BTreeSet<XorName>)>,
+    ) {
+        self.pending = pending;
+    }
+
+    /// Records the data the given holder confirmed it holds, returning the data which
+    /// has now been confirmed by all its holders, along with the digest of the replica
+    /// they confirmed. Confirmations of a replica other than ours don't count.
+    pub(crate) fn confirm(
+        &mut self,
+        holder: XorName,
+        held: Vec<(DataAddress, ReplicaDigest)>,
+    ) -> Vec<(DataAddress, ReplicaDigest)> {
+        let mut confirmed = vec![];
+        for (address, digest) in held {
+            let all_confirmed = match self.pending.get_mut(&address) {
+                Some((pending_digest, holders)) if *pending_digest == digest => {
+                    let _ = holders.remove(&holder);
+                    holders.is_empty()
+                }
+                _ => false,
+            };
+
+            if all_confirmed {
+                let _ = self.pending.remove(&address);
+                confirmed.push((address, digest));
+            }
+        }
+
+        confirmed
+    }
+}
+
+/// Digest of the content of the replica, regardless of the order its
+/// cmds or shares were read in, so equal replicas have the same digest.
+pub(crate) fn replica_digest(replica: &ReplicatedData) -> Result<ReplicaDigest> {
+    let mut items = match replica {
+        ReplicatedData::RegisterLog(log) => log
+            .op_log
+            .iter()
+            .map(bincode::serialize)
+            .collect::<bincode::Result<Vec<_>>>()?,
+        // the spent transaction is left out, as the shares are signed for its hash
+        ReplicatedData::SpentbookLog(log) => log
+            .spent_proof_shares
+            .iter()
+            .map(bincode::serialize)
+            .collect::<bincode::Result<Vec<_>>>()?,
+        other => vec![bincode::serialize(other)?],
+    };
+    items.sort();
+
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    hasher.update(&bincode::serialize(&replica.address())?);
+    for item in items {
+        hasher.update(&item);
+    }
+    hasher.finalize(&mut output);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replica_digest, DataPruning};
+
+    use sn_interface::types::{
+        ChunkAddress, DataAddress, RegisterAddress, ReplicatedData, ReplicatedSpentbookLog,
+        SpentbookAddress,
+    };
+
+    use eyre::Result;
+    use sn_dbc::{Hash, IndexedSignatureShare, SpentProofContent, SpentProofShare};
+    use std::collections::{BTreeMap, BTreeSet};
+    use xor_name::{rand::random, XorName};
+
+    #[test]
+    fn data_is_confirmed_once_all_holders_hold_it() {
+        let address = DataAddress::Bytes(ChunkAddress(random()));
+        let digest = [1; 32];
+        let holders = (0..3).map(|_| random()).collect::<Vec<XorName>>();
+
+        let mut pruning = DataPruning::default();
+        pruning.reset(BTreeMap::from([(
+            address,
+            (digest, holders.iter().copied().collect::<BTreeSet<_>>()),
+        )]));
+
+        assert!(pruning
+            .confirm(holders[0], vec![(address, digest)])
+            .is_empty());
+        // confirmations from nodes which are not holders don't count
+        assert!(pruning
+            .confirm(random(), vec![(address, digest)])
+            .is_empty());
+        assert!(pruning
+            .confirm(holders[1], vec![(address, digest)])
+            .is_empty());
+        assert_eq!(
+            pruning.confirm(holders[2], vec![(address, digest)]),
+            vec![(address, digest)]
+        );
+        // and it's confirmed only once
+        assert!(pruning
+            .confirm(holders[2], vec![(address, digest)])
+            .is_empty());
+    }
+
+    #[test]
+    fn confirmations_of_other_replicas_dont_count() {
+        let address = DataAddress::Register(RegisterAddress::new(random(), 15000));
+        let holder = random();
+
+        let mut pruning = DataPruning::default();
+        pruning.reset(BTreeMap::from([(
+            address,
+            ([1; 32], BTreeSet::from([holder])),
+        )]));
+
+        // the holder has a replica which differs from ours, e.g. it lacks some of our cmds
+        assert!(pruning.confirm(holder, vec![(address, [2; 32])]).is_empty());
+        assert_eq!(
+            pruning.confirm(holder, vec![(address, [1; 32])]),
+            vec![(address, [1; 32])]
+        );
+    }
+
+    #[test]
+    fn reset_drops_pending_data() {
+        let address = DataAddress::Bytes(ChunkAddress(random()));
+        let holder = random();
+
+        let mut pruning = DataPruning::default();
+        pruning.reset(BTreeMap::from([(
+            address,
+            ([1; 32], BTreeSet::from([holder])),
+        )]));
+        pruning.reset(BTreeMap::new());
+
+        assert!(pruning.confirm(holder, vec![(address, [1; 32])]).is_empty());
+    }
+
+    #[test]
+    fn replica_digest_ignores_the_order_of_its_content() -> Result<()> {
+        let address = SpentbookAddress::new(random());
+        let replica = |spent_proof_shares| {
+            ReplicatedData::SpentbookLog(ReplicatedSpentbookLog {
+                address,
+                spent_proof_shares,
+                spent_transaction: None,
+            })
+        };
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let content = SpentProofContent {
+            key_image: bls::SecretKey::random().public_key(),
+            transaction_hash: Hash::from([1; 32]),
+            public_commitments: vec![],
+        };
+        let shares: Vec<_> = (0..2)
+            .map(|index| SpentProofShare {
+                content: content.clone(),
+                spentbook_pks: sk_set.public_keys(),
+                spentbook_sig_share: IndexedSignatureShare::new(
+                    index,
+                    sk_set.secret_key_share(index).sign(content.hash().as_ref()),
+                ),
+            })
+            .collect();
+
+        let digest = replica_digest(&replica(shares.clone()))?;
+        let reversed = shares.iter().rev().cloned().collect();
+        assert_eq!(replica_digest(&replica(reversed))?, digest);
+        assert_ne!(replica_digest(&replica(shares[..1].to_vec()))?, digest);
+
+        Ok(())
+    }
+}
@@ -14,7 +14,10 @@ use sn_dysfunction::IssueType;
 use sn_interface::{
     data_copy_count,
     messaging::{
-        data::{ClientDataResponse, DataCmd, DataQuery, MetadataExchange, StorageLevel},
+        data::{
+            ClientDataResponse, DataCmd, DataQuery, DataQueryVariant, MetadataExchange,
+            QueryResponse, StorageLevel,
+        },
         system::{NodeDataCmd, NodeDataQuery, NodeDataResponse, NodeMsg, OperationId},
         AuthorityProof, ClientAuth, Dst, MsgId, MsgKind, MsgType, WireMsg,
     },
@@ -120,7 +123,8 @@ impl MyNode {
         Ok(futures::future::join_all(send_tasks).await)
     }
 
-    // Locate ideal holders for this data, instruct them to store the data
+    // Locate ideal holders for this data, instruct them to store the data.
+    // Returns whether all of them acked they stored it.
     pub(crate) async fn replicate_data_to_adults_and_ack_to_client(
         snapshot: &NodeContext,
         cmd: DataCmd,
@@ -128,17 +132,21 @@ impl MyNode {
         msg_id: MsgId,
         targets: BTreeSet<Peer>,
         client_response_stream: Arc<Mutex<SendStream>>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let targets_len = targets.len();
 
         let responses = MyNode::replicate_data_to_adults(snapshot, data, msg_id, targets).await?;
         let mut success_count = 0;
+        let mut stored_count = 0;
         let mut ack_response = None;
         let mut last_error = None;
         for (peer, the_response) in responses {
             match the_response {
                 Ok(response) => {
                     success_count += 1;
+                    if is_stored_ack(&response) {
+                        stored_count += 1;
+                    }
                     debug!("Response in from {peer:?} for {msg_id:?} {response:?}");
                     ack_response = Some(response);
                 }
@@ -177,7 +185,7 @@ impl MyNode {
             }
         }
 
-        Ok(())
+        Ok(stored_count == targets_len)
     }
 
     /// Parses WireMsg and if DataStored Ack, we send a response to the client
@@ -339,6 +347,62 @@ impl MyNode {
         Ok(vec![])
     }
 
+    /// Sends a query to the given Adult on behalf of a client, and awaits its response.
+    /// Returns `None` if the Adult didn't respond in time, or responded with an unexpected msg.
+    pub(crate) async fn query_adult(
+        context: &NodeContext,
+        target: Peer,
+        query: DataQueryVariant,
+        auth: ClientAuth,
+    ) -> Result<Option<QueryResponse>> {
+        let msg_id = MsgId::new();
+        let operation_id = OperationId::from(&Bytes::copy_from_slice(msg_id.as_ref()));
+        let msg = NodeMsg::NodeDataQuery(NodeDataQuery {
+            query,
+            auth,
+            operation_id,
+        });
+
+        let (kind, payload) = MyNode::serialize_node_msg(context.name, msg)?;
+        let bytes_to_adult = MyNode::form_usr_msg_bytes_to_node(
+            context.network_knowledge.section_key(),
+            payload,
+            kind,
+            Some(target),
+            msg_id,
+        )?;
+
+        debug!("Sending out {msg_id:?} to Adult {target:?}");
+        let response = match timeout(
+            *ADULT_RESPONSE_TIMEOUT,
+            context
+                .comm
+                .send_out_bytes_to_peer_and_return_response(target, msg_id, bytes_to_adult),
+        )
+        .await
+        {
+            Ok(resp) => resp?,
+            Err(_elapsed) => {
+                warn!(
+                    "{msg_id:?}: No response from {target:?} after {:?} timeout",
+                    *ADULT_RESPONSE_TIMEOUT
+                );
+                return Ok(None);
+            }
+        };
+
+        match response.into_msg()? {
+            MsgType::NodeDataResponse {
+                msg: NodeDataResponse::QueryResponse { response, .. },
+                ..
+            } => Ok(Some(response)),
+            other => {
+                error!("Unexpected reponse to query from node {target:?}: {other:?}");
+                Ok(None)
+            }
+        }
+    }
+
     /// Send an OutgoingMsg on a given stream
     pub(crate) async fn send_msg_on_stream(
         section_key: bls::PublicKey,
@@ -481,3 +545,14 @@ impl MyNode {
         candidates
     }
 }
+
+// Whether the response of an Adult acks it stored the data it was instructed to store
+fn is_stored_ack(response: &WireMsg) -> bool {
+    matches!(
+        response.clone().into_msg(),
+        Ok(MsgType::NodeDataResponse {
+            msg: NodeDataResponse::CmdResponse { response, .. },
+            ..
+        }) if response.is_success()
+    )
+}
@@ -259,6 +259,7 @@ impl From<Error> for ErrorMsg {
                 ErrorMsg::SpentProofUnknownSectionKey(unknown_section_key)
             }
             Error::NetworkData(error) => error.into(),
+            Error::ClientMsg(error) => error,
             other => {
                 ErrorMsg::InvalidOperation(format!("Failed to perform operation: {:?}", other))
             }
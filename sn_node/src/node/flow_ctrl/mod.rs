@@ -256,25 +256,25 @@ static DATA_BUNDLE_SIZE_LIMIT: u64 = 10_000_000;
 static DATA_BUNDLE_ENTRY_LIMIT: usize = 250;
 
 #[derive(Default)]
-struct DataBundle {
+pub(crate) struct DataBundle {
     data_batch: Vec<ReplicatedData>,
     total_size: u64,
 }
 
 impl DataBundle {
-    fn push(&mut self, data: ReplicatedData) {
+    pub(crate) fn push(&mut self, data: ReplicatedData) {
         self.total_size += data.size();
         self.data_batch.push(data);
     }
 
-    fn take(&mut self) -> Vec<ReplicatedData> {
+    pub(crate) fn take(&mut self) -> Vec<ReplicatedData> {
         let data_batch = self.data_batch.clone();
         self.data_batch = vec![];
         self.total_size = 0;
         data_batch
     }
 
-    fn shall_flush(&self) -> bool {
+    pub(crate) fn shall_flush(&self) -> bool {
         self.total_size >= DATA_BUNDLE_SIZE_LIMIT
             || self.data_batch.len() >= DATA_BUNDLE_ENTRY_LIMIT
     }
@@ -27,6 +27,9 @@ const DYSFUNCTION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 // Which should hopefully trigger dysfunction if we're not getting responses back
 // const ADULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 const ELDER_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+const USED_SPACE_RECONCILIATION_INTERVAL: Duration = Duration::from_secs(600);
+const DATA_SCRUB_INTERVAL: Duration = Duration::from_secs(3600);
+const RATE_LIMITER_STATS_INTERVAL: Duration = Duration::from_secs(60);
 
 pub(super) struct PeriodicChecksTimestamps {
     last_probe: Instant,
@@ -36,6 +39,9 @@ pub(super) struct PeriodicChecksTimestamps {
     last_vote_check: Instant,
     last_dkg_msg_check: Instant,
     last_dysfunction_check: Instant,
+    last_used_space_reconciliation: Instant,
+    last_data_scrub: Instant,
+    last_rate_limiter_stats: Instant,
 }
 
 impl PeriodicChecksTimestamps {
@@ -48,6 +54,9 @@ impl PeriodicChecksTimestamps {
             last_vote_check: Instant::now(),
             last_dkg_msg_check: Instant::now(),
             last_dysfunction_check: Instant::now(),
+            last_used_space_reconciliation: Instant::now(),
+            last_data_scrub: Instant::now(),
+            last_rate_limiter_stats: Instant::now(),
         }
     }
 }
@@ -63,6 +72,21 @@ impl FlowCtrl {
             )
         };
 
+        if self.timestamps.last_used_space_reconciliation.elapsed()
+            > USED_SPACE_RECONCILIATION_INTERVAL
+        {
+            self.timestamps.last_used_space_reconciliation = Instant::now();
+            Self::reconcile_used_space(context);
+        }
+
+        if self.timestamps.last_rate_limiter_stats.elapsed() > RATE_LIMITER_STATS_INTERVAL {
+            self.timestamps.last_rate_limiter_stats = Instant::now();
+            info!(
+                "Spam protection stats: {:?}",
+                context.comm.rate_limiter_stats()
+            );
+        }
+
         if !context.is_elder {
             self.enqueue_cmds_for_adult_periodic_checks(context).await;
 
@@ -85,6 +109,11 @@ impl FlowCtrl {
             cmds.push(Self::probe_the_section(context).await);
         }
 
+        if self.timestamps.last_data_scrub.elapsed() > DATA_SCRUB_INTERVAL {
+            self.timestamps.last_data_scrub = Instant::now();
+            Self::scrub_data_storage(context.clone(), self.cmd_sender_channel.clone());
+        }
+
         for cmd in cmds {
             if let Err(error) = self.cmd_sender_channel.send((cmd, vec![])).await {
                 error!("Error queuing adult periodic check: {error:?}");
@@ -317,6 +346,51 @@ impl FlowCtrl {
         });
     }
 
+    /// Corrects any drift between the tracked used space and the data actually on disk
+    fn reconcile_used_space(context: &NodeContext) {
+        info!("Reconciling used space with data on disk");
+        let data_storage = context.data_storage.clone();
+
+        // Walking the whole storage dir can take a while, move off thread to unblock the main loop
+        let _handle = tokio::task::spawn_blocking(move || {
+            let used_on_disk = data_storage.reconcile_used_space();
+            debug!("Used space reconciled, {used_on_disk} bytes found on disk");
+        });
+    }
+
+    /// Checks the integrity of the data stored, and asks our neighbours
+    /// for any data we had to quarantine because it was found corrupted
+    fn scrub_data_storage(context: NodeContext, cmd_channel: CmdChannel) {
+        info!("Scrubbing data storage");
+
+        // Reading all the data stored can take a while, move off thread to unblock the main loop
+        let _handle = tokio::task::spawn(async move {
+            let section_keys = context.network_knowledge.known_keys();
+            let quarantined = match context.data_storage.scrub(&section_keys).await {
+                Some(quarantined) => quarantined,
+                None => {
+                    debug!("Data storage is still being scrubbed, not starting another scrub");
+                    return;
+                }
+            };
+            if quarantined.is_empty() {
+                debug!("Data storage scrubbed, no corrupted data found");
+                return;
+            }
+
+            info!(
+                "Data storage scrubbed, {} corrupted data item/s quarantined",
+                quarantined.len()
+            );
+            // the data quarantined is no longer listed as held by us,
+            // so our neighbours shall send it back to us
+            let cmd = MyNode::ask_for_any_new_data(&context).await;
+            if let Err(error) = cmd_channel.send((cmd, vec![])).await {
+                error!("Error queuing request for quarantined data: {error:?}");
+            }
+        });
+    }
+
     async fn check_for_dysfunction(&mut self) -> Vec<Cmd> {
         info!("Performing dysfunction checking");
         let mut cmds = vec![];
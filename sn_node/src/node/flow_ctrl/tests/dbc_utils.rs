@@ -11,7 +11,7 @@ use sn_dbc::{
     Dbc, KeyImage, Owner, OwnerOnce, RingCtTransaction, SpentProof, SpentProofShare, Token,
     TransactionBuilder,
 };
-use sn_interface::{dbcs::gen_genesis_dbc, messaging::data::RegisterCmd, types::ReplicatedData};
+use sn_interface::{dbcs::gen_genesis_dbc, types::ReplicatedData};
 use std::collections::BTreeSet;
 
 /// Get the spent proof share that's packaged inside the data that's to be replicated to the adults
@@ -20,14 +20,7 @@ pub(crate) fn get_spent_proof_share_from_replicated_data(
     replicated_data: ReplicatedData,
 ) -> Result<SpentProofShare> {
     match replicated_data {
-        ReplicatedData::SpentbookWrite(reg_cmd) => match reg_cmd {
-            RegisterCmd::Edit(signed_edit) => {
-                let entry = signed_edit.op.edit.crdt_op.value;
-                let spent_proof_share: SpentProofShare = rmp_serde::from_slice(&entry)?;
-                Ok(spent_proof_share)
-            }
-            _ => Err(eyre!("A RegisterCmd::Edit variant was expected")),
-        },
+        ReplicatedData::SpentbookWrite(spent_proof_share, _) => Ok(spent_proof_share),
         _ => Err(eyre!(
             "A ReplicatedData::SpentbookWrite variant was expected"
         )),
@@ -887,7 +887,7 @@ async fn spentbook_spend_client_message_should_replicate_to_adults_and_send_ack(
     assert_eq!(recipients.len(), replication_count);
 
     let replicated_data = replicate_cmd.get_replicated_data()?;
-    assert_matches!(replicated_data, ReplicatedData::SpentbookWrite(_));
+    assert_matches!(replicated_data, ReplicatedData::SpentbookWrite(..));
 
     let spent_proof_share = dbc_utils::get_spent_proof_share_from_replicated_data(replicated_data)?;
     assert_eq!(key_image.to_hex(), spent_proof_share.key_image().to_hex());
@@ -2,7 +2,7 @@ use crate::{
     comm::{Comm, MsgFromPeer},
     node::{
         cfg::create_test_max_capacity_and_root_storage, core::MyNode,
-        flow_ctrl::dispatcher::Dispatcher,
+        flow_ctrl::dispatcher::Dispatcher, DataStorage,
     },
     UsedSpace,
 };
@@ -145,8 +145,13 @@ impl<R: RngCore> TestNetworkBuilder<R> {
 
             let (tx, rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
             let socket_addr: SocketAddr = (Ipv4Addr::LOCALHOST, 0).into();
-            let comm = futures::executor::block_on(Comm::new(socket_addr, Default::default(), tx))
-                .expect("failed to create comm");
+            let comm = futures::executor::block_on(Comm::new(
+                socket_addr,
+                Default::default(),
+                Default::default(),
+                tx,
+            ))
+            .expect("failed to create comm");
             let mut node = node.clone();
             node.addr = comm.socket_addr();
 
@@ -782,8 +787,13 @@ impl TestNetwork {
         let _ = handle.enter();
         let (tx, rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
         let socket_addr: SocketAddr = (Ipv4Addr::LOCALHOST, 0).into();
-        let comm = futures::executor::block_on(Comm::new(socket_addr, Default::default(), tx))
-            .expect("failed  to create comm");
+        let comm = futures::executor::block_on(Comm::new(
+            socket_addr,
+            Default::default(),
+            Default::default(),
+            tx,
+        ))
+        .expect("failed  to create comm");
         let info = MyNodeInfo::new(
             gen_keypair(&prefix.unwrap_or_default().range_inclusive(), age),
             comm.socket_addr(),
@@ -808,12 +818,14 @@ impl TestNetwork {
 
         let (max_capacity, root_storage_dir) =
             create_test_max_capacity_and_root_storage().expect("Failed to create root storage");
+        let data_storage = DataStorage::new(&root_storage_dir, UsedSpace::new(max_capacity))
+            .expect("Failed to create data storage");
         let mut my_node = futures::executor::block_on(MyNode::new(
             comm.clone(),
             info.keypair.clone(),
             network_knowledge.clone(),
             sk_share.clone(),
-            UsedSpace::new(max_capacity),
+            data_storage,
             root_storage_dir,
             mpsc::channel(10).0,
         ))
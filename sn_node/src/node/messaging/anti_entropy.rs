@@ -325,6 +325,9 @@ impl MyNode {
             // only done if adult, since as an elder we dont want to get any more
             // data for our name (elders will eventually be caching data in general)
             cmds.push(MyNode::ask_for_any_new_data(&latest_context).await);
+            cmds.extend(
+                MyNode::prune_data_no_longer_responsible_for(node.clone(), &latest_context).await,
+            );
         }
 
         if updated {
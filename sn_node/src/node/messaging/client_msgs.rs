@@ -8,33 +8,30 @@
 
 use crate::node::{core::NodeContext, flow_ctrl::cmds::Cmd, Error, MyNode, Result};
 
-use bytes::BufMut;
-
+use futures::future::join_all;
 use qp2p::SendStream;
 use sn_dbc::{
-    get_public_commitments_from_transaction, Commitment, KeyImage, RingCtTransaction, SpentProof,
+    bls_ringct::ringct::OutputProof, Commitment, Hash, KeyImage, RingCtTransaction, SpentProof,
     SpentProofShare,
 };
 use sn_interface::{
     data_copy_count,
     messaging::{
         data::{
-            ClientDataResponse, ClientMsg, DataCmd, DataQueryVariant, EditRegister,
-            SignedRegisterEdit, SpentbookCmd,
+            ClientDataResponse, ClientMsg, DataCmd, DataQuery, DataQueryVariant, Error as ErrorMsg,
+            QueryResponse, RegisterCmd, SpentbookCmd, SpentbookQuery,
         },
         system::{NodeDataResponse, OperationId},
         AuthorityProof, ClientAuth, MsgId,
     },
     network_knowledge::section_keys::build_spent_proof_share,
     types::{
-        log_markers::LogMarker,
-        register::{Permissions, Policy, Register, User},
-        Keypair, Peer, RegisterCmd, ReplicatedData, SPENTBOOK_TYPE_TAG,
+        log_markers::LogMarker, register::User, DataAddress, Peer, ReplicatedData, SpentbookAddress,
     },
 };
 use tokio::sync::Mutex;
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use xor_name::XorName;
 
@@ -163,6 +160,21 @@ impl MyNode {
 
         let cmd = match msg {
             ClientMsg::Cmd(cmd) => cmd,
+            ClientMsg::Query(DataQuery {
+                variant: DataQueryVariant::GetStoreCost { address, bytes },
+                ..
+            }) => {
+                // quotes are provided by us Elders, there's no need to query Adults
+                return MyNode::send_store_cost_quote(
+                    context,
+                    address,
+                    bytes,
+                    origin,
+                    msg_id,
+                    send_stream,
+                )
+                .await;
+            }
             ClientMsg::Query(query) => {
                 return MyNode::read_data_from_adult_and_respond_to_client(
                     context,
@@ -179,7 +191,27 @@ impl MyNode {
         // extract the data from the request
         let data_result = match cmd.clone() {
             // These reads/writes are for adult nodes...
-            DataCmd::StoreChunk(chunk) => Ok(ReplicatedData::Chunk(chunk)),
+            DataCmd::StoreChunk { chunk, payment } => MyNode::verify_payment(
+                &context,
+                payment.as_ref(),
+                DataAddress::Bytes(*chunk.address()),
+                chunk.value().len(),
+            )
+            .map(|()| ReplicatedData::Chunk(chunk)),
+            DataCmd::Register(RegisterCmd::Create {
+                cmd,
+                section_sig,
+                payment,
+            }) => {
+                // payments are not kept by Adults along with the register
+                let create = RegisterCmd::Create {
+                    cmd,
+                    section_sig,
+                    payment: None,
+                };
+                MyNode::verify_register_payment(&context, &create, payment.as_ref())
+                    .map(|()| ReplicatedData::RegisterWrite(create))
+            }
             DataCmd::Register(cmd) => Ok(ReplicatedData::RegisterWrite(cmd)),
             DataCmd::Spentbook(cmd) => {
                 let SpentbookCmd::Spend {
@@ -214,7 +246,7 @@ impl MyNode {
                     };
                     return Ok(vec![update_command]);
                 }
-                MyNode::extract_contents_as_replicated_data(&context, cmd)
+                MyNode::extract_contents_as_replicated_data(&context, cmd, auth.into_inner()).await
             }
         };
 
@@ -260,7 +292,11 @@ impl MyNode {
         // the replication msg sent to adults
         // cmds here may be dysfunction tracking.
         // CmdAcks are sent over the send stream herein
-        MyNode::replicate_data_to_adults_and_ack_to_client(
+        let spent_key_image = match &data {
+            ReplicatedData::SpentbookWrite(share, _) => Some(*share.key_image()),
+            _ => None,
+        };
+        let stored = MyNode::replicate_data_to_adults_and_ack_to_client(
             &context,
             cmd,
             data,
@@ -270,6 +306,11 @@ impl MyNode {
         )
         .await?;
 
+        // the Adults are now checked for any later spend of the key image
+        if let Some(key_image) = spent_key_image.filter(|_| stored) {
+            context.signed_key_images.release_stored(&key_image);
+        }
+
         // TODO: handle failed responses
         // cmds.extend();
 
@@ -277,9 +318,10 @@ impl MyNode {
     }
 
     // helper to extract the contents of the cmd as ReplicatedData
-    fn extract_contents_as_replicated_data(
+    async fn extract_contents_as_replicated_data(
         context: &NodeContext,
         cmd: SpentbookCmd,
+        auth: ClientAuth,
     ) -> Result<ReplicatedData> {
         let SpentbookCmd::Spend {
             key_image,
@@ -291,24 +333,31 @@ impl MyNode {
 
         info!("Processing spend request for key image: {:?}", key_image);
 
-        let spent_proof_share = MyNode::gen_spent_proof_share(
+        let result = MyNode::gen_spent_proof_share(
             context,
             &key_image,
             &tx,
             &spent_proofs,
             &spent_transactions,
-        )?;
-        let reg_cmd = MyNode::gen_register_cmd(context, &key_image, &spent_proof_share)?;
-        Ok(ReplicatedData::SpentbookWrite(reg_cmd))
+            auth,
+        )
+        .await;
+        if result.is_err() {
+            context.signed_key_images.release_unsigned(&key_image);
+        }
+        let spent_proof_share = result?;
+        debug!("Successfully generated spent proof share for spend request");
+        Ok(ReplicatedData::SpentbookWrite(spent_proof_share, tx))
     }
 
     /// Generate a spent proof share from the information provided by the client.
-    fn gen_spent_proof_share(
+    async fn gen_spent_proof_share(
         context: &NodeContext,
         key_image: &KeyImage,
         tx: &RingCtTransaction,
         spent_proofs: &BTreeSet<SpentProof>,
         spent_transactions: &BTreeSet<RingCtTransaction>,
+        auth: ClientAuth,
     ) -> Result<SpentProofShare> {
         // Verify spent proof signatures are valid.
         let mut spent_proofs_keys = BTreeSet::new();
@@ -354,8 +403,25 @@ impl MyNode {
             return Err(Error::SpentbookError(err.to_string()));
         }
 
-        // TODO:
-        // Check the key_image wasn't already spent with a different TX (i.e. double spent)
+        // Spends of the key image are processed one at a time, so no two different
+        // TXs spending it can pass the checks below and both get a share signed by us
+        let tx_hash = Hash::from(tx.hash());
+        let mut signed_tx_hash = context.signed_key_images.lock(*key_image).await;
+        match *signed_tx_hash {
+            Some(signed) if signed != tx_hash => {
+                warn!(
+                    "{:?}: {key_image:?}",
+                    LogMarker::SpentbookDoubleSpendRejected
+                );
+                return Err(Error::ClientMsg(ErrorMsg::DoubleSpendAttempt(*key_image)));
+            }
+            // we already signed a share for this very TX, e.g. the client is retrying
+            Some(_) => {}
+            // Check the key_image wasn't already spent with a different TX (i.e. double spent)
+            None => {
+                MyNode::check_key_image_not_spent(context, key_image, tx_hash, auth).await?;
+            }
+        }
 
         // Grab the commitments specific to the spent key image.
         let public_commitments: Vec<Commitment> = public_commitments_info
@@ -377,54 +443,271 @@ impl MyNode {
             &context.section_keys_provider,
             public_commitments,
         )?;
+        *signed_tx_hash = Some(tx_hash);
+
         Ok(spent_proof_share)
     }
 
-    /// Generate the RegisterCmd to write the SpentProofShare as an entry in the Spentbook
-    /// (Register).
-    fn gen_register_cmd(
+    /// Checks with the Adults holding the spentbook of the key image that it wasn't
+    /// already spent with a transaction other than the one with the given hash.
+    ///
+    /// All of them need to respond, as any Adult which doesn't could be the one holding
+    /// a spent proof share for a different transaction, thus the check fails otherwise.
+    async fn check_key_image_not_spent(
         context: &NodeContext,
         key_image: &KeyImage,
-        spent_proof_share: &SpentProofShare,
-    ) -> Result<RegisterCmd> {
-        let mut permissions = BTreeMap::new();
-        let _ = permissions.insert(User::Anyone, Permissions::new(true));
-
-        // use our own keypair for generating the register command
-        let own_keypair = Keypair::Ed25519(context.keypair.clone());
-        let owner = User::Key(own_keypair.public_key());
-        let policy = Policy { owner, permissions };
-
-        let mut register = Register::new(
-            owner,
-            XorName::from_content(&key_image.to_bytes()),
-            SPENTBOOK_TYPE_TAG,
-            policy,
-        );
-        let mut entry = vec![].writer();
-        rmp_serde::encode::write(&mut entry, spent_proof_share).map_err(|err| {
-            Error::SpentbookError(format!(
-                "Failed to serialise SpentProofShare to insert it into the spentbook (Register): {:?}",
-                err
-            ))
-        })?;
-
-        let (_, op) = register.write(entry.into_inner(), BTreeSet::default())?;
-        let op = EditRegister {
-            address: *register.address(),
-            edit: op,
-        };
+        tx_hash: Hash,
+        auth: ClientAuth,
+    ) -> Result<()> {
+        let address = SpentbookAddress::new(XorName::from_content(&key_image.to_bytes()));
+        let query = DataQueryVariant::Spentbook(SpentbookQuery::SpentProofShares(address));
 
-        let signature = own_keypair.sign(&bincode::serialize(&op)?);
-        let signed_edit = SignedRegisterEdit {
-            op,
-            auth: ClientAuth {
-                public_key: own_keypair.public_key(),
-                signature,
-            },
-        };
+        let holders = MyNode::target_data_holders(context, *address.name());
+        if holders.is_empty() {
+            let msg = format!("No Adults to check the spentbook of {key_image:?} with");
+            debug!("Dropping spend request: {msg}");
+            return Err(Error::SpentbookError(msg));
+        }
 
-        debug!("Successfully generated spent proof share for spend request");
-        Ok(RegisterCmd::Edit(signed_edit))
+        let queries = holders
+            .into_iter()
+            .map(|holder| MyNode::query_adult(context, holder, query.clone(), auth.clone()));
+
+        let mut unchecked = 0;
+        for response in join_all(queries).await {
+            match response {
+                Ok(Some(QueryResponse::SpentProofShares(Ok(shares)))) => {
+                    if shares
+                        .iter()
+                        .any(|share| share.transaction_hash() != tx_hash)
+                    {
+                        warn!(
+                            "{:?}: {key_image:?}",
+                            LogMarker::SpentbookDoubleSpendRejected
+                        );
+                        return Err(Error::ClientMsg(ErrorMsg::DoubleSpendAttempt(*key_image)));
+                    }
+                }
+                Ok(Some(QueryResponse::SpentProofShares(Err(ErrorMsg::DataNotFound(_))))) => {}
+                other => {
+                    debug!("Failed to check spentbook of {key_image:?} with an Adult: {other:?}");
+                    unchecked += 1;
+                }
+            }
+        }
+
+        if unchecked > 0 {
+            let msg = format!(
+                "The spentbook of {key_image:?} could not be checked with {unchecked} Adult/s"
+            );
+            debug!("Dropping spend request: {msg}");
+            return Err(Error::SpentbookError(msg));
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the public commitments of the members of each ring of the transaction, i.e. of each
+/// true input and the decoys it was mixed with, for each of the key images being spent.
+///
+/// Each ring member must be an output of one of the spent transactions provided, and each of
+/// those transactions must in turn have been spent, i.e. one of the spent proofs provided
+/// must be for it, otherwise a client could fake the decoys' commitments.
+fn get_public_commitments_from_transaction(
+    tx: &RingCtTransaction,
+    spent_proofs: &BTreeSet<SpentProof>,
+    spent_transactions: &BTreeSet<RingCtTransaction>,
+) -> Result<Vec<(KeyImage, Vec<Commitment>)>> {
+    let spent_tx_hashes: BTreeSet<Hash> = spent_proofs
+        .iter()
+        .map(|proof| proof.transaction_hash())
+        .collect();
+    let spent_outputs: Vec<&OutputProof> = spent_transactions
+        .iter()
+        .filter(|spent_tx| spent_tx_hashes.contains(&Hash::from(spent_tx.hash())))
+        .flat_map(|spent_tx| spent_tx.outputs.iter())
+        .collect();
+
+    let mut public_commitments_info = Vec::<(KeyImage, Vec<Commitment>)>::new();
+    for mlsag in &tx.mlsags {
+        let key_image = KeyImage::from(mlsag.key_image);
+        let ring = mlsag.public_keys();
+        let mut commitments = Vec::with_capacity(ring.len());
+        for (i, input_pk) in ring.iter().enumerate() {
+            if ring[..i].contains(input_pk) {
+                let msg =
+                    format!("Ring of key image {key_image:?} contains an input more than once");
+                debug!("Dropping spend request: {msg}");
+                return Err(Error::SpentbookError(msg));
+            }
+
+            match spent_outputs
+                .iter()
+                .find(|output| output.public_key() == input_pk)
+            {
+                Some(output) => commitments.push(output.commitment()),
+                None => {
+                    let msg = format!(
+                        "Ring of key image {key_image:?} contains an input which is not an \
+                        output of any of the spent transactions provided"
+                    );
+                    debug!("Dropping spend request: {msg}");
+                    return Err(Error::SpentbookError(msg));
+                }
+            }
+        }
+
+        public_commitments_info.push((key_image, commitments));
+    }
+
+    Ok(public_commitments_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_public_commitments_from_transaction;
+    use crate::node::Error;
+
+    use sn_dbc::{
+        bls_ringct::{bls_bulletproofs::PedersenGens, group::Curve},
+        rng, Dbc, DecoyInput, Hash, IndexedSignatureShare, Owner, OwnerOnce, RevealedCommitment,
+        RingCtTransaction, SpentProofContent, SpentProofShare, Token, TransactionBuilder,
+    };
+    use sn_interface::dbcs::gen_genesis_dbc;
+
+    use assert_matches::assert_matches;
+    use eyre::{eyre, Result};
+
+    // Reissues the given DBC into the given number of bearer DBCs of the same amount,
+    // signing the spent proof for it with the given (single share) secret key set.
+    fn reissue(input: &Dbc, sk_set: &bls::SecretKeySet, num_outputs: u64) -> Result<Vec<Dbc>> {
+        let amount = input.amount_secrets_bearer()?.amount().as_nano() / num_outputs;
+        let mut tx_builder = TransactionBuilder::default()
+            .set_decoys_per_input(0)
+            .set_require_all_decoys(false)
+            .add_input_dbc_bearer(input)?;
+        for _ in 0..num_outputs {
+            let owner = Owner::from_random_secret_key(&mut rng::thread_rng());
+            tx_builder = tx_builder.add_output_by_amount(
+                Token::from_nano(amount),
+                OwnerOnce::from_owner_base(owner, &mut rng::thread_rng()),
+            );
+        }
+        let mut dbc_builder = tx_builder.build(rng::thread_rng())?;
+
+        let input_commitment = RevealedCommitment::from(input.amount_secrets_bearer()?)
+            .commit(&PedersenGens::default())
+            .to_affine();
+        for (key_image, tx) in dbc_builder.inputs() {
+            let content = SpentProofContent {
+                key_image,
+                transaction_hash: Hash::from(tx.hash()),
+                public_commitments: vec![input_commitment],
+            };
+            let sig_share = sk_set.secret_key_share(0).sign(content.hash().as_ref());
+            dbc_builder = dbc_builder
+                .add_spent_proof_share(SpentProofShare {
+                    content,
+                    spentbook_pks: sk_set.public_keys(),
+                    spentbook_sig_share: IndexedSignatureShare::new(0, sig_share),
+                })
+                .add_spent_transaction(tx);
+        }
+
+        Ok(dbc_builder
+            .build_without_verifying()?
+            .into_iter()
+            .map(|(dbc, ..)| dbc)
+            .collect())
+    }
+
+    // Builds a transaction spending the given DBC, mixing it with the given number of decoys.
+    fn spend_with_decoys(
+        dbc: &Dbc,
+        decoys: Vec<DecoyInput>,
+        decoys_per_input: usize,
+    ) -> Result<RingCtTransaction> {
+        let owner = Owner::from_random_secret_key(&mut rng::thread_rng());
+        let dbc_builder = TransactionBuilder::default()
+            .set_decoys_per_input(decoys_per_input)
+            .set_require_all_decoys(true)
+            .add_decoy_inputs(decoys)
+            .add_input_dbc_bearer(dbc)?
+            .add_output_by_amount(
+                dbc.amount_secrets_bearer()?.amount(),
+                OwnerOnce::from_owner_base(owner, &mut rng::thread_rng()),
+            )
+            .build(rng::thread_rng())?;
+        let (_, tx) = dbc_builder
+            .inputs()
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre!("The transaction should have one input"))?;
+        Ok(tx)
+    }
+
+    fn outputs_as_decoys(tx: &RingCtTransaction) -> Vec<DecoyInput> {
+        tx.outputs
+            .iter()
+            .map(|output| DecoyInput {
+                public_key: *output.public_key(),
+                commitment: output.commitment(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ring_members_commitments_are_taken_from_spent_transactions() -> Result<()> {
+        let sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+        let dbcs = reissue(&genesis_dbc, &sk_set, 3)?;
+
+        // the DBC being spent is not used as a decoy of itself
+        let tx = spend_with_decoys(&dbcs[0], outputs_as_decoys(&dbcs[0].transaction), 2)?;
+        let commitments = get_public_commitments_from_transaction(
+            &tx,
+            &dbcs[0].spent_proofs,
+            &dbcs[0].spent_transactions,
+        )?;
+
+        assert_eq!(commitments.len(), 1);
+        assert_eq!(commitments[0].1.len(), 3);
+        let spent_tx = &dbcs[0].transaction;
+        for (pk, commitment) in tx.mlsags[0].public_keys().iter().zip(&commitments[0].1) {
+            assert!(spent_tx
+                .outputs
+                .iter()
+                .any(|output| output.public_key() == pk && &output.commitment() == commitment));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ring_members_must_be_outputs_of_spent_transactions() -> Result<()> {
+        let sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+        let dbcs = reissue(&genesis_dbc, &sk_set, 2)?;
+
+        // decoys output by a transaction we are not given a spent proof for
+        let other_sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let other_genesis_dbc = gen_genesis_dbc(&other_sk_set, &other_sk_set.secret_key())?;
+        let other_dbcs = reissue(&other_genesis_dbc, &other_sk_set, 2)?;
+
+        let tx = spend_with_decoys(&dbcs[0], outputs_as_decoys(&other_dbcs[0].transaction), 2)?;
+        let mut spent_transactions = dbcs[0].spent_transactions.clone();
+        spent_transactions.extend(other_dbcs[0].spent_transactions.clone());
+
+        assert_matches!(
+            get_public_commitments_from_transaction(
+                &tx,
+                &dbcs[0].spent_proofs,
+                &spent_transactions
+            ),
+            Err(Error::SpentbookError(_))
+        );
+
+        Ok(())
     }
 }
@@ -14,6 +14,7 @@ mod handover;
 mod join;
 mod membership;
 mod node_msgs;
+mod payments;
 mod proposal;
 mod relocation;
 mod serialize;
@@ -112,8 +113,14 @@ impl MyNode {
             } => {
                 debug!("Valid client msg {msg_id:?}");
 
+                // the signature has been verified, so the client key can now be charged for it
+                if !context.comm.allow_client_msg_from(auth.public_key) {
+                    debug!("Dropping client msg {msg_id:?} from {origin:?}, rate limit exceeded");
+                    return Ok(vec![]);
+                }
+
                 let Some(send_stream) = send_stream else {
-                    return Err(Error::NoClientResponseStream)
+                    return Err(Error::NoClientResponseStream);
                 };
 
                 // Check for entropy before we proceed further, if AE response was sent
@@ -21,7 +21,7 @@ use sn_interface::{
         system::{JoinResponse, NodeDataCmd, NodeDataQuery, NodeDataResponse, NodeEvent, NodeMsg},
         MsgId,
     },
-    types::{log_markers::LogMarker, Keypair, Peer, PublicKey, ReplicatedData},
+    types::{log_markers::LogMarker, Peer, PublicKey, ReplicatedData},
 };
 use std::collections::BTreeSet;
 use std::sync::Arc;
@@ -51,8 +51,6 @@ impl MyNode {
         original_msg_id: MsgId,
     ) -> Result<Vec<Cmd>> {
         let mut cmds = vec![];
-        let section_pk = PublicKey::Bls(context.network_knowledge.section_key());
-        let node_keypair = Keypair::Ed25519(context.keypair.clone());
         let data_addr = data.address();
         let our_node_name = context.name;
 
@@ -61,11 +59,8 @@ impl MyNode {
         // We are an adult here, so just store away!
         // This may return a NotEnoughSpace error... but we should have reported storage increase
         // well before this
-        let response = match context
-            .data_storage
-            .store(&data, section_pk, node_keypair.clone())
-            .await
-        {
+        let section_keys = context.network_knowledge.known_keys();
+        let response = match context.data_storage.store(&data, &section_keys).await {
             Ok(level_report) => {
                 trace!("Data has been stored: {data_addr:?}");
                 info!("Storage level report: {:?}", level_report);
@@ -84,6 +79,10 @@ impl MyNode {
                 cmds.push(MyNode::send_msg_to_our_elders(context, msg));
                 CmdResponse::err(data, StorageError::NotEnoughSpace.into())?
             }
+            Err(error @ StorageError::DoubleSpendAttempt(_)) => {
+                warn!("Rejected storing spent proof share {data_addr:?}: {error}");
+                CmdResponse::err(data, error.into())?
+            }
             Err(error) => {
                 // the rest seem to be non-problematic errors.. (?)
                 // this could be an "we already have it" error... so we should continue with that...
@@ -442,16 +441,11 @@ impl MyNode {
                 }
 
                 let mut cmds = vec![];
-
-                let section_pk = PublicKey::Bls(context.network_knowledge.section_key());
-                let node_keypair = Keypair::Ed25519(context.keypair.clone());
+                let section_keys = context.network_knowledge.known_keys();
 
                 for data in data_collection {
                     // grab the write lock each time in the loop to not hold it over large data sets
-                    let store_result = context
-                        .data_storage
-                        .store(&data, section_pk, node_keypair.clone())
-                        .await;
+                    let store_result = context.data_storage.store(&data, &section_keys).await;
 
                     // We are an adult here, so just store away!
                     // This may return a DatabaseFull error... but we should have reported storage increase
@@ -502,6 +496,23 @@ impl MyNode {
                         .collect(),
                 )
             }
+            NodeMsg::NodeDataCmd(NodeDataCmd::ConfirmDataHeld(replicas)) => {
+                if context.is_elder {
+                    // as an elder we don't hold data, so we cannot confirm it
+                    return Ok(vec![]);
+                }
+
+                MyNode::confirm_data_held_for_node(&context, sender, replicas).await
+            }
+            NodeMsg::NodeDataCmd(NodeDataCmd::DataHeld(data_held)) => {
+                if context.is_elder {
+                    // as an elder we don't prune data, we're not holding it as an Adult
+                    return Ok(vec![]);
+                }
+
+                MyNode::remove_data_confirmed_held(node, &context, sender, data_held).await;
+                Ok(vec![])
+            }
             NodeMsg::NodeDataQuery(NodeDataQuery {
                 query,
                 auth,
@@ -530,7 +541,7 @@ impl MyNode {
 
     /// Sets Cmd to locally record the storage level and send msgs to Elders
     /// Advising the same
-    fn record_storage_level_if_any(
+    pub(crate) fn record_storage_level_if_any(
         context: &NodeContext,
         level: Option<StorageLevel>,
     ) -> Result<Vec<Cmd>> {
@@ -0,0 +1,391 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{
+    core::NodeContext, data::store_cost, flow_ctrl::cmds::Cmd, Error, MyNode, Result,
+};
+
+use qp2p::SendStream;
+use sn_dbc::TransactionVerifier;
+use sn_interface::{
+    messaging::{
+        data::{
+            ClientDataResponse, Error as ErrorMsg, PaymentProof, QueryResponse, RegisterCmd,
+            StoreCostQuote,
+        },
+        MsgId,
+    },
+    network_knowledge::NetworkKnowledge,
+    types::{log_markers::LogMarker, DataAddress, Peer},
+};
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use xor_name::XorName;
+
+impl MyNode {
+    /// Sends back to the client a quote, signed by us, of the cost of storing the given data
+    pub(crate) async fn send_store_cost_quote(
+        context: NodeContext,
+        address: DataAddress,
+        bytes: usize,
+        source_client: Peer,
+        correlation_id: MsgId,
+        send_stream: Arc<Mutex<SendStream>>,
+    ) -> Result<Vec<Cmd>> {
+        let price = store_cost(bytes, context.avg_storage_usage);
+        let response = StoreCostQuote::new(
+            address,
+            bytes,
+            price,
+            context.network_knowledge.section_key(),
+            &context.keypair,
+        );
+        trace!(
+            "{:?} {address:?} ({bytes} bytes) at {price}",
+            LogMarker::StoreCostQuoted
+        );
+
+        let msg = ClientDataResponse::QueryResponse {
+            response: QueryResponse::GetStoreCost(response),
+            correlation_id,
+        };
+        let (kind, payload) = MyNode::serialize_client_msg_response(context.name, msg)?;
+
+        MyNode::send_msg_on_stream(
+            context.network_knowledge.section_key(),
+            payload,
+            kind,
+            send_stream,
+            Some(source_client),
+            correlation_id,
+        )
+        .await?;
+
+        Ok(vec![])
+    }
+
+    /// Verifies the payment attached to a cmd, if any, is valid for storing
+    /// the given data, i.e. the quote it pays for was issued by one of our current
+    /// Elders for this very data, and the quoted price was paid in full.
+    ///
+    /// Cmds without a payment are still accepted, until clients pay for all their writes.
+    pub(crate) fn verify_payment(
+        context: &NodeContext,
+        payment: Option<&PaymentProof>,
+        address: DataAddress,
+        bytes: usize,
+    ) -> Result<()> {
+        let payment = match payment {
+            Some(payment) => payment,
+            None => {
+                trace!("No payment was attached for storing {address:?}");
+                return Ok(());
+            }
+        };
+        let quote = &payment.quote;
+
+        if quote.address != address {
+            return Err(invalid_payment(format!(
+                "quote is for storing {:?}, not {address:?}",
+                quote.address
+            )));
+        }
+        if bytes > quote.bytes {
+            return Err(invalid_payment(format!(
+                "quote is for storing {} bytes, but data is {bytes} bytes",
+                quote.bytes
+            )));
+        }
+
+        quote.verify()?;
+        let elder = XorName::from(quote.elder);
+        if !context
+            .network_knowledge
+            .elders()
+            .iter()
+            .any(|peer| peer.name() == elder)
+        {
+            return Err(invalid_payment(format!(
+                "quote was not issued by a current Elder of our section: {elder}"
+            )));
+        }
+
+        let verifier = SpentProofKeyVerifier {
+            network_knowledge: &context.network_knowledge,
+        };
+        TransactionVerifier::verify(&verifier, &payment.tx, &payment.spent_proofs)
+            .map_err(|err| invalid_payment(format!("payment transaction is invalid: {err}")))?;
+
+        let paid = payment.paid_amount()?;
+        if paid < quote.price {
+            return Err(invalid_payment(format!(
+                "paid {paid}, but {} was quoted",
+                quote.price
+            )));
+        }
+
+        trace!("{:?} {address:?} with {paid}", LogMarker::PaymentVerified);
+        Ok(())
+    }
+
+    /// Verifies the payment attached to the cmd creating a Register, if any, and that the
+    /// cmd carries the section signature over the spend which paid for it, to be stored with it.
+    pub(crate) fn verify_register_payment(
+        context: &NodeContext,
+        cmd: &RegisterCmd,
+        payment: Option<&PaymentProof>,
+    ) -> Result<()> {
+        let (address, section_sig) = match cmd {
+            RegisterCmd::Create {
+                cmd, section_sig, ..
+            } => (cmd.op.address(), section_sig),
+            RegisterCmd::Edit(_) | RegisterCmd::UpdatePolicy(_) => return Ok(()),
+        };
+
+        // registers take up the same space regardless of their content
+        MyNode::verify_payment(context, payment, DataAddress::Register(address), 0)?;
+        if let Some(payment) = payment {
+            if *section_sig != payment.section_sig() {
+                return Err(invalid_payment(format!(
+                    "section signature of {address:?} is not the one over its payment"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_payment(reason: String) -> Error {
+    Error::ClientMsg(ErrorMsg::InvalidPayment(reason))
+}
+
+// Verifies the spent proofs of a payment are signed by sections we know of
+struct SpentProofKeyVerifier<'a> {
+    network_knowledge: &'a NetworkKnowledge,
+}
+
+impl sn_dbc::SpentProofKeyVerifier for SpentProofKeyVerifier<'_> {
+    type Error = Error;
+
+    fn verify_known_key(&self, key: &bls::PublicKey) -> Result<()> {
+        if self.network_knowledge.verify_section_key_is_known(key) {
+            Ok(())
+        } else {
+            Err(Error::SpentProofUnknownSectionKey(*key))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::node::{flow_ctrl::tests::network_builder::TestNetworkBuilder, Error, MyNode};
+
+    use sn_dbc::{
+        bls_ringct::{bls_bulletproofs::PedersenGens, group::Curve},
+        rng, Hash, IndexedSignatureShare, OwnerOnce, RevealedCommitment, SpentProofContent,
+        SpentProofShare, Token, TransactionBuilder,
+    };
+    use sn_interface::{
+        dbcs::gen_genesis_dbc,
+        elder_count,
+        messaging::{
+            data::{
+                CreateRegister, Error as ErrorMsg, PaymentProof, RegisterCmd, SignedRegisterCreate,
+                StoreCostQuote,
+            },
+            ClientAuth, SectionSig,
+        },
+        types::{
+            register::{Policy, User},
+            ChunkAddress, DataAddress, Keypair, RegisterAddress,
+        },
+    };
+
+    use assert_matches::assert_matches;
+    use eyre::{eyre, Result};
+    use std::collections::BTreeMap;
+    use xor_name::{rand::random, Prefix};
+
+    // Reissues a new genesis DBC, paying the given amount to the quote's payment owner,
+    // and signing the spent proof for it with the given (single share) secret key set.
+    fn pay(
+        quote: &StoreCostQuote,
+        amount: Token,
+        sk_set: &bls::SecretKeySet,
+    ) -> Result<PaymentProof> {
+        let genesis_dbc = gen_genesis_dbc(sk_set, &sk_set.secret_key())?;
+        let payment_owner = quote.payment_owner()?;
+        let change_owner =
+            OwnerOnce::from_owner_base(genesis_dbc.owner_base().clone(), &mut rng::thread_rng());
+
+        let tx_builder = TransactionBuilder::default()
+            .set_decoys_per_input(0)
+            .set_require_all_decoys(false)
+            .add_input_dbc_bearer(&genesis_dbc)?;
+        let change = tx_builder
+            .inputs_amount_sum()
+            .checked_sub(amount)
+            .ok_or_else(|| eyre!("Not enough funds"))?;
+        let mut dbc_builder = tx_builder
+            .add_output_by_amount(amount, payment_owner.clone())
+            .add_output_by_amount(change, change_owner)
+            .build(rng::thread_rng())?;
+
+        let genesis_commitment = RevealedCommitment::from(genesis_dbc.amount_secrets_bearer()?)
+            .commit(&PedersenGens::default())
+            .to_affine();
+        for (key_image, tx) in dbc_builder.inputs() {
+            let content = SpentProofContent {
+                key_image,
+                transaction_hash: Hash::from(tx.hash()),
+                public_commitments: vec![genesis_commitment],
+            };
+            let sig_share = sk_set.secret_key_share(0).sign(content.hash().as_ref());
+            dbc_builder = dbc_builder
+                .add_spent_proof_share(SpentProofShare {
+                    content,
+                    spentbook_pks: sk_set.public_keys(),
+                    spentbook_sig_share: IndexedSignatureShare::new(0, sig_share),
+                })
+                .add_spent_transaction(tx);
+        }
+
+        let (dbc, _, amount_secrets) = dbc_builder
+            .build_without_verifying()?
+            .into_iter()
+            .find(|(_, owner, _)| owner == &payment_owner)
+            .ok_or_else(|| eyre!("Payment output not found"))?;
+
+        Ok(PaymentProof::new(quote.clone(), &dbc, amount_secrets))
+    }
+
+    #[tokio::test]
+    async fn payments_are_verified() -> Result<()> {
+        let env = TestNetworkBuilder::new(rand::thread_rng())
+            .sap(Prefix::default(), elder_count(), 0, None, Some(0))
+            .build();
+        let context = env
+            .get_nodes(Prefix::default(), 1, 0, None)
+            .remove(0)
+            .context();
+        let sk_set = env.get_secret_key_set(Prefix::default(), None);
+
+        let address = DataAddress::Bytes(ChunkAddress(random()));
+        let price = Token::from_nano(1_000);
+        let quote = StoreCostQuote::new(
+            address,
+            1024,
+            price,
+            context.network_knowledge.section_key(),
+            &context.keypair,
+        )?;
+
+        // a valid payment
+        let payment = pay(&quote, price, &sk_set)?;
+        MyNode::verify_payment(&context, Some(&payment), address, 1024)?;
+
+        // a missing payment, accepted until clients pay for their writes
+        MyNode::verify_payment(&context, None, address, 1024)?;
+
+        // invalid payments: for other data, or more data than quoted
+        let other_address = DataAddress::Bytes(ChunkAddress(random()));
+        assert_matches!(
+            MyNode::verify_payment(&context, Some(&payment), other_address, 1024),
+            Err(Error::ClientMsg(ErrorMsg::InvalidPayment(_)))
+        );
+        assert_matches!(
+            MyNode::verify_payment(&context, Some(&payment), address, 1025),
+            Err(Error::ClientMsg(ErrorMsg::InvalidPayment(_)))
+        );
+
+        // paying less than quoted
+        let underpayment = pay(&quote, Token::from_nano(999), &sk_set)?;
+        assert_matches!(
+            MyNode::verify_payment(&context, Some(&underpayment), address, 1024),
+            Err(Error::ClientMsg(ErrorMsg::InvalidPayment(_)))
+        );
+
+        // paying with DBCs spent at a section we don't know of
+        let other_sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let unknown_spend = pay(&quote, price, &other_sk_set)?;
+        assert_matches!(
+            MyNode::verify_payment(&context, Some(&unknown_spend), address, 1024),
+            Err(Error::ClientMsg(ErrorMsg::InvalidPayment(_)))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn registers_are_created_with_the_section_sig_of_their_payment() -> Result<()> {
+        let env = TestNetworkBuilder::new(rand::thread_rng())
+            .sap(Prefix::default(), elder_count(), 0, None, Some(0))
+            .build();
+        let context = env
+            .get_nodes(Prefix::default(), 1, 0, None)
+            .remove(0)
+            .context();
+        let sk_set = env.get_secret_key_set(Prefix::default(), None);
+
+        let keypair = Keypair::new_ed25519();
+        let auth = ClientAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(b"create"),
+        };
+        let op = CreateRegister {
+            name: random(),
+            tag: 15000,
+            policy: Policy {
+                owner: User::Key(keypair.public_key()),
+                permissions: BTreeMap::new(),
+            },
+            private: false,
+        };
+        let address = RegisterAddress::new(op.name, op.tag);
+        let price = Token::from_nano(1_000);
+        let quote = StoreCostQuote::new(
+            DataAddress::Register(address),
+            0,
+            price,
+            context.network_knowledge.section_key(),
+            &context.keypair,
+        )?;
+        let payment = pay(&quote, price, &sk_set)?;
+
+        let create = |section_sig| RegisterCmd::Create {
+            cmd: SignedRegisterCreate {
+                op: op.clone(),
+                auth: auth.clone(),
+            },
+            section_sig,
+            payment: None,
+        };
+
+        MyNode::verify_register_payment(&context, &create(payment.section_sig()), Some(&payment))?;
+
+        // unpaid creates are accepted until clients pay for them
+        MyNode::verify_register_payment(&context, &create(None), None)?;
+
+        // the section sig doesn't prove the payment was made
+        let sk = bls::SecretKey::random();
+        let forged = Some(SectionSig {
+            public_key: sk.public_key(),
+            signature: sk.sign("section-sig"),
+        });
+        for section_sig in [None, forged] {
+            assert_matches!(
+                MyNode::verify_register_payment(&context, &create(section_sig), Some(&payment)),
+                Err(Error::ClientMsg(ErrorMsg::InvalidPayment(_)))
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -6,16 +6,29 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{core::NodeContext, flow_ctrl::cmds::Cmd, messaging::Peers, MyNode};
+use crate::{
+    node::{
+        core::NodeContext,
+        data::{replica_digest, ReplicaDigest},
+        flow_ctrl::{cmds::Cmd, DataBundle},
+        messaging::Peers,
+        MyNode, Result,
+    },
+    storage::Error as StorageError,
+};
 
 use sn_interface::{
     data_copy_count,
     messaging::system::{NodeDataCmd, NodeMsg},
-    types::{log_markers::LogMarker, DataAddress, Peer},
+    types::{log_markers::LogMarker, DataAddress, Peer, ReplicatedData},
 };
 
 use itertools::Itertools;
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
 
 impl MyNode {
     /// Given what data the peer has, we shall calculate what data the peer is missing that
@@ -116,4 +129,179 @@ impl MyNode {
         let msg = NodeMsg::NodeDataCmd(NodeDataCmd::SendAnyMissingRelevantData(data_i_have));
         MyNode::send_system_msg(msg, Peers::Multiple(target_members), context.clone())
     }
+
+    /// Finds the data we hold but are no longer responsible for, i.e. we are not among the
+    /// Adults closest to it anymore, and pushes our replica of it to the Adults now responsible
+    /// for it, asking them to confirm they hold all its content. Once all of them confirm,
+    /// the data is removed from our storage.
+    #[instrument(skip_all)]
+    pub(crate) async fn prune_data_no_longer_responsible_for(
+        node: Arc<RwLock<MyNode>>,
+        context: &NodeContext,
+    ) -> Vec<Cmd> {
+        trace!("{:?}", LogMarker::DataPruningUnderway);
+        let our_prefix = context.network_knowledge.prefix();
+        let mut pending = BTreeMap::new();
+        let mut bundles = BTreeMap::<Peer, DataBundle>::new();
+        let mut cmds = vec![];
+        for data in context.data_storage.data_addrs().await {
+            if !our_prefix.matches(data.name()) {
+                // The data has left our section after a split. The Adults of the section now
+                // responsible for it are not among our members, thus we cannot work out which
+                // of them shall hold it, so we keep our replica rather than losing it.
+                trace!("Not pruning {data:?}, it's no longer within our prefix {our_prefix:?}");
+                continue;
+            }
+
+            let holders = MyNode::target_data_holders(context, *data.name());
+            if holders.iter().any(|peer| peer.name() == context.name) {
+                continue;
+            }
+
+            let replica = match context.data_storage.get_from_local_store(&data).await {
+                Ok(replica) => replica,
+                Err(error) => {
+                    warn!("Failed to read {data:?} to be pruned from our storage: {error:?}");
+                    continue;
+                }
+            };
+            let digest = match replica_digest(&replica) {
+                Ok(digest) => digest,
+                Err(error) => {
+                    warn!("Failed to work out the digest of our replica of {data:?}: {error:?}");
+                    continue;
+                }
+            };
+
+            for holder in &holders {
+                let bundle = bundles.entry(*holder).or_default();
+                bundle.push(replica.clone());
+                if bundle.shall_flush() {
+                    cmds.push(MyNode::confirm_data_held_msg(
+                        bundle.take(),
+                        *holder,
+                        context,
+                    ));
+                }
+            }
+            let _ = pending.insert(data, (digest, holders.iter().map(Peer::name).collect()));
+        }
+
+        debug!(
+            "{} data item/s found we are no longer responsible for",
+            pending.len()
+        );
+        node.write().await.data_pruning.reset(pending);
+
+        for (holder, mut bundle) in bundles {
+            let replicas = bundle.take();
+            if !replicas.is_empty() {
+                cmds.push(MyNode::confirm_data_held_msg(replicas, holder, context));
+            }
+        }
+
+        cmds
+    }
+
+    fn confirm_data_held_msg(
+        replicas: Vec<ReplicatedData>,
+        holder: Peer,
+        context: &NodeContext,
+    ) -> Cmd {
+        let msg = NodeMsg::NodeDataCmd(NodeDataCmd::ConfirmDataHeld(replicas));
+        MyNode::send_system_msg(msg, Peers::Single(holder), context.clone())
+    }
+
+    /// Given the replicas of some data the peer is about to remove, we merge them into
+    /// our own replicas, and respond with the digest of those we now hold all the content of.
+    #[instrument(skip(context, replicas))]
+    pub(crate) async fn confirm_data_held_for_node(
+        context: &NodeContext,
+        sender: Peer,
+        replicas: Vec<ReplicatedData>,
+    ) -> Result<Vec<Cmd>> {
+        let mut cmds = vec![];
+        let section_keys = context.network_knowledge.known_keys();
+        let mut data_held = vec![];
+        for replica in replicas {
+            let address = replica.address();
+            match context.data_storage.store(&replica, &section_keys).await {
+                Ok(level_report) => {
+                    cmds.extend(MyNode::record_storage_level_if_any(context, level_report)?)
+                }
+                Err(StorageError::DataExists(_)) => {}
+                Err(error) => {
+                    warn!("Failed to store replica of {address:?} from {sender:?}: {error:?}")
+                }
+            }
+
+            match context.data_storage.holds_replica(&replica).await {
+                Ok(true) => data_held.push((address, replica_digest(&replica)?)),
+                Ok(false) => {
+                    trace!("We don't hold all the content of {address:?} {sender:?} holds")
+                }
+                Err(error) => warn!("Failed to check our replica of {address:?}: {error:?}"),
+            }
+        }
+
+        if data_held.is_empty() {
+            trace!("We hold none of the data {sender:?} asked about");
+            return Ok(cmds);
+        }
+
+        let msg = NodeMsg::NodeDataCmd(NodeDataCmd::DataHeld(data_held));
+        cmds.push(MyNode::send_system_msg(
+            msg,
+            Peers::Single(sender),
+            context.clone(),
+        ));
+        Ok(cmds)
+    }
+
+    /// Records the data the peer confirmed it holds, and removes from our storage
+    /// the data which has been confirmed by all the Adults now responsible for it.
+    #[instrument(skip(node, context, data_held))]
+    pub(crate) async fn remove_data_confirmed_held(
+        node: Arc<RwLock<MyNode>>,
+        context: &NodeContext,
+        sender: Peer,
+        data_held: Vec<(DataAddress, ReplicaDigest)>,
+    ) {
+        let confirmed = node
+            .write()
+            .await
+            .data_pruning
+            .confirm(sender.name(), data_held);
+
+        for (data, digest) in confirmed {
+            // we may have become responsible for it again since we asked
+            let holders = MyNode::target_data_holders(context, *data.name());
+            if !context.network_knowledge.prefix().matches(data.name())
+                || holders.iter().any(|peer| peer.name() == context.name)
+            {
+                continue;
+            }
+
+            // and we may have received content since we pushed our replica, which they lack
+            match context.data_storage.get_from_local_store(&data).await {
+                Ok(replica) if replica_digest(&replica).ok() == Some(digest) => {}
+                Ok(_) => {
+                    debug!("Our replica of {data:?} changed since holders confirmed it, not removing it");
+                    continue;
+                }
+                Err(error) => {
+                    warn!("Failed to read {data:?} to be removed from our storage: {error:?}");
+                    continue;
+                }
+            }
+
+            match context.data_storage.remove(&data).await {
+                Ok(()) => info!(
+                    "{:?}: {data:?}",
+                    LogMarker::DataNoLongerResponsibleForRemoved
+                ),
+                Err(error) => warn!("Failed to remove {data:?} from our storage: {error:?}"),
+            }
+        }
+    }
 }
@@ -55,18 +55,15 @@ pub use xor_name::{Prefix, XorName, XOR_NAME_LEN}; // TODO remove pub on API upd
 
 mod core {
     use crate::comm::Comm;
-    use crate::{
-        node::{
-            bootstrap::JoiningAsRelocated,
-            data::Capacity,
-            dkg::DkgVoter,
-            flow_ctrl::{cmds::Cmd, dysfunction::DysCmds},
-            handover::Handover,
-            membership::{elder_candidates, try_split_dkg, Membership},
-            messaging::Peers,
-            DataStorage, Error, Proposal, Result, XorName,
-        },
-        UsedSpace,
+    use crate::node::{
+        bootstrap::JoiningAsRelocated,
+        data::{Capacity, DataPruning, SignedKeyImages},
+        dkg::DkgVoter,
+        flow_ctrl::{cmds::Cmd, dysfunction::DysCmds},
+        handover::Handover,
+        membership::{elder_candidates, try_split_dkg, Membership},
+        messaging::Peers,
+        DataStorage, Error, Proposal, Result, XorName,
     };
 
     use sn_dysfunction::IssueType;
@@ -130,6 +127,8 @@ mod core {
         pub(crate) joins_allowed: bool,
         // Trackers
         pub(crate) capacity: Capacity,
+        pub(crate) data_pruning: DataPruning,
+        pub(crate) signed_key_images: SignedKeyImages,
         pub(crate) dysfunction_cmds_sender: mpsc::Sender<DysCmds>,
     }
 
@@ -146,6 +145,9 @@ mod core {
         #[debug(skip)]
         pub(crate) comm: Comm,
         pub(crate) joins_allowed: bool,
+        // Avg storage usage of the section's Adults as tracked by Elders, between 0 and 10
+        pub(crate) avg_storage_usage: u8,
+        pub(crate) signed_key_images: SignedKeyImages,
     }
 
     impl NodeContext {
@@ -176,6 +178,8 @@ mod core {
                 comm: self.comm.clone(),
                 joins_allowed: self.joins_allowed,
                 data_storage: self.data_storage.clone(),
+                avg_storage_usage: self.capacity.avg_usage(),
+                signed_key_images: self.signed_key_images.clone(),
             }
         }
 
@@ -185,7 +189,7 @@ mod core {
             keypair: Arc<Keypair>,
             network_knowledge: NetworkKnowledge,
             section_key_share: Option<SectionKeyShare>,
-            used_space: UsedSpace,
+            data_storage: DataStorage,
             root_storage_dir: PathBuf,
             dysfunction_cmds_sender: mpsc::Sender<DysCmds>,
         ) -> Result<Self> {
@@ -213,8 +217,6 @@ mod core {
 
             let section_keys_provider = SectionKeysProvider::new(section_key_share.clone());
 
-            let data_storage = DataStorage::new(&root_storage_dir, used_space)?;
-
             // create handover
             let handover = if let Some(key) = section_key_share {
                 let secret_key = (key.index as u8, key.secret_key_share);
@@ -244,6 +246,8 @@ mod core {
                 joins_allowed: true,
                 data_storage,
                 capacity: Capacity::default(),
+                data_pruning: DataPruning::default(),
+                signed_key_images: SignedKeyImages::default(),
                 dysfunction_cmds_sender,
                 membership,
             };
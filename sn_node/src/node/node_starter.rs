@@ -14,7 +14,7 @@ use crate::node::{
     },
     join_network,
     logging::{log_ctx::LogCtx, log_system_details},
-    Config, Error, MyNode, Result, STANDARD_CHANNEL_SIZE,
+    Config, DataStorage, Error, MyNode, Result, STANDARD_CHANNEL_SIZE,
 };
 use crate::UsedSpace;
 
@@ -93,9 +93,10 @@ async fn new_node(
     };
 
     let used_space = UsedSpace::new(config.max_capacity());
+    let data_storage = DataStorage::with_backend(root_dir, used_space, config.storage_backend())?;
 
     let (node, cmd_channel, rejoin_network_rx) =
-        bootstrap_node(config, used_space, root_dir, join_timeout).await?;
+        bootstrap_node(config, data_storage, root_dir, join_timeout).await?;
 
     {
         debug!("[NODE WRITE]: new node...");
@@ -131,7 +132,7 @@ async fn new_node(
 // Private helper to create a new node using the given config and bootstraps it to the network.
 async fn bootstrap_node(
     config: &Config,
-    used_space: UsedSpace,
+    data_storage: DataStorage,
     root_storage_dir: &Path,
     join_timeout: Duration,
 ) -> Result<(
@@ -146,6 +147,7 @@ async fn bootstrap_node(
     let comm = Comm::new(
         config.local_addr(),
         config.network_config().clone(),
+        config.rate_limits(),
         incoming_msg_pipe,
     )
     .await?;
@@ -153,7 +155,7 @@ async fn bootstrap_node(
     let node = if config.is_first() {
         bootstrap_genesis_node(
             comm,
-            used_space,
+            data_storage,
             root_storage_dir,
             dysfunction_cmds_sender.clone(),
         )
@@ -164,7 +166,7 @@ async fn bootstrap_node(
             comm,
             &mut incoming_msg_receiver,
             join_timeout,
-            used_space,
+            data_storage,
             root_storage_dir,
             dysfunction_cmds_sender.clone(),
         )
@@ -187,7 +189,7 @@ async fn bootstrap_node(
 
 async fn bootstrap_genesis_node(
     comm: Comm,
-    used_space: UsedSpace,
+    data_storage: DataStorage,
     root_storage_dir: &Path,
     dysfunction_cmds_sender: mpsc::Sender<DysCmds>,
 ) -> Result<MyNode> {
@@ -207,7 +209,7 @@ async fn bootstrap_genesis_node(
     let (node, genesis_dbc) = MyNode::first_node(
         comm,
         Arc::new(keypair),
-        used_space.clone(),
+        data_storage,
         root_storage_dir.to_path_buf(),
         genesis_sk_set,
         dysfunction_cmds_sender,
@@ -237,7 +239,7 @@ async fn bootstrap_normal_node(
     comm: Comm,
     incoming_msg_receiver: &mut tokio::sync::mpsc::Receiver<MsgFromPeer>,
     join_timeout: Duration,
-    used_space: UsedSpace,
+    data_storage: DataStorage,
     root_storage_dir: &Path,
     dysfunction_cmds_sender: mpsc::Sender<DysCmds>,
 ) -> Result<MyNode> {
@@ -269,7 +271,7 @@ async fn bootstrap_normal_node(
         info.keypair.clone(),
         network_knowledge,
         None,
-        used_space.clone(),
+        data_storage,
         root_storage_dir.to_path_buf(),
         dysfunction_cmds_sender,
     )
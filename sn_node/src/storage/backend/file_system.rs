@@ -0,0 +1,209 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{StorageBackend, StorageKey};
+
+use crate::storage::Result;
+
+use async_trait::async_trait;
+use hex::FromHex;
+use std::{
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{create_dir_all, metadata, read, remove_dir_all, remove_file, File},
+    io::AsyncWriteExt,
+};
+use walkdir::WalkDir;
+use xor_name::XorName;
+
+const BIT_TREE_DEPTH: usize = 20;
+
+/// Backend which stores each value in its own file, within a tree of
+/// directories built from the first bits of the data item's name.
+#[derive(Clone, Debug)]
+pub(super) struct FileSystemBackend {
+    root: PathBuf,
+}
+
+impl FileSystemBackend {
+    pub(super) fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    // An item stored as a single value is a file named with the hex-encoded name of the item,
+    // whilst the entries of an item are all stored in a folder named after the item.
+    fn key_to_path(&self, key: &StorageKey) -> PathBuf {
+        let path = prefix_tree_path(&self.root, key.name).join(hex::encode(key.name));
+        match &key.entry {
+            Some(entry) => path.join(entry),
+            None => path,
+        }
+    }
+
+    // Keys are decoded from the whole layout `key_to_path` stores values with, rather than
+    // guessed from the file name, since entry ids can be hex-encoded names too.
+    fn path_to_key(&self, path: &Path) -> Option<StorageKey> {
+        let components = path
+            .strip_prefix(&self.root)
+            .ok()?
+            .iter()
+            .skip(BIT_TREE_DEPTH)
+            .map(|component| component.to_str())
+            .collect::<Option<Vec<_>>>()?;
+
+        let key = match components.as_slice() {
+            [name] => StorageKey::item(XorName(<[u8; 32]>::from_hex(name).ok()?)),
+            [name, entry] => {
+                StorageKey::entry(XorName(<[u8; 32]>::from_hex(name).ok()?), entry.to_string())
+            }
+            _ => return None,
+        };
+
+        // the file is only ours if it's found where we'd have stored it
+        (self.key_to_path(&key) == path).then_some(key)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for FileSystemBackend {
+    async fn write(&self, key: &StorageKey, value: &[u8]) -> Result<bool> {
+        let filepath = self.key_to_path(key);
+        if filepath.exists() {
+            return Ok(false);
+        }
+
+        if let Some(dirs) = filepath.parent() {
+            create_dir_all(dirs).await?;
+        }
+
+        let mut file = File::create(filepath).await?;
+
+        file.write_all(value).await?;
+        // Let's sync up OS data to disk to reduce the chances of
+        // concurrent reading failing by reading an empty/incomplete file
+        file.sync_data().await?;
+
+        Ok(true)
+    }
+
+    async fn read(&self, key: &StorageKey) -> Result<Option<Vec<u8>>> {
+        match read(self.key_to_path(key)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(io_error @ io::Error { .. }) if io_error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(other) => Err(other.into()),
+        }
+    }
+
+    fn exists(&self, key: &StorageKey) -> Result<bool> {
+        Ok(self.key_to_path(key).exists())
+    }
+
+    async fn remove(&self, key: &StorageKey) -> Result<Option<usize>> {
+        let filepath = self.key_to_path(key);
+        let meta = match metadata(&filepath).await {
+            Ok(meta) => meta,
+            Err(io_error @ io::Error { .. }) if io_error.kind() == ErrorKind::NotFound => {
+                return Ok(None)
+            }
+            Err(other) => return Err(other.into()),
+        };
+        remove_file(filepath).await?;
+        Ok(Some(meta.len() as usize))
+    }
+
+    async fn read_entries(&self, name: &XorName) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        for filepath in list_files_in(&self.key_to_path(&StorageKey::item(*name))) {
+            let entry = match filepath.file_name().and_then(|f| f.to_str()) {
+                Some(entry) => entry.to_string(),
+                None => continue,
+            };
+
+            match read(&filepath).await {
+                Ok(bytes) => entries.push((entry, bytes)),
+                Err(err) => warn!(
+                    "Store: failed to read entry from {}: {err:?}",
+                    filepath.display()
+                ),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    async fn remove_entries(&self, name: &XorName) -> Result<usize> {
+        let path = self.key_to_path(&StorageKey::item(*name));
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let size = used_space_in(&path);
+        remove_dir_all(path).await?;
+        Ok(size)
+    }
+
+    fn keys(&self) -> Vec<StorageKey> {
+        list_files_in(&self.root)
+            .iter()
+            .filter_map(|filepath| self.path_to_key(filepath))
+            .collect()
+    }
+
+    fn used_space(&self) -> usize {
+        used_space_in(&self.root)
+    }
+}
+
+// Helper that returns the prefix tree path of depth BIT_TREE_DEPTH for a given xorname
+// Example:
+// - with a xorname with starting bits `010001110110....`
+// - and a BIT_TREE_DEPTH of `6`
+// returns the path `ROOT_PATH/0/1/0/0/0/1`
+fn prefix_tree_path(root: &Path, xorname: XorName) -> PathBuf {
+    let bin = format!("{:b}", xorname);
+    let prefix_dir_path: PathBuf = bin.chars().take(BIT_TREE_DEPTH).map(String::from).collect();
+    root.join(prefix_dir_path)
+}
+
+fn list_files_in(path: &Path) -> Vec<PathBuf> {
+    if !path.exists() {
+        return vec![];
+    }
+
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| match e {
+            Ok(direntry) => Some(direntry),
+            Err(err) => {
+                warn!("Store: failed to process filesystem entry: {}", err);
+                None
+            }
+        })
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.path().to_path_buf())
+        .collect()
+}
+
+// Helper that returns the total size of all files found under the given path
+fn used_space_in(path: &Path) -> usize {
+    list_files_in(path)
+        .iter()
+        .filter_map(|filepath| match filepath.metadata() {
+            Ok(meta) => Some(meta.len() as usize),
+            Err(err) => {
+                warn!(
+                    "Store: failed to read metadata of {}: {err}",
+                    filepath.display()
+                );
+                None
+            }
+        })
+        .sum()
+}
@@ -0,0 +1,265 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+mod file_system;
+mod sled_db;
+
+use super::{Result, UsedSpace};
+
+use file_system::FileSystemBackend;
+use sled_db::SledBackend;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use strum_macros::EnumIter;
+use xor_name::XorName;
+
+// Name of the folder where the sled db files are stored, within the root storage dir
+const SLED_DB_DIR_NAME: &str = "db";
+// Namespace where data items found corrupted are moved to, kept apart from the data we serve
+const QUARANTINE_DIR_NAME: &str = "quarantine";
+// Max space the values quarantined in a namespace can take, beyond which
+// corrupted values are removed without keeping a copy of them
+const MAX_QUARANTINE_SPACE: usize = 50 * 1024 * 1024;
+
+/// Backend to be used by a node to persist the data it's responsible for.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, EnumIter, clap::ValueEnum,
+)]
+pub enum StorageBackendKind {
+    /// One file per data item, within a tree of directories built from the item's name.
+    #[default]
+    FileSystem,
+    /// Embedded key-value store, which keeps all data items within a few large files.
+    Sled,
+}
+
+/// Key of a value held by a `StorageBackend`.
+///
+/// Values are grouped by the name of the data item they belong to, e.g. a Chunk
+/// is stored as a single value, whilst a Register is stored as a set of entries, one per cmd.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct StorageKey {
+    pub(super) name: XorName,
+    pub(super) entry: Option<String>,
+}
+
+impl StorageKey {
+    /// Key of a data item stored as a single value
+    pub(super) fn item(name: XorName) -> Self {
+        Self { name, entry: None }
+    }
+
+    /// Key of one of the entries a data item is stored as
+    pub(super) fn entry(name: XorName, entry: String) -> Self {
+        Self {
+            name,
+            entry: Some(entry),
+        }
+    }
+}
+
+/// Operations a storage backend needs to support to persist data items.
+#[async_trait]
+pub(super) trait StorageBackend: Debug + Send + Sync {
+    /// Writes the value unless there is already one stored for the key.
+    /// Returns `false` if the value already existed and was thus not written.
+    async fn write(&self, key: &StorageKey, value: &[u8]) -> Result<bool>;
+
+    /// Reads the value stored for the key, if any.
+    async fn read(&self, key: &StorageKey) -> Result<Option<Vec<u8>>>;
+
+    /// Checks if there is a value stored for the key.
+    fn exists(&self, key: &StorageKey) -> Result<bool>;
+
+    /// Removes the value stored for the key, returning its size, if there was any.
+    async fn remove(&self, key: &StorageKey) -> Result<Option<usize>>;
+
+    /// Reads all the entries stored for a data item, along with their entry ids.
+    async fn read_entries(&self, name: &XorName) -> Result<Vec<(String, Vec<u8>)>>;
+
+    /// Removes all the entries stored for a data item, returning their total size.
+    async fn remove_entries(&self, name: &XorName) -> Result<usize>;
+
+    /// Keys of all the values stored.
+    fn keys(&self) -> Vec<StorageKey>;
+
+    /// Total size of all the values stored.
+    fn used_space(&self) -> usize;
+}
+
+/// Root location from where backends for each type of data can be opened.
+#[derive(Debug)]
+pub(super) enum BackendRoot {
+    FileSystem(PathBuf),
+    Sled(sled::Db),
+}
+
+impl BackendRoot {
+    /// Opens the root location of the given kind of backend at the specified path
+    pub(super) fn open(kind: StorageBackendKind, path: &Path) -> Result<Self> {
+        match kind {
+            StorageBackendKind::FileSystem => Ok(Self::FileSystem(path.to_path_buf())),
+            StorageBackendKind::Sled => {
+                // Values are flushed as soon as they are written, so there is no need
+                // for the periodic flushing thread, which would also hold the db lock
+                // for a while after the db is dropped.
+                let db = sled::Config::new()
+                    .path(path.join(SLED_DB_DIR_NAME))
+                    .flush_every_ms(None)
+                    .open()?;
+                Ok(Self::Sled(db))
+            }
+        }
+    }
+
+    /// Opens the backend for the given namespace, keeping it apart from any other namespace
+    pub(super) fn backend(&self, namespace: &str) -> Result<Arc<dyn StorageBackend>> {
+        match self {
+            Self::FileSystem(path) => Ok(Arc::new(FileSystemBackend::new(path.join(namespace)))),
+            Self::Sled(db) => Ok(Arc::new(SledBackend::new(db.open_tree(namespace)?))),
+        }
+    }
+
+    /// Opens the quarantine where corrupted items from the given namespace are kept
+    pub(super) fn quarantine(&self, namespace: &str) -> Result<Quarantine> {
+        let backend = self.backend(&format!("{QUARANTINE_DIR_NAME}/{namespace}"))?;
+        // the space taken by the values already quarantined is only measured
+        // when opening it, and then tracked as more values are kept
+        let used_space = UsedSpace::new(MAX_QUARANTINE_SPACE);
+        used_space.set(backend.used_space());
+        Ok(Quarantine {
+            backend,
+            used_space,
+        })
+    }
+}
+
+/// Copies of the values found corrupted, kept apart from the data we serve so they can be
+/// inspected. The space they take is capped, so it cannot grow unbounded.
+#[derive(Clone, Debug)]
+pub(super) struct Quarantine {
+    backend: Arc<dyn StorageBackend>,
+    used_space: UsedSpace,
+}
+
+impl Quarantine {
+    /// Keeps a copy of a corrupted value, unless there is already a copy of it,
+    /// or the quarantine is full, in which case no copy is kept.
+    pub(super) async fn keep(&self, key: &StorageKey, value: &[u8]) -> Result<()> {
+        if !self.used_space.can_add(value.len()) {
+            warn!("Quarantine is full, no copy is kept of the corrupted value at {key:?}");
+            return Ok(());
+        }
+
+        if self.backend.write(key, value).await? {
+            self.used_space.increase(value.len());
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(super) fn exists(&self, key: &StorageKey) -> Result<bool> {
+        self.backend.exists(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackendRoot, StorageBackendKind, StorageKey, MAX_QUARANTINE_SPACE};
+
+    use eyre::Result;
+    use strum::IntoEnumIterator;
+    use tempfile::tempdir;
+    use xor_name::XorName;
+
+    #[tokio::test]
+    async fn keys_of_items_and_entries_are_listed_back() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let tmp_dir = tempdir()?;
+            let root = BackendRoot::open(kind, tmp_dir.path())?;
+            let backend = root.backend("test")?;
+
+            let mut rng = rand::thread_rng();
+            let item = StorageKey::item(XorName::random(&mut rng));
+            // entry ids which look like hex-encoded names are not mistaken for items
+            let entry = StorageKey::entry(
+                XorName::random(&mut rng),
+                hex::encode(XorName::random(&mut rng)),
+            );
+            assert!(backend.write(&item, b"item").await?);
+            assert!(backend.write(&entry, b"entry").await?);
+
+            let mut expected = vec![item.clone(), entry.clone()];
+            expected.sort();
+            let mut keys = backend.keys();
+            keys.sort();
+            assert_eq!(keys, expected, "backend: {kind:?}");
+
+            assert_eq!(backend.remove(&item).await?, Some(4));
+            assert_eq!(backend.remove_entries(&entry.name).await?, 5);
+            assert!(backend.keys().is_empty(), "backend: {kind:?}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quarantine_space_is_capped() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let tmp_dir = tempdir()?;
+            let quarantine = BackendRoot::open(kind, tmp_dir.path())?.quarantine("test")?;
+
+            let mut rng = rand::thread_rng();
+            let kept = StorageKey::item(XorName::random(&mut rng));
+            let dropped = StorageKey::item(XorName::random(&mut rng));
+            quarantine.keep(&kept, &[0; 10]).await?;
+            quarantine
+                .keep(&dropped, &vec![0; MAX_QUARANTINE_SPACE])
+                .await?;
+
+            assert!(quarantine.exists(&kept)?, "backend: {kind:?}");
+            assert!(!quarantine.exists(&dropped)?, "backend: {kind:?}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quarantine_space_is_tracked() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let tmp_dir = tempdir()?;
+            let root = BackendRoot::open(kind, tmp_dir.path())?;
+            let quarantine = root.quarantine("test")?;
+
+            let mut rng = rand::thread_rng();
+            let mut key = || StorageKey::item(XorName::random(&mut rng));
+            let (kept, filler, dropped) = (key(), key(), key());
+
+            // a value kept again doesn't take any more space
+            quarantine.keep(&kept, &[0; 10]).await?;
+            quarantine.keep(&kept, &[0; 10]).await?;
+            quarantine
+                .keep(&filler, &vec![0; MAX_QUARANTINE_SPACE - 10])
+                .await?;
+            assert!(quarantine.exists(&filler)?, "backend: {kind:?}");
+
+            quarantine.keep(&dropped, &[0; 1]).await?;
+            assert!(!quarantine.exists(&dropped)?, "backend: {kind:?}");
+
+            // the space taken by what was already quarantined is found when reopening it
+            let reopened = root.quarantine("test")?;
+            reopened.keep(&dropped, &[0; 1]).await?;
+            assert!(!reopened.exists(&dropped)?, "backend: {kind:?}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{StorageBackend, StorageKey};
+
+use crate::storage::Result;
+
+use async_trait::async_trait;
+use sled::{IVec, Tree};
+use xor_name::{XorName, XOR_NAME_LEN};
+
+// Tags following the item's name in a db key, for items stored as a single value or as entries
+const ITEM_TAG: u8 = 0;
+const ENTRY_TAG: u8 = 1;
+
+/// Backend which stores all values in a tree of an embedded sled key-value db.
+///
+/// Keys are made of the data item's name, followed by a tag telling if the value is the whole
+/// item or one of its entries, and the entry id if it's an entry, so all entries of an item
+/// can be retrieved with a prefix scan.
+#[derive(Clone, Debug)]
+pub(super) struct SledBackend {
+    tree: Tree,
+}
+
+impl SledBackend {
+    pub(super) fn new(tree: Tree) -> Self {
+        Self { tree }
+    }
+
+    fn encode_key(key: &StorageKey) -> Vec<u8> {
+        let mut db_key = key.name.0.to_vec();
+        match &key.entry {
+            Some(entry) => {
+                db_key.push(ENTRY_TAG);
+                db_key.extend_from_slice(entry.as_bytes());
+            }
+            None => db_key.push(ITEM_TAG),
+        }
+        db_key
+    }
+
+    fn decode_key(db_key: &[u8]) -> Option<StorageKey> {
+        if db_key.len() <= XOR_NAME_LEN {
+            return None;
+        }
+
+        let (name, rest) = db_key.split_at(XOR_NAME_LEN);
+        let mut xorname = XorName::default();
+        xorname.0.copy_from_slice(name);
+        match rest.split_first()? {
+            (&ITEM_TAG, []) => Some(StorageKey::item(xorname)),
+            (&ENTRY_TAG, entry) => {
+                let entry = String::from_utf8(entry.to_vec()).ok()?;
+                Some(StorageKey::entry(xorname, entry))
+            }
+            _ => None,
+        }
+    }
+
+    fn scan_entries(&self, name: &XorName) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> {
+        let mut prefix = name.0.to_vec();
+        prefix.push(ENTRY_TAG);
+        self.tree.scan_prefix(prefix)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for SledBackend {
+    async fn write(&self, key: &StorageKey, value: &[u8]) -> Result<bool> {
+        let db_key = Self::encode_key(key);
+        // only write the value if there is none stored yet for the key
+        let current: Option<&[u8]> = None;
+        let written = self
+            .tree
+            .compare_and_swap(db_key, current, Some(value))?
+            .is_ok();
+
+        if written {
+            // Let's flush to disk so the value is persisted before we acknowledge it's stored
+            let _ = self.tree.flush_async().await?;
+        }
+
+        Ok(written)
+    }
+
+    async fn read(&self, key: &StorageKey) -> Result<Option<Vec<u8>>> {
+        let value = self.tree.get(Self::encode_key(key))?;
+        Ok(value.map(|bytes| bytes.to_vec()))
+    }
+
+    fn exists(&self, key: &StorageKey) -> Result<bool> {
+        Ok(self.tree.contains_key(Self::encode_key(key))?)
+    }
+
+    async fn remove(&self, key: &StorageKey) -> Result<Option<usize>> {
+        let removed = self.tree.remove(Self::encode_key(key))?;
+        if removed.is_some() {
+            // as with writes, the removal is persisted before we acknowledge it
+            let _ = self.tree.flush_async().await?;
+        }
+        Ok(removed.map(|bytes| bytes.len()))
+    }
+
+    async fn read_entries(&self, name: &XorName) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        for item in self.scan_entries(name) {
+            let (db_key, value) = item?;
+            match Self::decode_key(&db_key).and_then(|key| key.entry) {
+                Some(entry) => entries.push((entry, value.to_vec())),
+                None => warn!("Store: ignoring db entry with an invalid key: {db_key:?}"),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    async fn remove_entries(&self, name: &XorName) -> Result<usize> {
+        let mut size = 0;
+        for item in self.scan_entries(name) {
+            let (db_key, _) = item?;
+            if let Some(value) = self.tree.remove(db_key)? {
+                size += value.len();
+            }
+        }
+        if size > 0 {
+            let _ = self.tree.flush_async().await?;
+        }
+
+        Ok(size)
+    }
+
+    fn keys(&self) -> Vec<StorageKey> {
+        self.tree
+            .iter()
+            .keys()
+            .filter_map(|db_key| match db_key {
+                Ok(db_key) => Self::decode_key(&db_key),
+                Err(err) => {
+                    warn!("Store: failed to read db key: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn used_space(&self) -> usize {
+        self.tree
+            .iter()
+            .values()
+            .filter_map(|value| value.ok())
+            .map(|value| value.len())
+            .sum()
+    }
+}
@@ -6,7 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{list_files_in, prefix_tree_path, Error, Result, UsedSpace};
+use super::{
+    backend::{BackendRoot, Quarantine, StorageBackend, StorageKey},
+    Error, Result, UsedSpace,
+};
 
 use sn_interface::{
     messaging::system::NodeQueryResponse,
@@ -14,25 +17,19 @@ use sn_interface::{
 };
 
 use bytes::Bytes;
-use hex::FromHex;
 use std::{
     fmt::{self, Display, Formatter},
-    io::{self, ErrorKind},
-    path::{Path, PathBuf},
-};
-use tokio::{
-    fs::{create_dir_all, metadata, read, remove_file, File},
-    io::AsyncWriteExt,
+    sync::Arc,
 };
 use tracing::info;
-use xor_name::XorName;
 
 const CHUNKS_STORE_DIR_NAME: &str = "chunks";
 
 /// Operations on data chunks.
 #[derive(Clone, Debug)]
 pub(super) struct ChunkStorage {
-    file_store_path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+    quarantine: Quarantine,
     used_space: UsedSpace,
 }
 
@@ -42,54 +39,46 @@ impl ChunkStorage {
     /// If the location specified already contains a `ChunkStorage`, it is simply used
     ///
     /// Used space of the dir is tracked
-    pub(super) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+    pub(super) fn new(root: &BackendRoot, used_space: UsedSpace) -> Result<Self> {
         Ok(Self {
-            file_store_path: path.join(CHUNKS_STORE_DIR_NAME),
+            backend: root.backend(CHUNKS_STORE_DIR_NAME)?,
+            quarantine: root.quarantine(CHUNKS_STORE_DIR_NAME)?,
             used_space,
         })
     }
 
     pub(super) fn addrs(&self) -> Vec<ChunkAddress> {
-        list_files_in(&self.file_store_path)
-            .iter()
-            .filter_map(|filepath| Self::chunk_filepath_to_address(filepath).ok())
+        self.backend
+            .keys()
+            .into_iter()
+            .filter(|key| key.entry.is_none())
+            .map(|key| ChunkAddress(key.name))
             .collect()
     }
 
-    fn chunk_filepath_to_address(path: &Path) -> Result<ChunkAddress> {
-        let filename = path
-            .file_name()
-            .ok_or_else(|| Error::NoFilename(path.to_path_buf()))?
-            .to_str()
-            .ok_or_else(|| Error::InvalidFilename(path.to_path_buf()))?;
-
-        let xorname = XorName(<[u8; 32]>::from_hex(filename)?);
-        Ok(ChunkAddress(xorname))
-    }
-
-    fn chunk_addr_to_filepath(&self, addr: &ChunkAddress) -> Result<PathBuf> {
-        let xorname = *addr.name();
-        let path = prefix_tree_path(&self.file_store_path, xorname);
-        let filename = hex::encode(xorname);
-        Ok(path.join(filename))
+    /// Total size of the chunks found on disk
+    pub(super) fn used_space_on_disk(&self) -> usize {
+        self.backend.used_space()
     }
 
-    #[allow(dead_code)]
     pub(super) async fn remove_chunk(&self, address: &ChunkAddress) -> Result<()> {
         trace!("Removing chunk, {:?}", address);
-        let filepath = self.chunk_addr_to_filepath(address)?;
-        let meta = metadata(filepath.clone()).await?;
-        remove_file(filepath).await?;
-        self.used_space.decrease(meta.len() as usize);
+        let key = StorageKey::item(*address.name());
+        let size = self
+            .backend
+            .remove(&key)
+            .await?
+            .ok_or(Error::ChunkNotFound(*address.name()))?;
+        self.used_space.decrease(size);
         Ok(())
     }
 
     pub(super) async fn get_chunk(&self, address: &ChunkAddress) -> Result<Chunk> {
         debug!("Getting chunk {:?}", address);
 
-        let file_path = self.chunk_addr_to_filepath(address)?;
-        match read(file_path).await {
-            Ok(bytes) => {
+        let key = StorageKey::item(*address.name());
+        match self.backend.read(&key).await? {
+            Some(bytes) => {
                 let chunk = Chunk::new(Bytes::from(bytes));
                 if chunk.address() != address {
                     // This can happen if the content read is empty, or incomplete,
@@ -100,10 +89,39 @@ impl ChunkStorage {
                     Ok(chunk)
                 }
             }
-            Err(io_error @ io::Error { .. }) if io_error.kind() == ErrorKind::NotFound => {
-                Err(Error::ChunkNotFound(*address.name()))
-            }
-            Err(other) => Err(other.into()),
+            None => Err(Error::ChunkNotFound(*address.name())),
+        }
+    }
+
+    /// Re-hashes the chunk stored at rest, and quarantines it if its content
+    /// no longer matches its address. Returns `true` if it was quarantined.
+    pub(super) async fn scrub_chunk(&self, address: &ChunkAddress) -> Result<bool> {
+        let key = StorageKey::item(*address.name());
+        if !self.is_corrupted(&key, address).await? {
+            return Ok(false);
+        }
+
+        // The chunk could be being written concurrently, in which case we could have read it
+        // incomplete, so let's read it once more before considering it corrupted.
+        if !self.is_corrupted(&key, address).await? {
+            return Ok(false);
+        }
+
+        if let Some(bytes) = self.backend.read(&key).await? {
+            self.quarantine.keep(&key, &bytes).await?;
+        }
+        if let Some(size) = self.backend.remove(&key).await? {
+            self.used_space.decrease(size);
+        }
+
+        Ok(true)
+    }
+
+    async fn is_corrupted(&self, key: &StorageKey, address: &ChunkAddress) -> Result<bool> {
+        match self.backend.read(key).await? {
+            Some(bytes) => Ok(Chunk::new(Bytes::from(bytes)).address() != address),
+            // it was removed in the meantime, nothing to check
+            None => Ok(false),
         }
     }
 
@@ -117,9 +135,9 @@ impl ChunkStorage {
     #[instrument(skip_all)]
     pub(super) async fn store(&self, chunk: &Chunk) -> Result<()> {
         let addr = chunk.address();
-        let filepath = self.chunk_addr_to_filepath(addr)?;
+        let key = StorageKey::item(*addr.name());
 
-        if filepath.exists() {
+        if self.backend.exists(&key)? {
             info!(
                 "{}: Chunk data already exists, not storing: {:?}",
                 self, addr
@@ -137,17 +155,11 @@ impl ChunkStorage {
 
         // Store the data on disk
         trace!("{:?} {addr:?}", LogMarker::StoringChunk);
-        if let Some(dirs) = filepath.parent() {
-            create_dir_all(dirs).await?;
+        if !self.backend.write(&key, chunk.value()).await? {
+            // it was stored concurrently by another task
+            return Err(Error::DataExists(DataAddress::Bytes(*addr)));
         }
 
-        let mut file = File::create(filepath).await?;
-
-        file.write_all(chunk.value()).await?;
-        // Let's sync up OS data to disk to reduce the chances of
-        // concurrent reading failing by reading an empty/incomplete file
-        file.sync_data().await?;
-
         self.used_space.increase(chunk.value().len());
         trace!("{:?} {addr:?}", LogMarker::StoredNewChunk);
 
@@ -164,85 +176,124 @@ impl Display for ChunkStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::StorageBackendKind;
     use sn_interface::types::utils::random_bytes;
 
     use eyre::{eyre, Result};
     use futures::future::join_all;
     use rayon::prelude::*;
-    use tempfile::tempdir;
+    use strum::IntoEnumIterator;
+    use tempfile::{tempdir, TempDir};
 
-    fn init_file_store() -> ChunkStorage {
+    // The temp dir is returned so it's not removed till the test finishes
+    fn init_store(kind: StorageBackendKind) -> (TempDir, ChunkStorage) {
         let root = tempdir().expect("Failed to create temporary directory for chunk disk store");
-        ChunkStorage::new(root.path(), UsedSpace::new(usize::MAX))
-            .expect("Failed to create chunk disk store")
+        let backend_root =
+            BackendRoot::open(kind, root.path()).expect("Failed to open storage backend");
+        let store = ChunkStorage::new(&backend_root, UsedSpace::new(usize::MAX))
+            .expect("Failed to create chunk disk store");
+        (root, store)
     }
 
     #[tokio::test]
     #[ignore]
     async fn test_write_read_chunk() {
-        let storage = init_file_store();
-        // test that a range of different chunks return the written chunk
-        for _ in 0..10 {
-            let chunk = Chunk::new(random_bytes(100));
+        for kind in StorageBackendKind::iter() {
+            let (_root, storage) = init_store(kind);
+            // test that a range of different chunks return the written chunk
+            for _ in 0..10 {
+                let chunk = Chunk::new(random_bytes(100));
 
-            storage.store(&chunk).await.expect("Failed to write chunk.");
+                storage.store(&chunk).await.expect("Failed to write chunk.");
 
-            let read_chunk = storage
-                .get_chunk(chunk.address())
-                .await
-                .expect("Failed to read chunk.");
+                let read_chunk = storage
+                    .get_chunk(chunk.address())
+                    .await
+                    .expect("Failed to read chunk.");
 
-            assert_eq!(chunk.value(), read_chunk.value());
+                assert_eq!(chunk.value(), read_chunk.value());
+            }
         }
     }
 
     #[tokio::test]
     async fn test_write_read_async_multiple_chunks() {
-        let store = init_file_store();
-        let size = 100;
-        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(size)))
-            .take(7)
-            .collect();
-        write_and_read_chunks(&chunks, store).await;
+        for kind in StorageBackendKind::iter() {
+            let (_root, store) = init_store(kind);
+            let size = 100;
+            let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(size)))
+                .take(7)
+                .collect();
+            write_and_read_chunks(&chunks, store).await;
+        }
     }
 
     #[tokio::test]
     async fn test_write_read_async_multiple_identical_chunks() {
-        let store = init_file_store();
-        let chunks: Vec<Chunk> = std::iter::repeat(Chunk::new(Bytes::from("test_concurrent")))
-            .take(7)
-            .collect();
-        write_and_read_chunks(&chunks, store).await;
+        for kind in StorageBackendKind::iter() {
+            let (_root, store) = init_store(kind);
+            let chunks: Vec<Chunk> = std::iter::repeat(Chunk::new(Bytes::from("test_concurrent")))
+                .take(7)
+                .collect();
+            write_and_read_chunks(&chunks, store).await;
+        }
     }
 
     #[tokio::test]
     async fn test_read_chunk_empty_file() -> Result<()> {
-        let storage = init_file_store();
+        for kind in StorageBackendKind::iter() {
+            let (_root, storage) = init_store(kind);
 
-        let chunk = Chunk::new(random_bytes(100));
-        let address = chunk.address();
-
-        // create chunk file but with empty content
-        let filepath = storage.chunk_addr_to_filepath(address)?;
-        if let Some(dirs) = filepath.parent() {
-            create_dir_all(dirs).await?;
-        }
-        let mut file = File::create(&filepath).await?;
-        file.write_all(b"").await?;
-
-        // trying to read the chunk shall return ChunkNotFound error since
-        // its content shouldn't match chunk address
-        match storage.get_chunk(address).await {
-            Ok(chunk) => Err(eyre!(
-                "Unexpected Chunk read (size: {}): {chunk:?}",
-                chunk.value().len()
-            )),
-            Err(Error::ChunkNotFound(name)) => {
-                assert_eq!(name, *address.name(), "Wrong Chunk name returned in error");
-                Ok(())
+            let chunk = Chunk::new(random_bytes(100));
+            let address = chunk.address();
+
+            // store the chunk but with empty content
+            let key = StorageKey::item(*address.name());
+            let _ = storage.backend.write(&key, b"").await?;
+
+            // trying to read the chunk shall return ChunkNotFound error since
+            // its content shouldn't match chunk address
+            match storage.get_chunk(address).await {
+                Ok(chunk) => {
+                    return Err(eyre!(
+                        "Unexpected Chunk read (size: {}): {chunk:?}",
+                        chunk.value().len()
+                    ))
+                }
+                Err(Error::ChunkNotFound(name)) => {
+                    assert_eq!(name, *address.name(), "Wrong Chunk name returned in error");
+                }
+                Err(other) => return Err(eyre!("Unexpected Error type returned: {other:?}")),
             }
-            Err(other) => Err(eyre!("Unexpected Error type returned: {other:?}")),
         }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scrub_quarantines_corrupted_chunk() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_root, storage) = init_store(kind);
+
+            let chunk = Chunk::new(random_bytes(100));
+            storage.store(&chunk).await?;
+
+            // store some other content at the address of a different chunk
+            let corrupted_address = *Chunk::new(random_bytes(100)).address();
+            let key = StorageKey::item(*corrupted_address.name());
+            let corrupted_content = random_bytes(100);
+            let _ = storage.backend.write(&key, &corrupted_content).await?;
+            storage.used_space.increase(corrupted_content.len());
+
+            assert!(!storage.scrub_chunk(chunk.address()).await?);
+            assert!(storage.scrub_chunk(&corrupted_address).await?);
+
+            assert_eq!(storage.addrs(), vec![*chunk.address()]);
+            assert!(storage.quarantine.exists(&key)?);
+            assert_eq!(storage.used_space.used(), chunk.value().len());
+        }
+
+        Ok(())
     }
 
     async fn write_and_read_chunks(chunks: &[Chunk], storage: ChunkStorage) {
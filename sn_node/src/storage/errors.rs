@@ -6,9 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use sn_dbc::KeyImage;
 use sn_interface::{
     messaging::data::Error as ErrorMsg,
-    types::{ChunkAddress, DataAddress, PublicKey, RegisterAddress},
+    types::{ChunkAddress, DataAddress, PublicKey, RegisterAddress, SpentbookAddress},
 };
 
 use std::{io, path::PathBuf};
@@ -32,6 +33,18 @@ pub enum Error {
     /// Chunk not found.
     #[error("Chunk not found: {0:?}")]
     ChunkNotFound(XorName),
+    /// Spentbook not found in local storage.
+    #[error("Spentbook data not found in local storage: {0:?}")]
+    SpentbookNotFound(SpentbookAddress),
+    /// The key image was already spent with a different transaction.
+    #[error("Key image has already been spent with a different transaction: {0:?}")]
+    DoubleSpendAttempt(KeyImage),
+    /// The signature share of a spent proof share is not valid for its key set.
+    #[error("Spent proof share for {0:?} has an invalid signature share")]
+    InvalidSpentProofShare(SpentbookAddress),
+    /// The transaction is not the one the key image of the spentbook was spent with.
+    #[error("Transaction provided for {0:?} is not the one its key image was spent with")]
+    InvalidSpentTransaction(SpentbookAddress),
     /// Data already exists for this node
     #[error("Data already exists at this node: {0:?}")]
     DataExists(DataAddress),
@@ -44,6 +57,9 @@ pub enum Error {
     /// I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
+    /// Sled db error.
+    #[error("Sled db error:: {0}")]
+    Sled(#[from] sled::Error),
     /// Bincode error.
     #[error("Bincode error:: {0}")]
     Bincode(#[from] bincode::Error),
@@ -62,6 +78,20 @@ pub enum Error {
     /// Invalid filename
     #[error("Invalid chunk filename: {0}")]
     InvalidFilename(PathBuf),
+    /// The version of a policy update sent by a client is not newer than the current policy's
+    #[error("Policy update for Register {address:?} has version {version}, which is not newer than the current version {current}")]
+    StalePolicyUpdate {
+        address: RegisterAddress,
+        version: u64,
+        current: u64,
+    },
+    /// The policy version an edit sent by a client was authored under is not the current one
+    #[error("Edit for Register {address:?} was authored under policy version {version}, but the current version is {current}")]
+    StaleRegisterEdit {
+        address: RegisterAddress,
+        version: u64,
+        current: u64,
+    },
     /// Register command/op destinaation adddress mistmatch
     #[error(
         "Register command destination address ({cmd_dst_addr:?}) doesn't match stored Register address: {reg_addr:?}"
@@ -83,7 +113,11 @@ impl From<Error> for ErrorMsg {
             Error::ChunkNotFound(xorname) => {
                 ErrorMsg::DataNotFound(DataAddress::Bytes(ChunkAddress(xorname)))
             }
+            Error::SpentbookNotFound(address) => {
+                ErrorMsg::DataNotFound(DataAddress::Spentbook(address))
+            }
             Error::DataExists(address) => ErrorMsg::DataExists(address),
+            Error::DoubleSpendAttempt(key_image) => ErrorMsg::DoubleSpendAttempt(key_image),
             Error::NetworkData(error) => error.into(),
             other => {
                 ErrorMsg::InvalidOperation(format!("Failed to perform operation: {:?}", other))
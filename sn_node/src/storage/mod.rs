@@ -6,36 +6,40 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod backend;
 mod chunks;
 mod errors;
 mod register_store;
 mod registers;
+mod spentbooks;
 mod used_space;
 
+pub use backend::StorageBackendKind;
 pub use used_space::UsedSpace;
 
 pub(crate) use errors::{Error, Result};
 
+use backend::BackendRoot;
 use chunks::ChunkStorage;
 use registers::RegisterStorage;
+use spentbooks::{is_legacy_spentbook, SpentbookStorage};
 
-use sn_dbc::SpentProofShare;
 use sn_interface::{
     messaging::{
-        data::{DataQueryVariant, Error as MessagingError, RegisterQuery, StorageLevel},
+        data::{DataQueryVariant, Error as ErrorMsg, SpentbookQuery, StorageLevel},
         system::NodeQueryResponse,
     },
-    types::{
-        register::User, DataAddress, Keypair, PublicKey, RegisterAddress, ReplicatedData,
-        SPENTBOOK_TYPE_TAG,
-    },
+    types::{log_markers::LogMarker, register::User, DataAddress, ReplicatedData},
 };
 
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
-use xor_name::XorName;
-
-const BIT_TREE_DEPTH: usize = 20;
+use std::{
+    collections::BTreeSet,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 /// Operations on data stored to disk.
 /// As data the storage struct may be cloned throughoout the node
@@ -45,19 +49,156 @@ const BIT_TREE_DEPTH: usize = 20;
 pub struct DataStorage {
     chunks: ChunkStorage,
     registers: RegisterStorage,
+    spentbooks: SpentbookStorage,
     used_space: UsedSpace,
     last_recorded_level: StorageLevel,
+    scrub_running: Arc<AtomicBool>,
+}
+
+// Marks a scrub of the data storage as running for as long as it's held,
+// i.e. until the scrub completes, or it's cancelled.
+struct ScrubGuard(Arc<AtomicBool>);
+
+impl Drop for ScrubGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
 }
 
 impl DataStorage {
-    /// Set up a new `DataStorage` instance
+    /// Set up a new `DataStorage` instance, using the default storage backend
+    ///
+    /// If the path already contains stored data, the used space tracker
+    /// is seeded with the size of that data.
     pub fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
-        Ok(Self {
-            chunks: ChunkStorage::new(path, used_space.clone())?,
-            registers: RegisterStorage::new(path, used_space.clone())?,
+        Self::with_backend(path, used_space, StorageBackendKind::default())
+    }
+
+    /// Set up a new `DataStorage` instance, using the given kind of storage backend
+    ///
+    /// If the path already contains stored data, the used space tracker
+    /// is seeded with the size of that data.
+    pub fn with_backend(
+        path: &Path,
+        used_space: UsedSpace,
+        backend: StorageBackendKind,
+    ) -> Result<Self> {
+        let root = BackendRoot::open(backend, path)?;
+        let storage = Self {
+            chunks: ChunkStorage::new(&root, used_space.clone())?,
+            registers: RegisterStorage::new(&root, used_space.clone())?,
+            spentbooks: SpentbookStorage::new(&root, used_space.clone())?,
             used_space,
             last_recorded_level: StorageLevel::zero(),
-        })
+            scrub_running: Arc::new(AtomicBool::new(false)),
+        };
+
+        let used_on_disk = storage.used_space_on_disk();
+        info!("Found {used_on_disk} bytes of data already stored at {path:?}");
+        storage.used_space.set(used_on_disk);
+
+        Ok(storage)
+    }
+
+    /// Measure the space used by the data stored on disk and correct
+    /// the tracked used space if it has drifted from it.
+    /// Returns the used space measured on disk.
+    pub(crate) fn reconcile_used_space(&self) -> usize {
+        // Stores/removals happening while we walk the disk can make this measure
+        // slightly off, any such difference will be corrected in the next pass.
+        let used_on_disk = self.used_space_on_disk();
+        let tracked = self.used_space.used();
+        if tracked != used_on_disk {
+            warn!(
+                "{:?}: tracked {tracked} bytes, but found {used_on_disk} bytes on disk",
+                LogMarker::UsedSpaceDriftCorrected
+            );
+            self.used_space.set(used_on_disk);
+        }
+
+        used_on_disk
+    }
+
+    /// Check the integrity of all the data stored on disk, re-hashing chunks and
+    /// re-verifying register cmds and spent proof shares signatures, the latter against
+    /// the given known section keys. Any corrupted data item is quarantined, thus it's no
+    /// longer served and it can be replicated again from other nodes.
+    /// Spentbooks still stored as Registers by legacy nodes are migrated first.
+    /// Returns the addresses of the data items quarantined, or `None` if a scrub was already
+    /// running, as a scrub reads all the data stored and one at a time is enough.
+    pub(crate) async fn scrub(
+        &self,
+        section_keys: &BTreeSet<bls::PublicKey>,
+    ) -> Option<Vec<DataAddress>> {
+        let _guard = self.start_scrub()?;
+        self.migrate_legacy_spentbooks(section_keys).await;
+
+        let mut quarantined = Vec::new();
+        for addr in self.data_addrs().await {
+            let result = match &addr {
+                DataAddress::Bytes(chunk_addr) => self.chunks.scrub_chunk(chunk_addr).await,
+                DataAddress::Register(reg_addr) => self.registers.scrub_register(reg_addr).await,
+                DataAddress::Spentbook(spentbook_addr) => {
+                    self.spentbooks
+                        .scrub_spentbook(spentbook_addr, section_keys)
+                        .await
+                }
+                other => Err(Error::UnsupportedDataType(*other)),
+            };
+
+            match result {
+                Ok(false) => {}
+                Ok(true) => {
+                    warn!("{:?}: {addr:?}", LogMarker::CorruptedDataQuarantined);
+                    quarantined.push(addr);
+                }
+                Err(error) => warn!("Failed to check integrity of {addr:?}: {error:?}"),
+            }
+        }
+
+        Some(quarantined)
+    }
+
+    // Marks a scrub as running, unless one already is
+    fn start_scrub(&self) -> Option<ScrubGuard> {
+        self.scrub_running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| ScrubGuard(self.scrub_running.clone()))
+    }
+
+    // Moves the spent proof shares of the spentbooks legacy nodes stored as Registers
+    // into the spentbook storage, removing each Register once all its shares are stored.
+    async fn migrate_legacy_spentbooks(&self, section_keys: &BTreeSet<bls::PublicKey>) {
+        for addr in self.registers.addrs().await {
+            if !is_legacy_spentbook(&addr) {
+                continue;
+            }
+
+            let result = match self.registers.get_register_replica(&addr).await {
+                Ok(log) => {
+                    self.spentbooks
+                        .migrate_legacy_register(&log, section_keys)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+
+            match result {
+                Ok(true) => match self.registers.remove_register(&addr).await {
+                    Ok(()) => info!("Legacy spentbook {addr:?} migrated"),
+                    Err(error) => warn!("Failed to remove migrated spentbook {addr:?}: {error:?}"),
+                },
+                Ok(false) => {}
+                Err(error) => warn!("Failed to migrate legacy spentbook {addr:?}: {error:?}"),
+            }
+        }
+    }
+
+    fn used_space_on_disk(&self) -> usize {
+        self.chunks.used_space_on_disk()
+            + self.registers.used_space_on_disk()
+            + self.spentbooks.used_space_on_disk()
     }
 
     /// Update the storage level on data storage
@@ -66,13 +207,13 @@ impl DataStorage {
         self.last_recorded_level = new_level;
     }
 
-    /// Store data in the local store
-    #[instrument(skip(self))]
+    /// Store data in the local store. Spent proof shares must be
+    /// signed by one of the given known section keys.
+    #[instrument(skip(self, section_keys))]
     pub async fn store(
         &self,
         data: &ReplicatedData,
-        section_pk: PublicKey,
-        node_keypair: Keypair,
+        section_keys: &BTreeSet<bls::PublicKey>,
     ) -> Result<Option<StorageLevel>> {
         debug!("Replicating {data:?}");
         match data {
@@ -82,14 +223,12 @@ impl DataStorage {
                 self.registers.update(data).await?
             }
             ReplicatedData::RegisterWrite(cmd) => self.registers.write(cmd).await?,
-            ReplicatedData::SpentbookWrite(cmd) => {
-                // FIMXE: this is temporary logic to have spentbooks as Registers.
-                // Spentbooks shall always exist, and the section nodes shall create them by default.
-                self.registers
-                    .write_spentbook_register(cmd, section_pk, node_keypair)
-                    .await?;
+            ReplicatedData::SpentbookWrite(share, tx) => {
+                self.spentbooks.write(share, Some(tx), section_keys).await?
+            }
+            ReplicatedData::SpentbookLog(data) => {
+                self.spentbooks.update(data, section_keys).await?
             }
-            ReplicatedData::SpentbookLog(data) => self.registers.update(data).await?,
         };
 
         // check if we've filled another approx. 10%-points of our storage
@@ -118,46 +257,17 @@ impl DataStorage {
         match query {
             DataQueryVariant::GetChunk(addr) => self.chunks.get(addr).await,
             DataQueryVariant::Register(read) => self.registers.read(read, requester).await,
-            DataQueryVariant::Spentbook(read) => {
-                // TODO: this is temporary till spentbook native data type is implemented,
-                // we read from the Register where we store the spentbook data
-                let reg_addr = RegisterAddress::new(read.dst_name(), SPENTBOOK_TYPE_TAG);
-
-                match self
-                    .registers
-                    .read(&RegisterQuery::Get(reg_addr), requester)
-                    .await
-                {
-                    NodeQueryResponse::GetRegister(Err(MessagingError::DataNotFound(_))) => {
-                        NodeQueryResponse::SpentProofShares(Ok(Vec::new()))
-                    }
-                    NodeQueryResponse::GetRegister(result) => {
-                        let proof_shares_result = result.map(|reg| {
-                            let mut proof_shares = Vec::new();
-                            let entries = reg.read();
-                            for (_, entry) in entries {
-                                // Deserialise spent proof share from the entry
-                                let spent_proof_share: SpentProofShare = match rmp_serde::from_slice(&entry) {
-                                    Ok(proof) => proof,
-                                    Err(err) => {
-                                        warn!("Ignoring entry found in Spentbook since it cannot be deserialised as a valid SpentProofShare: {:?}", err);
-                                        continue;
-                                    }
-                                };
-
-                                proof_shares.push(spent_proof_share);
-                            }
-                            proof_shares
-                        });
-
-                        NodeQueryResponse::SpentProofShares(proof_shares_result)
-                    }
-                    other => {
-                        // TODO: this is temporary till spentbook native data type is implemented,
-                        // for now we just return the response even that it's a Register query response.
-                        other
-                    }
-                }
+            DataQueryVariant::Spentbook(SpentbookQuery::SpentProofShares(address)) => {
+                self.spentbooks.get(address).await
+            }
+            DataQueryVariant::Spentbook(SpentbookQuery::SpentTransactions(_)) => {
+                self.spentbooks.sample_transactions().await
+            }
+            // quotes are provided by Elders, they are never sent to Adults
+            DataQueryVariant::GetStoreCost { .. } => {
+                query.to_error_response(ErrorMsg::InvalidOperation(
+                    "Store cost quotes are not provided by Adults".to_string(),
+                ))
             }
         }
     }
@@ -178,26 +288,46 @@ impl DataStorage {
                 .get_register_replica(addr)
                 .await
                 .map(ReplicatedData::RegisterLog),
-            DataAddress::Spentbook(addr) => {
-                let reg_addr = RegisterAddress::new(*addr.name(), SPENTBOOK_TYPE_TAG);
-                self.registers
-                    .get_register_replica(&reg_addr)
-                    .await
-                    .map(ReplicatedData::SpentbookLog)
-            }
+            DataAddress::Spentbook(addr) => self
+                .spentbooks
+                .get_spentbook_replica(addr)
+                .await
+                .map(ReplicatedData::SpentbookLog),
             other => Err(Error::UnsupportedDataType(*other)),
         }
     }
 
-    #[allow(dead_code)]
-    pub(crate) async fn remove(&mut self, address: &DataAddress) -> Result<()> {
+    /// Returns `true` if our replica of the data holds all the content of the given replica
+    pub(crate) async fn holds_replica(&self, replica: &ReplicatedData) -> Result<bool> {
+        let ours = match self.get_from_local_store(&replica.address()).await {
+            Ok(ours) => ours,
+            Err(
+                Error::ChunkNotFound(_) | Error::RegisterNotFound(_) | Error::SpentbookNotFound(_),
+            ) => return Ok(false),
+            Err(error) => return Err(error),
+        };
+
+        let holds_all = match (replica, &ours) {
+            (ReplicatedData::Chunk(chunk), ReplicatedData::Chunk(our_chunk)) => chunk == our_chunk,
+            (ReplicatedData::RegisterLog(log), ReplicatedData::RegisterLog(our_log)) => {
+                log.op_log.iter().all(|cmd| our_log.op_log.contains(cmd))
+            }
+            (ReplicatedData::SpentbookLog(log), ReplicatedData::SpentbookLog(our_log)) => log
+                .spent_proof_shares
+                .iter()
+                .all(|share| our_log.spent_proof_shares.contains(share)),
+            _ => false,
+        };
+
+        Ok(holds_all)
+    }
+
+    /// Remove data from the local store, decreasing the used space accordingly
+    pub(crate) async fn remove(&self, address: &DataAddress) -> Result<()> {
         match address {
             DataAddress::Bytes(addr) => self.chunks.remove_chunk(addr).await,
             DataAddress::Register(addr) => self.registers.remove_register(addr).await,
-            DataAddress::Spentbook(addr) => {
-                let reg_addr = RegisterAddress::new(*addr.name(), SPENTBOOK_TYPE_TAG);
-                self.registers.remove_register(&reg_addr).await
-            }
+            DataAddress::Spentbook(addr) => self.spentbooks.remove_spentbook(addr).await,
             other => Err(Error::UnsupportedDataType(*other)),
         }
     }
@@ -216,65 +346,52 @@ impl DataStorage {
                     .into_iter()
                     .map(DataAddress::Register),
             )
+            .chain(
+                self.spentbooks
+                    .addrs()
+                    .into_iter()
+                    .map(DataAddress::Spentbook),
+            )
             .collect()
     }
 }
 
-// Helper that returns the prefix tree path of depth BIT_TREE_DEPTH for a given xorname
-// Example:
-// - with a xorname with starting bits `010001110110....`
-// - and a BIT_TREE_DEPTH of `6`
-// returns the path `ROOT_PATH/0/1/0/0/0/1`
-fn prefix_tree_path(root: &Path, xorname: XorName) -> PathBuf {
-    let bin = format!("{:b}", xorname);
-    let prefix_dir_path: PathBuf = bin.chars().take(BIT_TREE_DEPTH).map(String::from).collect();
-    root.join(prefix_dir_path)
-}
-
-fn list_files_in(path: &Path) -> Vec<PathBuf> {
-    if !path.exists() {
-        return vec![];
-    }
-
-    WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| match e {
-            Ok(direntry) => Some(direntry),
-            Err(err) => {
-                warn!("Store: failed to process filesystem entry: {}", err);
-                None
-            }
-        })
-        .filter(|entry| entry.file_type().is_file())
-        .map(|entry| entry.path().to_path_buf())
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{DataStorage, Error, UsedSpace};
+    use super::{DataStorage, Error, StorageBackendKind, UsedSpace};
     use sn_interface::{
         init_logger,
         messaging::{
-            data::{CreateRegister, DataQueryVariant, SignedRegisterCreate},
+            data::{
+                CreateRegister, DataQueryVariant, EditRegister, SignedRegisterCreate,
+                SignedRegisterEdit,
+            },
             system::NodeQueryResponse,
+            ClientAuth,
         },
         test_utils::TestKeys,
         types::{
-            register::{Policy, User},
+            register::{Permissions, Policy, Register, User},
             utils::random_bytes,
-            Chunk, ChunkAddress, DataAddress, Keypair, PublicKey, RegisterCmd, ReplicatedData,
-            SectionSig,
+            Chunk, ChunkAddress, DataAddress, Keypair, RegisterAddress, RegisterCmd,
+            ReplicatedData, ReplicatedRegisterLog, SectionSig, SpentbookAddress,
         },
     };
 
-    use eyre::Result;
+    use eyre::{bail, Result};
     use proptest::{
         collection::SizeRange,
         prelude::{any, prop_oneof, proptest},
         strategy::Strategy,
     };
-    use std::{cmp::max, collections::BTreeMap, thread, time::Duration};
+    use sn_dbc::{Hash, IndexedSignatureShare, SpentProofContent, SpentProofShare};
+    use std::{
+        cmp::max,
+        collections::{BTreeMap, BTreeSet},
+        thread,
+        time::Duration,
+    };
+    use strum::IntoEnumIterator;
     use tempfile::tempdir;
     use tokio::runtime::Runtime;
     use xor_name::XorName;
@@ -285,6 +402,13 @@ mod tests {
 
     #[tokio::test]
     async fn data_storage_basics() -> Result<(), Error> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_basics_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_basics_with(kind: StorageBackendKind) -> Result<(), Error> {
         // Generate temp path for storage
         // Cleaned up automatically after test completes
         let tmp_dir = tempdir()?;
@@ -292,18 +416,15 @@ mod tests {
         let used_space = UsedSpace::new(usize::MAX);
 
         // Create instance
-        let mut storage = DataStorage::new(path, used_space)?;
+        let storage = DataStorage::with_backend(path, used_space, kind)?;
 
         // 5mb random data chunk
         let bytes = random_bytes(5 * 1024 * 1024);
         let chunk = Chunk::new(bytes);
         let replicated_data = ReplicatedData::Chunk(chunk.clone());
 
-        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let keypair = Keypair::new_ed25519();
-
         // Store the chunk
-        let _ = storage.store(&replicated_data, pk, keypair).await?;
+        let _ = storage.store(&replicated_data, &BTreeSet::new()).await?;
 
         // Test local fetch
         let fetched_data = storage
@@ -336,6 +457,13 @@ mod tests {
 
     #[tokio::test]
     async fn data_storage_chunk_keys_returned() -> Result<(), Error> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_chunk_keys_returned_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_chunk_keys_returned_with(kind: StorageBackendKind) -> Result<(), Error> {
         init_logger();
         // Generate temp path for storage
         // Cleaned up automatically after test completes
@@ -344,18 +472,15 @@ mod tests {
         let used_space = UsedSpace::new(usize::MAX);
 
         // Create instance
-        let storage = DataStorage::new(path, used_space)?;
+        let storage = DataStorage::with_backend(path, used_space, kind)?;
 
         // 5mb random data chunk
         let bytes = random_bytes(5 * 1024 * 1024);
         let chunk = Chunk::new(bytes);
         let replicated_chunk = ReplicatedData::Chunk(chunk.clone());
 
-        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let keypair = Keypair::new_ed25519();
-
         // Store the chunk
-        let _ = storage.store(&replicated_chunk, pk, keypair).await?;
+        let _ = storage.store(&replicated_chunk, &BTreeSet::new()).await?;
 
         let keys = storage.data_addrs().await;
 
@@ -368,6 +493,66 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn data_storage_used_space_seeded_on_restart() -> Result<(), Error> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_used_space_seeded_on_restart_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_used_space_seeded_on_restart_with(
+        kind: StorageBackendKind,
+    ) -> Result<(), Error> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path();
+        let used_space = UsedSpace::new(usize::MAX);
+        let storage = DataStorage::with_backend(path, used_space.clone(), kind)?;
+
+        let chunk = Chunk::new(random_bytes(1024 * 1024));
+        let replicated_chunk = ReplicatedData::Chunk(chunk.clone());
+        let _ = storage.store(&replicated_chunk, &BTreeSet::new()).await?;
+        assert_eq!(used_space.used(), chunk.value().len());
+
+        // a new instance over the same root dir shall account for the data already stored
+        drop(storage);
+        let restarted_used_space = UsedSpace::new(usize::MAX);
+        let _restarted = DataStorage::with_backend(path, restarted_used_space.clone(), kind)?;
+        assert_eq!(restarted_used_space.used(), chunk.value().len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_storage_used_space_reconciled() -> Result<(), Error> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_used_space_reconciled_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_used_space_reconciled_with(
+        kind: StorageBackendKind,
+    ) -> Result<(), Error> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path();
+        let used_space = UsedSpace::new(usize::MAX);
+        let storage = DataStorage::with_backend(path, used_space.clone(), kind)?;
+
+        let chunk = Chunk::new(random_bytes(1024));
+        let replicated_chunk = ReplicatedData::Chunk(chunk.clone());
+        let _ = storage.store(&replicated_chunk, &BTreeSet::new()).await?;
+
+        // make the tracked used space drift away from what's on disk
+        used_space.increase(5000);
+        assert_eq!(storage.reconcile_used_space(), chunk.value().len());
+        assert_eq!(used_space.used(), chunk.value().len());
+
+        Ok(())
+    }
+
     fn section_sig() -> SectionSig {
         let sk = bls::SecretKey::random();
         TestKeys::get_section_sig_bytes(&sk, "hello".as_bytes())
@@ -375,6 +560,15 @@ mod tests {
 
     #[tokio::test]
     async fn data_storage_register_keys_returned() -> Result<(), Error> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_register_keys_returned_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_register_keys_returned_with(
+        kind: StorageBackendKind,
+    ) -> Result<(), Error> {
         init_logger();
         // Generate temp path for storage
         // Cleaned up automatically after test completes
@@ -383,7 +577,7 @@ mod tests {
         let used_space = UsedSpace::new(usize::MAX);
 
         // Create instance
-        let storage = DataStorage::new(path, used_space)?;
+        let storage = DataStorage::with_backend(path, used_space, kind)?;
 
         // create reg cmd
 
@@ -400,7 +594,12 @@ mod tests {
 
         let policy = public_policy(owner);
 
-        let op = CreateRegister { name, tag, policy };
+        let op = CreateRegister {
+            name,
+            tag,
+            policy,
+            private: false,
+        };
         let signature = keypair.sign(&bincode::serialize(&op).expect("could not serialize op"));
         let section_auth = section_sig();
         let cmd = RegisterCmd::Create {
@@ -411,18 +610,18 @@ mod tests {
                     signature,
                 },
             },
-            section_sig: section_auth.clone(), // obtained after presenting a valid payment to the network
+            section_sig: Some(section_auth.clone()),
+            payment: None,
         };
 
         // ReplicatedData::RegisterWrite(reg_cmd)
 
         let replicated_register = ReplicatedData::RegisterWrite(cmd);
 
-        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let keypair = Keypair::new_ed25519();
-
         // Store the chunk
-        let _ = storage.store(&replicated_register, pk, keypair).await?;
+        let _ = storage
+            .store(&replicated_register, &BTreeSet::new())
+            .await?;
 
         let keys = storage.data_addrs().await;
 
@@ -435,6 +634,193 @@ mod tests {
         Ok(())
     }
 
+    fn create_register_cmd(
+        name: XorName,
+        tag: u64,
+        policy: Policy,
+        keypair: &Keypair,
+    ) -> Result<RegisterCmd> {
+        let op = CreateRegister {
+            name,
+            tag,
+            policy,
+            private: false,
+        };
+        let signature = keypair.sign(&bincode::serialize(&op)?);
+        Ok(RegisterCmd::Create {
+            cmd: SignedRegisterCreate {
+                op,
+                auth: ClientAuth {
+                    public_key: keypair.public_key(),
+                    signature,
+                },
+            },
+            section_sig: Some(section_sig()),
+            payment: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn data_storage_scrubbed_one_at_a_time() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let storage = DataStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        let no_keys = BTreeSet::new();
+
+        let running = storage.start_scrub();
+        assert!(running.is_some());
+        // the storage is shared by clones, which see the scrub running too
+        assert_eq!(storage.clone().scrub(&no_keys).await, None);
+
+        drop(running);
+        assert_eq!(storage.scrub(&no_keys).await, Some(vec![]));
+        assert_eq!(storage.scrub(&no_keys).await, Some(vec![]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_storage_legacy_spentbooks_migrated() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_legacy_spentbooks_migrated_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_legacy_spentbooks_migrated_with(kind: StorageBackendKind) -> Result<()> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let storage = DataStorage::with_backend(tmp_dir.path(), UsedSpace::new(usize::MAX), kind)?;
+        let no_keys = BTreeSet::new();
+
+        // a spent proof share, as legacy nodes wrote it to the Register named after its key image
+        let sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let key_image = bls::SecretKey::random().public_key();
+        let content = SpentProofContent {
+            key_image,
+            transaction_hash: Hash::from([1; 32]),
+            public_commitments: vec![],
+        };
+        let sig_share = sk_set.secret_key_share(0).sign(content.hash().as_ref());
+        let share = SpentProofShare {
+            content,
+            spentbook_pks: sk_set.public_keys(),
+            spentbook_sig_share: IndexedSignatureShare::new(0, sig_share),
+        };
+
+        let node_keypair = Keypair::new_ed25519();
+        let owner = User::Key(node_keypair.public_key());
+        let policy = Policy {
+            owner,
+            permissions: BTreeMap::from([(User::Anyone, Permissions::new(true))]),
+        };
+        let name = XorName::from_content(&key_image.to_bytes());
+        let mut register = Register::new(owner, name, 0, policy.clone());
+        let (_, edit) = register.write(rmp_serde::to_vec(&share)?, BTreeSet::default())?;
+        let op = EditRegister {
+            address: *register.address(),
+            edit,
+            policy_version: register.policy_version(),
+        };
+        let edit = RegisterCmd::Edit(SignedRegisterEdit {
+            auth: ClientAuth {
+                public_key: node_keypair.public_key(),
+                signature: node_keypair.sign(&bincode::serialize(&op)?),
+            },
+            op,
+        });
+        let create = create_register_cmd(name, 0, policy.clone(), &node_keypair)?;
+        for cmd in [create, edit] {
+            let _ = storage
+                .store(&ReplicatedData::RegisterWrite(cmd), &no_keys)
+                .await?;
+        }
+
+        // a Register with the same type tag but no spent proof shares is not a spentbook
+        let other_name = xor_name::rand::random();
+        let other = create_register_cmd(other_name, 0, policy, &node_keypair)?;
+        let _ = storage
+            .store(&ReplicatedData::RegisterWrite(other), &no_keys)
+            .await?;
+
+        let legacy_addr = DataAddress::Register(RegisterAddress::new(name, 0));
+        let other_addr = DataAddress::Register(RegisterAddress::new(other_name, 0));
+        let spentbook_addr = DataAddress::Spentbook(SpentbookAddress::new(name));
+
+        // shares signed by sections we don't know of are not migrated
+        assert_eq!(storage.scrub(&no_keys).await, Some(vec![]));
+        assert!(storage.data_addrs().await.contains(&legacy_addr));
+        assert!(!storage.data_addrs().await.contains(&spentbook_addr));
+
+        let section_keys = BTreeSet::from([sk_set.public_keys().public_key()]);
+        assert_eq!(storage.scrub(&section_keys).await, Some(vec![]));
+        let addrs = storage.data_addrs().await;
+        assert!(!addrs.contains(&legacy_addr));
+        assert!(addrs.contains(&other_addr));
+        match storage.get_from_local_store(&spentbook_addr).await? {
+            ReplicatedData::SpentbookLog(log) => assert_eq!(log.spent_proof_shares, vec![share]),
+            other => bail!("Unexpected data: {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_storage_holds_replica() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            data_storage_holds_replica_with(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn data_storage_holds_replica_with(kind: StorageBackendKind) -> Result<()> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let storage = DataStorage::with_backend(tmp_dir.path(), UsedSpace::new(usize::MAX), kind)?;
+        let no_keys = BTreeSet::new();
+
+        let chunk = ReplicatedData::Chunk(Chunk::new(random_bytes(1024)));
+        assert!(!storage.holds_replica(&chunk).await?);
+        let _ = storage.store(&chunk, &no_keys).await?;
+        assert!(storage.holds_replica(&chunk).await?);
+
+        // a replica of a Register holding a cmd we lack
+        let keypair = Keypair::new_ed25519();
+        let owner = User::Key(keypair.public_key());
+        let policy = Policy {
+            owner,
+            permissions: BTreeMap::new(),
+        };
+        let name = xor_name::rand::random();
+        let mut register = Register::new(owner, name, 15000, policy.clone());
+        let (_, edit) = register.write(random_bytes(10).to_vec(), BTreeSet::default())?;
+        let op = EditRegister {
+            address: *register.address(),
+            edit,
+            policy_version: register.policy_version(),
+        };
+        let edit = RegisterCmd::Edit(SignedRegisterEdit {
+            auth: ClientAuth {
+                public_key: keypair.public_key(),
+                signature: keypair.sign(&bincode::serialize(&op)?),
+            },
+            op,
+        });
+        let create = create_register_cmd(name, 15000, policy, &keypair)?;
+        let _ = storage
+            .store(&ReplicatedData::RegisterWrite(create.clone()), &no_keys)
+            .await?;
+
+        let replica = ReplicatedData::RegisterLog(ReplicatedRegisterLog {
+            address: *register.address(),
+            op_log: vec![create, edit],
+        });
+        assert!(!storage.holds_replica(&replica).await?);
+        let _ = storage.store(&replica, &no_keys).await?;
+        assert!(storage.holds_replica(&replica).await?);
+
+        Ok(())
+    }
+
     // Model-based testing where random sets of Operations are performed on the Storage module and
     // a hashmap. The behaviour of both the models should be identical.
     proptest! {
@@ -474,14 +860,19 @@ mod tests {
     }
 
     fn model_based_test_imp(ops: Vec<Op>) -> Result<(), Error> {
+        for kind in StorageBackendKind::iter() {
+            model_based_test_with(ops.clone(), kind)?;
+        }
+        Ok(())
+    }
+
+    fn model_based_test_with(ops: Vec<Op>, kind: StorageBackendKind) -> Result<(), Error> {
         let mut model: BTreeMap<XorName, ReplicatedData> = BTreeMap::new();
         let temp_dir = tempdir()?;
         let path = temp_dir.path();
         let used_space = UsedSpace::new(usize::MAX);
         let runtime = Runtime::new()?;
-        let mut storage = DataStorage::new(path, used_space)?;
-        let owner_pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let owner_keypair = Keypair::new_ed25519();
+        let storage = DataStorage::with_backend(path, used_space, kind)?;
         for op in ops {
             match op {
                 Op::Store(flag, chunk_size) => {
@@ -505,7 +896,7 @@ mod tests {
                         }
                     };
                     runtime.block_on(async {
-                        match storage.store(&data, owner_pk, owner_keypair.clone()).await {
+                        match storage.store(&data, &BTreeSet::new()).await {
                             Ok(_) => {
                                 // do nothing
                                 Ok(())
@@ -6,12 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{list_files_in, prefix_tree_path, Error, Result};
+use super::{
+    backend::{Quarantine, StorageBackend, StorageKey},
+    Error, Result,
+};
 
 use crate::UsedSpace;
 
 use sn_interface::{
-    messaging::data::SignedRegisterCreate,
+    messaging::data::{LegacyRegisterAddress, LegacyRegisterCmd, SignedRegisterCreate},
     types::{
         register::{EntryHash, Register},
         utils::{deserialise, serialise},
@@ -20,16 +23,15 @@ use sn_interface::{
 };
 
 use bincode::serialize;
-use std::{
-    collections::{btree_map::Entry, BTreeMap},
-    mem::size_of,
-    path::{Path, PathBuf},
-};
+use std::{collections::BTreeSet, sync::Arc};
 use tiny_keccak::{Hasher, Sha3};
-use tokio::fs::{create_dir_all, metadata, read, remove_file, File};
-use tokio::io::AsyncWriteExt;
 use xor_name::XorName;
 
+// Register cmds are stored prefixed with the version of the format they are serialised with.
+// Those stored before the format was versioned have no prefix, and they start with the index
+// of their variant instead, i.e. either 0 or 1, hence versions start at 2.
+const REGISTER_CMD_FORMAT_VERSION: u32 = 2;
+
 // Deterministic Id for a register Cmd, takes into account the underlying cmd, and all sigs
 type RegisterCmdId = String;
 
@@ -39,67 +41,82 @@ pub(super) type RegisterLog = Vec<RegisterCmd>;
 pub(super) struct StoredRegister {
     pub(super) state: Option<Register>,
     pub(super) op_log: RegisterLog,
-    pub(super) op_log_path: PathBuf,
+    pub(super) reg_id: XorName,
 }
 
 /// A disk store for Registers
 #[derive(Clone, Debug)]
 pub(super) struct RegisterStore {
-    file_store_path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+    quarantine: Quarantine,
     used_space: UsedSpace,
 }
 
 impl RegisterStore {
-    /// Creates a new `RegisterStore` at the specified root location
+    /// Creates a new `RegisterStore` on the specified backend
     ///
-    /// If the location specified already contains a `RegisterStore`, it is simply used
+    /// If the backend specified already contains a `RegisterStore`, it is simply used
     ///
     /// Used space of the dir is tracked
-    pub(super) fn new(file_store_path: PathBuf, used_space: UsedSpace) -> Result<Self> {
+    ///
+    /// Register logs found corrupted are moved to the `quarantine`
+    pub(super) fn new(
+        backend: Arc<dyn StorageBackend>,
+        quarantine: Quarantine,
+        used_space: UsedSpace,
+    ) -> Result<Self> {
         Ok(Self {
-            file_store_path,
+            backend,
+            quarantine,
             used_space,
         })
     }
 
-    pub(super) fn address_to_filepath(&self, addr: &RegisterAddress) -> Result<PathBuf> {
-        // this is a unique identifier of the Register,
-        // since it encodes both the xorname and tag.
-        let reg_id = XorName::from_content(&serialize(addr)?);
-        let path = prefix_tree_path(&self.file_store_path, reg_id);
+    pub(super) fn register_id(&self, addr: &RegisterAddress) -> Result<XorName> {
+        // this is a unique identifier of the Register, since it encodes the xorname, the tag,
+        // and whether it's private. Public Registers are identified by their legacy address,
+        // so those stored before private ones were introduced keep the same id.
+        let bytes = match LegacyRegisterAddress::from_address(addr) {
+            Some(legacy_addr) => serialize(&legacy_addr)?,
+            None => serialize(addr)?,
+        };
+        Ok(XorName::from_content(&bytes))
+    }
 
-        // we need to append a folder for the file specifically so bit depth is an issue when low.
-        // we use hex to get full id, not just first bytes
-        Ok(path.join(hex::encode(reg_id)))
+    /// Total size of the register cmds found on disk
+    pub(super) fn used_space_on_disk(&self) -> usize {
+        self.backend.used_space()
     }
 
     pub(super) async fn list_all_reg_addrs(&self) -> Vec<RegisterAddress> {
         trace!("Listening all register addrs");
-        let iter = list_files_in(&self.file_store_path)
-            .into_iter()
-            .filter_map(|e| e.parent().map(|parent| (parent.to_path_buf(), e.clone())));
-
-        let mut addrs = BTreeMap::<PathBuf, RegisterAddress>::new();
-        for (parent, op_file) in iter {
-            if let Entry::Vacant(vacant) = addrs.entry(parent) {
-                if let Ok(Ok(cmd)) = read(op_file)
-                    .await
-                    .map(|serialized_data| deserialise::<RegisterCmd>(&serialized_data))
-                {
-                    let _existing = vacant.insert(cmd.dst_address());
+        let mut reg_ids = BTreeSet::new();
+        let mut addrs = Vec::new();
+        for key in self.backend.keys() {
+            if key.entry.is_none() || reg_ids.contains(&key.name) {
+                continue;
+            }
+
+            // all cmds in a register log target the same register, so we can read any of them
+            if let Ok(Some(serialized_data)) = self.backend.read(&key).await {
+                if let Ok(cmd) = decode_stored_cmd(&serialized_data) {
+                    let _ = reg_ids.insert(key.name);
+                    addrs.push(cmd.dst_address());
                 }
             }
         }
 
         trace!("Listening all register addrs done");
-        addrs.into_values().collect()
+        addrs
     }
 
     pub(super) async fn delete_data(&self, addr: &RegisterAddress) -> Result<()> {
-        let filepath = self.address_to_filepath(addr)?;
-        let meta = metadata(filepath.clone()).await?;
-        remove_file(filepath).await?;
-        self.used_space.decrease(meta.len() as usize);
+        let reg_id = self.register_id(addr)?;
+        let size = self.backend.remove_entries(&reg_id).await?;
+        if size == 0 {
+            return Err(Error::RegisterNotFound(*addr));
+        }
+        self.used_space.decrease(size);
         Ok(())
     }
 
@@ -109,25 +126,23 @@ impl RegisterStore {
         &self,
         addr: &RegisterAddress,
     ) -> Result<StoredRegister> {
-        let path = self.address_to_filepath(addr)?;
+        let reg_id = self.register_id(addr)?;
         let mut stored_reg = StoredRegister {
             state: None,
             op_log: RegisterLog::new(),
-            op_log_path: path.clone(),
+            reg_id,
         };
 
-        if !path.exists() {
-            trace!("Register log does not exist yet: {}", path.display());
+        let entries = self.backend.read_entries(&reg_id).await?;
+        if entries.is_empty() {
+            trace!("Register log does not exist yet: {reg_id:?}");
             return Ok(stored_reg);
         }
 
-        trace!("Register log path exists: {}", path.display());
-        for filepath in list_files_in(&path) {
-            match read(&filepath)
-                .await
-                .map(|serialized_data| deserialise::<RegisterCmd>(&serialized_data))
-            {
-                Ok(Ok(reg_cmd)) => {
+        trace!("Register log exists: {reg_id:?}");
+        for (cmd_id, serialized_data) in entries {
+            match decode_stored_cmd(&serialized_data) {
+                Ok(reg_cmd) => {
                     stored_reg.op_log.push(reg_cmd.clone());
 
                     if let RegisterCmd::Create { cmd, .. } = reg_cmd {
@@ -136,15 +151,14 @@ impl RegisterStore {
                         let SignedRegisterCreate { op, .. } = cmd;
                         if stored_reg.state.is_none() {
                             let register =
-                                Register::new(*op.policy.owner(), op.name, op.tag, op.policy);
+                                Register::with_address(*op.policy.owner(), op.address(), op.policy);
                             stored_reg.state = Some(register);
                         }
                     }
                 }
                 other => {
                     warn!(
-                        "Ignoring corrupted register cmd from storage found at {}: {other:?}",
-                        filepath.display()
+                        "Ignoring corrupted register cmd from storage found at {reg_id:?}/{cmd_id}: {other:?}",
                     )
                 }
             }
@@ -153,22 +167,62 @@ impl RegisterStore {
         Ok(stored_reg)
     }
 
+    /// Reads the log of RegisterCmds for a given register address, checking all cmds
+    /// are intact, i.e. they can be deserialised, target the register, and match the id
+    /// they were stored with. Returns `None` if any of the cmds is corrupted.
+    pub(super) async fn read_intact_reg_log(
+        &self,
+        addr: &RegisterAddress,
+    ) -> Result<Option<RegisterLog>> {
+        let reg_id = self.register_id(addr)?;
+        let mut log = RegisterLog::new();
+        for (cmd_id, serialized_data) in self.backend.read_entries(&reg_id).await? {
+            let cmd = match decode_stored_cmd(&serialized_data) {
+                Ok(cmd) => cmd,
+                Err(err) => {
+                    warn!("Register cmd stored at {reg_id:?}/{cmd_id} cannot be deserialised: {err:?}");
+                    return Ok(None);
+                }
+            };
+
+            if &cmd.dst_address() != addr || register_operation_id(&cmd)? != cmd_id {
+                warn!("Register cmd stored at {reg_id:?}/{cmd_id} doesn't match its id: {cmd:?}");
+                return Ok(None);
+            }
+
+            log.push(cmd);
+        }
+
+        Ok(Some(log))
+    }
+
+    /// Moves the whole log of a register to quarantine, so it's no longer served
+    /// and can be replicated again from other nodes.
+    pub(super) async fn quarantine_data(&self, addr: &RegisterAddress) -> Result<()> {
+        let reg_id = self.register_id(addr)?;
+        for (cmd_id, serialized_data) in self.backend.read_entries(&reg_id).await? {
+            let key = StorageKey::entry(reg_id, cmd_id);
+            self.quarantine.keep(&key, &serialized_data).await?;
+        }
+
+        let size = self.backend.remove_entries(&reg_id).await?;
+        self.used_space.decrease(size);
+        Ok(())
+    }
+
     /// Persists a RegisterLog to disk
-    pub(super) async fn write_log_to_disk(&self, log: &RegisterLog, path: &Path) -> Result<()> {
+    pub(super) async fn write_log_to_disk(&self, log: &RegisterLog, reg_id: XorName) -> Result<()> {
         trace!(
-            "Writing to register log with {} cmd/s at {}",
+            "Writing to register log with {} cmd/s at {reg_id:?}",
             log.len(),
-            path.display()
         );
         if log.is_empty() {
             return Ok(());
         }
 
-        create_dir_all(path).await?;
-
         let mut last_err = None;
         for cmd in log {
-            if let Err(err) = self.write_register_cmd(cmd, path).await {
+            if let Err(err) = self.write_register_cmd(cmd, reg_id).await {
                 error!("Failed to write Register cmd {cmd:?} to disk: {err:?}");
                 last_err = Some(err);
             }
@@ -178,76 +232,79 @@ impl RegisterStore {
             Err(err)
         } else {
             trace!(
-                "Log of {} cmd/s written successfully at {}",
+                "Log of {} cmd/s written successfully at {reg_id:?}",
                 log.len(),
-                path.display()
             );
             Ok(())
         }
     }
 
     /// Persists a RegisterCmd to disk
-    pub(super) async fn write_register_cmd(&self, cmd: &RegisterCmd, path: &Path) -> Result<()> {
-        // rough estimate of the RegisterCmd
-        let required_space = size_of::<RegisterCmd>();
+    pub(super) async fn write_register_cmd(
+        &self,
+        cmd: &RegisterCmd,
+        reg_id: XorName,
+    ) -> Result<()> {
+        let serialized_data = serialise(&(REGISTER_CMD_FORMAT_VERSION, cmd))?;
+        let required_space = serialized_data.len();
         if !self.used_space.can_add(required_space) {
             return Err(Error::NotEnoughSpace);
         }
 
         let reg_cmd_id = register_operation_id(cmd)?;
-        let path = path.join(reg_cmd_id.clone());
         let addr = cmd.dst_address();
 
-        trace!(
-            "Writing cmd register log for {addr:?} at {}",
-            path.display()
-        );
+        trace!("Writing cmd register log for {addr:?} at {reg_id:?}/{reg_cmd_id}");
 
         let entry_hash = if let RegisterCmd::Edit(edit_cmd) = cmd {
             let entry_hash = EntryHash(edit_cmd.op.edit.crdt_op.hash());
             trace!(
-                "Writing RegisterEdit cmd log for {addr:?}, entry hash: {entry_hash}, at {}",
-                path.display()
+                "Writing RegisterEdit cmd log for {addr:?}, entry hash: {entry_hash}, at {reg_id:?}",
             );
             Some(entry_hash)
         } else {
-            trace!(
-                "Writing RegisterCreate cmd log for {addr:?} at {}",
-                path.display()
-            );
+            trace!("Writing RegisterCreate cmd log for {addr:?} at {reg_id:?}");
             None
         };
 
-        // it's deterministic, so they are exactly the same op so we can leave
-        if path.exists() {
+        // it's deterministic, so if it exists they are exactly the same op so we can leave
+        let key = StorageKey::entry(reg_id, reg_cmd_id.clone());
+        if !self.backend.write(&key, &serialized_data).await? {
             trace!("RegisterCmd exists on disk for {addr:?}, entry hash: {entry_hash:?}, so was not written: {cmd:?}");
             return Ok(());
         }
 
-        let mut file = File::create(&path).await?;
-
-        let serialized_data = serialise(cmd)?;
-        file.write_all(&serialized_data).await?;
-        // Let's sync up OS data to disk to reduce the chances of
-        // concurrent reading failing by reading an empty/incomplete file
-        file.sync_data().await?;
-
         self.used_space.increase(required_space);
 
         trace!(
-            "RegisterCmd writing successful for {addr:?}, id {reg_cmd_id}, at {}, entry hash: {entry_hash:?}",
-            path.display()
+            "RegisterCmd writing successful for {addr:?}, id {reg_cmd_id}, at {reg_id:?}, entry hash: {entry_hash:?}",
         );
         Ok(())
     }
 }
 
+// Deserialises a RegisterCmd stored on disk, either in the current format,
+// or in the legacy layout cmds were stored with before the format was versioned.
+fn decode_stored_cmd(bytes: &[u8]) -> Result<RegisterCmd> {
+    let version = REGISTER_CMD_FORMAT_VERSION.to_le_bytes();
+    if bytes.get(..version.len()) == Some(&version[..]) {
+        Ok(deserialise(&bytes[version.len()..])?)
+    } else {
+        Ok(deserialise::<LegacyRegisterCmd>(bytes)?.into())
+    }
+}
+
 // Gets an operation id, deterministic for a RegisterCmd, it takes
-// the full Cmd and all signers into consideration
+// the full Cmd and all signers into consideration. Cmds which can be represented in
+// the legacy layout are identified by it, so those stored before the format was
+// versioned keep the same id.
 fn register_operation_id(cmd: &RegisterCmd) -> Result<RegisterCmdId> {
     let mut hasher = Sha3::v256();
 
-    let bytes = serialise(cmd)?;
+    let bytes = match LegacyRegisterCmd::from_cmd(cmd) {
+        Some(legacy_cmd) => serialise(&legacy_cmd)?,
+        None => serialise(cmd)?,
+    };
     let mut output = [0; 64];
     hasher.update(&bytes);
     hasher.finalize(&mut output);
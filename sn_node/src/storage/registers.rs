@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
+    backend::BackendRoot,
     register_store::{RegisterStore, StoredRegister},
     Error, Result,
 };
@@ -14,28 +15,23 @@ use super::{
 use sn_interface::{
     messaging::{
         data::{
-            CreateRegister, EditRegister, RegisterCmd, RegisterQuery, SignedRegisterCreate,
-            SignedRegisterEdit,
+            EditRegister, LegacyCreateRegister, LegacyEditRegister, RegisterCmd, RegisterQuery,
+            SignedRegisterCreate, SignedRegisterEdit, SignedRegisterPolicyUpdate,
+            UpdateRegisterPolicy,
         },
         system::NodeQueryResponse,
-        ClientAuth, SectionSig, VerifyAuthority,
+        ClientAuth, VerifyAuthority,
     },
     types::{
-        register::{Action, EntryHash, Permissions, Policy, Register, User},
-        DataAddress, Keypair, PublicKey, RegisterAddress, ReplicatedRegisterLog,
-        SPENTBOOK_TYPE_TAG,
+        register::{Action, EntryHash, Policy, Register, User},
+        DataAddress, Error as NetworkDataError, RegisterAddress, ReplicatedRegisterLog,
     },
 };
 
 use crate::UsedSpace;
 use bincode::serialize;
-use std::{
-    collections::BTreeMap,
-    fmt::{self, Display, Formatter},
-    path::Path,
-};
+use std::fmt::{self, Display, Formatter};
 use tracing::info;
-use xor_name::XorName;
 
 const REGISTER_STORE_DIR_NAME: &str = "register";
 
@@ -47,21 +43,52 @@ pub(super) struct RegisterStorage {
 
 impl RegisterStorage {
     /// Create new `RegisterStorage`
-    pub(super) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
-        let file_store = RegisterStore::new(path.join(REGISTER_STORE_DIR_NAME), used_space)?;
+    pub(super) fn new(root: &BackendRoot, used_space: UsedSpace) -> Result<Self> {
+        let file_store = RegisterStore::new(
+            root.backend(REGISTER_STORE_DIR_NAME)?,
+            root.quarantine(REGISTER_STORE_DIR_NAME)?,
+            used_space,
+        )?;
         Ok(Self { file_store })
     }
 
-    #[allow(dead_code)]
     pub(super) async fn remove_register(&self, address: &RegisterAddress) -> Result<()> {
         trace!("Removing register, {:?}", address);
         self.file_store.delete_data(address).await
     }
 
+    /// Total size of the registers found on disk
+    pub(super) fn used_space_on_disk(&self) -> usize {
+        self.file_store.used_space_on_disk()
+    }
+
     pub(super) async fn addrs(&self) -> Vec<RegisterAddress> {
         self.file_store.list_all_reg_addrs().await
     }
 
+    /// Checks the integrity of the register log stored at rest, re-verifying the signature
+    /// of each cmd, and quarantines the register if any cmd is found corrupted.
+    /// Returns `true` if it was quarantined.
+    pub(super) async fn scrub_register(&self, address: &RegisterAddress) -> Result<bool> {
+        let is_intact = match self.file_store.read_intact_reg_log(address).await? {
+            Some(log) => log.iter().all(|cmd| match verify_cmd_signature(cmd) {
+                Ok(()) => true,
+                Err(err) => {
+                    warn!("Register cmd stored for {address:?} is not valid: {err:?}");
+                    false
+                }
+            }),
+            None => false,
+        };
+
+        if is_intact {
+            return Ok(false);
+        }
+
+        self.file_store.quarantine_data(address).await?;
+        Ok(true)
+    }
+
     /// Used for replication of data to new Adults.
     pub(super) async fn get_register_replica(
         &self,
@@ -81,10 +108,19 @@ impl RegisterStorage {
         debug!("Updating Register store: {:?}", data.address);
         let mut stored_reg = self.try_load_stored_register(&data.address).await?;
 
+        // Policy updates go first so edits are checked against the permissions they were
+        // made under, and the create cmd last so a new replica is rebuilt from the whole log.
+        let mut replicated_log: Vec<&RegisterCmd> = data.op_log.iter().collect();
+        replicated_log.sort_by_key(|cmd| match cmd {
+            RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate { op, .. }) => (0, op.version),
+            RegisterCmd::Edit(_) => (1, 0),
+            RegisterCmd::Create { .. } => (2, 0),
+        });
+
         let mut log_to_write = Vec::new();
-        for replicated_cmd in &data.op_log {
+        for replicated_cmd in replicated_log {
             if let Err(err) = self
-                .try_to_apply_cmd_against_register_state(replicated_cmd, &mut stored_reg)
+                .try_to_apply_cmd_against_register_state(replicated_cmd, &mut stored_reg, true)
                 .await
             {
                 warn!(
@@ -98,7 +134,7 @@ impl RegisterStorage {
 
         // Write the new cmds all to disk
         self.file_store
-            .write_log_to_disk(&log_to_write, &stored_reg.op_log_path)
+            .write_log_to_disk(&log_to_write, stored_reg.reg_id)
             .await
     }
 
@@ -110,12 +146,12 @@ impl RegisterStorage {
         // we have in local storage, to then try to apply the new command onto it.
         let mut stored_reg = self.try_load_stored_register(&cmd.dst_address()).await?;
 
-        self.try_to_apply_cmd_against_register_state(cmd, &mut stored_reg)
+        self.try_to_apply_cmd_against_register_state(cmd, &mut stored_reg, false)
             .await?;
 
         // Everything went fine, let's write the single cmd to disk
         self.file_store
-            .write_log_to_disk(&vec![cmd.clone()], &stored_reg.op_log_path)
+            .write_log_to_disk(&vec![cmd.clone()], stored_reg.reg_id)
             .await
     }
 
@@ -129,6 +165,12 @@ impl RegisterStorage {
             Read(address) => self.read_register(*address, requester).await,
             GetOwner(address) => self.get_owner(*address, requester).await,
             GetEntry { address, hash } => self.get_entry(*address, *hash, requester).await,
+            GetEntryAncestors { address, hash } => {
+                self.get_entry_ancestors(*address, *hash, requester).await
+            }
+            GetHistory(address) => self.get_history(*address, requester).await,
+            ReadAsOf { address, hash } => self.read_as_of(*address, *hash, requester).await,
+            GetOpLog(address) => self.get_op_log(*address, requester).await,
             GetPolicy(address) => self.get_policy(*address, requester).await,
             GetUserPermissions { address, user } => {
                 self.get_user_permissions(*address, *user, requester).await
@@ -204,6 +246,68 @@ impl RegisterStorage {
         NodeQueryResponse::GetRegisterEntry(result)
     }
 
+    async fn get_entry_ancestors(
+        &self,
+        address: RegisterAddress,
+        hash: EntryHash,
+        requester: User,
+    ) -> NodeQueryResponse {
+        let result = match self
+            .get_register(&address, Action::Read, requester)
+            .await
+            .and_then(|register| register.ancestors(hash).map_err(Error::from))
+        {
+            Ok(res) => Ok(res),
+            Err(error) => Err(error.into()),
+        };
+
+        NodeQueryResponse::GetRegisterEntryAncestors(result)
+    }
+
+    async fn get_history(&self, address: RegisterAddress, requester: User) -> NodeQueryResponse {
+        let result = match self.get_register(&address, Action::Read, requester).await {
+            Ok(register) => Ok(register.history()),
+            Err(error) => Err(error.into()),
+        };
+
+        NodeQueryResponse::GetRegisterHistory(result)
+    }
+
+    async fn read_as_of(
+        &self,
+        address: RegisterAddress,
+        hash: EntryHash,
+        requester: User,
+    ) -> NodeQueryResponse {
+        let result = match self
+            .get_register(&address, Action::Read, requester)
+            .await
+            .and_then(|register| register.read_as_of(hash).map_err(Error::from))
+        {
+            Ok(res) => Ok(res),
+            Err(error) => Err(error.into()),
+        };
+
+        NodeQueryResponse::ReadRegisterAsOf(result)
+    }
+
+    async fn get_op_log(&self, address: RegisterAddress, requester: User) -> NodeQueryResponse {
+        let result = match self.try_load_stored_register(&address).await {
+            Ok(StoredRegister {
+                state: Some(register),
+                op_log,
+                ..
+            }) => register
+                .check_permissions(Action::Read, Some(requester))
+                .map(|()| op_log)
+                .map_err(|error| Error::from(error).into()),
+            Ok(_) => Err(Error::RegisterNotFound(address).into()),
+            Err(error) => Err(error.into()),
+        };
+
+        NodeQueryResponse::GetRegisterOpLog(result)
+    }
+
     async fn get_user_permissions(
         &self,
         address: RegisterAddress,
@@ -239,67 +343,50 @@ impl RegisterStorage {
     // =========================== Helpers ====================================
     // ========================================================================
 
-    // Temporary helper function which makes sure there exists a Register for the spentbook,
-    // this shouldn't be required once we have a Spentbook data type.
-    pub(super) async fn write_spentbook_register(
-        &self,
-        cmd: &RegisterCmd,
-        section_pk: PublicKey,
-        node_keypair: Keypair,
-    ) -> Result<()> {
-        let address = cmd.dst_address();
-        trace!("Creating new spentbook register: {:?}", address);
-
-        let mut permissions = BTreeMap::new();
-        let _ = permissions.insert(User::Anyone, Permissions::new(true));
-        let owner = User::Key(section_pk);
-        let policy = Policy { owner, permissions };
-
-        let create_cmd =
-            create_reg_w_policy(*address.name(), SPENTBOOK_TYPE_TAG, policy, &node_keypair)?;
-
-        self.update(&ReplicatedRegisterLog {
-            address,
-            op_log: [create_cmd, cmd.clone()].to_vec(),
-        })
-        .await
-    }
-
     // Private helper which does all verification and tries to apply given cmd to given Register
     // state. It accumulates the cmd, if valid, into the log so further calls can be made with
     // the same state and log, as used by the `update` function.
     // Note the cmd is always pushed to the log even if it's a duplicated cmd.
+    // Edits sent by clients are checked against the current policy. Edits replicated from
+    // other nodes may have been made before the policy was last updated, thus, just like when
+    // replaying the log, they are checked against the policy of the version they were authored
+    // under, so every replica accepts the same edits regardless of the order cmds arrive in.
     async fn try_to_apply_cmd_against_register_state(
         &self,
         cmd: &RegisterCmd,
         stored_reg: &mut StoredRegister,
+        replicated: bool,
     ) -> Result<()> {
+        let policies = match (&stored_reg.state, cmd) {
+            (Some(_), RegisterCmd::Edit(edit)) if replicated => {
+                policies_of_version(&stored_reg.op_log, edit.op.policy_version)
+            }
+            (Some(register), _) => vec![register.policy().clone()],
+            (None, _) => vec![],
+        };
+
         // If we have the target Register, try to apply the cmd, otherwise let's keep
         // the cmd in the log anyway, whenever we receive the 'Register create' cmd
         // it can be reconstructed from all cmds we hold in the log. If this is a 'Register create'
         // cmd let's verify it's valid before accepting it, however 'Edits cmds' cannot be
         // verified untill we have the `Register create` cmd.
         match (stored_reg.state.as_mut(), cmd) {
-            (Some(ref mut register), cmd) => self.apply(cmd, register).await?,
-            (None, RegisterCmd::Create { cmd, .. }) => {
+            (Some(ref mut register), cmd) => {
+                self.apply(cmd, register, &policies, replicated).await?
+            }
+            (None, RegisterCmd::Create { cmd: create, .. }) => {
                 // the target Register is not in our store or we don't have the 'Register create',
                 // let's verify the create cmd we received is valid and try to apply stored cmds we may have.
-                let SignedRegisterCreate { op, auth } = cmd;
-                let public_key = auth.public_key;
-                let _ = auth
-                    .clone()
-                    .verify_authority(serialize(op)?)
-                    .or(Err(Error::InvalidSignature(public_key)))?;
+                verify_cmd_signature(cmd)?;
+                let SignedRegisterCreate { op, .. } = create;
 
-                trace!("Creating new register: {:?}", cmd.dst_address());
+                trace!("Creating new register: {:?}", create.dst_address());
                 // let's do a final check, let's try to apply all cmds to it,
                 // those which are new cmds were not validated yet, so let's do it now.
                 let mut register =
-                    Register::new(*op.policy.owner(), op.name, op.tag, op.policy.clone());
+                    Register::with_address(*op.policy.owner(), op.address(), op.policy.clone());
 
-                for cmd in &stored_reg.op_log {
-                    self.apply(cmd, &mut register).await?;
-                }
+                self.replay_log(&stored_reg.op_log, &mut register).await?;
 
                 stored_reg.state = Some(register);
             }
@@ -310,8 +397,20 @@ impl RegisterStorage {
         Ok(())
     }
 
-    // Try to apply the provided cmd to the register state, performing all op validations
-    async fn apply(&self, cmd: &RegisterCmd, register: &mut Register) -> Result<()> {
+    // Try to apply the provided cmd to the register state, performing all op validations.
+    // Edits are allowed if their author can write under any of the given policies, which
+    // are more than one only if the policy the edit was authored under was updated concurrently.
+    // Edits sent by clients must have been authored under the current policy, so they are
+    // accepted on replay too. Policy updates sent by clients must be newer than the current
+    // policy, whereas replicated ones may have been superseded already, in which case they are
+    // kept without taking effect.
+    async fn apply(
+        &self,
+        cmd: &RegisterCmd,
+        register: &mut Register,
+        policies: &[Policy],
+        replicated: bool,
+    ) -> Result<()> {
         let addr = cmd.dst_address();
         if &addr != register.address() {
             return Err(Error::RegisterAddrMismatch {
@@ -322,31 +421,108 @@ impl RegisterStorage {
 
         match cmd {
             RegisterCmd::Create { .. } => Err(Error::DataExists(DataAddress::Register(addr))),
-            RegisterCmd::Edit(SignedRegisterEdit { op, auth }) => {
+            RegisterCmd::Edit(edit) => {
+                if !replicated && edit.op.policy_version != register.policy_version() {
+                    return Err(Error::StaleRegisterEdit {
+                        address: addr,
+                        version: edit.op.policy_version,
+                        current: register.policy_version(),
+                    });
+                }
+                self.apply_edit(edit, register, policies)
+            }
+            RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate { op, auth }) => {
+                verify_cmd_signature(cmd)?;
                 let public_key = auth.public_key;
-                let _ = auth
-                    .clone()
-                    .verify_authority(serialize(op)?)
-                    .or(Err(Error::InvalidSignature(public_key)))?;
-
-                info!("Editing Register: {:?}", addr);
-                register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
-                let result = register
-                    .apply_op(op.edit.clone())
-                    .map_err(Error::NetworkData);
-
-                match result {
-                    Ok(()) => {
-                        trace!("Editing Register success: {:?}", addr);
-                        Ok(())
-                    }
-                    Err(err) => {
-                        trace!("Editing Register failed {:?}: {:?}", addr, err);
-                        Err(err)
+
+                info!("Updating Register policy: {:?}", addr);
+                register.check_permissions(Action::ManagePolicy, Some(User::Key(public_key)))?;
+                if !replicated && op.version <= register.policy_version() {
+                    return Err(Error::StalePolicyUpdate {
+                        address: addr,
+                        version: op.version,
+                        current: register.policy_version(),
+                    });
+                }
+                register.update_policy(op.version, op.policy.clone());
+                trace!("Updating Register policy success: {:?}", addr);
+                Ok(())
+            }
+        }
+    }
+
+    // Verify and apply an edit, which is allowed if its author can write under any of the policies
+    fn apply_edit(
+        &self,
+        cmd: &SignedRegisterEdit,
+        register: &mut Register,
+        policies: &[Policy],
+    ) -> Result<()> {
+        let SignedRegisterEdit { op, auth } = cmd;
+        let addr = *register.address();
+        let public_key = auth.public_key;
+        verify_edit_signature(cmd)?;
+
+        info!("Editing Register: {:?}", addr);
+        let user = User::Key(public_key);
+        if !policies
+            .iter()
+            .any(|policy| policy.is_action_allowed(user, Action::Write).is_ok())
+        {
+            return Err(Error::NetworkData(NetworkDataError::AccessDenied(user)));
+        }
+
+        let result = register
+            .apply_op(op.edit.clone())
+            .map_err(Error::NetworkData);
+
+        match result {
+            Ok(()) => {
+                trace!("Editing Register success: {:?}", addr);
+                Ok(())
+            }
+            Err(err) => {
+                trace!("Editing Register failed {:?}: {:?}", addr, err);
+                Err(err)
+            }
+        }
+    }
+
+    // Apply the cmds we held before receiving the 'Register create' cmd. They can be in any
+    // order, and the policy edits were authorised against may have been updated since, thus
+    // policy updates are applied first, in the order of their versions, and edits are accepted
+    // if their author was allowed to write under the policy of the version they were authored under.
+    async fn replay_log(&self, op_log: &[RegisterCmd], register: &mut Register) -> Result<()> {
+        let mut policy_updates: Vec<&RegisterCmd> = op_log
+            .iter()
+            .filter(|cmd| matches!(cmd, RegisterCmd::UpdatePolicy(_)))
+            .collect();
+        policy_updates.sort_by_key(|cmd| match cmd {
+            RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate { op, .. }) => op.version,
+            _ => 0,
+        });
+
+        // the 'Register create' cmd is not in the log yet, its policy is the first version's
+        let create_policy = register.policy().clone();
+        for cmd in policy_updates {
+            self.apply(cmd, register, &[], true).await?;
+        }
+
+        for cmd in op_log {
+            match cmd {
+                RegisterCmd::Edit(edit) if &cmd.dst_address() == register.address() => {
+                    let mut policies = policies_of_version(op_log, edit.op.policy_version);
+                    if edit.op.policy_version == 0 {
+                        policies.push(create_policy.clone());
                     }
+                    self.apply_edit(edit, register, &policies)?
                 }
+                RegisterCmd::UpdatePolicy(_) => {}
+                cmd => self.apply(cmd, register, &[], true).await?,
             }
         }
+
+        Ok(())
     }
 
     // Gets stored register log from disk, trying to reconstruct the Register
@@ -357,11 +533,20 @@ impl RegisterStorage {
         // if we have the Register creation cmd, apply all ops to reconstruct the Register
         if let Some(register) = &mut stored_reg.state {
             for cmd in &stored_reg.op_log {
-                if let RegisterCmd::Edit(SignedRegisterEdit { op, .. }) = cmd {
-                    let EditRegister { edit, .. } = op;
-                    register
-                        .apply_op(edit.clone())
-                        .map_err(Error::NetworkData)?;
+                match cmd {
+                    RegisterCmd::Edit(SignedRegisterEdit { op, .. }) => {
+                        let EditRegister { edit, .. } = op;
+                        register
+                            .apply_op(edit.clone())
+                            .map_err(Error::NetworkData)?;
+                    }
+                    RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate { op, .. }) => {
+                        let UpdateRegisterPolicy {
+                            version, policy, ..
+                        } = op;
+                        register.update_policy(*version, policy.clone());
+                    }
+                    RegisterCmd::Create { .. } => {}
                 }
             }
         }
@@ -376,51 +561,87 @@ impl Display for RegisterStorage {
     }
 }
 
-// Helper functions temporarily used for spentbook logic, but also used for tests.
-// This shouldn't be required outside of tests once we have a Spentbook data type.
-fn create_reg_w_policy(
-    name: XorName,
-    tag: u64,
-    policy: Policy,
-    node_keypair: &Keypair,
-) -> Result<RegisterCmd> {
-    let op = CreateRegister { name, tag, policy };
-    let signature = node_keypair.sign(&serialize(&op)?);
-
-    let auth = ClientAuth {
-        public_key: node_keypair.public_key(),
-        signature,
+// Policies of the given version a Register went through, as per the 'Register create' and
+// policy update cmds in its log. There is more than one if the policy was updated concurrently.
+fn policies_of_version(op_log: &[RegisterCmd], version: u64) -> Vec<Policy> {
+    op_log
+        .iter()
+        .filter_map(|cmd| match cmd {
+            RegisterCmd::Create { cmd, .. } if version == 0 => Some(cmd.op.policy.clone()),
+            RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate { op, .. })
+                if op.version == version =>
+            {
+                Some(op.policy.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Verifies the cmd was signed by the client which authored it. Clients which signed it
+// before the register cmds format was versioned did so over the cmd's legacy layout.
+fn verify_cmd_signature(cmd: &RegisterCmd) -> Result<()> {
+    let (auth, payload, legacy_payload) = match cmd {
+        RegisterCmd::Create { cmd, .. } => (
+            &cmd.auth,
+            serialize(&cmd.op)?,
+            LegacyCreateRegister::from_op(&cmd.op)
+                .map(|op| serialize(&op))
+                .transpose()?,
+        ),
+        RegisterCmd::Edit(cmd) => return verify_edit_signature(cmd),
+        RegisterCmd::UpdatePolicy(cmd) => (&cmd.auth, serialize(&cmd.op)?, None),
     };
 
-    Ok(RegisterCmd::Create {
-        cmd: SignedRegisterCreate { op, auth },
-        section_sig: section_sig(),
-    })
+    verify_signature(auth, payload, legacy_payload)
+}
+
+// Verifies the edit was signed by the client which authored it,
+// either over the edit or over its legacy layout.
+fn verify_edit_signature(cmd: &SignedRegisterEdit) -> Result<()> {
+    let legacy_payload = LegacyEditRegister::from_op(&cmd.op)
+        .map(|op| serialize(&op))
+        .transpose()?;
+    verify_signature(&cmd.auth, serialize(&cmd.op)?, legacy_payload)
 }
 
-fn section_sig() -> SectionSig {
-    let sk = bls::SecretKey::random();
-    let public_key = sk.public_key();
-    let data = "TODO-spentbook".to_string();
-    let signature = sk.sign(data);
-    SectionSig {
-        public_key,
-        signature,
+fn verify_signature(
+    auth: &ClientAuth,
+    payload: Vec<u8>,
+    legacy_payload: Option<Vec<u8>>,
+) -> Result<()> {
+    let public_key = auth.public_key;
+    let verified = auth.clone().verify_authority(payload).is_ok()
+        || legacy_payload
+            .map(|payload| auth.clone().verify_authority(payload).is_ok())
+            .unwrap_or(false);
+
+    if verified {
+        Ok(())
+    } else {
+        Err(Error::InvalidSignature(public_key))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{create_reg_w_policy, Error, RegisterStorage, UsedSpace};
+    use super::{BackendRoot, Error, RegisterStorage, UsedSpace, REGISTER_STORE_DIR_NAME};
+    use crate::storage::{backend::StorageKey, StorageBackendKind};
     use sn_interface::{
         messaging::{
-            data::{EditRegister, RegisterCmd, RegisterQuery, SignedRegisterEdit},
+            data::{
+                CreateRegister, EditRegister, LegacyCreateRegister, LegacyEditRegister,
+                LegacyPolicy, LegacyRegisterAddress, LegacyRegisterCmd, LegacySignedRegisterCreate,
+                LegacySignedRegisterEdit, RegisterCmd, RegisterQuery, SignedRegisterCreate,
+                SignedRegisterEdit, SignedRegisterPolicyUpdate, UpdateRegisterPolicy,
+            },
             system::NodeQueryResponse,
-            ClientAuth,
+            ClientAuth, SectionSig,
         },
         types::{
-            register::{EntryHash, Policy, Register, User},
-            DataAddress, Keypair,
+            register::{EntryHash, Permissions, Policy, Register, User},
+            DataAddress, Error as NetworkDataError, Keypair, RegisterAddress,
+            ReplicatedRegisterLog,
         },
     };
 
@@ -428,30 +649,75 @@ mod test {
     use eyre::{bail, eyre, Result};
     use rand::{distributions::Alphanumeric, Rng};
     use std::collections::BTreeSet;
-    use tempfile::tempdir;
+    use strum::IntoEnumIterator;
+    use tempfile::{tempdir, TempDir};
+    use tiny_keccak::{Hasher, Sha3};
     use xor_name::XorName;
 
+    fn create_reg_w_policy(
+        address: RegisterAddress,
+        policy: Policy,
+        node_keypair: &Keypair,
+    ) -> Result<RegisterCmd> {
+        let op = CreateRegister {
+            name: address.name,
+            tag: address.tag,
+            policy,
+            private: address.private,
+        };
+        let signature = node_keypair.sign(&serialize(&op)?);
+
+        let auth = ClientAuth {
+            public_key: node_keypair.public_key(),
+            signature,
+        };
+
+        Ok(RegisterCmd::Create {
+            cmd: SignedRegisterCreate { op, auth },
+            section_sig: Some(section_sig()),
+            payment: None,
+        })
+    }
+
+    fn section_sig() -> SectionSig {
+        let sk = bls::SecretKey::random();
+        let public_key = sk.public_key();
+        let data = "TODO-section-sig".to_string();
+        let signature = sk.sign(data);
+        SectionSig {
+            public_key,
+            signature,
+        }
+    }
+
     #[tokio::test]
     async fn test_register_try_load_stored() -> Result<()> {
-        let store = new_store()?;
+        for kind in StorageBackendKind::iter() {
+            register_try_load_stored(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_try_load_stored(kind: StorageBackendKind) -> Result<()> {
+        let (_tmp_dir, store) = new_store(kind)?;
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = store.file_store.register_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
 
         let stored_reg = store.try_load_stored_register(&addr).await?;
         // it should *not* contain the create cmd
         assert!(stored_reg.state.is_none());
         assert!(stored_reg.op_log.is_empty());
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         store.write(&cmd_create).await?;
         let stored_reg = store.try_load_stored_register(&addr).await?;
         // it should contain the create cmd
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
         assert_eq!(stored_reg.op_log, vec![cmd_create.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(0));
 
         // let's now edit the register
@@ -469,7 +735,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(1));
 
         Ok(())
@@ -477,11 +743,18 @@ mod test {
 
     #[tokio::test]
     async fn test_register_try_load_stored_inverted_cmds_order() -> Result<()> {
-        let store = new_store()?;
+        for kind in StorageBackendKind::iter() {
+            register_try_load_stored_inverted_cmds_order(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_try_load_stored_inverted_cmds_order(kind: StorageBackendKind) -> Result<()> {
+        let (_tmp_dir, store) = new_store(kind)?;
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = store.file_store.register_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
 
         // let's first store an edit cmd for the register
@@ -492,7 +765,7 @@ mod test {
         // it should contain the edit cmd only
         assert_eq!(stored_reg.state, None);
         assert_eq!(stored_reg.op_log, vec![cmd_edit.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         // and now store the create cmd for the register
         store.write(&cmd_create).await?;
@@ -508,7 +781,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(1));
 
         Ok(())
@@ -516,27 +789,34 @@ mod test {
 
     #[tokio::test]
     async fn test_register_apply_cmd_against_state() -> Result<()> {
-        let store = new_store()?;
+        for kind in StorageBackendKind::iter() {
+            register_apply_cmd_against_state(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_apply_cmd_against_state(kind: StorageBackendKind) -> Result<()> {
+        let (_tmp_dir, store) = new_store(kind)?;
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = store.file_store.register_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
         let mut stored_reg = store.try_load_stored_register(&addr).await?;
 
         // apply the create cmd
         store
-            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg, false)
             .await?;
         // it should contain the create cmd
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
         assert_eq!(stored_reg.op_log, vec![cmd_create.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.as_ref().map(|reg| reg.size()), Some(0));
 
         // apply the create cmd again should fail with DataExists
         match store
-            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg, false)
             .await
         {
             Ok(()) => bail!("An error should occur for this test case"),
@@ -549,7 +829,7 @@ mod test {
         // let's now apply an edit cmd
         let cmd_edit = edit_register(&mut register, &keypair)?;
         store
-            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg, false)
             .await?;
         // it should contain the create and edit cmds
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
@@ -561,13 +841,13 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.as_ref().map(|reg| reg.size()), Some(1));
 
         // applying the edit cmd again shouldn't fail or alter the register content,
         // although the log will contain the edit cmd duplicated
         store
-            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg, false)
             .await?;
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
         assert_eq!(stored_reg.op_log.len(), 3);
@@ -578,7 +858,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(1));
 
         Ok(())
@@ -586,28 +866,37 @@ mod test {
 
     #[tokio::test]
     async fn test_register_apply_cmd_against_state_inverted_cmds_order() -> Result<()> {
-        let store = new_store()?;
+        for kind in StorageBackendKind::iter() {
+            register_apply_cmd_against_state_inverted_cmds_order(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_apply_cmd_against_state_inverted_cmds_order(
+        kind: StorageBackendKind,
+    ) -> Result<()> {
+        let (_tmp_dir, store) = new_store(kind)?;
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = store.file_store.register_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
         let mut stored_reg = store.try_load_stored_register(&addr).await?;
 
         // apply an edit cmd first
         let cmd_edit = edit_register(&mut register, &keypair)?;
         store
-            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg, false)
             .await?;
         // it should contain the edit cmd
         assert_eq!(stored_reg.state, None);
         assert_eq!(stored_reg.op_log, vec![cmd_edit.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         // applying the edit cmd again shouldn't fail,
         // although the log will contain the edit cmd duplicated
         store
-            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_edit, &mut stored_reg, false)
             .await?;
         assert_eq!(stored_reg.state, None);
         assert_eq!(stored_reg.op_log.len(), 2);
@@ -615,11 +904,11 @@ mod test {
             stored_reg.op_log.iter().all(|op| op == &cmd_edit),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         // let's apply the create cmd now
         store
-            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg, false)
             .await?;
         // it should contain the create and edit cmds
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
@@ -631,12 +920,12 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.as_ref().map(|reg| reg.size()), Some(1));
 
         // apply the create cmd again should fail with DataExists
         match store
-            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg)
+            .try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg, false)
             .await
         {
             Ok(()) => bail!("An error should occur for this test case"),
@@ -651,8 +940,15 @@ mod test {
 
     #[tokio::test]
     async fn test_register_write() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_write(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_write(kind: StorageBackendKind) -> Result<()> {
         // setup store
-        let store = new_store()?;
+        let (_tmp_dir, store) = new_store(kind)?;
 
         // create register
         let (cmd, authority, _, _, _) = create_register()?;
@@ -684,10 +980,69 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn test_register_scrub() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_scrub(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_scrub(kind: StorageBackendKind) -> Result<()> {
+        // setup store
+        let (_tmp_dir, store) = new_store(kind)?;
+
+        let (cmd_create, _authority, keypair, name, policy) = create_register()?;
+        let addr = cmd_create.dst_address();
+        let mut register = Register::new(*policy.owner(), name, 0, policy);
+
+        store.write(&cmd_create).await?;
+        let cmd_edit = edit_register(&mut register, &keypair)?;
+        store.write(&cmd_edit).await?;
+
+        // an intact register log shall be left untouched
+        assert!(!store.scrub_register(&addr).await?);
+        assert_eq!(store.addrs().await, vec![addr]);
+
+        // store an edit cmd with a forged signature, bypassing validations
+        let (_, other_keypair) = random_user();
+        let forged_edit = match edit_register(&mut register, &keypair)? {
+            RegisterCmd::Edit(SignedRegisterEdit { op, auth }) => {
+                RegisterCmd::Edit(SignedRegisterEdit {
+                    auth: ClientAuth {
+                        signature: other_keypair.sign(&serialize(&op)?),
+                        ..auth
+                    },
+                    op,
+                })
+            }
+            other => bail!("An edit cmd was expected: {other:?}"),
+        };
+        let reg_id = store.file_store.register_id(&addr)?;
+        store
+            .file_store
+            .write_register_cmd(&forged_edit, reg_id)
+            .await?;
+
+        // the register shall now be quarantined
+        assert!(store.scrub_register(&addr).await?);
+        assert!(store.addrs().await.is_empty());
+        assert_eq!(store.used_space_on_disk(), 0);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_export() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_export(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_export(kind: StorageBackendKind) -> Result<()> {
         // setup store
-        let store = new_store()?;
+        let (_tmp_dir, store) = new_store(kind)?;
 
         let (cmd_create, authority, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
@@ -717,7 +1072,7 @@ mod test {
         let all_addrs = store.addrs().await;
 
         // create new store and update it with the data from first store
-        let new_store = new_store()?;
+        let (_new_tmp_dir, new_store) = new_store(kind)?;
         for addr in all_addrs {
             let replica = store.get_register_replica(&addr).await?;
             new_store.update(&replica).await?;
@@ -751,10 +1106,356 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_policy_update() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_policy_update(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_policy_update(kind: StorageBackendKind) -> Result<()> {
+        // setup store
+        let (_tmp_dir, store) = new_store(kind)?;
+
+        let (cmd_create, owner, owner_keypair, name, policy) = create_register()?;
+        let addr = cmd_create.dst_address();
+        store.write(&cmd_create).await?;
+
+        // a user who was not granted permissions can neither write nor manage the policy
+        let (writer, writer_keypair) = random_user();
+        let mut writer_replica = Register::new(writer, name, 0, policy.clone());
+        let cmd_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+        match store.write(&cmd_edit).await {
+            Err(Error::NetworkData(NetworkDataError::AccessDenied(user))) => {
+                assert_eq!(user, writer)
+            }
+            other => bail!("An AccessDenied error was expected: {other:?}"),
+        }
+
+        let mut permissions = policy.permissions.clone();
+        let _ = permissions.insert(writer, Permissions::new(true));
+        let grant_writer = Policy { owner, permissions };
+        let cmd_update = update_register_policy(addr, 1, grant_writer.clone(), &writer_keypair)?;
+        match store.write(&cmd_update).await {
+            Err(Error::NetworkData(NetworkDataError::AccessDenied(user))) => {
+                assert_eq!(user, writer)
+            }
+            other => bail!("An AccessDenied error was expected: {other:?}"),
+        }
+
+        // once the owner grants it, the user can write but still not manage the policy
+        let cmd_update = update_register_policy(addr, 1, grant_writer.clone(), &owner_keypair)?;
+        store.write(&cmd_update).await?;
+        match store.write(&cmd_edit).await {
+            Err(Error::StaleRegisterEdit {
+                version: 0,
+                current: 1,
+                ..
+            }) => {}
+            other => bail!("A StaleRegisterEdit error was expected: {other:?}"),
+        }
+        writer_replica.update_policy(1, grant_writer.clone());
+        let cmd_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+        store.write(&cmd_edit).await?;
+
+        let cmd_update = update_register_policy(addr, 2, policy.clone(), &writer_keypair)?;
+        match store.write(&cmd_update).await {
+            Err(Error::NetworkData(NetworkDataError::AccessDenied(user))) => {
+                assert_eq!(user, writer)
+            }
+            other => bail!("An AccessDenied error was expected: {other:?}"),
+        }
+
+        // an update with an outdated version is rejected, as well as one concurrent to the last
+        for version in [0, 1] {
+            let cmd_stale_update =
+                update_register_policy(addr, version, policy.clone(), &owner_keypair)?;
+            match store.write(&cmd_stale_update).await {
+                Err(Error::StalePolicyUpdate {
+                    version: stale,
+                    current: 1,
+                    ..
+                }) => assert_eq!(stale, version),
+                other => bail!("A StalePolicyUpdate error was expected: {other:?}"),
+            }
+        }
+
+        let stored_reg = store.try_load_stored_register(&addr).await?;
+        let register = stored_reg
+            .state
+            .ok_or_else(|| eyre!("The register should have been found"))?;
+        assert_eq!(register.policy(), &grant_writer);
+        assert_eq!(register.policy_version(), 1);
+        assert_eq!(register.size(), 1);
+
+        // the policy update is replicated along with the rest of the log
+        let replica = store.get_register_replica(&addr).await?;
+        let (_new_tmp_dir, new_store) = new_store(kind)?;
+        new_store.update(&replica).await?;
+        match new_store.read(&RegisterQuery::GetPolicy(addr), owner).await {
+            NodeQueryResponse::GetRegisterPolicy(Ok(replicated_policy)) => {
+                assert_eq!(replicated_policy, grant_writer)
+            }
+            other => bail!("Could not read the policy: {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_policy_revoked_then_replicated() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_policy_revoked_then_replicated(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_policy_revoked_then_replicated(kind: StorageBackendKind) -> Result<()> {
+        let (cmd_create, owner, owner_keypair, name, policy) = create_register()?;
+        let addr = cmd_create.dst_address();
+
+        // the owner grants a user write permissions, who then makes an edit
+        let (writer, writer_keypair) = random_user();
+        let mut permissions = policy.permissions.clone();
+        let _ = permissions.insert(writer, Permissions::new(true));
+        let grant_writer = Policy { owner, permissions };
+        let cmd_grant = update_register_policy(addr, 1, grant_writer.clone(), &owner_keypair)?;
+        let mut writer_replica = Register::new(writer, name, 0, policy.clone());
+        writer_replica.update_policy(1, grant_writer);
+        let cmd_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+
+        // edits authored under policies the user wasn't allowed to write under,
+        // i.e. before the permissions were granted and after they were revoked
+        let mut stale_replica = Register::new(writer, name, 0, policy.clone());
+        let cmd_stale_edit = edit_register(&mut stale_replica, &writer_keypair)?;
+        let mut revoked_replica = Register::new(writer, name, 0, policy.clone());
+        revoked_replica.update_policy(2, policy.clone());
+        let cmd_revoked_edit = edit_register(&mut revoked_replica, &writer_keypair)?;
+
+        // a replica learns about the revocation before the edit is replicated to it
+        let (_tmp_dir, store) = new_store(kind)?;
+        store.write(&cmd_create).await?;
+        store.write(&cmd_grant).await?;
+        let cmd_revoke = update_register_policy(addr, 2, policy, &owner_keypair)?;
+        store.write(&cmd_revoke).await?;
+
+        // new edits from the user are rejected...
+        let cmd_new_edit = edit_register(&mut revoked_replica, &writer_keypair)?;
+        match store.write(&cmd_new_edit).await {
+            Err(Error::NetworkData(NetworkDataError::AccessDenied(user))) => {
+                assert_eq!(user, writer)
+            }
+            other => bail!("An AccessDenied error was expected: {other:?}"),
+        }
+
+        // ...and so are replicated edits authored under policies which didn't allow the user to
+        // write, even if the user was allowed to by another policy the Register went through...
+        for cmd in [cmd_stale_edit, cmd_revoked_edit] {
+            let edit_log = ReplicatedRegisterLog {
+                address: addr,
+                op_log: vec![cmd],
+            };
+            store.update(&edit_log).await?;
+        }
+        let register = store
+            .try_load_stored_register(&addr)
+            .await?
+            .state
+            .ok_or_else(|| eyre!("The register should have been found"))?;
+        assert_eq!(register.size(), 0);
+
+        // ...but the edit made before the revocation is accepted when replicated
+        let edit_log = ReplicatedRegisterLog {
+            address: addr,
+            op_log: vec![cmd_edit],
+        };
+        store.update(&edit_log).await?;
+        let register = store
+            .try_load_stored_register(&addr)
+            .await?
+            .state
+            .ok_or_else(|| eyre!("The register should have been found"))?;
+        assert_eq!(register.size(), 1);
+
+        // and a fresh replica rebuilt from the whole log converges to the same state
+        let replica = store.get_register_replica(&addr).await?;
+        let (_new_tmp_dir, new_store) = new_store(kind)?;
+        new_store.update(&replica).await?;
+        let new_register = new_store
+            .try_load_stored_register(&addr)
+            .await?
+            .state
+            .ok_or_else(|| eyre!("The register should have been found"))?;
+        assert_eq!(new_register.read(), register.read());
+        assert_eq!(new_register.policy(), register.policy());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_legacy_log() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_legacy_log(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_legacy_log(kind: StorageBackendKind) -> Result<()> {
+        // a register log stored, and signed, in the layout used before the format was versioned
+        let (owner, owner_keypair) = random_user();
+        let name = xor_name::rand::random();
+        let legacy_addr = LegacyRegisterAddress { name, tag: 0 };
+        let policy = Policy {
+            owner,
+            permissions: Default::default(),
+        };
+        let create_op = LegacyCreateRegister {
+            name,
+            tag: 0,
+            policy: LegacyPolicy {
+                owner,
+                permissions: Default::default(),
+            },
+        };
+        let cmd_create = LegacyRegisterCmd::Create {
+            cmd: LegacySignedRegisterCreate {
+                auth: ClientAuth {
+                    public_key: owner_keypair.public_key(),
+                    signature: owner_keypair.sign(&serialize(&create_op)?),
+                },
+                op: create_op,
+            },
+            section_sig: section_sig(),
+        };
+
+        let mut owner_replica = Register::new(owner, name, 0, policy.clone());
+        let (_, edit) = owner_replica.write(b"legacy entry".to_vec(), BTreeSet::default())?;
+        let edit_op = LegacyEditRegister::from_op(&EditRegister {
+            address: RegisterAddress::new(name, 0),
+            edit,
+            policy_version: 0,
+        })
+        .ok_or_else(|| eyre!("The edit should be representable in the legacy layout"))?;
+        let cmd_edit = LegacyRegisterCmd::Edit(LegacySignedRegisterEdit {
+            auth: ClientAuth {
+                public_key: owner_keypair.public_key(),
+                signature: owner_keypair.sign(&serialize(&edit_op)?),
+            },
+            op: edit_op,
+        });
+
+        let tmp_dir = tempdir()?;
+        {
+            let backend =
+                BackendRoot::open(kind, tmp_dir.path())?.backend(REGISTER_STORE_DIR_NAME)?;
+            let reg_id = XorName::from_content(&serialize(&legacy_addr)?);
+            for cmd in [&cmd_create, &cmd_edit] {
+                let bytes = serialize(cmd)?;
+                let mut hasher = Sha3::v256();
+                let mut cmd_id = [0; 64];
+                hasher.update(&bytes);
+                hasher.finalize(&mut cmd_id);
+                let key = StorageKey::entry(reg_id, hex::encode(cmd_id));
+                let _ = backend.write(&key, &bytes).await?;
+            }
+        }
+        let store = RegisterStorage::new(
+            &BackendRoot::open(kind, tmp_dir.path())?,
+            UsedSpace::new(usize::MAX),
+        )?;
+
+        // the register is found at its public address, and it's intact
+        let addr = RegisterAddress::new(name, 0);
+        assert_eq!(store.addrs().await, vec![addr]);
+        assert!(!store.scrub_register(&addr).await?);
+        let register = store
+            .try_load_stored_register(&addr)
+            .await?
+            .state
+            .ok_or_else(|| eyre!("The register should have been found"))?;
+        assert_eq!(register.policy(), &policy);
+        assert_eq!(register.size(), 1);
+
+        // the same cmds received again are identified as the ones already stored
+        let used_space = store.used_space_on_disk();
+        store.write(&RegisterCmd::from(cmd_edit)).await?;
+        assert_eq!(store.used_space_on_disk(), used_space);
+
+        // new cmds can be written on top, and the whole log replicated
+        let cmd_new_edit = edit_register(&mut owner_replica, &owner_keypair)?;
+        store.write(&cmd_new_edit).await?;
+        let replica = store.get_register_replica(&addr).await?;
+        let (_new_tmp_dir, new_store) = new_store(kind)?;
+        new_store.update(&replica).await?;
+        let new_register = new_store
+            .try_load_stored_register(&addr)
+            .await?
+            .state
+            .ok_or_else(|| eyre!("The register should have been found"))?;
+        assert_eq!(new_register.size(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_private_address_space() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_private_address_space(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_private_address_space(kind: StorageBackendKind) -> Result<()> {
+        let (_tmp_dir, store) = new_store(kind)?;
+
+        // a public and a private Register with the same name and tag
+        let (cmd_create, authority, keypair, name, policy) = create_register()?;
+        store.write(&cmd_create).await?;
+        let private_addr = RegisterAddress::new_private(name, 0);
+        let cmd_create_private = create_reg_w_policy(private_addr, policy.clone(), &keypair)?;
+        store.write(&cmd_create_private).await?;
+
+        // they are different Registers, edits to one don't affect the other
+        let mut private_register = Register::with_address(authority, private_addr, policy);
+        let cmd_edit = edit_register(&mut private_register, &keypair)?;
+        store.write(&cmd_edit).await?;
+
+        match store
+            .read(&RegisterQuery::Get(cmd_create.dst_address()), authority)
+            .await
+        {
+            NodeQueryResponse::GetRegister(Ok(register)) => {
+                assert!(!register.address().is_private());
+                assert_eq!(register.size(), 0);
+            }
+            other => bail!("Could not read the public Register: {other:?}"),
+        }
+        match store
+            .read(&RegisterQuery::Get(private_addr), authority)
+            .await
+        {
+            NodeQueryResponse::GetRegister(Ok(register)) => {
+                assert!(register.address().is_private());
+                assert_eq!(register.size(), 1);
+            }
+            other => bail!("Could not read the private Register: {other:?}"),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_non_existing_entry() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_non_existing_entry(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_non_existing_entry(kind: StorageBackendKind) -> Result<()> {
         // setup store
-        let store = new_store()?;
+        let (_tmp_dir, store) = new_store(kind)?;
 
         // create register
         let (cmd_create, authority, _, _, _) = create_register()?;
@@ -780,10 +1481,78 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_get_op_log() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_get_op_log(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_get_op_log(kind: StorageBackendKind) -> Result<()> {
+        let (_tmp_dir, store) = new_store(kind)?;
+
+        let (cmd_create, authority, keypair, name, policy) = create_register()?;
+        let address = cmd_create.dst_address();
+        let mut register = Register::new(*policy.owner(), name, 0, policy);
+        store.write(&cmd_create).await?;
+
+        let cmd_edit = edit_register(&mut register, &keypair)?;
+        store.write(&cmd_edit).await?;
+
+        let op_log = match store
+            .read(&RegisterQuery::GetOpLog(address), authority)
+            .await
+        {
+            NodeQueryResponse::GetRegisterOpLog(Ok(op_log)) => op_log,
+            other => bail!("Unexpected response when getting the op log: {other:?}"),
+        };
+        // the create cmd is among them, so the log is enough to repair a replica
+        assert_eq!(op_log.len(), 2);
+        assert!(op_log.contains(&cmd_create));
+        assert!(op_log.contains(&cmd_edit));
+
+        // a replica missing the Register altogether is repaired with the log of another one
+        let (_tmp_dir, missing) = new_store(kind)?;
+        for cmd in &op_log {
+            missing.write(cmd).await?;
+        }
+        let query = RegisterQuery::Get(address);
+        match (
+            store.read(&query, authority).await,
+            missing.read(&query, authority).await,
+        ) {
+            (
+                NodeQueryResponse::GetRegister(Ok(stored)),
+                NodeQueryResponse::GetRegister(Ok(repaired)),
+            ) => assert_eq!(repaired, stored),
+            other => bail!("Unexpected responses when getting the Register: {other:?}"),
+        }
+
+        // the log of a Register we don't have cannot be retrieved
+        let (cmd_create, authority, ..) = create_register()?;
+        let res = store
+            .read(
+                &RegisterQuery::GetOpLog(cmd_create.dst_address()),
+                authority,
+            )
+            .await;
+        assert!(res.is_data_not_found(), "Unexpected response: {res:?}");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_non_existing_permissions() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            register_non_existing_permissions(kind).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_non_existing_permissions(kind: StorageBackendKind) -> Result<()> {
         // setup store
-        let store = new_store()?;
+        let (_tmp_dir, store) = new_store(kind)?;
 
         // create register
         let (cmd_create, authority, _, _, _) = create_register()?;
@@ -813,12 +1582,13 @@ mod test {
         Ok(())
     }
 
-    fn new_store() -> Result<RegisterStorage> {
+    // The temp dir is returned so it's not removed till the test finishes
+    fn new_store(kind: StorageBackendKind) -> Result<(TempDir, RegisterStorage)> {
         let tmp_dir = tempdir()?;
         let path = tmp_dir.path();
         let used_space = UsedSpace::new(usize::MAX);
-        let store = RegisterStorage::new(path, used_space)?;
-        Ok(store)
+        let store = RegisterStorage::new(&BackendRoot::open(kind, path)?, used_space)?;
+        Ok((tmp_dir, store))
     }
 
     fn random_user() -> (User, Keypair) {
@@ -834,7 +1604,7 @@ mod test {
             permissions: Default::default(),
         };
         let xorname = xor_name::rand::random();
-        let cmd = create_reg_w_policy(xorname, 0, policy.clone(), &keypair)?;
+        let cmd = create_reg_w_policy(RegisterAddress::new(xorname, 0), policy.clone(), &keypair)?;
 
         Ok((cmd, authority, keypair, xorname, policy))
     }
@@ -848,6 +1618,7 @@ mod test {
         let op = EditRegister {
             address: *register.address(),
             edit,
+            policy_version: register.policy_version(),
         };
         let signature = keypair.sign(&serialize(&op)?);
 
@@ -859,4 +1630,26 @@ mod test {
             },
         }))
     }
+
+    fn update_register_policy(
+        address: RegisterAddress,
+        version: u64,
+        policy: Policy,
+        keypair: &Keypair,
+    ) -> Result<RegisterCmd> {
+        let op = UpdateRegisterPolicy {
+            address,
+            version,
+            policy,
+        };
+        let signature = keypair.sign(&serialize(&op)?);
+
+        Ok(RegisterCmd::UpdatePolicy(SignedRegisterPolicyUpdate {
+            op,
+            auth: ClientAuth {
+                public_key: keypair.public_key(),
+                signature,
+            },
+        }))
+    }
 }
@@ -0,0 +1,869 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    backend::{BackendRoot, Quarantine, StorageBackend, StorageKey},
+    Error, Result, UsedSpace,
+};
+
+use rand::seq::SliceRandom;
+use sn_dbc::{Hash, KeyImage, RingCtTransaction, SpentProofShare};
+use sn_interface::{
+    messaging::{
+        data::{RegisterCmd, SpentTransaction},
+        system::NodeQueryResponse,
+    },
+    types::{
+        log_markers::LogMarker,
+        utils::{deserialise, serialise},
+        DataAddress, RegisterAddress, ReplicatedRegisterLog, ReplicatedSpentbookLog,
+        SpentbookAddress,
+    },
+};
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+use tiny_keccak::{Hasher, Sha3};
+use tokio::sync::Mutex;
+use xor_name::XorName;
+
+const SPENTBOOK_STORE_DIR_NAME: &str = "spentbook";
+const SPENT_TRANSACTIONS_STORE_DIR_NAME: &str = "spent_transactions";
+
+// Max number of spent transactions sampled for a client to pick decoys from
+const MAX_SAMPLED_TRANSACTIONS: usize = 64;
+
+// Number of entries a spentbook can be stored as before they are compacted into a single one
+const COMPACTION_THRESHOLD: usize = 16;
+
+// Type tag of the Registers spentbooks were stored as before they had their own storage
+pub(super) const LEGACY_SPENTBOOK_TYPE_TAG: u64 = 0;
+
+/// Operations over the Spentbook data type and its storage.
+///
+/// The spent proof shares of a key image are stored as entries of the item named
+/// after the key image. Each entry holds a list of shares, which is a single share when
+/// written, and all of them once the entries of the key image have been compacted.
+/// The transaction the key image was spent with is stored apart, as an item named
+/// after the key image, so clients can sample decoys out of the outputs of spent transactions.
+#[derive(Clone, Debug)]
+pub(super) struct SpentbookStorage {
+    backend: Arc<dyn StorageBackend>,
+    transactions: Arc<dyn StorageBackend>,
+    quarantine: Quarantine,
+    used_space: UsedSpace,
+    // Writes are serialised so no two shares for different
+    // transactions can be stored concurrently for a key image
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl SpentbookStorage {
+    /// Creates a new `SpentbookStorage` at the specified root location
+    ///
+    /// If the location specified already contains a `SpentbookStorage`, it is simply used
+    ///
+    /// Used space of the dir is tracked
+    pub(super) fn new(root: &BackendRoot, used_space: UsedSpace) -> Result<Self> {
+        Ok(Self {
+            backend: root.backend(SPENTBOOK_STORE_DIR_NAME)?,
+            transactions: root.backend(SPENT_TRANSACTIONS_STORE_DIR_NAME)?,
+            quarantine: root.quarantine(SPENTBOOK_STORE_DIR_NAME)?,
+            used_space,
+            write_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    pub(super) fn addrs(&self) -> Vec<SpentbookAddress> {
+        self.backend
+            .keys()
+            .into_iter()
+            .filter(|key| key.entry.is_some())
+            .map(|key| key.name)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(SpentbookAddress::new)
+            .collect()
+    }
+
+    /// Total size of the spentbooks found on disk
+    pub(super) fn used_space_on_disk(&self) -> usize {
+        self.backend.used_space() + self.transactions.used_space()
+    }
+
+    pub(super) async fn remove_spentbook(&self, address: &SpentbookAddress) -> Result<()> {
+        trace!("Removing spentbook, {:?}", address);
+        let size = self.backend.remove_entries(address.name()).await?;
+        if size == 0 {
+            return Err(Error::SpentbookNotFound(*address));
+        }
+        self.used_space.decrease(size);
+
+        let key = StorageKey::item(*address.name());
+        if let Some(size) = self.transactions.remove(&key).await? {
+            self.used_space.decrease(size);
+        }
+        Ok(())
+    }
+
+    /// Used for replication of data to new Adults.
+    pub(super) async fn get_spentbook_replica(
+        &self,
+        address: &SpentbookAddress,
+    ) -> Result<ReplicatedSpentbookLog> {
+        let spent_proof_shares = self.read_shares(address).await?;
+        if spent_proof_shares.is_empty() {
+            return Err(Error::SpentbookNotFound(*address));
+        }
+
+        Ok(ReplicatedSpentbookLog {
+            address: *address,
+            spent_proof_shares,
+            spent_transaction: self.read_transaction(address).await?,
+        })
+    }
+
+    // Read the spent proof shares from local store and return NodeQueryResponse
+    pub(super) async fn get(&self, address: &SpentbookAddress) -> NodeQueryResponse {
+        // a key image which hasn't been spent simply has no shares
+        NodeQueryResponse::SpentProofShares(
+            self.read_shares(address)
+                .await
+                .map_err(|error| error.into()),
+        )
+    }
+
+    // Read a sample of the spent transactions stored and return NodeQueryResponse
+    pub(super) async fn sample_transactions(&self) -> NodeQueryResponse {
+        NodeQueryResponse::SpentTransactions(self.read_sample().await.map_err(|error| error.into()))
+    }
+
+    /// Update our spentbook replica on receiving data from other nodes.
+    pub(super) async fn update(
+        &self,
+        data: &ReplicatedSpentbookLog,
+        section_keys: &BTreeSet<bls::PublicKey>,
+    ) -> Result<()> {
+        debug!("Updating Spentbook store: {:?}", data.address);
+        let mut last_err = None;
+        for share in &data.spent_proof_shares {
+            if spentbook_addr(share.key_image()) != data.address {
+                warn!(
+                    "Spent proof share for {:?} doesn't belong to spentbook {:?}",
+                    share.key_image(),
+                    data.address
+                );
+                last_err = Some(Error::InvalidSpentProofShare(data.address));
+                continue;
+            }
+
+            match self
+                .write(share, data.spent_transaction.as_ref(), section_keys)
+                .await
+            {
+                Ok(()) | Err(Error::DataExists(_)) => {}
+                Err(err) => {
+                    warn!(
+                        "Failed to store spent proof share for {:?}: {err:?}",
+                        data.address
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        match last_err {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Stores a spent proof share, unless there is already a share stored
+    /// for the same key image but a different transaction, i.e. a double spend.
+    /// The share must be signed by one of the given known section keys.
+    /// The transaction the share was signed for is stored too if provided, unless it already is.
+    #[instrument(skip_all)]
+    pub(super) async fn write(
+        &self,
+        share: &SpentProofShare,
+        tx: Option<&RingCtTransaction>,
+        section_keys: &BTreeSet<bls::PublicKey>,
+    ) -> Result<()> {
+        let address = spentbook_addr(share.key_image());
+        verify_share(share, &address, section_keys)?;
+
+        let _lock = self.write_lock.lock().await;
+
+        let stored_shares = self.read_shares(&address).await?;
+        let already_stored = stored_shares.contains(share);
+        if !already_stored
+            && stored_shares
+                .iter()
+                .any(|stored| stored.transaction_hash() != share.transaction_hash())
+        {
+            warn!(
+                "{:?}: {:?}",
+                LogMarker::SpentbookDoubleSpendRejected,
+                share.key_image()
+            );
+            return Err(Error::DoubleSpendAttempt(*share.key_image()));
+        }
+
+        if let Some(tx) = tx {
+            self.write_transaction(&address, share, tx).await?;
+        }
+        if already_stored {
+            return Err(Error::DataExists(DataAddress::Spentbook(address)));
+        }
+
+        let value = serialise(&vec![share.clone()])?;
+        if !self.used_space.can_add(value.len()) {
+            return Err(Error::NotEnoughSpace);
+        }
+
+        let key = StorageKey::entry(*address.name(), entry_id(&value));
+        if !self.backend.write(&key, &value).await? {
+            return Err(Error::DataExists(DataAddress::Spentbook(address)));
+        }
+        self.used_space.increase(value.len());
+        trace!(
+            "{:?}: {:?}",
+            LogMarker::SpentProofShareStored,
+            share.key_image()
+        );
+
+        if self.backend.read_entries(address.name()).await?.len() > COMPACTION_THRESHOLD {
+            self.compact(&address).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the integrity of the spentbook stored at rest, re-verifying the signature
+    /// share of each spent proof share against the given known section keys. Only the
+    /// entries found corrupted are quarantined, the valid shares they held are stored again.
+    /// Returns `true` if any entry was quarantined.
+    pub(super) async fn scrub_spentbook(
+        &self,
+        address: &SpentbookAddress,
+        section_keys: &BTreeSet<bls::PublicKey>,
+    ) -> Result<bool> {
+        let mut quarantined = false;
+        for (id, value) in self.backend.read_entries(address.name()).await? {
+            let valid_shares = match deserialise::<Vec<SpentProofShare>>(&value) {
+                Ok(shares) => {
+                    let total = shares.len();
+                    let valid_shares: Vec<_> = shares
+                        .into_iter()
+                        .filter(|share| {
+                            spentbook_addr(share.key_image()) == *address
+                                && verify_share(share, address, section_keys).is_ok()
+                        })
+                        .collect();
+                    if valid_shares.len() == total {
+                        continue;
+                    }
+                    warn!(
+                        "Spentbook entry stored at {address:?}/{id} holds {} invalid share/s",
+                        total - valid_shares.len()
+                    );
+                    valid_shares
+                }
+                Err(err) => {
+                    warn!("Spentbook entry stored at {address:?}/{id} cannot be deserialised: {err:?}");
+                    vec![]
+                }
+            };
+
+            let key = StorageKey::entry(*address.name(), id);
+            self.quarantine.keep(&key, &value).await?;
+            if let Some(size) = self.backend.remove(&key).await? {
+                self.used_space.decrease(size);
+            }
+            quarantined = true;
+
+            if !valid_shares.is_empty() {
+                let value = serialise(&valid_shares)?;
+                let key = StorageKey::entry(*address.name(), entry_id(&value));
+                if self.backend.write(&key, &value).await? {
+                    self.used_space.increase(value.len());
+                }
+            }
+        }
+
+        Ok(quarantined)
+    }
+
+    /// Stores the spent proof shares a legacy spentbook Register holds, as long as it
+    /// is one, i.e. it has entries and all of them are shares of the key image it's named after.
+    /// Returns `true` if all of them were stored, thus the Register can be removed.
+    pub(super) async fn migrate_legacy_register(
+        &self,
+        log: &ReplicatedRegisterLog,
+        section_keys: &BTreeSet<bls::PublicKey>,
+    ) -> Result<bool> {
+        let shares = match legacy_register_shares(log) {
+            Some(shares) if !shares.is_empty() => shares,
+            _ => return Ok(false),
+        };
+
+        let mut migrated = true;
+        for share in &shares {
+            match self.write(share, None, section_keys).await {
+                Ok(()) | Err(Error::DataExists(_)) => {}
+                Err(err) => {
+                    warn!(
+                        "Failed to migrate spent proof share of legacy spentbook {:?}: {err:?}",
+                        log.address
+                    );
+                    migrated = false;
+                }
+            }
+        }
+
+        Ok(migrated)
+    }
+
+    // Stores the transaction the key image of the spentbook was spent with, unless it already is.
+    // It must be the transaction the share was signed for, spending the key image of the share.
+    async fn write_transaction(
+        &self,
+        address: &SpentbookAddress,
+        share: &SpentProofShare,
+        tx: &RingCtTransaction,
+    ) -> Result<()> {
+        let key = StorageKey::item(*address.name());
+        if self.transactions.exists(&key)? {
+            return Ok(());
+        }
+
+        let spends_key_image = tx
+            .mlsags
+            .iter()
+            .any(|mlsag| KeyImage::from(mlsag.key_image) == *share.key_image());
+        if Hash::from(tx.hash()) != share.transaction_hash() || !spends_key_image {
+            warn!(
+                "Transaction provided for {address:?} is not the one its key image was spent with"
+            );
+            return Err(Error::InvalidSpentTransaction(*address));
+        }
+
+        let value = serialise(tx)?;
+        if !self.used_space.can_add(value.len()) {
+            return Err(Error::NotEnoughSpace);
+        }
+        if self.transactions.write(&key, &value).await? {
+            self.used_space.increase(value.len());
+        }
+
+        Ok(())
+    }
+
+    // Reads the transaction the key image of the spentbook was spent with, if it's stored.
+    // A transaction which cannot be deserialised is not returned.
+    async fn read_transaction(
+        &self,
+        address: &SpentbookAddress,
+    ) -> Result<Option<RingCtTransaction>> {
+        let key = StorageKey::item(*address.name());
+        let tx = self
+            .transactions
+            .read(&key)
+            .await?
+            .and_then(|value| match deserialise(&value) {
+                Ok(tx) => Some(tx),
+                Err(err) => {
+                    warn!("Ignoring corrupted spent transaction found at {address:?}: {err:?}");
+                    None
+                }
+            });
+
+        Ok(tx)
+    }
+
+    // Reads a random sample of the spent transactions stored, each along with the shares
+    // stored for the key image it was stored for. Transactions without any are left out.
+    async fn read_sample(&self) -> Result<Vec<SpentTransaction>> {
+        let addresses: Vec<_> = self
+            .transactions
+            .keys()
+            .choose_multiple(&mut rand::thread_rng(), MAX_SAMPLED_TRANSACTIONS)
+            .map(|key| SpentbookAddress::new(key.name))
+            .collect();
+
+        let mut sample = Vec::new();
+        for address in addresses {
+            let tx = match self.read_transaction(&address).await? {
+                Some(tx) => tx,
+                None => continue,
+            };
+
+            let tx_hash = Hash::from(tx.hash());
+            let spent_proof_shares: Vec<_> = self
+                .read_shares(&address)
+                .await?
+                .into_iter()
+                .filter(|share| share.transaction_hash() == tx_hash)
+                .collect();
+            if !spent_proof_shares.is_empty() {
+                sample.push(SpentTransaction {
+                    tx,
+                    spent_proof_shares,
+                });
+            }
+        }
+
+        Ok(sample)
+    }
+
+    // Reads all the spent proof shares stored for the spentbook, without duplicates.
+    // Entries which cannot be deserialised are skipped, these are quarantined when scrubbed.
+    async fn read_shares(&self, address: &SpentbookAddress) -> Result<Vec<SpentProofShare>> {
+        let mut shares = Vec::new();
+        for (entry_id, value) in self.backend.read_entries(address.name()).await? {
+            match deserialise::<Vec<SpentProofShare>>(&value) {
+                Ok(entry_shares) => {
+                    for share in entry_shares {
+                        if !shares.contains(&share) {
+                            shares.push(share);
+                        }
+                    }
+                }
+                Err(err) => warn!(
+                    "Ignoring corrupted spentbook entry found at {address:?}/{entry_id}: {err:?}"
+                ),
+            }
+        }
+
+        Ok(shares)
+    }
+
+    // Compacts all the entries of the spentbook into a single one. The compacted entry is
+    // written before the entries it replaces are removed, so no share is ever missing.
+    async fn compact(&self, address: &SpentbookAddress) -> Result<()> {
+        let entries = self.backend.read_entries(address.name()).await?;
+        let mut shares = Vec::new();
+        let mut compacted_ids = Vec::new();
+        for (entry_id, value) in entries {
+            // corrupted entries are left for the scrubber to quarantine them
+            if let Ok(entry_shares) = deserialise::<Vec<SpentProofShare>>(&value) {
+                for share in entry_shares {
+                    if !shares.contains(&share) {
+                        shares.push(share);
+                    }
+                }
+                compacted_ids.push(entry_id);
+            }
+        }
+
+        let value = serialise(&shares)?;
+        let compacted_id = entry_id(&value);
+        let key = StorageKey::entry(*address.name(), compacted_id.clone());
+        if self.backend.write(&key, &value).await? {
+            self.used_space.increase(value.len());
+        }
+
+        for entry_id in compacted_ids {
+            if entry_id == compacted_id {
+                continue;
+            }
+            let key = StorageKey::entry(*address.name(), entry_id);
+            if let Some(size) = self.backend.remove(&key).await? {
+                self.used_space.decrease(size);
+            }
+        }
+
+        debug!(
+            "Spentbook {address:?} compacted into a single entry with {} shares",
+            shares.len()
+        );
+        Ok(())
+    }
+}
+
+impl Display for SpentbookStorage {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "SpentbookStorage")
+    }
+}
+
+/// Address of the spentbook where the spent proof shares of a key image are stored
+fn spentbook_addr(key_image: &KeyImage) -> SpentbookAddress {
+    SpentbookAddress::new(XorName::from_content(&key_image.to_bytes()))
+}
+
+/// Returns `true` if the Register could be a spentbook stored by legacy nodes
+pub(super) fn is_legacy_spentbook(address: &RegisterAddress) -> bool {
+    address.tag == LEGACY_SPENTBOOK_TYPE_TAG && !address.is_private()
+}
+
+// Decodes the spent proof shares written as entries of a legacy spentbook Register.
+// Returns `None` if any of its entries is not a share of the key image it's named after.
+fn legacy_register_shares(log: &ReplicatedRegisterLog) -> Option<Vec<SpentProofShare>> {
+    let address = SpentbookAddress::new(*log.address.name());
+    log.op_log
+        .iter()
+        .filter_map(|cmd| match cmd {
+            RegisterCmd::Edit(edit) => Some(&edit.op.edit.crdt_op.value),
+            _ => None,
+        })
+        .map(|entry| {
+            rmp_serde::from_slice::<SpentProofShare>(entry)
+                .ok()
+                .filter(|share| spentbook_addr(share.key_image()) == address)
+        })
+        .collect()
+}
+
+// Verifies the signature share of the spent proof share against its spentbook key set,
+// which must be the key set of a section known to us, as anyone can make up a key set.
+fn verify_share(
+    share: &SpentProofShare,
+    address: &SpentbookAddress,
+    section_keys: &BTreeSet<bls::PublicKey>,
+) -> Result<()> {
+    let spentbook_pks = share.spentbook_pks();
+    if !section_keys.contains(&spentbook_pks.public_key()) {
+        warn!(
+            "Spent proof share for {address:?} is signed by an unknown section key: {:?}",
+            spentbook_pks.public_key()
+        );
+        return Err(Error::InvalidSpentProofShare(*address));
+    }
+
+    let (index, sig_share) = share.spentbook_sig_share().threshold_crypto();
+    if spentbook_pks
+        .public_key_share(index)
+        .verify(sig_share, share.content.hash().as_ref())
+    {
+        Ok(())
+    } else {
+        Err(Error::InvalidSpentProofShare(*address))
+    }
+}
+
+// Deterministic id of an entry, derived from its content. As for register cmd ids,
+// it's not 32 bytes long so it cannot be mistaken for the name of a data item.
+fn entry_id(value: &[u8]) -> String {
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 64];
+    hasher.update(value);
+    hasher.finalize(&mut output);
+    hex::encode(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageBackendKind;
+
+    use sn_dbc::{IndexedSignatureShare, OwnerOnce, SpentProofContent, TransactionBuilder};
+    use sn_interface::dbcs::gen_genesis_dbc;
+
+    use eyre::{bail, Result};
+    use strum::IntoEnumIterator;
+    use tempfile::{tempdir, TempDir};
+
+    // The temp dir is returned so it's not removed till the test finishes
+    fn init_store(kind: StorageBackendKind) -> Result<(TempDir, SpentbookStorage)> {
+        let root = tempdir()?;
+        let backend_root = BackendRoot::open(kind, root.path())?;
+        let store = SpentbookStorage::new(&backend_root, UsedSpace::new(usize::MAX))?;
+        Ok((root, store))
+    }
+
+    fn spent_proof_shares(
+        key_image: KeyImage,
+        transaction_hash: Hash,
+        count: u64,
+    ) -> Vec<SpentProofShare> {
+        let sk_set = bls::SecretKeySet::random(count as usize, &mut rand::thread_rng());
+        let content = SpentProofContent {
+            key_image,
+            transaction_hash,
+            public_commitments: vec![],
+        };
+
+        (0..count)
+            .map(|index| {
+                let sig_share = sk_set.secret_key_share(index).sign(content.hash().as_ref());
+                SpentProofShare {
+                    content: content.clone(),
+                    spentbook_pks: sk_set.public_keys(),
+                    spentbook_sig_share: IndexedSignatureShare::new(index, sig_share),
+                }
+            })
+            .collect()
+    }
+
+    // Keys of the sections which signed the given shares
+    fn section_keys(shares: &[SpentProofShare]) -> BTreeSet<bls::PublicKey> {
+        shares
+            .iter()
+            .map(|share| share.spentbook_pks().public_key())
+            .collect()
+    }
+
+    // Shares are read in no particular order, so we sort them by their signature share index
+    fn sorted(mut shares: Vec<SpentProofShare>) -> Vec<SpentProofShare> {
+        shares.sort_by_key(|share| share.spentbook_sig_share().threshold_crypto().0);
+        shares
+    }
+
+    fn random_key_image() -> KeyImage {
+        bls::SecretKey::random().public_key()
+    }
+
+    // A transaction spending a genesis DBC, along with the key image it spends
+    fn spent_transaction() -> Result<(KeyImage, RingCtTransaction)> {
+        let sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+        let owner =
+            OwnerOnce::from_owner_base(genesis_dbc.owner_base().clone(), &mut rand::thread_rng());
+        let tx_builder = TransactionBuilder::default()
+            .set_decoys_per_input(0)
+            .set_require_all_decoys(false)
+            .add_input_dbc_bearer(&genesis_dbc)?;
+        let amount = tx_builder.inputs_amount_sum();
+        let dbc_builder = tx_builder
+            .add_output_by_amount(amount, owner)
+            .build(rand::thread_rng())?;
+        let (key_image, tx) = dbc_builder.inputs().remove(0);
+        Ok((key_image, tx))
+    }
+
+    #[tokio::test]
+    async fn spentbook_write_and_read() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let key_image = random_key_image();
+            let address = spentbook_addr(&key_image);
+            let shares = spent_proof_shares(key_image, Hash::from([1; 32]), 3);
+            let keys = section_keys(&shares);
+
+            for share in &shares {
+                store.write(share, None, &keys).await?;
+            }
+            // the same share is never stored twice
+            assert!(matches!(
+                store.write(&shares[0], None, &keys).await,
+                Err(Error::DataExists(_))
+            ));
+
+            match store.get(&address).await {
+                NodeQueryResponse::SpentProofShares(Ok(stored)) => {
+                    assert_eq!(sorted(stored), sorted(shares))
+                }
+                other => bail!("Unexpected response: {other:?}"),
+            }
+            assert_eq!(store.addrs(), vec![address]);
+
+            // key images not spent have no shares
+            let not_spent = spentbook_addr(&random_key_image());
+            assert_eq!(
+                store.get(&not_spent).await,
+                NodeQueryResponse::SpentProofShares(Ok(vec![]))
+            );
+            assert!(matches!(
+                store.get_spentbook_replica(&not_spent).await,
+                Err(Error::SpentbookNotFound(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbook_rejects_double_spends() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let key_image = random_key_image();
+            let address = spentbook_addr(&key_image);
+            let shares = spent_proof_shares(key_image, Hash::from([1; 32]), 1);
+            let double_spend = spent_proof_shares(key_image, Hash::from([2; 32]), 1);
+            let keys = section_keys(&[shares.clone(), double_spend.clone()].concat());
+
+            store.write(&shares[0], None, &keys).await?;
+            assert!(matches!(
+                store.write(&double_spend[0], None, &keys).await,
+                Err(Error::DoubleSpendAttempt(image)) if image == key_image
+            ));
+
+            // nor can it be stored when replicated
+            let log = ReplicatedSpentbookLog {
+                address,
+                spent_proof_shares: double_spend,
+                spent_transaction: None,
+            };
+            assert!(matches!(
+                store.update(&log, &keys).await,
+                Err(Error::DoubleSpendAttempt(_))
+            ));
+            assert_eq!(
+                store
+                    .get_spentbook_replica(&address)
+                    .await?
+                    .spent_proof_shares,
+                shares
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbook_stores_and_samples_spent_transactions() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let (key_image, tx) = spent_transaction()?;
+            let address = spentbook_addr(&key_image);
+            let shares = spent_proof_shares(key_image, Hash::from(tx.hash()), 2);
+            let keys = section_keys(&shares);
+
+            // only the transaction the shares were signed for can be stored
+            let (_, other_tx) = spent_transaction()?;
+            assert!(matches!(
+                store.write(&shares[0], Some(&other_tx), &keys).await,
+                Err(Error::InvalidSpentTransaction(_))
+            ));
+
+            for share in &shares {
+                store.write(share, Some(&tx), &keys).await?;
+            }
+            let replica = store.get_spentbook_replica(&address).await?;
+            assert_eq!(replica.spent_transaction, Some(tx.clone()));
+
+            match store.sample_transactions().await {
+                NodeQueryResponse::SpentTransactions(Ok(sample)) => {
+                    assert_eq!(sample.len(), 1);
+                    assert_eq!(sample[0].tx, tx);
+                    assert_eq!(sorted(sample[0].spent_proof_shares.clone()), sorted(shares));
+                }
+                other => bail!("Unexpected response: {other:?}"),
+            }
+
+            // the transaction is removed along with the spentbook
+            store.remove_spentbook(&address).await?;
+            assert_eq!(
+                store.sample_transactions().await,
+                NodeQueryResponse::SpentTransactions(Ok(vec![]))
+            );
+            assert_eq!(store.used_space_on_disk(), store.used_space.used());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbook_rejects_invalid_shares() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let mut share =
+                spent_proof_shares(random_key_image(), Hash::from([1; 32]), 1).remove(0);
+            let keys = section_keys(&[share.clone()]);
+
+            // a share signed by a section we don't know of is not valid
+            assert!(matches!(
+                store.write(&share, None, &BTreeSet::new()).await,
+                Err(Error::InvalidSpentProofShare(_))
+            ));
+
+            share.content.transaction_hash = Hash::from([2; 32]);
+            assert!(matches!(
+                store.write(&share, None, &keys).await,
+                Err(Error::InvalidSpentProofShare(_))
+            ));
+            assert!(store.addrs().is_empty());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbook_entries_are_compacted() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let key_image = random_key_image();
+            let address = spentbook_addr(&key_image);
+            let count = COMPACTION_THRESHOLD as u64 + 4;
+            let shares = spent_proof_shares(key_image, Hash::from([1; 32]), count);
+            let keys = section_keys(&shares);
+
+            for share in &shares {
+                store.write(share, None, &keys).await?;
+            }
+
+            let entries = store.backend.read_entries(address.name()).await?;
+            assert!(entries.len() <= COMPACTION_THRESHOLD);
+
+            let mut stored = store
+                .get_spentbook_replica(&address)
+                .await?
+                .spent_proof_shares;
+            let mut expected = shares;
+            stored.sort_by_key(|share| share.spentbook_sig_share().threshold_crypto().0);
+            expected.sort_by_key(|share| share.spentbook_sig_share().threshold_crypto().0);
+            assert_eq!(stored, expected);
+
+            // used space accounts for the compacted entry only
+            assert_eq!(store.used_space_on_disk(), store.used_space.used());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbook_scrub_quarantines_corrupted_entries() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let key_image = random_key_image();
+            let address = spentbook_addr(&key_image);
+            let shares = spent_proof_shares(key_image, Hash::from([1; 32]), 2);
+            let keys = section_keys(&shares);
+            for share in &shares {
+                store.write(share, None, &keys).await?;
+            }
+            assert!(!store.scrub_spentbook(&address, &keys).await?);
+
+            let corrupted = StorageKey::entry(*address.name(), "corrupted".to_string());
+            let _ = store.backend.write(&corrupted, &[1, 2, 3]).await?;
+            store.used_space.increase(3);
+
+            // only the corrupted entry is quarantined, the valid shares are kept
+            assert!(store.scrub_spentbook(&address, &keys).await?);
+            assert!(store.quarantine.exists(&corrupted)?);
+            assert_eq!(store.addrs(), vec![address]);
+            assert_eq!(sorted(store.read_shares(&address).await?), sorted(shares));
+            assert_eq!(store.used_space_on_disk(), store.used_space.used());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbook_scrub_keeps_valid_shares_of_corrupted_entries() -> Result<()> {
+        for kind in StorageBackendKind::iter() {
+            let (_dir, store) = init_store(kind)?;
+            let key_image = random_key_image();
+            let address = spentbook_addr(&key_image);
+            let shares = spent_proof_shares(key_image, Hash::from([1; 32]), 2);
+            let keys = section_keys(&shares);
+
+            // an entry holding a valid share along with one which was tampered with
+            let mut tampered = shares[1].clone();
+            tampered.content.transaction_hash = Hash::from([2; 32]);
+            let value = serialise(&vec![shares[0].clone(), tampered])?;
+            let key = StorageKey::entry(*address.name(), entry_id(&value));
+            let _ = store.backend.write(&key, &value).await?;
+            store.used_space.increase(value.len());
+
+            assert!(store.scrub_spentbook(&address, &keys).await?);
+            assert!(store.quarantine.exists(&key)?);
+            assert_eq!(store.read_shares(&address).await?, vec![shares[0].clone()]);
+            assert!(!store.scrub_spentbook(&address, &keys).await?);
+            assert_eq!(store.used_space_on_disk(), store.used_space.used());
+        }
+        Ok(())
+    }
+}
@@ -34,7 +34,22 @@ impl UsedSpace {
     }
 
     pub(crate) fn decrease(&self, size: usize) {
-        let _ = self.used_space.fetch_sub(size, Ordering::Relaxed);
+        // saturate at zero, our tracked value may have drifted from what's actually on disk
+        let _ = self
+            .used_space
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(size))
+            });
+    }
+
+    /// Currently tracked used space
+    pub(crate) fn used(&self) -> usize {
+        self.used_space.load(Ordering::Relaxed)
+    }
+
+    /// Overwrite the tracked used space, e.g. with the size measured on disk
+    pub(crate) fn set(&self, size: usize) {
+        self.used_space.store(size, Ordering::Relaxed);
     }
 
     pub(crate) fn can_add(&self, size: usize) -> bool {